@@ -35,6 +35,26 @@ impl Component for ConfigView {
                 ),
             ]),
             Line::from(""),
+            Line::from(vec![
+                Span::styled(
+                    "    Backend:           ",
+                    Style::default().fg(Color::Rgb(150, 150, 150)),
+                ),
+                Span::styled(
+                    self.config.database.backend.to_string(),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    "    SQLite File:       ",
+                    Style::default().fg(Color::Rgb(150, 150, 150)),
+                ),
+                Span::styled(
+                    &self.config.database.sqlite_path,
+                    Style::default().fg(Color::White),
+                ),
+            ]),
             Line::from(vec![
                 Span::styled(
                     "    Host:              ",
@@ -125,6 +145,69 @@ impl Component for ConfigView {
                     Style::default().fg(Color::White),
                 ),
             ]),
+            Line::from(vec![
+                Span::styled(
+                    "    SSL Mode:          ",
+                    Style::default().fg(Color::Rgb(150, 150, 150)),
+                ),
+                Span::styled(
+                    self.config.database.ssl_mode.to_string(),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    "    SSL Root CA:       ",
+                    Style::default().fg(Color::Rgb(150, 150, 150)),
+                ),
+                Span::styled(
+                    or_none(&self.config.database.ssl_root_cert),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    "    SSL Client Cert:   ",
+                    Style::default().fg(Color::Rgb(150, 150, 150)),
+                ),
+                Span::styled(
+                    or_none(&self.config.database.ssl_client_cert),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    "    Application:       ",
+                    Style::default().fg(Color::Rgb(150, 150, 150)),
+                ),
+                Span::styled(
+                    self.config.database.application_name.clone(),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    "    Stmt Timeout:      ",
+                    Style::default().fg(Color::Rgb(150, 150, 150)),
+                ),
+                Span::styled(
+                    match self.config.database.statement_timeout {
+                        0 => "none".to_string(),
+                        seconds => format!("{}s", seconds),
+                    },
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    "    Search Path:       ",
+                    Style::default().fg(Color::Rgb(150, 150, 150)),
+                ),
+                Span::styled(
+                    or_none(&self.config.database.search_path),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
             Line::from(""),
             // JWT section
             Line::from(vec![
@@ -177,3 +260,12 @@ impl Component for ConfigView {
         Paragraph::new(content).block(block).render(area, buffer);
     }
 }
+
+/// Value of an optional setting, or "none" when it is empty
+fn or_none(value: &str) -> String {
+    if value.is_empty() {
+        "none".to_string()
+    } else {
+        value.to_string()
+    }
+}
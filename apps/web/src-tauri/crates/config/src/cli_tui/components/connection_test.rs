@@ -0,0 +1,151 @@
+use crate::cli_tui::{CheckItem, CheckStatus, ConnectionCheck, ConnectionCheckError};
+use reratui::prelude::*;
+use reratui::ratatui::widgets::BorderType;
+
+#[derive(Clone)]
+pub struct ConnectionTest {
+    /// Database being tested, without the password
+    pub target: String,
+    pub pending: bool,
+    pub result: Option<Result<ConnectionCheck, ConnectionCheckError>>,
+}
+
+impl Component for ConnectionTest {
+    fn render(&self, area: Rect, buffer: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .border_type(BorderType::Rounded)
+            .title(" 🔌 Connection Test ")
+            .title_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .style(Style::default().bg(Color::Rgb(20, 25, 35)));
+
+        let mut lines = vec![
+            Line::from(""),
+            field_line("Target", self.target.clone(), Color::White),
+            Line::from(""),
+        ];
+
+        match (&self.result, self.pending) {
+            (_, true) | (None, false) => {
+                lines.push(Line::from(vec![Span::styled(
+                    "  ⏳ Connecting...",
+                    Style::default().fg(Color::Cyan),
+                )]));
+            }
+            (Some(Ok(report)), false) => render_report(report, &mut lines),
+            (Some(Err(error)), false) => render_error(error, &mut lines),
+        }
+
+        Paragraph::new(lines).block(block).render(area, buffer);
+    }
+}
+
+fn field_line(label: &str, value: String, color: Color) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(
+            format!("    {:<14}", label),
+            Style::default().fg(Color::Rgb(150, 150, 150)),
+        ),
+        Span::styled(value, Style::default().fg(color)),
+    ])
+}
+
+fn item_line(item: &CheckItem) -> Line<'static> {
+    let (icon, color) = match item.status {
+        CheckStatus::Ok => ("✓", Color::Green),
+        CheckStatus::Warning => ("○", Color::Yellow),
+        CheckStatus::Failed => ("✗", Color::Red),
+    };
+    Line::from(vec![
+        Span::styled(
+            format!("      {} {} ", icon, item.name),
+            Style::default().fg(color),
+        ),
+        Span::styled(
+            format!("({})", item.note),
+            Style::default().fg(Color::Rgb(150, 150, 150)),
+        ),
+    ])
+}
+
+fn render_report(report: &ConnectionCheck, lines: &mut Vec<Line<'static>>) {
+    lines.push(Line::from(vec![Span::styled(
+        "  ✓ Connected",
+        Style::default()
+            .fg(Color::Green)
+            .add_modifier(Modifier::BOLD),
+    )]));
+    lines.push(Line::from(""));
+    lines.push(field_line(
+        "Server",
+        report.server_version.clone(),
+        Color::White,
+    ));
+    lines.push(field_line(
+        "Latency",
+        format!(
+            "{} ms round trip ({} ms to connect)",
+            report.latency.as_millis(),
+            report.connect_time.as_millis()
+        ),
+        Color::White,
+    ));
+
+    let pending = report.pending_migrations();
+    lines.push(field_line(
+        "Migrations",
+        format!("{} applied, {} pending", report.applied_migrations, pending),
+        if pending == 0 {
+            Color::Green
+        } else {
+            Color::Yellow
+        },
+    ));
+    lines.extend(report.migrations.iter().map(item_line));
+
+    if !report.extensions.is_empty() {
+        let missing = report.missing_extensions();
+        lines.push(field_line(
+            "Extensions",
+            if missing == 0 {
+                "all installed".to_string()
+            } else {
+                format!("{} missing", missing)
+            },
+            if missing == 0 {
+                Color::Green
+            } else {
+                Color::Yellow
+            },
+        ));
+        lines.extend(report.extensions.iter().map(item_line));
+    }
+}
+
+fn render_error(error: &ConnectionCheckError, lines: &mut Vec<Line<'static>>) {
+    lines.push(Line::from(vec![Span::styled(
+        format!("  ✗ {}", error.summary),
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    )]));
+    lines.push(Line::from(""));
+    lines.push(field_line("Error", error.message.clone(), Color::Red));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("  ", Style::default()),
+        Span::styled(
+            "💡 Tip: ",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            error.hint.clone(),
+            Style::default().fg(Color::Rgb(150, 150, 150)),
+        ),
+    ]));
+}
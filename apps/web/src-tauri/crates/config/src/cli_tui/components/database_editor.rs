@@ -22,14 +22,23 @@ impl Component for DatabaseEditor {
             )
             .style(Style::default().bg(Color::Rgb(20, 25, 35)));
 
+        let backend_str = self.state.config.database.backend.to_string();
         let masked_password = mask_password(&self.state.config.database.password);
         let port_str = self.state.config.database.port.to_string();
         let max_conn_str = self.state.config.database.max_connections.to_string();
         let min_conn_str = self.state.config.database.min_connections.to_string();
         let conn_timeout_str = self.state.config.database.connect_timeout.to_string();
         let idle_timeout_str = self.state.config.database.idle_timeout.to_string();
+        let ssl_mode_str = self.state.config.database.ssl_mode.to_string();
+        let statement_timeout_str = self.state.config.database.statement_timeout.to_string();
 
         let fields = vec![
+            ("Backend", EditField::DbBackend, backend_str.as_str()),
+            (
+                "SQLite File",
+                EditField::DbSqlitePath,
+                self.state.config.database.sqlite_path.as_str(),
+            ),
             (
                 "Host",
                 EditField::DbHost,
@@ -67,6 +76,37 @@ impl Component for DatabaseEditor {
                 EditField::DbIdleTimeout,
                 idle_timeout_str.as_str(),
             ),
+            ("SSL Mode", EditField::DbSslMode, ssl_mode_str.as_str()),
+            (
+                "SSL Root CA",
+                EditField::DbSslRootCert,
+                self.state.config.database.ssl_root_cert.as_str(),
+            ),
+            (
+                "SSL Client Cert",
+                EditField::DbSslClientCert,
+                self.state.config.database.ssl_client_cert.as_str(),
+            ),
+            (
+                "SSL Client Key",
+                EditField::DbSslClientKey,
+                self.state.config.database.ssl_client_key.as_str(),
+            ),
+            (
+                "Application Name",
+                EditField::DbAppName,
+                self.state.config.database.application_name.as_str(),
+            ),
+            (
+                "Statement Timeout (s)",
+                EditField::DbStatementTimeout,
+                statement_timeout_str.as_str(),
+            ),
+            (
+                "Search Path",
+                EditField::DbSearchPath,
+                self.state.config.database.search_path.as_str(),
+            ),
         ];
 
         let mut content = vec![
@@ -34,8 +34,13 @@ impl Component for Footer {
             // Context-aware help text
             let help_text = match self.screen {
                 Screen::Main => "↑↓ Navigate  │  Enter Select  │  Q Quit",
-                Screen::EditDatabase | Screen::EditJwt => {
-                    "↑↓ Navigate  │  Enter Edit  │  S Save  │  Esc Back"
+                Screen::EditDatabase => {
+                    "↑↓ Navigate  │  Enter Edit  │  T Test  │  S Save  │  Esc Back"
+                }
+                Screen::EditJwt => "↑↓ Navigate  │  Enter Edit  │  S Save  │  Esc Back",
+                Screen::ConnectionTest => "T Test Again  │  Esc Back",
+                Screen::Profiles => {
+                    "↑↓ Navigate  │  Enter Switch  │  T Training  │  P Password  │  Esc Back"
                 }
                 Screen::Export | Screen::Import => "Enter Confirm  │  Esc Cancel",
                 Screen::Confirm(_) => "Y Confirm  │  N Cancel",
@@ -2,7 +2,10 @@ use reratui::prelude::*;
 use reratui::ratatui::widgets::BorderType;
 
 #[derive(Clone)]
-pub struct Header;
+pub struct Header {
+    pub profile: String,
+    pub training: bool,
+}
 
 impl Component for Header {
     fn render(&self, area: Rect, buffer: &mut Buffer) {
@@ -48,6 +51,21 @@ impl Component for Header {
                         .add_modifier(Modifier::ITALIC),
                 ),
             ]),
+            Line::from(vec![
+                Span::styled("Profile: ", Style::default().fg(Color::Rgb(150, 150, 150))),
+                Span::styled(
+                    self.profile.clone(),
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    if self.training { "  ⚠ TRAINING" } else { "" },
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]),
         ];
 
         Paragraph::new(title)
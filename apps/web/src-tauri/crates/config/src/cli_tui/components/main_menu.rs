@@ -29,6 +29,7 @@ impl Component for MainMenu {
             ("📤", "Export Config", "Save configuration to file"),
             ("📥", "Import Config", "Load configuration from file"),
             ("🗑️", "Delete Config", "Remove configuration file"),
+            ("🗂️", "Profiles", "Switch or create a configuration profile"),
         ];
 
         let mut lines = vec![Line::from("")];
@@ -1,5 +1,6 @@
 pub mod config_view;
 pub mod confirm_dialog;
+pub mod connection_test;
 pub mod database_editor;
 pub mod export_dialog;
 pub mod footer;
@@ -7,9 +8,11 @@ pub mod header;
 pub mod import_dialog;
 pub mod jwt_editor;
 pub mod main_menu;
+pub mod profile_selector;
 
 pub use config_view::ConfigView;
 pub use confirm_dialog::ConfirmDialog;
+pub use connection_test::ConnectionTest;
 pub use database_editor::DatabaseEditor;
 pub use export_dialog::ExportDialog;
 pub use footer::Footer;
@@ -17,3 +20,4 @@ pub use header::Header;
 pub use import_dialog::ImportDialog;
 pub use jwt_editor::JwtEditor;
 pub use main_menu::MainMenu;
+pub use profile_selector::ProfileSelector;
@@ -0,0 +1,124 @@
+use crate::cli_tui::state::{AppState, EditField};
+use reratui::prelude::*;
+use reratui::ratatui::widgets::BorderType;
+
+#[derive(Clone)]
+pub struct ProfileSelector {
+    pub state: AppState,
+}
+
+impl Component for ProfileSelector {
+    fn render(&self, area: Rect, buffer: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .border_type(BorderType::Rounded)
+            .title(" 🗂️ Profiles ")
+            .title_style(
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .style(Style::default().bg(Color::Rgb(20, 25, 35)));
+
+        let mut lines = vec![Line::from("")];
+
+        // Stored profiles, then the entry that creates a new one
+        let new_profile = "New profile".to_string();
+        let entries = self.state.profiles.iter().chain([&new_profile]);
+
+        for (i, name) in entries.enumerate() {
+            let is_selected = i == self.state.selected_profile;
+            let is_new = i == self.state.profiles.len();
+            let is_current = !is_new && *name == self.state.profile;
+
+            let icon = if is_new {
+                "➕"
+            } else if is_current {
+                "●"
+            } else {
+                "○"
+            };
+
+            let mut spans = vec![
+                Span::styled(
+                    if is_selected { "  ▶ " } else { "    " },
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("{} ", icon),
+                    Style::default().fg(if is_current {
+                        Color::Green
+                    } else {
+                        Color::Rgb(100, 150, 200)
+                    }),
+                ),
+                Span::styled(
+                    name.clone(),
+                    if is_selected {
+                        Style::default()
+                            .fg(Color::White)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(Color::Rgb(200, 200, 200))
+                    },
+                ),
+            ];
+
+            if is_current && self.state.config.training {
+                spans.push(Span::styled(
+                    "  TRAINING",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+
+            lines.push(Line::from(spans));
+        }
+
+        lines.push(Line::from(""));
+
+        if self.state.editing {
+            // The new password is masked as it is typed
+            let (label, value) = if self.state.edit_field == Some(EditField::NewPassword) {
+                (
+                    "  New password for this profile: ",
+                    "*".repeat(self.state.edit_buffer.chars().count()),
+                )
+            } else {
+                ("  Profile name: ", self.state.edit_buffer.clone())
+            };
+            lines.push(Line::from(vec![Span::styled(
+                label,
+                Style::default().fg(Color::Rgb(150, 150, 150)),
+            )]));
+            lines.push(Line::from(vec![
+                Span::styled("  ", Style::default()),
+                Span::styled(
+                    format!("  {}_", value),
+                    Style::default().fg(Color::White).bg(Color::Rgb(40, 80, 40)),
+                ),
+            ]));
+            lines.push(Line::from(""));
+        }
+
+        lines.push(Line::from(vec![
+            Span::styled("  ", Style::default()),
+            Span::styled(
+                "💡 Tip: ",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                "The app opens a profile with `meditrack --profile <name>`",
+                Style::default().fg(Color::Rgb(150, 150, 150)),
+            ),
+        ]));
+
+        Paragraph::new(lines).block(block).render(area, buffer);
+    }
+}
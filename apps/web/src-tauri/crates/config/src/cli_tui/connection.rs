@@ -0,0 +1,85 @@
+//! Connection test of the database settings being edited
+//!
+//! Testing a connection needs the database drivers and migrations, which this
+//! crate does not depend on. The binary starting the TUI passes in a
+//! [`ConnectionTester`], and the TUI only shows the [`ConnectionCheck`] it
+//! returns.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::DatabaseConfig;
+
+/// State of a migration or extension looked at by the test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    /// Needs attention, such as a pending migration
+    Warning,
+    /// Cannot be fixed from here, such as a migration of a newer version
+    Failed,
+}
+
+/// Migration or extension, with a short note on its state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckItem {
+    pub name: String,
+    pub note: String,
+    pub status: CheckStatus,
+}
+
+/// Outcome of a successful connection test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionCheck {
+    /// Time to open the connection
+    pub connect_time: Duration,
+    /// Round trip of a trivial query
+    pub latency: Duration,
+    pub server_version: String,
+    pub applied_migrations: usize,
+    /// Migrations that are not applied; applied ones are only counted
+    pub migrations: Vec<CheckItem>,
+    /// Required extensions; empty on SQLite
+    pub extensions: Vec<CheckItem>,
+}
+
+impl ConnectionCheck {
+    pub fn pending_migrations(&self) -> usize {
+        self.migrations
+            .iter()
+            .filter(|migration| migration.status == CheckStatus::Warning)
+            .count()
+    }
+
+    pub fn missing_extensions(&self) -> usize {
+        self.extensions
+            .iter()
+            .filter(|extension| extension.status != CheckStatus::Ok)
+            .count()
+    }
+}
+
+/// Failed connection test
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{summary}: {message}")]
+pub struct ConnectionCheckError {
+    /// What failed, e.g. "Authentication failed"
+    pub summary: String,
+    pub message: String,
+    /// What to check next
+    pub hint: String,
+}
+
+/// Tests database settings; supplied by the binary starting the TUI
+pub type ConnectionTester = Arc<
+    dyn Fn(
+            DatabaseConfig,
+        )
+            -> Pin<Box<dyn Future<Output = Result<ConnectionCheck, ConnectionCheckError>> + Send>>
+        + Send
+        + Sync,
+>;
@@ -1,6 +1,7 @@
+use super::connection::{ConnectionCheck, ConnectionCheckError};
 use super::state::*;
 use super::utils::*;
-use crate::{AppConfig, ConfigStorage};
+use crate::{AppConfig, ConfigStorage, DatabaseConfig};
 use reratui::prelude::*;
 
 pub fn handle_key_event(code: KeyCode, state: &AppState, set_state: StateSetter<AppState>) {
@@ -18,7 +19,12 @@ fn handle_navigation_mode(code: KeyCode, state: &AppState, set_state: StateSette
                 request_exit();
             } else {
                 let mut new_state = state.clone();
-                new_state.screen = Screen::Main;
+                // The connection test returns to the settings it tested
+                new_state.screen = if state.screen == Screen::ConnectionTest {
+                    Screen::EditDatabase
+                } else {
+                    Screen::Main
+                };
                 new_state.message = None;
                 set_state.set(new_state);
             }
@@ -37,6 +43,21 @@ fn handle_navigation_mode(code: KeyCode, state: &AppState, set_state: StateSette
                 handle_confirm_yes(state, set_state);
             }
         }
+        KeyCode::Char('t') => {
+            if state.screen == Screen::Profiles {
+                handle_toggle_training(state, set_state);
+            }
+        }
+        KeyCode::Char('p') => {
+            if state.screen == Screen::Profiles {
+                let mut new_state = state.clone();
+                new_state.editing = true;
+                new_state.edit_field = Some(EditField::NewPassword);
+                new_state.edit_buffer.clear();
+                new_state.message = None;
+                set_state.set(new_state);
+            }
+        }
         KeyCode::Char('n') => {
             if matches!(state.screen, Screen::Confirm(_)) {
                 let mut new_state = state.clone();
@@ -84,11 +105,17 @@ fn handle_down(state: &AppState, set_state: StateSetter<AppState>) {
 
     match &state.screen {
         Screen::Main => {
-            new_state.selected_menu = (state.selected_menu + 1).min(6);
+            new_state.selected_menu = (state.selected_menu + 1).min(7);
+        }
+        Screen::Profiles => {
+            // The last entry creates a new profile
+            new_state.selected_profile = (state.selected_profile + 1).min(state.profiles.len());
         }
         Screen::EditDatabase => {
             new_state.edit_field = Some(match state.edit_field {
-                None | Some(EditField::DbHost) => EditField::DbPort,
+                None | Some(EditField::DbBackend) => EditField::DbSqlitePath,
+                Some(EditField::DbSqlitePath) => EditField::DbHost,
+                Some(EditField::DbHost) => EditField::DbPort,
                 Some(EditField::DbPort) => EditField::DbName,
                 Some(EditField::DbName) => EditField::DbUsername,
                 Some(EditField::DbUsername) => EditField::DbPassword,
@@ -96,8 +123,15 @@ fn handle_down(state: &AppState, set_state: StateSetter<AppState>) {
                 Some(EditField::DbMaxConn) => EditField::DbMinConn,
                 Some(EditField::DbMinConn) => EditField::DbConnTimeout,
                 Some(EditField::DbConnTimeout) => EditField::DbIdleTimeout,
-                Some(EditField::DbIdleTimeout) => EditField::DbHost,
-                _ => EditField::DbHost,
+                Some(EditField::DbIdleTimeout) => EditField::DbSslMode,
+                Some(EditField::DbSslMode) => EditField::DbSslRootCert,
+                Some(EditField::DbSslRootCert) => EditField::DbSslClientCert,
+                Some(EditField::DbSslClientCert) => EditField::DbSslClientKey,
+                Some(EditField::DbSslClientKey) => EditField::DbAppName,
+                Some(EditField::DbAppName) => EditField::DbStatementTimeout,
+                Some(EditField::DbStatementTimeout) => EditField::DbSearchPath,
+                Some(EditField::DbSearchPath) => EditField::DbBackend,
+                _ => EditField::DbBackend,
             });
         }
         Screen::EditJwt => {
@@ -122,9 +156,14 @@ fn handle_up(state: &AppState, set_state: StateSetter<AppState>) {
         Screen::Main => {
             new_state.selected_menu = state.selected_menu.saturating_sub(1);
         }
+        Screen::Profiles => {
+            new_state.selected_profile = state.selected_profile.saturating_sub(1);
+        }
         Screen::EditDatabase => {
             new_state.edit_field = Some(match state.edit_field {
-                None | Some(EditField::DbHost) => EditField::DbIdleTimeout,
+                None | Some(EditField::DbBackend) => EditField::DbSearchPath,
+                Some(EditField::DbSqlitePath) => EditField::DbBackend,
+                Some(EditField::DbHost) => EditField::DbSqlitePath,
                 Some(EditField::DbPort) => EditField::DbHost,
                 Some(EditField::DbName) => EditField::DbPort,
                 Some(EditField::DbUsername) => EditField::DbName,
@@ -133,7 +172,14 @@ fn handle_up(state: &AppState, set_state: StateSetter<AppState>) {
                 Some(EditField::DbMinConn) => EditField::DbMaxConn,
                 Some(EditField::DbConnTimeout) => EditField::DbMinConn,
                 Some(EditField::DbIdleTimeout) => EditField::DbConnTimeout,
-                _ => EditField::DbHost,
+                Some(EditField::DbSslMode) => EditField::DbIdleTimeout,
+                Some(EditField::DbSslRootCert) => EditField::DbSslMode,
+                Some(EditField::DbSslClientCert) => EditField::DbSslRootCert,
+                Some(EditField::DbSslClientKey) => EditField::DbSslClientCert,
+                Some(EditField::DbAppName) => EditField::DbSslClientKey,
+                Some(EditField::DbStatementTimeout) => EditField::DbAppName,
+                Some(EditField::DbSearchPath) => EditField::DbStatementTimeout,
+                _ => EditField::DbBackend,
             });
         }
         Screen::EditJwt => {
@@ -159,7 +205,7 @@ fn handle_enter(state: &AppState, set_state: StateSetter<AppState>) {
             new_state.screen = match state.selected_menu {
                 0 => Screen::ViewConfig,
                 1 => {
-                    new_state.edit_field = Some(EditField::DbHost);
+                    new_state.edit_field = Some(EditField::DbBackend);
                     Screen::EditDatabase
                 }
                 2 => {
@@ -176,9 +222,30 @@ fn handle_enter(state: &AppState, set_state: StateSetter<AppState>) {
                     Screen::Import
                 }
                 6 => Screen::Confirm(ConfirmAction::Delete),
+                7 => {
+                    new_state.profiles = ConfigStorage::list_profiles(&state.base_dir)
+                        .unwrap_or_else(|_| state.profiles.clone());
+                    new_state.selected_profile = new_state
+                        .profiles
+                        .iter()
+                        .position(|profile| *profile == state.profile)
+                        .unwrap_or(0);
+                    Screen::Profiles
+                }
                 _ => Screen::Main,
             };
         }
+        Screen::Profiles => match state.profiles.get(state.selected_profile) {
+            Some(profile) => {
+                switch_profile(state, profile, set_state);
+                return;
+            }
+            None => {
+                new_state.editing = true;
+                new_state.edit_field = Some(EditField::ProfileName);
+                new_state.edit_buffer.clear();
+            }
+        },
         Screen::EditDatabase | Screen::EditJwt => {
             if let Some(field) = &state.edit_field {
                 new_state.editing = true;
@@ -205,6 +272,8 @@ fn handle_enter(state: &AppState, set_state: StateSetter<AppState>) {
 
 fn get_current_value(config: &AppConfig, field: &EditField) -> String {
     match field {
+        EditField::DbBackend => config.database.backend.to_string(),
+        EditField::DbSqlitePath => config.database.sqlite_path.clone(),
         EditField::DbHost => config.database.host.clone(),
         EditField::DbPort => config.database.port.to_string(),
         EditField::DbName => config.database.database.clone(),
@@ -214,11 +283,18 @@ fn get_current_value(config: &AppConfig, field: &EditField) -> String {
         EditField::DbMinConn => config.database.min_connections.to_string(),
         EditField::DbConnTimeout => config.database.connect_timeout.to_string(),
         EditField::DbIdleTimeout => config.database.idle_timeout.to_string(),
+        EditField::DbSslMode => config.database.ssl_mode.to_string(),
+        EditField::DbSslRootCert => config.database.ssl_root_cert.clone(),
+        EditField::DbSslClientCert => config.database.ssl_client_cert.clone(),
+        EditField::DbSslClientKey => config.database.ssl_client_key.clone(),
+        EditField::DbAppName => config.database.application_name.clone(),
+        EditField::DbStatementTimeout => config.database.statement_timeout.to_string(),
+        EditField::DbSearchPath => config.database.search_path.clone(),
         EditField::JwtSecret => config.jwt.secret.clone(),
         EditField::JwtIssuer => config.jwt.issuer.clone(),
         EditField::JwtAudience => config.jwt.audience.clone(),
         EditField::JwtExpiration => config.jwt.expiration_hours.to_string(),
-        EditField::FilePath => String::new(),
+        EditField::FilePath | EditField::ProfileName | EditField::NewPassword => String::new(),
     }
 }
 
@@ -227,6 +303,18 @@ fn apply_edit(state: &mut AppState) -> bool {
 
     if let Some(field) = &state.edit_field {
         match field {
+            EditField::DbBackend => {
+                if let Ok(backend) = validate_backend(value) {
+                    state.config.database.backend = backend;
+                    true
+                } else {
+                    false
+                }
+            }
+            EditField::DbSqlitePath => {
+                state.config.database.sqlite_path = value.to_string();
+                true
+            }
             EditField::DbHost => {
                 state.config.database.host = value.to_string();
                 true
@@ -283,6 +371,46 @@ fn apply_edit(state: &mut AppState) -> bool {
                     false
                 }
             }
+            EditField::DbSslMode => {
+                if let Ok(mode) = validate_ssl_mode(value) {
+                    state.config.database.ssl_mode = mode;
+                    true
+                } else {
+                    false
+                }
+            }
+            EditField::DbSslRootCert => {
+                state.config.database.ssl_root_cert = value.to_string();
+                true
+            }
+            EditField::DbSslClientCert => {
+                state.config.database.ssl_client_cert = value.to_string();
+                true
+            }
+            EditField::DbSslClientKey => {
+                state.config.database.ssl_client_key = value.to_string();
+                true
+            }
+            EditField::DbAppName => {
+                state.config.database.application_name = value.to_string();
+                true
+            }
+            EditField::DbStatementTimeout => {
+                if let Ok(n) = validate_u64(value) {
+                    state.config.database.statement_timeout = n;
+                    true
+                } else {
+                    false
+                }
+            }
+            EditField::DbSearchPath => {
+                if let Ok(search_path) = validate_search_path(value) {
+                    state.config.database.search_path = search_path;
+                    true
+                } else {
+                    false
+                }
+            }
             EditField::JwtSecret => {
                 state.config.jwt.secret = value.to_string();
                 true
@@ -303,7 +431,7 @@ fn apply_edit(state: &mut AppState) -> bool {
                     false
                 }
             }
-            EditField::FilePath => true,
+            EditField::FilePath | EditField::ProfileName | EditField::NewPassword => true,
         }
     } else {
         false
@@ -315,11 +443,11 @@ fn handle_confirm_yes(state: &AppState, set_state: StateSetter<AppState>) {
 
     match &state.screen {
         Screen::Confirm(ConfirmAction::Reset) => {
-            new_state.config = AppConfig::default();
-            match ConfigStorage::new_with_password(
+            new_state.config = AppConfig::for_profile(&state.profile);
+            match ConfigStorage::new_with_key_source(
                 "meditrack",
                 state.config_dir.clone(),
-                &state.password,
+                state.key_source.clone(),
             ) {
                 Ok(storage) => match storage.save(&new_state.config) {
                     Ok(_) => {
@@ -343,10 +471,10 @@ fn handle_confirm_yes(state: &AppState, set_state: StateSetter<AppState>) {
             new_state.screen = Screen::Main;
         }
         Screen::Confirm(ConfirmAction::Delete) => {
-            match ConfigStorage::new_with_password(
+            match ConfigStorage::new_with_key_source(
                 "meditrack",
                 state.config_dir.clone(),
-                &state.password,
+                state.key_source.clone(),
             ) {
                 Ok(storage) => match storage.delete() {
                     Ok(_) => {
@@ -378,7 +506,11 @@ fn handle_confirm_yes(state: &AppState, set_state: StateSetter<AppState>) {
 pub fn handle_save(state: &AppState, set_state: StateSetter<AppState>) {
     let mut new_state = state.clone();
 
-    match ConfigStorage::new_with_password("meditrack", state.config_dir.clone(), &state.password) {
+    match ConfigStorage::new_with_key_source(
+        "meditrack",
+        state.config_dir.clone(),
+        state.key_source.clone(),
+    ) {
         Ok(storage) => match storage.save(&new_state.config) {
             Ok(_) => {
                 new_state.message = Some((
@@ -401,6 +533,24 @@ pub fn handle_save(state: &AppState, set_state: StateSetter<AppState>) {
     set_state.set(new_state);
 }
 
+/// Test the database settings on screen, whether saved or not
+pub fn handle_test_connection(
+    state: &AppState,
+    set_state: StateSetter<AppState>,
+    mutation: &reratui::hooks::MutationHandle<
+        ConnectionCheck,
+        ConnectionCheckError,
+        DatabaseConfig,
+    >,
+) {
+    mutation.mutate(state.connection_target());
+
+    let mut new_state = state.clone();
+    new_state.screen = Screen::ConnectionTest;
+    new_state.message = None;
+    set_state.set(new_state);
+}
+
 pub fn handle_export(state: &AppState, set_state: StateSetter<AppState>) {
     let mut new_state = state.clone();
     let path = std::path::PathBuf::from(&state.edit_buffer);
@@ -434,13 +584,13 @@ pub fn handle_import(state: &AppState, set_state: StateSetter<AppState>) {
     let path = std::path::PathBuf::from(&state.edit_buffer);
 
     match std::fs::read_to_string(&path) {
-        Ok(json) => match serde_json::from_str::<AppConfig>(&json) {
+        Ok(json) => match AppConfig::from_json(&json) {
             Ok(config) => {
                 new_state.config = config;
-                match ConfigStorage::new_with_password(
+                match ConfigStorage::new_with_key_source(
                     "meditrack",
                     state.config_dir.clone(),
-                    &state.password,
+                    state.key_source.clone(),
                 ) {
                     Ok(storage) => match storage.save(&new_state.config) {
                         Ok(_) => {
@@ -468,8 +618,7 @@ pub fn handle_import(state: &AppState, set_state: StateSetter<AppState>) {
                 }
             }
             Err(e) => {
-                new_state.message =
-                    Some((format!("Failed to parse JSON: {}", e), MessageType::Error));
+                new_state.message = Some((format!("Failed to import: {}", e), MessageType::Error));
             }
         },
         Err(e) => {
@@ -479,3 +628,96 @@ pub fn handle_import(state: &AppState, set_state: StateSetter<AppState>) {
 
     set_state.set(new_state);
 }
+
+pub fn handle_new_profile(state: &AppState, set_state: StateSetter<AppState>) {
+    let name = state.edit_buffer.trim().to_string();
+    switch_profile(state, &name, set_state);
+}
+
+fn switch_profile(state: &AppState, profile: &str, set_state: StateSetter<AppState>) {
+    match state.switch_profile(profile) {
+        Ok(mut new_state) => {
+            new_state.message = Some((
+                format!("Switched to profile {}", profile),
+                MessageType::Success,
+            ));
+            set_state.set(new_state);
+        }
+        Err(e) => {
+            let mut new_state = state.clone();
+            let reason = match e {
+                crate::ConfigError::WrongPassword | crate::ConfigError::KeyUnavailable(_) => {
+                    format!(
+                        "Profile {} needs its own password; open it with `meditrack --config --profile {}`",
+                        profile, profile
+                    )
+                }
+                e => format!("Failed to open profile {}: {}", profile, e),
+            };
+            new_state.message = Some((reason, MessageType::Error));
+            set_state.set(new_state);
+        }
+    }
+}
+
+/// Re-encrypt the current profile with the password being edited
+pub fn handle_change_password(state: &AppState, set_state: StateSetter<AppState>) {
+    let mut new_state = state.clone();
+
+    match ConfigStorage::new_with_key_source(
+        "meditrack",
+        state.config_dir.clone(),
+        state.key_source.clone(),
+    )
+    .and_then(|storage| storage.rotate_password(&state.edit_buffer))
+    {
+        Ok(storage) => {
+            new_state.key_source = storage.key_source().clone();
+            new_state.editing = false;
+            new_state.edit_field = None;
+            new_state.edit_buffer.clear();
+            new_state.message = Some((
+                format!("Profile {} now opens with the new password", state.profile),
+                MessageType::Success,
+            ));
+        }
+        Err(e) => {
+            new_state.message = Some((
+                format!("Failed to change the password: {}", e),
+                MessageType::Error,
+            ));
+        }
+    }
+
+    set_state.set(new_state);
+}
+
+fn handle_toggle_training(state: &AppState, set_state: StateSetter<AppState>) {
+    let mut new_state = state.clone();
+    new_state.config.training = !state.config.training;
+
+    match ConfigStorage::new_with_key_source(
+        "meditrack",
+        state.config_dir.clone(),
+        state.key_source.clone(),
+    )
+    .and_then(|storage| storage.save(&new_state.config))
+    {
+        Ok(_) => {
+            new_state.message = Some((
+                if new_state.config.training {
+                    format!("Profile {} marked as training", state.profile)
+                } else {
+                    format!("Profile {} is no longer a training profile", state.profile)
+                },
+                MessageType::Success,
+            ));
+        }
+        Err(e) => {
+            new_state.config.training = state.config.training;
+            new_state.message = Some((format!("Failed to save: {:?}", e), MessageType::Error));
+        }
+    }
+
+    set_state.set(new_state);
+}
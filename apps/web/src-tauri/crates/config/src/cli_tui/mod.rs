@@ -1,25 +1,34 @@
 pub mod components;
+pub mod connection;
 pub mod handlers;
 pub mod state;
 pub mod utils;
 
 pub use components::*;
+pub use connection::*;
 pub use handlers::*;
 pub use state::*;
 pub use utils::*;
 
-use dialoguer::Password;
-use reratui::prelude::*;
+use crate::{ConfigError, ConfigStorage, DatabaseConfig, KeySource};
+use reratui::{hooks::use_mutation, prelude::*};
 
 #[derive(Clone)]
 pub struct MediTrackConfigTUI {
-    password: String,
+    key_source: KeySource,
+    profile: String,
+    tester: ConnectionTester,
 }
 
 impl Component for MediTrackConfigTUI {
     fn render(&self, area: Rect, buffer: &mut Buffer) {
-        let (state, set_state) =
-            use_state(|| AppState::new_with_password(get_config_dir(), &self.password));
+        let (state, set_state) = use_state(|| {
+            AppState::new_with_profile(get_config_dir(), &self.profile, self.key_source.clone())
+        });
+
+        // Connection test of the database settings being edited
+        let tester = self.tester.clone();
+        let connection_test = use_mutation(move |database: DatabaseConfig| tester(database), None);
 
         // Handle keyboard events
         if let Some(Event::Key(key)) = use_event()
@@ -30,11 +39,20 @@ impl Component for MediTrackConfigTUI {
                 if matches!(state.screen, Screen::EditDatabase | Screen::EditJwt) {
                     handle_save(&state, set_state);
                 }
+            } else if key.code == KeyCode::Char('t')
+                && !state.editing
+                && matches!(state.screen, Screen::EditDatabase | Screen::ConnectionTest)
+            {
+                handle_test_connection(&state, set_state, &connection_test);
             } else if key.code == KeyCode::Enter && state.editing {
                 // Handle export/import
                 match state.screen {
                     Screen::Export => handle_export(&state, set_state),
                     Screen::Import => handle_import(&state, set_state),
+                    Screen::Profiles if state.edit_field == Some(EditField::NewPassword) => {
+                        handle_change_password(&state, set_state)
+                    }
+                    Screen::Profiles => handle_new_profile(&state, set_state),
                     _ => handle_key_event(key.code, &state, set_state),
                 }
             } else {
@@ -53,7 +71,11 @@ impl Component for MediTrackConfigTUI {
             .split(area);
 
         // Render header component
-        Header.render(chunks[0], buffer);
+        Header {
+            profile: state.profile.clone(),
+            training: state.config.training,
+        }
+        .render(chunks[0], buffer);
 
         // Render content based on current screen using components
         match &state.screen {
@@ -75,12 +97,31 @@ impl Component for MediTrackConfigTUI {
                 }
                 .render(chunks[1], buffer);
             }
+            Screen::ConnectionTest => {
+                let result = if connection_test.is_error() {
+                    connection_test.error().map(Err)
+                } else {
+                    connection_test.data().map(Ok)
+                };
+                ConnectionTest {
+                    target: state.connection_target().safe_repr(),
+                    pending: connection_test.is_pending(),
+                    result,
+                }
+                .render(chunks[1], buffer);
+            }
             Screen::EditJwt => {
                 JwtEditor {
                     state: state.clone(),
                 }
                 .render(chunks[1], buffer);
             }
+            Screen::Profiles => {
+                ProfileSelector {
+                    state: state.clone(),
+                }
+                .render(chunks[1], buffer);
+            }
             Screen::Export => {
                 ExportDialog {
                     state: state.clone(),
@@ -110,20 +151,32 @@ impl Component for MediTrackConfigTUI {
     }
 }
 
-pub async fn run_config_tui() -> Result<(), Box<dyn std::error::Error>> {
+/// Run the configuration TUI on `profile`
+pub async fn run_config_tui(
+    profile: &str,
+    tester: ConnectionTester,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing (ignore if already initialized)
     let _ = tracing_subscriber::fmt()
         .with_max_level(tracing::Level::INFO)
         .try_init();
 
-    // Prompt for password
-    let password = Password::new()
-        .with_prompt("Enter configuration password")
-        .with_confirmation("Confirm password", "Passwords do not match")
-        .allow_empty_password(false)
-        .interact()?;
+    // Ask only for what the configuration's key source needs
+    let config_dir = ConfigStorage::profile_dir(&get_config_dir(), profile)?;
+    let key_source = prompt_key_source(&config_dir)?;
+
+    // A configuration from a newer version would be shown, and saved, with
+    // the settings this version does not know dropped
+    let storage = ConfigStorage::new_with_key_source("meditrack", config_dir, key_source.clone())?;
+    if let Err(e @ ConfigError::NewerVersion { .. }) = storage.load() {
+        return Err(e.into());
+    }
 
-    let tui = MediTrackConfigTUI { password };
+    let tui = MediTrackConfigTUI {
+        key_source,
+        profile: profile.to_string(),
+        tester,
+    };
     reratui::render(move || tui.clone()).await?;
     Ok(())
 }
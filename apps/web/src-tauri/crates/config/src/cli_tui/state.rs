@@ -1,4 +1,7 @@
-use crate::AppConfig;
+use crate::{
+    AppConfig, ConfigError, ConfigStorage, DEFAULT_PROFILE, DatabaseConfig, KEY_FILE_NAME,
+    KeySource,
+};
 use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug, PartialEq)]
@@ -6,7 +9,9 @@ pub enum Screen {
     Main,
     ViewConfig,
     EditDatabase,
+    ConnectionTest,
     EditJwt,
+    Profiles,
     Export,
     Import,
     Confirm(ConfirmAction),
@@ -27,6 +32,8 @@ pub enum MessageType {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum EditField {
+    DbBackend,
+    DbSqlitePath,
     DbHost,
     DbPort,
     DbName,
@@ -36,11 +43,20 @@ pub enum EditField {
     DbMinConn,
     DbConnTimeout,
     DbIdleTimeout,
+    DbSslMode,
+    DbSslRootCert,
+    DbSslClientCert,
+    DbSslClientKey,
+    DbAppName,
+    DbStatementTimeout,
+    DbSearchPath,
     JwtSecret,
     JwtIssuer,
     JwtAudience,
     JwtExpiration,
     FilePath,
+    ProfileName,
+    NewPassword,
 }
 
 #[derive(Clone, Debug)]
@@ -48,8 +64,14 @@ pub struct AppState {
     pub screen: Screen,
     pub selected_menu: usize,
     pub config: AppConfig,
+    /// Directory holding all profiles
+    pub base_dir: PathBuf,
+    /// Directory of the current profile
     pub config_dir: PathBuf,
-    pub password: String,
+    pub profile: String,
+    pub profiles: Vec<String>,
+    pub selected_profile: usize,
+    pub key_source: KeySource,
     pub message: Option<(String, MessageType)>,
     pub edit_field: Option<EditField>,
     pub edit_buffer: String,
@@ -57,16 +79,23 @@ pub struct AppState {
 }
 
 impl AppState {
-    pub fn new_with_password(config_dir: PathBuf, password: &str) -> Self {
-        // Try to load config with password, or create default
-        let config = Self::load_config_with_password(&config_dir, password);
+    pub fn new_with_profile(base_dir: PathBuf, profile: &str, key_source: KeySource) -> Self {
+        let config_dir =
+            ConfigStorage::profile_dir(&base_dir, profile).unwrap_or_else(|_| base_dir.clone());
+
+        // Try to load config with the key source, or create default
+        let config = Self::load_config_with_key_source(&config_dir, profile, &key_source);
 
         Self {
             screen: Screen::Main,
             selected_menu: 0,
             config,
+            profiles: ConfigStorage::list_profiles(&base_dir).unwrap_or_default(),
+            base_dir,
             config_dir,
-            password: password.to_string(),
+            profile: profile.to_string(),
+            selected_profile: 0,
+            key_source,
             message: None,
             edit_field: None,
             edit_buffer: String::new(),
@@ -74,24 +103,88 @@ impl AppState {
         }
     }
 
+    /// Open the default profile with the key source its file records,
+    /// falling back to the machine key
     pub fn new(config_dir: PathBuf) -> Self {
-        Self {
+        let key_source = crate::ConfigStorage::open("meditrack", config_dir.clone(), None)
+            .map(|storage| storage.key_source().clone())
+            .unwrap_or(KeySource::Machine);
+        Self::new_with_profile(config_dir, DEFAULT_PROFILE, key_source)
+    }
+
+    /// Switch to `profile`, creating it if it does not exist yet
+    ///
+    /// A password-protected profile is opened with the current password; a
+    /// new profile gets the current kind of key, with its own key file.
+    pub fn switch_profile(&self, profile: &str) -> crate::Result<Self> {
+        let config_dir = ConfigStorage::profile_dir(&self.base_dir, profile)?;
+        let key_source = match ConfigStorage::inspect(&config_dir)? {
+            None => match &self.key_source {
+                KeySource::KeyFile(_) => KeySource::KeyFile(config_dir.join(KEY_FILE_NAME)),
+                key_source => key_source.clone(),
+            },
+            Some(_) => {
+                let password = match &self.key_source {
+                    KeySource::Password(password) => Some(password.as_str()),
+                    _ => None,
+                };
+                ConfigStorage::open("meditrack", config_dir.clone(), password)?
+                    .key_source()
+                    .clone()
+            }
+        };
+
+        let storage = ConfigStorage::new_with_key_source(
+            "meditrack",
+            config_dir.clone(),
+            key_source.clone(),
+        )?;
+        let config = match storage.load() {
+            Ok(config) => config,
+            Err(ConfigError::NotFound) => {
+                let config = AppConfig::for_profile(profile);
+                storage.save(&config)?;
+                config
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
             screen: Screen::Main,
-            selected_menu: 0,
-            config: AppConfig::load_or_default(config_dir.clone()),
+            config,
             config_dir,
-            password: String::new(),
+            profile: profile.to_string(),
+            profiles: ConfigStorage::list_profiles(&self.base_dir)?,
+            selected_profile: 0,
+            key_source,
             message: None,
             edit_field: None,
             edit_buffer: String::new(),
             editing: false,
-        }
+            ..self.clone()
+        })
+    }
+
+    /// Database settings being edited, with the SQLite file resolved the
+    /// way the app resolves it
+    pub fn connection_target(&self) -> DatabaseConfig {
+        let mut database = self.config.database.clone();
+        database.resolve_sqlite_path(&self.config_dir);
+        database
     }
 
-    fn load_config_with_password(config_dir: &Path, password: &str) -> AppConfig {
+    fn load_config_with_key_source(
+        config_dir: &Path,
+        profile: &str,
+        key_source: &KeySource,
+    ) -> AppConfig {
         use crate::ConfigStorage;
 
-        match ConfigStorage::new_with_password("meditrack", config_dir.to_path_buf(), password) {
+        match ConfigStorage::new_with_key_source(
+            "meditrack",
+            config_dir.to_path_buf(),
+            key_source.clone(),
+        ) {
             Ok(storage) => match storage.load() {
                 Ok(config) => {
                     tracing::info!("Loaded configuration from storage");
@@ -99,16 +192,16 @@ impl AppState {
                 }
                 Err(crate::ConfigError::NotFound) => {
                     tracing::info!("Configuration not found, creating default");
-                    AppConfig::default()
+                    AppConfig::for_profile(profile)
                 }
                 Err(e) => {
                     tracing::warn!("Failed to load configuration: {:?}, using defaults", e);
-                    AppConfig::default()
+                    AppConfig::for_profile(profile)
                 }
             },
             Err(e) => {
                 tracing::warn!("Failed to initialize storage: {:?}, using defaults", e);
-                AppConfig::default()
+                AppConfig::for_profile(profile)
             }
         }
     }
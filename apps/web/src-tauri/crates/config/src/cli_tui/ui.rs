@@ -78,6 +78,11 @@ pub fn render_view_config(area: Rect, buffer: &mut Buffer, config: &AppConfig) {
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
+        Line::from(format!("  Backend:           {}", config.database.backend)),
+        Line::from(format!(
+            "  SQLite File:       {}",
+            config.database.sqlite_path
+        )),
         Line::from(format!("  Host:              {}", config.database.host)),
         Line::from(format!("  Port:              {}", config.database.port)),
         Line::from(format!("  Database:          {}", config.database.database)),
@@ -138,6 +143,7 @@ pub fn render_view_config(area: Rect, buffer: &mut Buffer, config: &AppConfig) {
 }
 
 pub fn render_edit_database(area: Rect, buffer: &mut Buffer, state: &AppState) {
+    let backend_str = state.config.database.backend.to_string();
     let masked_password = mask_password(&state.config.database.password);
     let port_str = state.config.database.port.to_string();
     let max_conn_str = state.config.database.max_connections.to_string();
@@ -146,6 +152,12 @@ pub fn render_edit_database(area: Rect, buffer: &mut Buffer, state: &AppState) {
     let idle_timeout_str = state.config.database.idle_timeout.to_string();
 
     let fields = vec![
+        ("Backend", EditField::DbBackend, backend_str.as_str()),
+        (
+            "SQLite File",
+            EditField::DbSqlitePath,
+            state.config.database.sqlite_path.as_str(),
+        ),
         (
             "Host",
             EditField::DbHost,
@@ -1,13 +1,74 @@
-use std::path::PathBuf;
+use crate::{
+    APP_IDENTIFIER, ConfigFileInfo, ConfigStorage, DatabaseBackend, KEY_FILE_NAME, KeyKind,
+    KeySource, SslMode, app_config_dir,
+};
+use dialoguer::{Password, Select};
+use std::path::{Path, PathBuf};
 
+/// Configuration directory shared with the desktop app
 pub fn get_config_dir() -> PathBuf {
-    std::env::var("MEDITRACK_CONFIG_DIR")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| {
-            dirs::config_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join("meditrack")
-        })
+    app_config_dir(APP_IDENTIFIER).unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Ask for what the configuration in `config_dir` needs to be decrypted
+///
+/// Only password-protected files prompt for a password. A new configuration
+/// lets the user pick its key source; a file from before key sources were
+/// recorded is opened with the password, or the machine key when the
+/// password is left empty, and upgraded.
+pub fn prompt_key_source(config_dir: &Path) -> Result<KeySource, Box<dyn std::error::Error>> {
+    let key_source = match ConfigStorage::inspect(config_dir)? {
+        Some(ConfigFileInfo {
+            key: Some(kind), ..
+        }) => {
+            let password = match kind {
+                KeyKind::Password => Some(
+                    Password::new()
+                        .with_prompt("Enter configuration password")
+                        .allow_empty_password(false)
+                        .interact()?,
+                ),
+                _ => None,
+            };
+            KeySource::resolve(kind, config_dir, password.as_deref())?
+        }
+        Some(ConfigFileInfo { key: None, .. }) => {
+            let password = Password::new()
+                .with_prompt("Enter configuration password (empty if it was saved by the app)")
+                .allow_empty_password(true)
+                .interact()?;
+            let password = Some(password).filter(|password| !password.is_empty());
+            ConfigStorage::open("meditrack", config_dir.to_path_buf(), password.as_deref())?
+                .key_source()
+                .clone()
+        }
+        None => {
+            println!("No configuration found. Creating new configuration.");
+            let choice = Select::new()
+                .with_prompt("Protect the configuration with")
+                .items([
+                    "Machine key (the app starts without asking for anything)",
+                    "Password (the app needs MEDITRACK_CONFIG_PASSWORD)",
+                    "Key file (copy config.key to move the configuration)",
+                ])
+                .default(0)
+                .interact()?;
+            match choice {
+                0 => KeySource::Machine,
+                1 => KeySource::Password(
+                    Password::new()
+                        .with_prompt("Create configuration password")
+                        .with_confirmation("Confirm password", "Passwords do not match")
+                        .allow_empty_password(false)
+                        .interact()?,
+                ),
+                _ => KeySource::KeyFile(config_dir.join(KEY_FILE_NAME)),
+            }
+        }
+    };
+
+    println!("Using the {} key", key_source.kind());
+    Ok(key_source)
 }
 
 pub fn mask_password(password: &str) -> String {
@@ -18,6 +79,30 @@ pub fn mask_password(password: &str) -> String {
     }
 }
 
+pub fn validate_backend(input: &str) -> Result<DatabaseBackend, String> {
+    input.parse::<DatabaseBackend>()
+}
+
+pub fn validate_ssl_mode(input: &str) -> Result<SslMode, String> {
+    input.parse::<SslMode>()
+}
+
+/// Comma-separated schema names; a schema name cannot contain spaces
+pub fn validate_search_path(input: &str) -> Result<String, String> {
+    let schemas: Vec<&str> = input
+        .split(',')
+        .map(str::trim)
+        .filter(|schema| !schema.is_empty())
+        .collect();
+    if schemas
+        .iter()
+        .any(|schema| schema.contains(char::is_whitespace))
+    {
+        return Err("Invalid search path".to_string());
+    }
+    Ok(schemas.join(", "))
+}
+
 pub fn validate_port(input: &str) -> Result<u16, String> {
     input
         .parse::<u16>()
@@ -41,3 +126,22 @@ pub fn validate_i64(input: &str) -> Result<i64, String> {
         .parse::<i64>()
         .map_err(|_| "Invalid number".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_dir_matches_app() {
+        // The app resolves its directory through Tauri from the bundle
+        // identifier, and through `app_config_dir` before Tauri starts
+        let tauri_conf: serde_json::Value =
+            serde_json::from_str(include_str!("../../../../tauri.conf.json")).unwrap();
+        assert_eq!(tauri_conf["identifier"], APP_IDENTIFIER);
+
+        assert_eq!(Some(get_config_dir()), app_config_dir(APP_IDENTIFIER));
+        if std::env::var_os(crate::CONFIG_DIR_ENV).is_none() {
+            assert!(get_config_dir().ends_with(APP_IDENTIFIER));
+        }
+    }
+}
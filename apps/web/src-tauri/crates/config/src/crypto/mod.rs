@@ -6,10 +6,175 @@ use aes_gcm::{
     aead::{Aead, OsRng},
 };
 use argon2::{
-    Argon2,
+    Algorithm, Argon2, Params, Version,
     password_hash::{PasswordHasher, SaltString, rand_core::RngCore},
 };
 use base64::{Engine as _, engine::general_purpose};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the key file, next to `config.enc`
+pub const KEY_FILE_NAME: &str = "config.key";
+
+/// Where the configuration encryption key comes from
+#[derive(Clone, PartialEq, Eq)]
+pub enum KeySource {
+    /// Derived from a password entered by the user
+    Password(String),
+
+    /// Derived from this machine's hostname and user name; needs no input
+    Machine,
+
+    /// Random key kept in a file, standing in for the OS keyring
+    KeyFile(PathBuf),
+}
+
+impl KeySource {
+    /// Key source of `kind` for the configuration in `config_dir`
+    pub fn resolve(kind: KeyKind, config_dir: &Path, password: Option<&str>) -> Result<Self> {
+        match kind {
+            KeyKind::Password => password
+                .map(|password| Self::Password(password.to_string()))
+                .ok_or_else(|| {
+                    ConfigError::KeyUnavailable(
+                        "the configuration is password protected and no password was given"
+                            .to_string(),
+                    )
+                }),
+            KeyKind::Machine => Ok(Self::Machine),
+            KeyKind::KeyFile => Ok(Self::KeyFile(config_dir.join(KEY_FILE_NAME))),
+        }
+    }
+
+    pub fn kind(&self) -> KeyKind {
+        match self {
+            Self::Password(_) => KeyKind::Password,
+            Self::Machine => KeyKind::Machine,
+            Self::KeyFile(_) => KeyKind::KeyFile,
+        }
+    }
+}
+
+impl fmt::Debug for KeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the password
+        match self {
+            Self::Password(_) => write!(f, "Password(****)"),
+            Self::Machine => write!(f, "Machine"),
+            Self::KeyFile(path) => f.debug_tuple("KeyFile").field(path).finish(),
+        }
+    }
+}
+
+/// Kind of key source, as recorded in the configuration file header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    Password,
+    Machine,
+    KeyFile,
+}
+
+impl KeyKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Password => "password",
+            Self::Machine => "machine",
+            Self::KeyFile => "keyfile",
+        }
+    }
+}
+
+impl std::str::FromStr for KeyKind {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "password" => Ok(Self::Password),
+            "machine" => Ok(Self::Machine),
+            "keyfile" => Ok(Self::KeyFile),
+            other => Err(ConfigError::InvalidConfig(format!(
+                "Unknown key source: {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for KeyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Length of the random salt stored with each configuration file
+pub const SALT_LEN: usize = 16;
+
+/// Largest Argon2 memory cost accepted from a file header (1 GiB), so a
+/// damaged or hostile header cannot exhaust memory
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+
+/// Largest Argon2 pass count accepted from a file header, so a damaged or
+/// hostile header cannot stall startup
+const MAX_ITERATIONS: u32 = 16;
+
+/// Largest Argon2 degree of parallelism accepted from a file header
+const MAX_PARALLELISM: u32 = 64;
+
+/// Plaintext sealed into the key check value
+const KEY_CHECK_PLAINTEXT: &[u8] = b"meditrack-config-key";
+
+/// Argon2id parameters and salt a configuration key is derived with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+    pub salt: [u8; SALT_LEN],
+}
+
+impl KdfParams {
+    /// Default Argon2id parameters with a fresh random salt
+    pub fn generate() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+            salt,
+        }
+    }
+
+    fn argon2(&self) -> Result<Argon2<'static>> {
+        if self.memory_kib > MAX_MEMORY_KIB {
+            return Err(ConfigError::Corrupt(format!(
+                "Argon2 memory cost {} KiB exceeds the {} KiB limit",
+                self.memory_kib, MAX_MEMORY_KIB
+            )));
+        }
+        if self.iterations > MAX_ITERATIONS {
+            return Err(ConfigError::Corrupt(format!(
+                "Argon2 pass count {} exceeds the limit of {}",
+                self.iterations, MAX_ITERATIONS
+            )));
+        }
+        if self.parallelism > MAX_PARALLELISM {
+            return Err(ConfigError::Corrupt(format!(
+                "Argon2 parallelism {} exceeds the limit of {}",
+                self.parallelism, MAX_PARALLELISM
+            )));
+        }
+
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| ConfigError::Corrupt(format!("Invalid Argon2 parameters: {}", e)))?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
 
 /// Secure storage for configuration encryption
 #[derive(Clone)]
@@ -31,8 +196,115 @@ impl SecureStorage {
         Ok(Self { key })
     }
 
+    /// Create a new secure storage with the key from `source`, deriving
+    /// password and machine keys with `kdf`
+    ///
+    /// Key files hold the key itself, so `kdf` does not apply to them.
+    pub fn derive(source: &KeySource, kdf: &KdfParams) -> Result<Self> {
+        let secret = match source {
+            KeySource::Password(password) => password.clone(),
+            KeySource::Machine => Self::get_machine_id()?,
+            KeySource::KeyFile(path) => return Self::from_key_file(path),
+        };
+
+        let mut key = [0u8; 32];
+        kdf.argon2()?
+            .hash_password_into(secret.as_bytes(), &kdf.salt, &mut key)
+            .map_err(|e| ConfigError::Encryption(format!("Key derivation failed: {}", e)))?;
+        Ok(Self { key })
+    }
+
+    /// Create a new secure storage with the key from `source`, using the
+    /// fixed salts of configuration files before format 2
+    pub fn from_key_source(source: &KeySource) -> Result<Self> {
+        match source {
+            KeySource::Password(password) => Self::new_with_password(password),
+            KeySource::Machine => Self::new(),
+            KeySource::KeyFile(path) => Self::from_key_file(path),
+        }
+    }
+
+    /// Create a new secure storage with the key kept in `path`
+    pub fn from_key_file(path: &Path) -> Result<Self> {
+        let encoded = fs::read_to_string(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                ConfigError::KeyUnavailable(format!("key file not found: {}", path.display()))
+            }
+            _ => ConfigError::Io(e),
+        })?;
+
+        let key = general_purpose::STANDARD
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| {
+                ConfigError::KeyUnavailable(format!("invalid key file: {}", path.display()))
+            })?;
+        Ok(Self { key })
+    }
+
+    /// Generate a random key and write it to a new key file at `path`,
+    /// readable only by the current user
+    pub fn create_key_file(path: &Path) -> Result<Self> {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(path)?;
+        std::io::Write::write_all(&mut file, general_purpose::STANDARD.encode(key).as_bytes())?;
+
+        tracing::info!("Created configuration key file: {}", path.display());
+        Ok(Self { key })
+    }
+
+    /// Value stored next to the ciphertext that tells a wrong key apart
+    /// from damaged data: the key seals a known plaintext
+    pub fn key_check(&self) -> Result<String> {
+        self.encrypt_bytes(KEY_CHECK_PLAINTEXT)
+            .map(|sealed| general_purpose::STANDARD.encode(sealed))
+    }
+
+    /// Whether this is the key that produced `check`
+    pub fn verify_key_check(&self, check: &str) -> bool {
+        general_purpose::STANDARD
+            .decode(check)
+            .ok()
+            .and_then(|sealed| self.decrypt_bytes(&sealed).ok())
+            .is_some_and(|plaintext| plaintext == KEY_CHECK_PLAINTEXT)
+    }
+
     /// Encrypt data
     pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let encrypted = self.encrypt_bytes(plaintext.as_bytes())?;
+
+        // Encode as base64
+        Ok(general_purpose::STANDARD.encode(encrypted))
+    }
+
+    /// Decrypt data
+    pub fn decrypt(&self, ciphertext: &str) -> Result<String> {
+        // Decode from base64
+        let data = general_purpose::STANDARD
+            .decode(ciphertext)
+            .map_err(|e| ConfigError::Decryption(e.to_string()))?;
+
+        let plaintext = self.decrypt_bytes(&data)?;
+
+        // Convert to string
+        String::from_utf8(plaintext)
+            .map_err(|e| ConfigError::Decryption(format!("Invalid UTF-8: {}", e)))
+    }
+
+    /// Encrypt binary data, returning the nonce followed by the ciphertext
+    pub fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
         // Generate a random nonce
         let mut nonce_bytes = [0u8; 12];
         OsRng.fill_bytes(&mut nonce_bytes);
@@ -43,24 +315,17 @@ impl SecureStorage {
 
         // Encrypt the data
         let ciphertext = cipher
-            .encrypt(nonce, plaintext.as_bytes())
+            .encrypt(nonce, plaintext)
             .map_err(|e| ConfigError::Encryption(e.to_string()))?;
 
         // Combine nonce and ciphertext
         let mut result = nonce_bytes.to_vec();
         result.extend_from_slice(&ciphertext);
-
-        // Encode as base64
-        Ok(general_purpose::STANDARD.encode(result))
+        Ok(result)
     }
 
-    /// Decrypt data
-    pub fn decrypt(&self, ciphertext: &str) -> Result<String> {
-        // Decode from base64
-        let data = general_purpose::STANDARD
-            .decode(ciphertext)
-            .map_err(|e| ConfigError::Decryption(e.to_string()))?;
-
+    /// Decrypt binary data produced by [`Self::encrypt_bytes`]
+    pub fn decrypt_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
         // Split nonce and ciphertext
         if data.len() < 12 {
             return Err(ConfigError::Decryption(
@@ -75,19 +340,15 @@ impl SecureStorage {
         let cipher = Aes256Gcm::new(&self.key.into());
 
         // Decrypt the data
-        let plaintext = cipher
+        cipher
             .decrypt(nonce, ciphertext_bytes)
-            .map_err(|e| ConfigError::Decryption(e.to_string()))?;
-
-        // Convert to string
-        String::from_utf8(plaintext)
-            .map_err(|e| ConfigError::Decryption(format!("Invalid UTF-8: {}", e)))
+            .map_err(|e| ConfigError::Decryption(e.to_string()))
     }
 
     /// Derive encryption key from password using Argon2
     fn derive_key_from_password(password: &str) -> Result<[u8; 32]> {
-        // Use a fixed salt for password-based encryption
-        // In production, you might want to store this salt with the encrypted data
+        // Fixed salt of configuration files before format 2; newer files use
+        // `derive` with their own salt
         let salt = SaltString::from_b64("bWVkaXRyYWNrY29uZmlnc2FsdA")
             .map_err(|e| ConfigError::Encryption(format!("Invalid salt: {}", e)))?;
 
@@ -168,6 +429,89 @@ mod tests {
         assert_ne!(ciphertext1, ciphertext2);
     }
 
+    #[test]
+    fn test_encrypt_decrypt_bytes() {
+        let storage = SecureStorage::new_with_password("backup-password").unwrap();
+        let data = [0u8, 159, 146, 150, 255];
+        let encrypted = storage.encrypt_bytes(&data).unwrap();
+        assert_eq!(storage.decrypt_bytes(&encrypted).unwrap(), data);
+
+        let other = SecureStorage::new_with_password("wrong-password").unwrap();
+        assert!(other.decrypt_bytes(&encrypted).is_err());
+    }
+
+    #[test]
+    fn test_derive_with_salt_and_key_check() {
+        let kdf = KdfParams {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+            salt: [7; SALT_LEN],
+        };
+        let source = KeySource::Password("secret".to_string());
+        let storage = SecureStorage::derive(&source, &kdf).unwrap();
+        let check = storage.key_check().unwrap();
+
+        // Same password and salt give the same key
+        let again = SecureStorage::derive(&source, &kdf).unwrap();
+        assert!(again.verify_key_check(&check));
+
+        // A different password or salt does not
+        let wrong = SecureStorage::derive(&KeySource::Password("wrong".to_string()), &kdf).unwrap();
+        assert!(!wrong.verify_key_check(&check));
+        let resalted = KdfParams {
+            salt: [8; SALT_LEN],
+            ..kdf
+        };
+        assert!(
+            !SecureStorage::derive(&source, &resalted)
+                .unwrap()
+                .verify_key_check(&check)
+        );
+
+        for hostile in [
+            KdfParams {
+                memory_kib: u32::MAX,
+                ..kdf
+            },
+            KdfParams {
+                iterations: MAX_ITERATIONS + 1,
+                ..kdf
+            },
+            KdfParams {
+                parallelism: MAX_PARALLELISM + 1,
+                ..kdf
+            },
+        ] {
+            assert!(matches!(
+                SecureStorage::derive(&source, &hostile),
+                Err(ConfigError::Corrupt(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_key_file() {
+        let path = std::env::temp_dir()
+            .join(format!("meditrack-key-{}", std::process::id()))
+            .join(KEY_FILE_NAME);
+        let _ = fs::remove_file(&path);
+
+        assert!(matches!(
+            SecureStorage::from_key_file(&path),
+            Err(ConfigError::KeyUnavailable(_))
+        ));
+
+        let created = SecureStorage::create_key_file(&path).unwrap();
+        let ciphertext = created.encrypt("Hello, World!").unwrap();
+        let loaded = SecureStorage::from_key_source(&KeySource::KeyFile(path.clone())).unwrap();
+        assert_eq!(loaded.decrypt(&ciphertext).unwrap(), "Hello, World!");
+
+        // An existing key is never overwritten
+        assert!(SecureStorage::create_key_file(&path).is_err());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
     #[test]
     fn test_decrypt_invalid_data() {
         let storage = SecureStorage::new().unwrap();
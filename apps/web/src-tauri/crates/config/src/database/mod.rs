@@ -1,10 +1,104 @@
 //! Database configuration types and utilities
 
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::error::{ConfigError, Result};
+
+/// Database engine used by the application
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseBackend {
+    /// PostgreSQL server (multi-workstation setups)
+    #[default]
+    Postgres,
+
+    /// Embedded SQLite file (single-PC setups)
+    Sqlite,
+}
+
+impl std::str::FromStr for DatabaseBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            "sqlite" => Ok(Self::Sqlite),
+            other => Err(format!("Unknown database backend: {}", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for DatabaseBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Postgres => write!(f, "PostgreSQL"),
+            Self::Sqlite => write!(f, "SQLite"),
+        }
+    }
+}
+
+/// TLS mode of PostgreSQL connections, as libpq's `sslmode`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SslMode {
+    /// Plain connection only
+    Disable,
+    /// Plain connection, TLS if the server insists
+    Allow,
+    /// TLS if the server supports it
+    #[default]
+    Prefer,
+    /// TLS without checking the certificate
+    Require,
+    /// TLS with a certificate signed by the root CA
+    VerifyCa,
+    /// As `VerifyCa`, and the certificate must name the host
+    VerifyFull,
+}
+
+impl std::str::FromStr for SslMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "disable" => Ok(Self::Disable),
+            "allow" => Ok(Self::Allow),
+            "prefer" => Ok(Self::Prefer),
+            "require" => Ok(Self::Require),
+            "verify-ca" => Ok(Self::VerifyCa),
+            "verify-full" => Ok(Self::VerifyFull),
+            other => Err(format!("Unknown SSL mode: {}", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for SslMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Disable => write!(f, "disable"),
+            Self::Allow => write!(f, "allow"),
+            Self::Prefer => write!(f, "prefer"),
+            Self::Require => write!(f, "require"),
+            Self::VerifyCa => write!(f, "verify-ca"),
+            Self::VerifyFull => write!(f, "verify-full"),
+        }
+    }
+}
 
 /// Database configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
+    /// Database engine
+    #[serde(default)]
+    pub backend: DatabaseBackend,
+
+    /// SQLite database file (relative paths resolve against the config directory)
+    #[serde(default = "default_sqlite_path")]
+    pub sqlite_path: String,
+
     /// Database host
     pub host: String,
 
@@ -31,29 +125,151 @@ pub struct DatabaseConfig {
 
     /// Idle timeout in seconds
     pub idle_timeout: u64,
+
+    /// PostgreSQL TLS mode
+    #[serde(default)]
+    pub ssl_mode: SslMode,
+
+    /// Root CA certificate the server certificate is checked against; empty
+    /// for the system roots
+    #[serde(default)]
+    pub ssl_root_cert: String,
+
+    /// Client certificate for certificate authentication; empty for none
+    #[serde(default)]
+    pub ssl_client_cert: String,
+
+    /// Private key of the client certificate
+    #[serde(default)]
+    pub ssl_client_key: String,
+
+    /// Name the server shows for the connections, e.g. in `pg_stat_activity`
+    #[serde(default = "default_application_name")]
+    pub application_name: String,
+
+    /// Statement timeout in seconds; 0 for none
+    #[serde(default)]
+    pub statement_timeout: u64,
+
+    /// Comma-separated schema search path; empty for the server's default
+    #[serde(default)]
+    pub search_path: String,
+}
+
+fn default_sqlite_path() -> String {
+    "meditrack.db".to_string()
+}
+
+fn default_application_name() -> String {
+    "meditrack".to_string()
 }
 
 impl DatabaseConfig {
-    /// Build PostgreSQL connection URL from configuration
-    pub fn connection_url(&self) -> String {
-        format!(
-            "postgresql://{}:{}@{}:{}/{}",
-            self.username, self.password, self.host, self.port, self.database
-        )
+    /// Build the connection URL for the configured backend
+    ///
+    /// Credentials and options are percent-encoded, so they may contain `@`,
+    /// `/` and the like. SQLite files are created on first connect.
+    pub fn connection_url(&self) -> Result<String> {
+        match self.backend {
+            DatabaseBackend::Postgres => self.postgres_url().map(String::from),
+            DatabaseBackend::Sqlite => Ok(format!("sqlite://{}?mode=rwc", self.sqlite_path)),
+        }
+    }
+
+    fn postgres_url(&self) -> Result<Url> {
+        let invalid = |what: &str| ConfigError::InvalidConfig(format!("Invalid database {}", what));
+
+        let mut url = Url::parse("postgresql://localhost").expect("static URL");
+        url.set_host(Some(&self.host))
+            .map_err(|_| invalid("host"))?;
+        url.set_port(Some(self.port)).map_err(|_| invalid("port"))?;
+        // `Url` encodes everything in user info but `%`, which it takes for
+        // an escape that is already there
+        url.set_username(&self.username.replace('%', "%25"))
+            .map_err(|_| invalid("username"))?;
+        if !self.password.is_empty() {
+            url.set_password(Some(&self.password.replace('%', "%25")))
+                .map_err(|_| invalid("password"))?;
+        }
+        url.path_segments_mut()
+            .map_err(|_| invalid("name"))?
+            .push(&self.database);
+
+        let search_path = self.normalized_search_path()?;
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("sslmode", &self.ssl_mode.to_string());
+            for (key, value) in [
+                ("sslrootcert", &self.ssl_root_cert),
+                ("sslcert", &self.ssl_client_cert),
+                ("sslkey", &self.ssl_client_key),
+                ("application_name", &self.application_name),
+            ] {
+                if !value.is_empty() {
+                    query.append_pair(key, value);
+                }
+            }
+            // Run-time parameters go to the server as `-c key=value` options
+            if self.statement_timeout > 0 {
+                query.append_pair(
+                    "options[statement_timeout]",
+                    &format!("{}s", self.statement_timeout),
+                );
+            }
+            if !search_path.is_empty() {
+                query.append_pair("options[search_path]", &search_path);
+            }
+        }
+
+        Ok(url)
+    }
+
+    /// `search_path` without the spaces the server's options string cannot
+    /// carry
+    fn normalized_search_path(&self) -> Result<String> {
+        let schemas: Vec<&str> = self
+            .search_path
+            .split(',')
+            .map(str::trim)
+            .filter(|schema| !schema.is_empty())
+            .collect();
+        if schemas
+            .iter()
+            .any(|schema| schema.contains(char::is_whitespace))
+        {
+            return Err(ConfigError::InvalidConfig(format!(
+                "Invalid search path: {}",
+                self.search_path
+            )));
+        }
+        Ok(schemas.join(","))
+    }
+
+    /// Make a relative `sqlite_path` absolute by joining it onto `base_dir`
+    pub fn resolve_sqlite_path(&mut self, base_dir: &Path) {
+        let path = Path::new(&self.sqlite_path);
+        if path.is_relative() {
+            self.sqlite_path = base_dir.join(path).to_string_lossy().into_owned();
+        }
     }
 
     /// Get safe representation (without password)
     pub fn safe_repr(&self) -> String {
-        format!(
-            "PostgreSQL: {}@{}:{}/{}",
-            self.username, self.host, self.port, self.database
-        )
+        match self.backend {
+            DatabaseBackend::Postgres => format!(
+                "PostgreSQL: {}@{}:{}/{}",
+                self.username, self.host, self.port, self.database
+            ),
+            DatabaseBackend::Sqlite => format!("SQLite: {}", self.sqlite_path),
+        }
     }
 }
 
 impl Default for DatabaseConfig {
     fn default() -> Self {
         Self {
+            backend: DatabaseBackend::default(),
+            sqlite_path: default_sqlite_path(),
             host: "localhost".to_string(),
             port: 5432,
             database: "meditrack".to_string(),
@@ -63,6 +279,99 @@ impl Default for DatabaseConfig {
             min_connections: 2,
             connect_timeout: 30,
             idle_timeout: 600,
+            ssl_mode: SslMode::default(),
+            ssl_root_cert: String::new(),
+            ssl_client_cert: String::new(),
+            ssl_client_key: String::new(),
+            application_name: default_application_name(),
+            statement_timeout: 0,
+            search_path: String::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_url_escapes_credentials_and_options() {
+        let config = DatabaseConfig {
+            host: "db.example.com".to_string(),
+            port: 6432,
+            database: "medi track".to_string(),
+            username: "pharm@cy".to_string(),
+            password: "p@ss/w:rd?#%41".to_string(),
+            ssl_mode: SslMode::VerifyFull,
+            ssl_root_cert: "/etc/ssl/root ca.pem".to_string(),
+            statement_timeout: 30,
+            search_path: "meditrack, public".to_string(),
+            ..DatabaseConfig::default()
+        };
+
+        let url = Url::parse(&config.connection_url().unwrap()).unwrap();
+        assert_eq!(url.scheme(), "postgresql");
+        assert_eq!(url.host_str(), Some("db.example.com"));
+        assert_eq!(url.port(), Some(6432));
+        assert_eq!(url.path(), "/medi%20track");
+
+        let decode = |value: &str| {
+            url::form_urlencoded::parse(format!("x={}", value).as_bytes())
+                .next()
+                .unwrap()
+                .1
+                .into_owned()
+        };
+        assert_eq!(decode(url.username()), "pharm@cy");
+        assert_eq!(decode(url.password().unwrap()), "p@ss/w:rd?#%41");
+
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        let pairs: Vec<(&str, &str)> = query
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("sslmode", "verify-full"),
+                ("sslrootcert", "/etc/ssl/root ca.pem"),
+                ("application_name", "meditrack"),
+                ("options[statement_timeout]", "30s"),
+                ("options[search_path]", "meditrack,public"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_connection_settings() {
+        let config = DatabaseConfig {
+            search_path: "my schema".to_string(),
+            ..DatabaseConfig::default()
+        };
+        assert!(matches!(
+            config.connection_url(),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+
+        let config = DatabaseConfig {
+            host: "local host".to_string(),
+            ..DatabaseConfig::default()
+        };
+        assert!(matches!(
+            config.connection_url(),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+
+        assert_eq!("verify_ca".parse::<SslMode>(), Ok(SslMode::VerifyCa));
+        assert!("strict".parse::<SslMode>().is_err());
+
+        let sqlite = DatabaseConfig {
+            backend: DatabaseBackend::Sqlite,
+            ..DatabaseConfig::default()
+        };
+        assert_eq!(
+            sqlite.connection_url().unwrap(),
+            "sqlite://meditrack.db?mode=rwc"
+        );
+    }
+}
@@ -24,6 +24,26 @@ pub enum ConfigError {
     #[error("Decryption error: {0}")]
     Decryption(String),
 
+    /// The password does not match the one the configuration was encrypted with
+    #[error("Wrong password for the configuration")]
+    WrongPassword,
+
+    /// The configuration file is damaged: the key matches but the data does
+    /// not decrypt, or the header cannot be read
+    #[error("Configuration file is corrupt: {0}")]
+    Corrupt(String),
+
+    /// The key the configuration is encrypted with cannot be obtained
+    #[error("Encryption key unavailable: {0}")]
+    KeyUnavailable(String),
+
+    /// The configuration was written by a newer version of the app, whose
+    /// settings this version would lose
+    #[error(
+        "Configuration version {found} was written by a newer version of MediTrack (this one reads up to version {supported}); update MediTrack to use it"
+    )]
+    NewerVersion { found: u32, supported: u32 },
+
     /// Invalid configuration
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
@@ -0,0 +1,443 @@
+//! Layered configuration: defaults, then `config.enc`, then an optional plain
+//! TOML file, then `MEDITRACK_*` environment variables, then command-line
+//! overrides
+//!
+//! Every setting has a dotted key (`database.host`), which is also its TOML
+//! path, its `--set` name and, upper-cased with `_` for `.`, its environment
+//! variable (`MEDITRACK_DATABASE_HOST`). Layers only change the configuration
+//! in memory; `config.enc` is never rewritten with them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::AppConfig;
+use crate::error::{ConfigError, Result};
+
+/// Prefix of the environment variables that override settings
+pub const ENV_PREFIX: &str = "MEDITRACK_";
+
+/// Environment variable naming the plain TOML file to layer in
+pub const CONFIG_FILE_ENV: &str = "MEDITRACK_CONFIG_FILE";
+
+/// Plain TOML file picked up from the config directory when no other is named
+pub const CONFIG_TOML_FILE_NAME: &str = "config.toml";
+
+/// A setting that can be overridden
+pub struct Setting {
+    /// Dotted key, e.g. `database.host`
+    pub key: &'static str,
+
+    /// Masked when printed
+    pub secret: bool,
+
+    get: fn(&AppConfig) -> String,
+    set: fn(&mut AppConfig, &str) -> std::result::Result<(), String>,
+}
+
+impl Setting {
+    /// Environment variable overriding this setting
+    pub fn env_var(&self) -> String {
+        format!(
+            "{}{}",
+            ENV_PREFIX,
+            self.key.replace('.', "_").to_ascii_uppercase()
+        )
+    }
+
+    /// Current value in `config`
+    pub fn value(&self, config: &AppConfig) -> String {
+        (self.get)(config)
+    }
+
+    /// Current value in `config`, masked for secrets
+    pub fn display_value(&self, config: &AppConfig) -> String {
+        if self.secret {
+            "****".to_string()
+        } else {
+            self.value(config)
+        }
+    }
+
+    /// Parse `value` into `config`
+    pub fn set_value(&self, config: &mut AppConfig, value: &str) -> Result<()> {
+        (self.set)(config, value).map_err(|e| {
+            ConfigError::InvalidConfig(format!("Invalid value for {}: {}", self.key, e))
+        })
+    }
+}
+
+macro_rules! setting {
+    ($section:ident . $field:ident) => {
+        setting!($section.$field, false)
+    };
+    ($section:ident . $field:ident, secret) => {
+        setting!($section.$field, true)
+    };
+    ($section:ident . $field:ident, $secret:literal) => {
+        Setting {
+            key: concat!(stringify!($section), ".", stringify!($field)),
+            secret: $secret,
+            get: |config| config.$section.$field.to_string(),
+            set: |config, value| {
+                config.$section.$field = value.parse().map_err(|e| format!("{}", e))?;
+                Ok(())
+            },
+        }
+    };
+}
+
+/// All settings, in the order they are printed
+pub const SETTINGS: &[Setting] = &[
+    setting!(database.backend),
+    setting!(database.sqlite_path),
+    setting!(database.host),
+    setting!(database.port),
+    setting!(database.database),
+    setting!(database.username),
+    setting!(database.password, secret),
+    setting!(database.max_connections),
+    setting!(database.min_connections),
+    setting!(database.connect_timeout),
+    setting!(database.idle_timeout),
+    setting!(database.ssl_mode),
+    setting!(database.ssl_root_cert),
+    setting!(database.ssl_client_cert),
+    setting!(database.ssl_client_key),
+    setting!(database.application_name),
+    setting!(database.statement_timeout),
+    setting!(database.search_path),
+    setting!(jwt.secret, secret),
+    setting!(jwt.issuer),
+    setting!(jwt.audience),
+    setting!(jwt.expiration_hours),
+    setting!(jwt.signing_key),
+];
+
+/// Look up a setting by its dotted key
+pub fn setting(key: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|setting| setting.key == key)
+}
+
+/// Layer a setting's effective value comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in default
+    Default,
+    /// The encrypted `config.enc`
+    Stored,
+    /// A plain TOML file
+    File(PathBuf),
+    /// An environment variable
+    Env(String),
+    /// A `--set` flag
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::Stored => write!(f, "config.enc"),
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Env(name) => write!(f, "env {}", name),
+            Self::Cli => write!(f, "--set"),
+        }
+    }
+}
+
+/// Overrides layered over the stored configuration
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLayers {
+    /// Plain TOML file; [`CONFIG_TOML_FILE_NAME`] in the config directory is
+    /// used when it exists and none is named
+    pub file: Option<PathBuf>,
+
+    /// `MEDITRACK_*` environment variables
+    pub env: Vec<(String, String)>,
+
+    /// `--set key=value` overrides, applied in order
+    pub cli: Vec<(String, String)>,
+}
+
+impl ConfigLayers {
+    /// Layers from the process environment and the given command-line
+    /// overrides; a `file` given on the command line wins over
+    /// [`CONFIG_FILE_ENV`]
+    pub fn from_env(file: Option<PathBuf>, cli: Vec<(String, String)>) -> Self {
+        let env: Vec<(String, String)> = std::env::vars()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+            .collect();
+        let file = file.or_else(|| {
+            env.iter()
+                .find(|(name, _)| name == CONFIG_FILE_ENV)
+                .map(|(_, path)| PathBuf::from(path))
+        });
+
+        Self { file, env, cli }
+    }
+
+    /// Apply the layers to `config`, read from `config.enc` when `stored` is
+    /// set and the defaults otherwise
+    pub fn apply(
+        &self,
+        mut config: AppConfig,
+        stored: bool,
+        config_dir: &Path,
+    ) -> Result<EffectiveConfig> {
+        let base = if stored {
+            ConfigSource::Stored
+        } else {
+            ConfigSource::Default
+        };
+        let mut sources: BTreeMap<&'static str, ConfigSource> = SETTINGS
+            .iter()
+            .map(|setting| (setting.key, base.clone()))
+            .collect();
+
+        // Plain TOML file
+        let file = self
+            .file
+            .clone()
+            .or_else(|| Some(config_dir.join(CONFIG_TOML_FILE_NAME)).filter(|path| path.exists()));
+        if let Some(path) = file {
+            for (key, value) in read_toml(&path)? {
+                let setting = setting(&key).ok_or_else(|| {
+                    ConfigError::InvalidConfig(format!(
+                        "Unknown setting {} in {}",
+                        key,
+                        path.display()
+                    ))
+                })?;
+                (setting.set)(&mut config, &value).map_err(|e| {
+                    ConfigError::InvalidConfig(format!(
+                        "Invalid value for {} in {}: {}",
+                        key,
+                        path.display(),
+                        e
+                    ))
+                })?;
+                sources.insert(setting.key, ConfigSource::File(path.clone()));
+            }
+        }
+
+        // Environment variables
+        for setting in SETTINGS {
+            let name = setting.env_var();
+            if let Some((_, value)) = self.env.iter().find(|(var, _)| *var == name) {
+                (setting.set)(&mut config, value).map_err(|e| {
+                    ConfigError::InvalidConfig(format!("Invalid value for {}: {}", name, e))
+                })?;
+                sources.insert(setting.key, ConfigSource::Env(name));
+            }
+        }
+
+        // Command-line overrides
+        for (key, value) in &self.cli {
+            let setting = setting(key)
+                .ok_or_else(|| ConfigError::InvalidConfig(format!("Unknown setting {}", key)))?;
+            (setting.set)(&mut config, value).map_err(|e| {
+                ConfigError::InvalidConfig(format!("Invalid value for {}: {}", key, e))
+            })?;
+            sources.insert(setting.key, ConfigSource::Cli);
+        }
+
+        Ok(EffectiveConfig { config, sources })
+    }
+}
+
+/// Read a plain TOML file into dotted keys and their values
+fn read_toml(path: &Path) -> Result<Vec<(String, String)>> {
+    let content = fs::read_to_string(path)?;
+    let table: toml::Table = toml::from_str(&content).map_err(|e| {
+        ConfigError::InvalidConfig(format!("Invalid TOML in {}: {}", path.display(), e))
+    })?;
+
+    let mut values = Vec::new();
+    flatten_toml(path, "", table, &mut values)?;
+    Ok(values)
+}
+
+fn flatten_toml(
+    path: &Path,
+    prefix: &str,
+    table: toml::Table,
+    values: &mut Vec<(String, String)>,
+) -> Result<()> {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+
+        let value = match value {
+            toml::Value::Table(table) => {
+                flatten_toml(path, &key, table, values)?;
+                continue;
+            }
+            toml::Value::String(value) => value,
+            toml::Value::Integer(value) => value.to_string(),
+            toml::Value::Float(value) => value.to_string(),
+            toml::Value::Boolean(value) => value.to_string(),
+            _ => {
+                return Err(ConfigError::InvalidConfig(format!(
+                    "Unsupported value for {} in {}",
+                    key,
+                    path.display()
+                )));
+            }
+        };
+        values.push((key, value));
+    }
+    Ok(())
+}
+
+/// Configuration with all layers applied, and where each value came from
+#[derive(Debug, Clone)]
+pub struct EffectiveConfig {
+    pub config: AppConfig,
+    sources: BTreeMap<&'static str, ConfigSource>,
+}
+
+impl EffectiveConfig {
+    /// Layer the value of `key` comes from
+    pub fn source(&self, key: &str) -> Option<&ConfigSource> {
+        self.sources.get(key)
+    }
+
+    /// Settings changed by a layer above `config.enc`
+    pub fn overrides(&self) -> impl Iterator<Item = (&'static str, &ConfigSource)> {
+        self.sources
+            .iter()
+            .filter_map(|(key, source)| match source {
+                ConfigSource::Default | ConfigSource::Stored => None,
+                source => Some((*key, source)),
+            })
+    }
+}
+
+impl fmt::Display for EffectiveConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = SETTINGS
+            .iter()
+            .map(|setting| setting.key.len())
+            .max()
+            .unwrap_or(0);
+
+        for setting in SETTINGS {
+            let source = self
+                .sources
+                .get(setting.key)
+                .unwrap_or(&ConfigSource::Default);
+            writeln!(
+                f,
+                "{:width$} = {}  ({})",
+                setting.key,
+                setting.display_value(&self.config),
+                source,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_layers_apply_in_order() {
+        let dir = std::env::temp_dir().join(format!("meditrack-layers-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let toml_path = dir.join(CONFIG_TOML_FILE_NAME);
+        fs::write(
+            &toml_path,
+            "[database]\nhost = \"db\"\nport = 6432\n\n[jwt]\nissuer = \"from-file\"\n",
+        )
+        .unwrap();
+
+        let layers = ConfigLayers {
+            file: None,
+            env: pairs(&[
+                ("MEDITRACK_DATABASE_PORT", "7432"),
+                ("MEDITRACK_JWT_SECRET", "env-secret"),
+                ("MEDITRACK_CONFIG_PASSWORD", "not-a-setting"),
+            ]),
+            cli: pairs(&[("database.port", "8432")]),
+        };
+        let effective = layers.apply(AppConfig::default(), true, &dir).unwrap();
+
+        assert_eq!(effective.config.database.host, "db");
+        assert_eq!(effective.config.database.port, 8432);
+        assert_eq!(effective.config.jwt.secret, "env-secret");
+        assert_eq!(effective.config.jwt.issuer, "from-file");
+        assert_eq!(
+            effective.source("database.host"),
+            Some(&ConfigSource::File(toml_path.clone()))
+        );
+        assert_eq!(effective.source("database.port"), Some(&ConfigSource::Cli));
+        assert_eq!(
+            effective.source("jwt.secret"),
+            Some(&ConfigSource::Env("MEDITRACK_JWT_SECRET".to_string()))
+        );
+        assert_eq!(
+            effective.source("database.username"),
+            Some(&ConfigSource::Stored)
+        );
+        assert_eq!(effective.overrides().count(), 4);
+
+        // Secrets are masked when printed
+        let printed = effective.to_string();
+        assert!(printed.lines().any(|line| line.starts_with("jwt.secret ")
+            && line.ends_with("= ****  (env MEDITRACK_JWT_SECRET)")));
+        assert!(!printed.contains("env-secret"));
+        assert!(!printed.contains("meditrack_dev_password"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_layers_reject_unknown_and_invalid_values() {
+        let dir = std::env::temp_dir().join("meditrack-layers-missing");
+
+        for layers in [
+            ConfigLayers {
+                cli: pairs(&[("database.hots", "db")]),
+                ..Default::default()
+            },
+            ConfigLayers {
+                cli: pairs(&[("database.port", "five")]),
+                ..Default::default()
+            },
+            ConfigLayers {
+                env: pairs(&[("MEDITRACK_DATABASE_BACKEND", "oracle")]),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                layers.apply(AppConfig::default(), false, &dir),
+                Err(ConfigError::InvalidConfig(_))
+            ));
+        }
+
+        // A named file must exist
+        let layers = ConfigLayers {
+            file: Some(dir.join("missing.toml")),
+            ..Default::default()
+        };
+        assert!(matches!(
+            layers.apply(AppConfig::default(), false, &dir),
+            Err(ConfigError::Io(_))
+        ));
+    }
+}
@@ -4,14 +4,47 @@ use serde::{Deserialize, Serialize};
 mod crypto;
 mod database;
 mod error;
+mod layers;
 mod storage;
+mod upgrade;
 
+pub mod cli;
 pub mod cli_tui;
 
 // Re-export types
-pub use database::DatabaseConfig;
+pub use crypto::{KEY_FILE_NAME, KdfParams, KeyKind, KeySource, SecureStorage};
+pub use database::{DatabaseBackend, DatabaseConfig, SslMode};
 pub use error::{ConfigError, Result};
-pub use storage::ConfigStorage;
+pub use layers::{
+    CONFIG_FILE_ENV, CONFIG_TOML_FILE_NAME, ConfigLayers, ConfigSource, EffectiveConfig, SETTINGS,
+    Setting, setting,
+};
+pub use storage::{ConfigFileInfo, ConfigStorage, DEFAULT_PROFILE, DEV_PROFILE, TRAINING_PROFILE};
+pub use upgrade::CONFIG_VERSION;
+
+/// Environment variable holding the password of a password-protected
+/// configuration, for processes that cannot prompt for it
+pub const CONFIG_PASSWORD_ENV: &str = "MEDITRACK_CONFIG_PASSWORD";
+
+/// Bundle identifier from `tauri.conf.json`, which names the config directory
+pub const APP_IDENTIFIER: &str = "com.pharmacy.meditrack";
+
+/// Environment variable pointing the app, the TUI and the seeder at another
+/// configuration directory
+pub const CONFIG_DIR_ENV: &str = "MEDITRACK_CONFIG_DIR";
+
+/// Directory the desktop app keeps its configuration in
+///
+/// Matches Tauri's `PathResolver::app_config_dir` for the bundle
+/// `identifier`, unless [`CONFIG_DIR_ENV`] is set. The app, the
+/// configuration TUI and the seeder all resolve it here so they read and
+/// write the same files.
+pub fn app_config_dir(identifier: &str) -> Option<std::path::PathBuf> {
+    match std::env::var_os(CONFIG_DIR_ENV) {
+        Some(dir) if !dir.is_empty() => Some(std::path::PathBuf::from(dir)),
+        _ => dirs::config_dir().map(|dir| dir.join(identifier)),
+    }
+}
 
 /// Main application configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Getters)]
@@ -20,6 +53,10 @@ pub struct AppConfig {
     #[serde(skip)]
     app_name: String,
 
+    /// Configuration version the document was written in; always
+    /// [`CONFIG_VERSION`] once loaded
+    version: u32,
+
     /// Database configuration
     #[serde(default)]
     pub database: DatabaseConfig,
@@ -27,12 +64,17 @@ pub struct AppConfig {
     /// JWT configuration
     #[serde(default)]
     pub jwt: JwtConfig,
+
+    /// Training profile: the app shows a banner so nobody mistakes it for
+    /// the live system
+    #[serde(default)]
+    pub training: bool,
 }
 
 /// JWT configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JwtConfig {
-    /// JWT secret key (should be changed in production)
+    /// JWT secret key, random for a new configuration
     pub secret: String,
 
     /// JWT issuer
@@ -43,15 +85,113 @@ pub struct JwtConfig {
 
     /// Token expiration in hours
     pub expiration_hours: i64,
+
+    /// Secrets replaced by [`JwtConfig::rotate_secret`], still accepted for
+    /// verification until their grace periods end
+    #[serde(default)]
+    pub retired_secrets: Vec<RetiredJwtSecret>,
+
+    /// Key pairs for EdDSA and RS256 tokens; see [`JwtConfig::rotate_key`]
+    #[serde(default)]
+    pub keys: Vec<jwt_keys::JwtKey>,
+
+    /// `kid` of the key pair tokens are signed with; empty to sign with
+    /// `secret` (HS256)
+    #[serde(default)]
+    pub signing_key: String,
+}
+
+/// JWT secret replaced by a rotation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetiredJwtSecret {
+    pub secret: String,
+
+    /// End of the grace period (Unix timestamp)
+    pub valid_until: i64,
+}
+
+impl JwtConfig {
+    /// Random secret of 64 bytes, base64url-encoded
+    pub fn generate_secret() -> String {
+        use aes_gcm::aead::{OsRng, rand_core::RngCore};
+        use base64::{Engine as _, engine::general_purpose};
+
+        let mut secret = [0u8; 64];
+        OsRng.fill_bytes(&mut secret);
+        general_purpose::URL_SAFE_NO_PAD.encode(secret)
+    }
+
+    /// Replace the secret with a random one
+    ///
+    /// The old secret keeps verifying tokens for `grace_hours`, so nobody is
+    /// logged out by the rotation; retired secrets whose grace period has
+    /// ended are dropped.
+    pub fn rotate_secret(&mut self, grace_hours: i64) {
+        let now = unix_now();
+
+        self.retired_secrets
+            .retain(|retired| retired.valid_until > now);
+        let secret = std::mem::replace(&mut self.secret, Self::generate_secret());
+        if grace_hours > 0 {
+            self.retired_secrets.push(RetiredJwtSecret {
+                secret,
+                valid_until: now + grace_hours * 3600,
+            });
+        }
+    }
+
+    /// Sign tokens with a new key pair from now on
+    ///
+    /// The previous signing key pair loses its private key but keeps
+    /// verifying tokens for `grace_hours`; key pairs whose grace period has
+    /// ended are dropped. Switching from the secret retires it the same way
+    /// as [`Self::rotate_secret`], since tokens without a `kid` are only
+    /// accepted from retired secrets once a key pair signs. Returns the
+    /// `kid` of the new key pair.
+    pub fn rotate_key(
+        &mut self,
+        algorithm: jwt_keys::JwtKeyAlgorithm,
+        grace_hours: i64,
+    ) -> Result<String> {
+        let key = jwt_keys::JwtKey::generate(algorithm)
+            .map_err(|e| ConfigError::Encryption(e.to_string()))?;
+        let now = unix_now();
+
+        if self.signing_key.is_empty() {
+            self.rotate_secret(grace_hours);
+        }
+        if let Some(previous) = self
+            .keys
+            .iter_mut()
+            .find(|previous| previous.kid() == self.signing_key)
+        {
+            previous.retire(now + grace_hours.max(0) * 3600);
+        }
+        self.keys.retain(|key| key.is_valid_at(now));
+
+        self.signing_key = key.kid().to_string();
+        self.keys.push(key);
+        Ok(self.signing_key.clone())
+    }
+}
+
+/// Current time as a Unix timestamp
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
 }
 
 impl Default for JwtConfig {
     fn default() -> Self {
         Self {
-            secret: "change-this-in-production-meditrack-secret-key".to_string(),
+            secret: Self::generate_secret(),
             issuer: "meditrack".to_string(),
             audience: "meditrack-app".to_string(),
             expiration_hours: 24,
+            retired_secrets: Vec::new(),
+            keys: Vec::new(),
+            signing_key: String::new(),
         }
     }
 }
@@ -60,49 +200,193 @@ impl Default for AppConfig {
     fn default() -> Self {
         Self {
             app_name: "meditrack".to_string(),
+            version: CONFIG_VERSION,
             database: DatabaseConfig::default(),
             jwt: JwtConfig::default(),
+            training: false,
         }
     }
 }
 
 impl AppConfig {
-    /// Load configuration from storage or create default if not found
-    pub fn load_or_default(config_dir: std::path::PathBuf) -> Self {
-        let storage = match ConfigStorage::new_with_path("meditrack", config_dir.clone()) {
-            Ok(s) => s,
-            Err(e) => {
-                tracing::warn!("Failed to initialize config storage: {:?}", e);
-                return Self::default();
-            }
-        };
+    /// Defaults for a new configuration of `profile`
+    ///
+    /// The [`TRAINING_PROFILE`] starts with the training banner turned on.
+    pub fn for_profile(profile: &str) -> Self {
+        Self {
+            training: profile == TRAINING_PROFILE,
+            ..Self::default()
+        }
+    }
+
+    /// Parse configuration JSON, upgrading it from older versions
+    ///
+    /// JSON written by a newer version of the app is refused with
+    /// [`ConfigError::NewerVersion`].
+    pub fn from_json(json: &str) -> Result<Self> {
+        Self::from_json_versioned(json).map(|(config, _)| config)
+    }
+
+    /// Parse configuration JSON like [`Self::from_json`], also returning the
+    /// version it was written in
+    pub(crate) fn from_json_versioned(json: &str) -> Result<(Self, u32)> {
+        let mut value: serde_json::Value = serde_json::from_str(json)?;
+        let version = upgrade::upgrade(&mut value)?;
+        Ok((serde_json::from_value(value)?, version))
+    }
+
+    /// Load the configuration of `profile` from storage, creating its
+    /// defaults ([`Self::for_profile`]) if not found
+    ///
+    /// The key source comes from the file header; a password-protected file
+    /// needs its password in [`CONFIG_PASSWORD_ENV`]. A file that exists but
+    /// cannot be read is an error, never replaced by defaults.
+    pub fn load(config_dir: std::path::PathBuf, profile: &str) -> Result<Self> {
+        let password = std::env::var(CONFIG_PASSWORD_ENV).ok();
+        let storage = ConfigStorage::open("meditrack", config_dir, password.as_deref()).map_err(
+            |e| match e {
+                ConfigError::KeyUnavailable(reason) => ConfigError::KeyUnavailable(format!(
+                    "{}; set {} or change the key source with --config",
+                    reason, CONFIG_PASSWORD_ENV
+                )),
+                e => e,
+            },
+        )?;
 
         match storage.load() {
             Ok(config) => {
-                tracing::info!("Loaded configuration from storage");
-                config
+                tracing::info!(
+                    "Loaded configuration from storage ({} key)",
+                    storage.key_source().kind()
+                );
+                Ok(config)
             }
             Err(ConfigError::NotFound) => {
                 tracing::info!("Configuration not found, creating default");
-                let config = Self::default();
+                let config = Self::for_profile(profile);
 
                 // Try to save default config for next time
                 if let Err(e) = storage.save(&config) {
                     tracing::warn!("Failed to save default configuration: {:?}", e);
                 }
 
-                config
+                Ok(config)
             }
-            Err(e) => {
-                tracing::warn!("Failed to load configuration: {:?}, using defaults", e);
-                Self::default()
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Load configuration from storage and apply `layers` over it
+    ///
+    /// Like [`Self::load`], a missing configuration is created with the
+    /// defaults; the layered values are never saved.
+    pub fn load_layered(
+        config_dir: std::path::PathBuf,
+        profile: &str,
+        layers: &ConfigLayers,
+    ) -> Result<EffectiveConfig> {
+        let stored = ConfigStorage::inspect(&config_dir)?.is_some();
+        let config = Self::load(config_dir.clone(), profile)?;
+        layers.apply(config, stored, &config_dir)
+    }
+
+    /// Problems that keep the configuration from working; empty when it is
+    /// valid
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let database = &self.database;
+
+        if let Err(e) = database.connection_url() {
+            problems.push(e.to_string());
+        }
+        match database.backend {
+            DatabaseBackend::Postgres => {
+                for (name, value) in [
+                    ("database.host", &database.host),
+                    ("database.database", &database.database),
+                    ("database.username", &database.username),
+                ] {
+                    if value.trim().is_empty() {
+                        problems.push(format!("{} is empty", name));
+                    }
+                }
+                if database.port == 0 {
+                    problems.push("database.port is 0".to_string());
+                }
+                for (name, path) in [
+                    ("database.ssl_root_cert", &database.ssl_root_cert),
+                    ("database.ssl_client_cert", &database.ssl_client_cert),
+                    ("database.ssl_client_key", &database.ssl_client_key),
+                ] {
+                    if !path.is_empty() && !std::path::Path::new(path).is_file() {
+                        problems.push(format!("{} {} does not exist", name, path));
+                    }
+                }
+                if database.ssl_client_cert.is_empty() != database.ssl_client_key.is_empty() {
+                    problems.push(
+                        "database.ssl_client_cert and database.ssl_client_key go together"
+                            .to_string(),
+                    );
+                }
+            }
+            DatabaseBackend::Sqlite => {
+                if database.sqlite_path.trim().is_empty() {
+                    problems.push("database.sqlite_path is empty".to_string());
+                }
+            }
+        }
+        if database.max_connections == 0 {
+            problems.push("database.max_connections is 0".to_string());
+        }
+        if database.min_connections > database.max_connections {
+            problems.push(format!(
+                "database.min_connections ({}) is above database.max_connections ({})",
+                database.min_connections, database.max_connections
+            ));
+        }
+
+        for (name, value) in [
+            ("jwt.secret", &self.jwt.secret),
+            ("jwt.issuer", &self.jwt.issuer),
+            ("jwt.audience", &self.jwt.audience),
+        ] {
+            if value.trim().is_empty() {
+                problems.push(format!("{} is empty", name));
+            }
+        }
+        if self.jwt.secret == jwt_keys::DEFAULT_SECRET {
+            problems.push("jwt.secret is the published default".to_string());
+        }
+        if !self.jwt.signing_key.is_empty() {
+            match self
+                .jwt
+                .keys
+                .iter()
+                .find(|key| key.kid() == self.jwt.signing_key)
+            {
+                None => problems.push(format!(
+                    "jwt.signing_key {} is not in jwt.keys",
+                    self.jwt.signing_key
+                )),
+                Some(key) if key.private_key.is_empty() => problems.push(format!(
+                    "jwt.signing_key {} has no private key",
+                    self.jwt.signing_key
+                )),
+                Some(_) => {}
             }
         }
+        if self.jwt.expiration_hours <= 0 {
+            problems.push("jwt.expiration_hours must be positive".to_string());
+        }
+
+        problems
     }
 
-    /// Save configuration to storage
+    /// Save configuration to storage, keeping the key source of the existing
+    /// file
     pub fn save(&self, config_dir: std::path::PathBuf) -> Result<()> {
-        let storage = ConfigStorage::new_with_path("meditrack", config_dir)?;
+        let password = std::env::var(CONFIG_PASSWORD_ENV).ok();
+        let storage = ConfigStorage::open("meditrack", config_dir, password.as_deref())?;
         storage.save(self)
     }
 }
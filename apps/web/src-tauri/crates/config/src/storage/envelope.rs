@@ -0,0 +1,235 @@
+//! Versioned header in front of the encrypted configuration
+//!
+//! `config.enc` starts with a line giving the format version, the key source
+//! the file was encrypted with, the Argon2id parameters and salt of derived
+//! keys and a key check value, followed by the base64 ciphertext:
+//!
+//! ```text
+//! MEDITRACK-CONFIG/2 key=password kdf=argon2id m=19456 t=2 p=1 salt=... check=...
+//! 3q2+7w...
+//! ```
+//!
+//! Key files hold the key itself, so their header has no `kdf` fields.
+//! Format 1 headers have only `key=` and use the fixed salts; files written
+//! before the header existed hold only the ciphertext.
+
+use std::fmt;
+
+use base64::{Engine as _, engine::general_purpose};
+
+use crate::crypto::{KdfParams, KeyKind, SALT_LEN};
+use crate::error::{ConfigError, Result};
+
+const MAGIC: &str = "MEDITRACK-CONFIG";
+
+/// Format version written by this build
+pub(crate) const FORMAT_VERSION: u32 = 2;
+
+/// Key derivation function named in the header
+const KDF: &str = "argon2id";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Envelope {
+    /// Format version; 0 for files without a header
+    pub(crate) version: u32,
+    /// Key source recorded in the header; `None` for files without one
+    pub(crate) key: Option<KeyKind>,
+    /// Parameters password and machine keys are derived with; `None` before
+    /// format 2 and for key files
+    pub(crate) kdf: Option<KdfParams>,
+    /// Key check value; `None` before format 2
+    pub(crate) check: Option<String>,
+    pub(crate) ciphertext: String,
+}
+
+impl Envelope {
+    pub(crate) fn new(
+        key: KeyKind,
+        kdf: Option<KdfParams>,
+        check: String,
+        ciphertext: String,
+    ) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            key: Some(key),
+            kdf,
+            check: Some(check),
+            ciphertext,
+        }
+    }
+
+    pub(crate) fn parse(content: &str) -> Result<Self> {
+        let content = content.trim();
+        let Some(rest) = content
+            .strip_prefix(MAGIC)
+            .and_then(|rest| rest.strip_prefix('/'))
+        else {
+            return Ok(Self {
+                version: 0,
+                key: None,
+                kdf: None,
+                check: None,
+                ciphertext: content.to_string(),
+            });
+        };
+
+        let invalid = || ConfigError::Corrupt("invalid file header".to_string());
+        let (header, ciphertext) = rest.split_once('\n').ok_or_else(invalid)?;
+        let mut fields = header.split_whitespace();
+
+        let version: u32 = fields
+            .next()
+            .and_then(|version| version.parse().ok())
+            .ok_or_else(invalid)?;
+        if version > FORMAT_VERSION {
+            return Err(ConfigError::InvalidConfig(format!(
+                "Config file format {} is newer than this version supports ({})",
+                version, FORMAT_VERSION
+            )));
+        }
+
+        let fields: Vec<(&str, &str)> = fields
+            .map(|field| field.split_once('=').ok_or_else(invalid))
+            .collect::<Result<_>>()?;
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+        };
+
+        let key: KeyKind = field("key")
+            .ok_or_else(invalid)?
+            .parse()
+            .map_err(|_| invalid())?;
+
+        let (kdf, check) = if version >= 2 {
+            let kdf = match (key, field("kdf")) {
+                (KeyKind::KeyFile, None) => None,
+                (_, Some(KDF)) => Some(Self::parse_kdf(&field).ok_or_else(invalid)?),
+                _ => return Err(invalid()),
+            };
+            (kdf, Some(field("check").ok_or_else(invalid)?.to_string()))
+        } else {
+            (None, None)
+        };
+
+        Ok(Self {
+            version,
+            key: Some(key),
+            kdf,
+            check,
+            ciphertext: ciphertext.trim().to_string(),
+        })
+    }
+
+    fn parse_kdf<'a>(field: &impl Fn(&str) -> Option<&'a str>) -> Option<KdfParams> {
+        let salt = general_purpose::STANDARD.decode(field("salt")?).ok()?;
+
+        Some(KdfParams {
+            memory_kib: field("m")?.parse().ok()?,
+            iterations: field("t")?.parse().ok()?,
+            parallelism: field("p")?.parse().ok()?,
+            salt: <[u8; SALT_LEN]>::try_from(salt).ok()?,
+        })
+    }
+}
+
+impl fmt::Display for Envelope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(key) = self.key {
+            write!(f, "{}/{} key={}", MAGIC, self.version, key)?;
+            if let Some(kdf) = &self.kdf {
+                write!(
+                    f,
+                    " kdf={} m={} t={} p={} salt={}",
+                    KDF,
+                    kdf.memory_kib,
+                    kdf.iterations,
+                    kdf.parallelism,
+                    general_purpose::STANDARD.encode(kdf.salt)
+                )?;
+            }
+            if let Some(check) = &self.check {
+                write!(f, " check={}", check)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "{}", self.ciphertext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header_and_legacy_files() {
+        let kdf = KdfParams {
+            memory_kib: 19456,
+            iterations: 2,
+            parallelism: 1,
+            salt: [0; SALT_LEN],
+        };
+        let envelope = Envelope::new(
+            KeyKind::Password,
+            Some(kdf),
+            "Y2hlY2s=".to_string(),
+            "c2VjcmV0".to_string(),
+        );
+        let content = envelope.to_string();
+        assert_eq!(
+            content,
+            "MEDITRACK-CONFIG/2 key=password kdf=argon2id m=19456 t=2 p=1 \
+             salt=AAAAAAAAAAAAAAAAAAAAAA== check=Y2hlY2s=\nc2VjcmV0\n"
+        );
+        assert_eq!(Envelope::parse(&content).unwrap(), envelope);
+
+        let key_file = Envelope::new(
+            KeyKind::KeyFile,
+            None,
+            "Y2hlY2s=".to_string(),
+            "c2VjcmV0".to_string(),
+        );
+        let content = key_file.to_string();
+        assert_eq!(
+            content,
+            "MEDITRACK-CONFIG/2 key=keyfile check=Y2hlY2s=\nc2VjcmV0\n"
+        );
+        assert_eq!(Envelope::parse(&content).unwrap(), key_file);
+
+        let v1 = Envelope::parse("MEDITRACK-CONFIG/1 key=machine\nc2VjcmV0\n").unwrap();
+        assert_eq!(v1.version, 1);
+        assert_eq!(v1.key, Some(KeyKind::Machine));
+        assert_eq!((v1.kdf, v1.check), (None, None));
+
+        let legacy = Envelope::parse("c2VjcmV0").unwrap();
+        assert_eq!(legacy.version, 0);
+        assert_eq!(legacy.key, None);
+        assert_eq!(legacy.ciphertext, "c2VjcmV0");
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_headers() {
+        assert!(matches!(
+            Envelope::parse("MEDITRACK-CONFIG/3 key=machine\nc2VjcmV0"),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+
+        for content in [
+            "MEDITRACK-CONFIG/1 key=keyring\nc2VjcmV0",
+            "MEDITRACK-CONFIG/1\nc2VjcmV0",
+            "MEDITRACK-CONFIG/x key=machine\nc2VjcmV0",
+            "MEDITRACK-CONFIG/2 key=machine check=Y2hlY2s=\nc2VjcmV0",
+            "MEDITRACK-CONFIG/2 key=machine kdf=scrypt m=1 t=1 p=1 salt=AA== check=Y2hlY2s=\nc2VjcmV0",
+            "MEDITRACK-CONFIG/2 key=password kdf=argon2id m=19456 t=2 p=1 salt=AA== check=Y2hlY2s=\nc2VjcmV0",
+            "MEDITRACK-CONFIG/2 key=keyfile\nc2VjcmV0",
+        ] {
+            assert!(
+                matches!(Envelope::parse(content), Err(ConfigError::Corrupt(_))),
+                "{}",
+                content
+            );
+        }
+    }
+}
@@ -1,111 +1,409 @@
 //! Configuration storage and persistence
 
+mod envelope;
+
 use crate::AppConfig;
-use crate::crypto::SecureStorage;
+use crate::crypto::{KdfParams, KeyKind, KeySource, SecureStorage};
 use crate::error::{ConfigError, Result};
+use crate::upgrade::CONFIG_VERSION;
+use envelope::Envelope;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// File name of the encrypted configuration
+const CONFIG_FILE_NAME: &str = "config.enc";
+
+/// Profile kept directly in the config directory
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Profile for development, the only one allowed to run with the published
+/// default JWT secret
+pub const DEV_PROFILE: &str = "dev";
+
+/// Profile created with the training banner turned on
+pub const TRAINING_PROFILE: &str = "training";
+
+/// Directory under the config directory holding the other profiles, one
+/// directory each
+const PROFILES_DIR: &str = "profiles";
+
+/// What an existing configuration file records about its encryption
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigFileInfo {
+    /// Format version; 0 for files written before the header existed
+    pub format_version: u32,
+
+    /// Key source the file is encrypted with; `None` for files without a header
+    pub key: Option<KeyKind>,
+}
 
 /// Configuration storage manager
 #[derive(Clone)]
 pub struct ConfigStorage {
     app_name: String,
     config_dir: Option<PathBuf>,
-    secure_storage: Option<SecureStorage>,
+    key_source: KeySource,
 }
 
 impl ConfigStorage {
-    /// Create a new configuration storage with password
-    pub fn new_with_password(app_name: &str, config_dir: PathBuf, password: &str) -> Result<Self> {
+    /// Create a new configuration storage with an explicit key source
+    pub fn new_with_key_source(
+        app_name: &str,
+        config_dir: PathBuf,
+        key_source: KeySource,
+    ) -> Result<Self> {
         Ok(Self {
             app_name: app_name.to_string(),
             config_dir: Some(config_dir),
-            secure_storage: Some(SecureStorage::new_with_password(password)?),
+            key_source,
         })
     }
 
+    /// Create a new configuration storage with password
+    pub fn new_with_password(app_name: &str, config_dir: PathBuf, password: &str) -> Result<Self> {
+        Self::new_with_key_source(
+            app_name,
+            config_dir,
+            KeySource::Password(password.to_string()),
+        )
+    }
+
     /// Create a new configuration storage (legacy, machine-based)
     pub fn new(app_name: &str) -> Result<Self> {
         Ok(Self {
             app_name: app_name.to_string(),
             config_dir: None,
-            secure_storage: Some(SecureStorage::new()?),
+            key_source: KeySource::Machine,
         })
     }
 
     /// Create a new configuration storage with custom path (legacy)
     pub fn new_with_path(app_name: &str, config_dir: PathBuf) -> Result<Self> {
-        Ok(Self {
-            app_name: app_name.to_string(),
-            config_dir: Some(config_dir),
-            secure_storage: Some(SecureStorage::new()?),
-        })
+        Self::new_with_key_source(app_name, config_dir, KeySource::Machine)
     }
 
-    /// Load configuration from storage
-    pub fn load(&self) -> Result<AppConfig> {
-        let config_path = self.get_config_path_internal()?;
+    /// Open the configuration in `config_dir` with the key source its file
+    /// header records
+    ///
+    /// `password` is only needed for password-protected files. A new file
+    /// uses the password when one is given, and the machine key otherwise.
+    /// A file written before the header existed is tried with the password
+    /// and then with the machine key, and upgraded in place with the key that
+    /// opens it.
+    pub fn open(app_name: &str, config_dir: PathBuf, password: Option<&str>) -> Result<Self> {
+        match Self::inspect(&config_dir)? {
+            Some(ConfigFileInfo {
+                key: Some(kind), ..
+            }) => {
+                let key_source = KeySource::resolve(kind, &config_dir, password)?;
+                Self::new_with_key_source(app_name, config_dir, key_source)
+            }
+            Some(ConfigFileInfo { key: None, .. }) => {
+                Self::open_legacy(app_name, config_dir, password)
+            }
+            None => {
+                let key_source = password
+                    .map(|password| KeySource::Password(password.to_string()))
+                    .unwrap_or(KeySource::Machine);
+                Self::new_with_key_source(app_name, config_dir, key_source)
+            }
+        }
+    }
+
+    /// Directory holding the configuration of `profile`
+    ///
+    /// The default profile lives in `config_dir` itself, so configurations
+    /// from before profiles existed become the default profile. Every other
+    /// profile has its own directory, with its own key source and key file.
+    pub fn profile_dir(config_dir: &Path, profile: &str) -> Result<PathBuf> {
+        let valid = !profile.is_empty()
+            && profile.len() <= 32
+            && profile
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid {
+            return Err(ConfigError::InvalidConfig(format!(
+                "Invalid profile name `{}`: use up to 32 lowercase letters, digits, '-' or '_'",
+                profile
+            )));
+        }
 
+        if profile == DEFAULT_PROFILE {
+            Ok(config_dir.to_path_buf())
+        } else {
+            Ok(config_dir.join(PROFILES_DIR).join(profile))
+        }
+    }
+
+    /// Profiles with a stored configuration in `config_dir`, the default
+    /// profile first and the others by name
+    pub fn list_profiles(config_dir: &Path) -> Result<Vec<String>> {
+        let mut profiles = Vec::new();
+        if config_dir.join(CONFIG_FILE_NAME).exists() {
+            profiles.push(DEFAULT_PROFILE.to_string());
+        }
+
+        let profiles_dir = config_dir.join(PROFILES_DIR);
+        if profiles_dir.is_dir() {
+            let mut named = Vec::new();
+            for entry in fs::read_dir(&profiles_dir)? {
+                let entry = entry?;
+                let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                    continue;
+                };
+                if name != DEFAULT_PROFILE
+                    && Self::profile_dir(config_dir, &name).is_ok()
+                    && entry.path().join(CONFIG_FILE_NAME).exists()
+                {
+                    named.push(name);
+                }
+            }
+            named.sort();
+            profiles.extend(named);
+        }
+
+        Ok(profiles)
+    }
+
+    /// Open the configuration of `profile` in `config_dir`, as [`Self::open`]
+    pub fn open_profile(
+        app_name: &str,
+        config_dir: &Path,
+        profile: &str,
+        password: Option<&str>,
+    ) -> Result<Self> {
+        Self::open(app_name, Self::profile_dir(config_dir, profile)?, password)
+    }
+
+    /// Read the header of the configuration in `config_dir`, if there is one
+    pub fn inspect(config_dir: &Path) -> Result<Option<ConfigFileInfo>> {
+        let config_path = config_dir.join(CONFIG_FILE_NAME);
         if !config_path.exists() {
-            return Err(ConfigError::NotFound);
+            return Ok(None);
         }
 
-        let secure_storage = self
-            .secure_storage
-            .as_ref()
-            .ok_or_else(|| ConfigError::Encryption("No encryption key available".to_string()))?;
+        let envelope = Envelope::parse(&fs::read_to_string(&config_path)?)?;
+        Ok(Some(ConfigFileInfo {
+            format_version: envelope.version,
+            key: envelope.key,
+        }))
+    }
 
-        // Read encrypted configuration
-        let encrypted_data = fs::read_to_string(&config_path).map_err(ConfigError::Io)?;
+    /// Key source used to read and write the configuration
+    pub fn key_source(&self) -> &KeySource {
+        &self.key_source
+    }
 
-        // Decrypt configuration
-        let decrypted_data = secure_storage.decrypt(&encrypted_data)?;
+    /// Load configuration from storage
+    ///
+    /// A file in an older format or configuration version is rewritten in
+    /// the current one once it has been decrypted; a file from a newer
+    /// configuration version is refused with [`ConfigError::NewerVersion`].
+    pub fn load(&self) -> Result<AppConfig> {
+        let config_path = self.get_config_path_internal()?;
+        let decrypted_data = self.read()?;
 
-        // Deserialize configuration
-        let mut config: AppConfig =
-            serde_json::from_str(&decrypted_data).map_err(ConfigError::Serialization)?;
+        // Deserialize configuration, upgrading it from older versions
+        let (mut config, version) = AppConfig::from_json_versioned(&decrypted_data)?;
 
         // Set app name (not serialized)
         config.app_name = self.app_name.clone();
 
+        if version < CONFIG_VERSION {
+            match self.save(&config) {
+                Ok(()) => tracing::info!(
+                    "Upgraded {} from configuration version {} to {}",
+                    config_path.display(),
+                    version,
+                    CONFIG_VERSION
+                ),
+                Err(e) => tracing::warn!("Failed to upgrade {}: {:?}", config_path.display(), e),
+            }
+        }
+
         tracing::debug!("Loaded configuration from: {}", config_path.display());
         Ok(config)
     }
 
+    /// Decrypt the stored configuration JSON
+    ///
+    /// In the current format a wrong key is reported as
+    /// [`ConfigError::WrongPassword`] (or [`ConfigError::KeyUnavailable`] for
+    /// the machine key and key files), and data the right key cannot decrypt
+    /// as [`ConfigError::Corrupt`]. Older formats cannot tell the two apart
+    /// and report [`ConfigError::Decryption`].
+    fn read(&self) -> Result<String> {
+        let config_path = self.get_config_path_internal()?;
+
+        if !config_path.exists() {
+            return Err(ConfigError::NotFound);
+        }
+
+        // Read encrypted configuration
+        let envelope = Envelope::parse(&fs::read_to_string(&config_path)?)?;
+        if let Some(kind) = envelope.key
+            && kind != self.key_source.kind()
+        {
+            return Err(ConfigError::KeyUnavailable(format!(
+                "{} is encrypted with the {} key, not the {} key",
+                config_path.display(),
+                kind,
+                self.key_source.kind()
+            )));
+        }
+
+        // Decrypt configuration
+        let decrypted_data = match &envelope.check {
+            Some(check) => {
+                let secure_storage = match &envelope.kdf {
+                    Some(kdf) => SecureStorage::derive(&self.key_source, kdf)?,
+                    None => SecureStorage::from_key_source(&self.key_source)?,
+                };
+
+                if !secure_storage.verify_key_check(check) {
+                    return Err(match self.key_source.kind() {
+                        KeyKind::Password => ConfigError::WrongPassword,
+                        kind => ConfigError::KeyUnavailable(format!(
+                            "the {} key does not match the one {} was encrypted with",
+                            kind,
+                            config_path.display()
+                        )),
+                    });
+                }
+
+                secure_storage
+                    .decrypt(&envelope.ciphertext)
+                    .map_err(|e| ConfigError::Corrupt(e.to_string()))?
+            }
+            None => {
+                SecureStorage::from_key_source(&self.key_source)?.decrypt(&envelope.ciphertext)?
+            }
+        };
+
+        if envelope.version < envelope::FORMAT_VERSION {
+            match self.write(&decrypted_data) {
+                Ok(()) => tracing::info!(
+                    "Upgraded {} from format {} to {} with the {} key",
+                    config_path.display(),
+                    envelope.version,
+                    envelope::FORMAT_VERSION,
+                    self.key_source.kind()
+                ),
+                Err(e) => tracing::warn!("Failed to upgrade {}: {:?}", config_path.display(), e),
+            }
+        }
+
+        Ok(decrypted_data)
+    }
+
     /// Get config path (internal method that respects custom path)
     fn get_config_path_internal(&self) -> Result<PathBuf> {
         let dir = self.config_dir.as_ref().ok_or_else(|| {
             ConfigError::InvalidConfig("Config directory not provided".to_string())
         })?;
-        Ok(dir.join("config.enc"))
+        Ok(dir.join(CONFIG_FILE_NAME))
     }
 
     /// Save configuration to storage
     pub fn save(&self, config: &AppConfig) -> Result<()> {
+        // Serialize configuration
+        let json_data = serde_json::to_string_pretty(config).map_err(ConfigError::Serialization)?;
+
+        self.write(&json_data)
+    }
+
+    /// Re-encrypt the configuration with `new_password`, returning the
+    /// storage that reads it from now on
+    ///
+    /// The file gets a fresh salt, and is replaced only once the new copy has
+    /// been written in full.
+    pub fn rotate_password(&self, new_password: &str) -> Result<Self> {
+        if new_password.is_empty() {
+            return Err(ConfigError::InvalidConfig(
+                "The new password must not be empty".to_string(),
+            ));
+        }
+
+        self.rotate_key(KeySource::Password(new_password.to_string()))
+    }
+
+    /// Re-encrypt the configuration with the key from `key_source`, returning
+    /// the storage that reads it from now on
+    pub fn rotate_key(&self, key_source: KeySource) -> Result<Self> {
+        let decrypted_data = self.read()?;
+
+        let rotated = Self {
+            key_source,
+            ..self.clone()
+        };
+        rotated.write(&decrypted_data)?;
+
+        tracing::info!(
+            "Re-encrypted configuration with the {} key",
+            rotated.key_source.kind()
+        );
+        Ok(rotated)
+    }
+
+    /// Encrypt `json_data` with a freshly salted key and write it with the
+    /// envelope header
+    fn write(&self, json_data: &str) -> Result<()> {
         let config_path = self.get_config_path_internal()?;
 
-        let secure_storage = self
-            .secure_storage
-            .as_ref()
-            .ok_or_else(|| ConfigError::Encryption("No encryption key available".to_string()))?;
+        // A key file is created the first time it is needed
+        let (secure_storage, kdf) = match &self.key_source {
+            KeySource::KeyFile(path) if !path.exists() => {
+                (SecureStorage::create_key_file(path)?, None)
+            }
+            KeySource::KeyFile(path) => (SecureStorage::from_key_file(path)?, None),
+            key_source => {
+                let kdf = KdfParams::generate();
+                (SecureStorage::derive(key_source, &kdf)?, Some(kdf))
+            }
+        };
 
         // Create parent directory if it doesn't exist
         if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent).map_err(ConfigError::Io)?;
         }
 
-        // Serialize configuration
-        let json_data = serde_json::to_string_pretty(config).map_err(ConfigError::Serialization)?;
-
         // Encrypt configuration
-        let encrypted_data = secure_storage.encrypt(&json_data)?;
-
-        // Write to file
-        fs::write(&config_path, encrypted_data).map_err(ConfigError::Io)?;
+        let envelope = Envelope::new(
+            self.key_source.kind(),
+            kdf,
+            secure_storage.key_check()?,
+            secure_storage.encrypt(json_data)?,
+        );
+
+        // Write to a temporary file first so an interrupted write never
+        // leaves a truncated configuration behind
+        let temp_path = config_path.with_extension("enc.tmp");
+        fs::write(&temp_path, envelope.to_string()).map_err(ConfigError::Io)?;
+        fs::rename(&temp_path, &config_path).map_err(ConfigError::Io)?;
 
         Ok(())
     }
 
+    /// Find the key that opens a file without a header, trying the password
+    /// before the machine key the desktop app used
+    fn open_legacy(app_name: &str, config_dir: PathBuf, password: Option<&str>) -> Result<Self> {
+        let candidates = password
+            .map(|password| KeySource::Password(password.to_string()))
+            .into_iter()
+            .chain([KeySource::Machine]);
+
+        let mut error = None;
+        for key_source in candidates {
+            let storage = Self::new_with_key_source(app_name, config_dir.clone(), key_source)?;
+            match storage.load() {
+                Ok(_) => return Ok(storage),
+                Err(e @ ConfigError::Decryption(_)) => error = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+        Err(error.unwrap_or(ConfigError::NotFound))
+    }
+
     /// Delete configuration from storage
     pub fn delete(&self) -> Result<()> {
         let config_path = self.get_config_path_internal()?;
@@ -141,8 +439,7 @@ impl ConfigStorage {
     pub fn import(&self, path: &PathBuf) -> Result<AppConfig> {
         let json_data = fs::read_to_string(path).map_err(ConfigError::Io)?;
 
-        let mut config: AppConfig =
-            serde_json::from_str(&json_data).map_err(ConfigError::Serialization)?;
+        let mut config = AppConfig::from_json(&json_data)?;
 
         config.app_name = self.app_name.clone();
 
@@ -153,3 +450,324 @@ impl ConfigStorage {
         Ok(config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("meditrack-config-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn config() -> AppConfig {
+        let mut config = AppConfig::default();
+        config.jwt.issuer = "branch-2".to_string();
+        config
+    }
+
+    #[test]
+    fn test_open_uses_recorded_key_source() {
+        let dir = config_dir("open");
+
+        // Nothing stored yet: the password wins over the machine key
+        let storage = ConfigStorage::open("meditrack", dir.clone(), Some("secret")).unwrap();
+        assert_eq!(storage.key_source().kind(), KeyKind::Password);
+        storage.save(&config()).unwrap();
+
+        let content = fs::read_to_string(dir.join(CONFIG_FILE_NAME)).unwrap();
+        assert!(content.starts_with("MEDITRACK-CONFIG/2 key=password kdf=argon2id "));
+
+        let storage = ConfigStorage::open("meditrack", dir.clone(), Some("secret")).unwrap();
+        assert_eq!(storage.load().unwrap().jwt.issuer, "branch-2");
+        assert!(matches!(
+            ConfigStorage::open("meditrack", dir.clone(), None),
+            Err(ConfigError::KeyUnavailable(_))
+        ));
+        // A storage with another key source is told why it cannot read the file
+        assert!(matches!(
+            ConfigStorage::new_with_path("meditrack", dir.clone())
+                .unwrap()
+                .load(),
+            Err(ConfigError::KeyUnavailable(_))
+        ));
+
+        // Key file: created on first save, found again from the header
+        let key_file = dir.join(crate::crypto::KEY_FILE_NAME);
+        ConfigStorage::new_with_key_source("meditrack", dir.clone(), KeySource::KeyFile(key_file))
+            .unwrap()
+            .save(&config())
+            .unwrap();
+        let storage = ConfigStorage::open("meditrack", dir.clone(), None).unwrap();
+        assert_eq!(storage.key_source().kind(), KeyKind::KeyFile);
+        assert_eq!(storage.load().unwrap().jwt.issuer, "branch-2");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_open_upgrades_legacy_files() {
+        let dir = config_dir("legacy");
+        fs::create_dir_all(&dir).unwrap();
+        let json = serde_json::to_string(&config()).unwrap();
+        let legacy_path = dir.join(CONFIG_FILE_NAME);
+
+        // Written by the desktop app with the machine key, opened by the TUI
+        let ciphertext = SecureStorage::new().unwrap().encrypt(&json).unwrap();
+        fs::write(&legacy_path, &ciphertext).unwrap();
+        let storage = ConfigStorage::open("meditrack", dir.clone(), Some("secret")).unwrap();
+        assert_eq!(storage.key_source().kind(), KeyKind::Machine);
+        assert_eq!(
+            ConfigStorage::inspect(&dir).unwrap(),
+            Some(ConfigFileInfo {
+                format_version: 2,
+                key: Some(KeyKind::Machine),
+            })
+        );
+        assert_eq!(storage.load().unwrap().jwt.issuer, "branch-2");
+
+        // Written by the TUI with a password
+        let ciphertext = SecureStorage::new_with_password("secret")
+            .unwrap()
+            .encrypt(&json)
+            .unwrap();
+        fs::write(&legacy_path, &ciphertext).unwrap();
+        assert!(matches!(
+            ConfigStorage::open("meditrack", dir.clone(), None),
+            Err(ConfigError::Decryption(_))
+        ));
+        assert!(matches!(
+            ConfigStorage::open("meditrack", dir.clone(), Some("wrong")),
+            Err(ConfigError::Decryption(_))
+        ));
+        let storage = ConfigStorage::open("meditrack", dir.clone(), Some("secret")).unwrap();
+        assert_eq!(storage.key_source().kind(), KeyKind::Password);
+        assert_eq!(
+            ConfigStorage::inspect(&dir).unwrap().unwrap().key,
+            Some(KeyKind::Password)
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_wrong_password_and_corrupt_files() {
+        let dir = config_dir("integrity");
+        let config_path = dir.join(CONFIG_FILE_NAME);
+        let storage = ConfigStorage::new_with_password("meditrack", dir.clone(), "secret").unwrap();
+        storage.save(&config()).unwrap();
+
+        // Each save draws a new salt
+        let first = Envelope::parse(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        storage.save(&config()).unwrap();
+        let content = fs::read_to_string(&config_path).unwrap();
+        let second = Envelope::parse(&content).unwrap();
+        assert_ne!(first.kdf.unwrap().salt, second.kdf.unwrap().salt);
+
+        assert!(matches!(
+            ConfigStorage::new_with_password("meditrack", dir.clone(), "wrong")
+                .unwrap()
+                .load(),
+            Err(ConfigError::WrongPassword)
+        ));
+
+        // Right password, damaged ciphertext
+        let (header, ciphertext) = content.split_once('\n').unwrap();
+        let mut damaged = ciphertext.trim().as_bytes().to_vec();
+        damaged[20] = if damaged[20] == b'A' { b'B' } else { b'A' };
+        fs::write(
+            &config_path,
+            format!("{}\n{}\n", header, String::from_utf8(damaged).unwrap()),
+        )
+        .unwrap();
+        assert!(matches!(storage.load(), Err(ConfigError::Corrupt(_))));
+
+        // Truncated header
+        fs::write(
+            &config_path,
+            "MEDITRACK-CONFIG/2 key=password kdf=argon2id\n",
+        )
+        .unwrap();
+        assert!(matches!(storage.load(), Err(ConfigError::Corrupt(_))));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotate_password() {
+        let dir = config_dir("rotate");
+        let storage = ConfigStorage::new_with_password("meditrack", dir.clone(), "old").unwrap();
+        storage.save(&config()).unwrap();
+
+        assert!(matches!(
+            storage.rotate_password(""),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+        let rotated = storage.rotate_password("new").unwrap();
+        assert_eq!(rotated.load().unwrap().jwt.issuer, "branch-2");
+        assert!(matches!(storage.load(), Err(ConfigError::WrongPassword)));
+        assert!(!dir.join("config.enc.tmp").exists());
+
+        // A wrong current password leaves the file untouched
+        let before = fs::read_to_string(dir.join(CONFIG_FILE_NAME)).unwrap();
+        assert!(matches!(
+            storage.rotate_password("newer"),
+            Err(ConfigError::WrongPassword)
+        ));
+        assert_eq!(
+            fs::read_to_string(dir.join(CONFIG_FILE_NAME)).unwrap(),
+            before
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Moving a machine-keyed file to a password
+        let dir = config_dir("rotate-machine");
+        let storage = ConfigStorage::new_with_path("meditrack", dir.clone()).unwrap();
+        storage.save(&config()).unwrap();
+        storage.rotate_password("secret").unwrap();
+        let storage = ConfigStorage::open("meditrack", dir.clone(), Some("secret")).unwrap();
+        assert_eq!(storage.key_source().kind(), KeyKind::Password);
+        assert_eq!(storage.load().unwrap().jwt.issuer, "branch-2");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_1_files_are_upgraded() {
+        let dir = config_dir("format-1");
+        fs::create_dir_all(&dir).unwrap();
+        let json = serde_json::to_string(&config()).unwrap();
+        let ciphertext = SecureStorage::new_with_password("secret")
+            .unwrap()
+            .encrypt(&json)
+            .unwrap();
+        fs::write(
+            dir.join(CONFIG_FILE_NAME),
+            format!("MEDITRACK-CONFIG/1 key=password\n{}\n", ciphertext),
+        )
+        .unwrap();
+
+        // Format 1 has no key check, so a wrong password looks like bad data
+        let wrong = ConfigStorage::open("meditrack", dir.clone(), Some("wrong")).unwrap();
+        assert!(matches!(wrong.load(), Err(ConfigError::Decryption(_))));
+
+        let storage = ConfigStorage::open("meditrack", dir.clone(), Some("secret")).unwrap();
+        assert_eq!(storage.load().unwrap().jwt.issuer, "branch-2");
+        assert_eq!(
+            ConfigStorage::inspect(&dir)
+                .unwrap()
+                .unwrap()
+                .format_version,
+            2
+        );
+        assert!(matches!(wrong.load(), Err(ConfigError::WrongPassword)));
+        assert_eq!(storage.load().unwrap().jwt.issuer, "branch-2");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_config_versions() {
+        let dir = config_dir("config-version");
+        let storage = ConfigStorage::open("meditrack", dir.clone(), Some("secret")).unwrap();
+        let mut json = serde_json::to_value(config()).unwrap();
+
+        // Written before configurations were versioned: upgraded and rewritten
+        json.as_object_mut().unwrap().remove("version");
+        storage.write(&json.to_string()).unwrap();
+        assert_eq!(storage.load().unwrap().jwt.issuer, "branch-2");
+        let stored: serde_json::Value = serde_json::from_str(&storage.read().unwrap()).unwrap();
+        assert_eq!(stored["version"], CONFIG_VERSION);
+
+        // Written by a newer version: refused and left alone
+        json["version"] = (CONFIG_VERSION + 1).into();
+        storage.write(&json.to_string()).unwrap();
+        assert!(matches!(
+            storage.load(),
+            Err(ConfigError::NewerVersion { found, .. }) if found == CONFIG_VERSION + 1
+        ));
+        let stored: serde_json::Value = serde_json::from_str(&storage.read().unwrap()).unwrap();
+        assert_eq!(stored, json);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_profiles() {
+        let dir = config_dir("profiles");
+
+        assert_eq!(
+            ConfigStorage::profile_dir(&dir, DEFAULT_PROFILE).unwrap(),
+            dir
+        );
+        assert_eq!(
+            ConfigStorage::profile_dir(&dir, "training").unwrap(),
+            dir.join("profiles").join("training")
+        );
+        for name in ["", "../production", "Training", "a/b"] {
+            assert!(matches!(
+                ConfigStorage::profile_dir(&dir, name),
+                Err(ConfigError::InvalidConfig(_))
+            ));
+        }
+        assert!(ConfigStorage::list_profiles(&dir).unwrap().is_empty());
+
+        // Each profile has its own file and key source
+        ConfigStorage::open_profile("meditrack", &dir, DEFAULT_PROFILE, None)
+            .unwrap()
+            .save(&config())
+            .unwrap();
+        let training = AppConfig {
+            training: true,
+            ..AppConfig::default()
+        };
+        let storage =
+            ConfigStorage::open_profile("meditrack", &dir, "training", Some("secret")).unwrap();
+        assert_eq!(storage.key_source().kind(), KeyKind::Password);
+        storage.save(&training).unwrap();
+
+        assert_eq!(
+            ConfigStorage::list_profiles(&dir).unwrap(),
+            vec!["default".to_string(), "training".to_string()]
+        );
+        let storage =
+            ConfigStorage::open_profile("meditrack", &dir, "training", Some("secret")).unwrap();
+        assert!(storage.load().unwrap().training);
+        let storage =
+            ConfigStorage::open_profile("meditrack", &dir, DEFAULT_PROFILE, None).unwrap();
+        assert_eq!(storage.key_source().kind(), KeyKind::Machine);
+        assert!(!storage.load().unwrap().training);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_creates_profile_defaults() {
+        let dir = config_dir("load-profiles");
+
+        let training_dir = ConfigStorage::profile_dir(&dir, TRAINING_PROFILE).unwrap();
+        assert!(
+            AppConfig::load(training_dir.clone(), TRAINING_PROFILE)
+                .unwrap()
+                .training
+        );
+        // The saved defaults are what the next load finds
+        assert!(
+            ConfigStorage::open("meditrack", training_dir, None)
+                .unwrap()
+                .load()
+                .unwrap()
+                .training
+        );
+        assert!(
+            !AppConfig::load(dir.clone(), DEFAULT_PROFILE)
+                .unwrap()
+                .training
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,224 @@
+//! Schema version of the decrypted configuration and the upgrades between
+//! versions
+//!
+//! Every section of [`AppConfig`](crate::AppConfig) falls back to its
+//! defaults, so an older document would load without complaint even after a
+//! setting moved or changed meaning. The JSON therefore records the version
+//! it was written in: older documents go through the upgrades one version at
+//! a time before they are deserialized, and documents from a newer version
+//! are refused rather than loaded without the settings this version does not
+//! know.
+//!
+//! This is separate from the format version in the header of the encrypted
+//! file, which describes how the file is encrypted rather than what it holds.
+
+use serde_json::{Map, Value};
+
+use crate::error::{ConfigError, Result};
+
+/// Configuration version written by this build
+pub const CONFIG_VERSION: u32 = 3;
+
+/// Upgrade of a configuration document by one version
+type Upgrade = fn(&mut Map<String, Value>);
+
+/// The upgrade at index `n` turns version `n` into version `n + 1`
+const UPGRADES: [Upgrade; CONFIG_VERSION as usize] = [v0_to_v1, v1_to_v2, v2_to_v3];
+
+/// Version 0 covers every document written before configurations were
+/// versioned
+///
+/// Documents from before the SQLite backend have no `database.backend` and
+/// are Postgres; record that so a change of the default backend cannot
+/// switch them over.
+fn v0_to_v1(config: &mut Map<String, Value>) {
+    if let Some(Value::Object(database)) = config.get_mut("database") {
+        database
+            .entry("backend")
+            .or_insert_with(|| Value::from("postgres"));
+    }
+}
+
+/// Version 2 adds `jwt.retired_secrets`, the secrets replaced by a rotation
+/// that still verify tokens during their grace periods
+///
+/// A version 1 build would drop the list on its next save and end the grace
+/// periods early, so it has to refuse version 2 documents.
+fn v1_to_v2(config: &mut Map<String, Value>) {
+    if let Some(Value::Object(jwt)) = config.get_mut("jwt") {
+        jwt.entry("retired_secrets")
+            .or_insert_with(|| Value::Array(Vec::new()));
+    }
+}
+
+/// Version 3 adds `jwt.keys` and `jwt.signing_key`, the key pairs tokens are
+/// signed and verified with
+///
+/// An older build would drop them on its next save, losing the signing
+/// private key and every `kid` verifiers trust.
+fn v2_to_v3(config: &mut Map<String, Value>) {
+    if let Some(Value::Object(jwt)) = config.get_mut("jwt") {
+        jwt.entry("keys")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        jwt.entry("signing_key").or_insert_with(|| Value::from(""));
+    }
+}
+
+/// Bring a configuration document up to [`CONFIG_VERSION`]
+///
+/// Returns the version the document was written in.
+pub(crate) fn upgrade(config: &mut Value) -> Result<u32> {
+    let Value::Object(config) = config else {
+        return Err(ConfigError::InvalidConfig(
+            "Configuration is not a JSON object".to_string(),
+        ));
+    };
+
+    let version = match config.get("version") {
+        None => 0,
+        Some(value) => value
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| {
+                ConfigError::InvalidConfig(format!("Invalid configuration version {}", value))
+            })?,
+    };
+    if version > CONFIG_VERSION {
+        return Err(ConfigError::NewerVersion {
+            found: version,
+            supported: CONFIG_VERSION,
+        });
+    }
+
+    for (from, upgrade) in UPGRADES.iter().enumerate().skip(version as usize) {
+        upgrade(config);
+        tracing::debug!(
+            "Upgraded configuration from version {} to {}",
+            from,
+            from + 1
+        );
+    }
+    config.insert("version".to_string(), Value::from(CONFIG_VERSION));
+
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{AppConfig, DatabaseBackend};
+
+    #[test]
+    fn test_upgrade_unversioned_config() {
+        let mut config = json!({
+            "database": {
+                "host": "db.branch-2",
+                "port": 5432,
+                "database": "meditrack",
+                "username": "meditrack",
+                "password": "meditrack",
+                "max_connections": 10,
+                "min_connections": 2,
+                "connect_timeout": 30,
+                "idle_timeout": 600
+            },
+            "jwt": {
+                "secret": "s3cret",
+                "issuer": "meditrack",
+                "audience": "meditrack-app",
+                "expiration_hours": 8
+            }
+        });
+
+        assert_eq!(upgrade(&mut config).unwrap(), 0);
+        assert_eq!(config["version"], CONFIG_VERSION);
+        assert_eq!(config["database"]["backend"], "postgres");
+
+        let config: AppConfig = serde_json::from_value(config).unwrap();
+        assert_eq!(*config.version(), CONFIG_VERSION);
+        assert_eq!(config.database.backend, DatabaseBackend::Postgres);
+        assert_eq!(config.database.host, "db.branch-2");
+        assert_eq!(config.jwt.expiration_hours, 8);
+    }
+
+    #[test]
+    fn test_upgrade_v1_config() {
+        let mut config = json!({
+            "version": 1,
+            "database": { "backend": "sqlite", "sqlite_path": "meditrack.db" },
+            "jwt": {
+                "secret": "s3cret",
+                "issuer": "meditrack",
+                "audience": "meditrack-app",
+                "expiration_hours": 8
+            }
+        });
+
+        assert_eq!(upgrade(&mut config).unwrap(), 1);
+        assert_eq!(config["version"], CONFIG_VERSION);
+        assert_eq!(config["jwt"]["retired_secrets"], json!([]));
+        assert_eq!(config["database"]["backend"], "sqlite");
+
+        // A document already carrying the list keeps it
+        let retired = json!([{ "secret": "old", "valid_until": 1_900_000_000 }]);
+        let mut config = json!({ "version": 1, "jwt": { "retired_secrets": retired } });
+        upgrade(&mut config).unwrap();
+        assert_eq!(config["jwt"]["retired_secrets"], retired);
+    }
+
+    #[test]
+    fn test_upgrade_v2_config() {
+        let mut config = json!({
+            "version": 2,
+            "jwt": {
+                "secret": "s3cret",
+                "issuer": "meditrack",
+                "audience": "meditrack-app",
+                "expiration_hours": 8,
+                "retired_secrets": []
+            }
+        });
+
+        assert_eq!(upgrade(&mut config).unwrap(), 2);
+        assert_eq!(config["version"], CONFIG_VERSION);
+        assert_eq!(config["jwt"]["keys"], json!([]));
+        assert_eq!(config["jwt"]["signing_key"], "");
+
+        let config: AppConfig = serde_json::from_value(config).unwrap();
+        assert!(config.jwt.keys.is_empty());
+        assert_eq!(config.jwt.secret, "s3cret");
+    }
+
+    #[test]
+    fn test_upgrade_current_config() {
+        let current = serde_json::to_value(AppConfig::default()).unwrap();
+        let mut config = current.clone();
+
+        assert_eq!(upgrade(&mut config).unwrap(), CONFIG_VERSION);
+        assert_eq!(config, current);
+    }
+
+    #[test]
+    fn test_refuse_newer_config() {
+        let mut config = json!({ "version": CONFIG_VERSION + 1, "telemetry": true });
+        assert!(matches!(
+            upgrade(&mut config),
+            Err(ConfigError::NewerVersion { found, supported })
+                if found == CONFIG_VERSION + 1 && supported == CONFIG_VERSION
+        ));
+
+        for invalid in [
+            json!({ "version": "1" }),
+            json!({ "version": -1 }),
+            json!([]),
+        ] {
+            let mut config = invalid;
+            assert!(matches!(
+                upgrade(&mut config),
+                Err(ConfigError::InvalidConfig(_))
+            ));
+        }
+    }
+}
@@ -6,10 +6,20 @@ mod roles;
 mod suppliers;
 mod tui;
 
+use clap::Parser;
 use db_service::ServiceManager;
 use error::{Result, SeederError};
 use std::sync::Arc;
 
+/// MediTrack database seeder
+#[derive(Parser, Debug)]
+#[command(name = "seeder")]
+struct Cli {
+    /// Configuration profile whose database is seeded, e.g. `training`
+    #[arg(long, value_name = "NAME", default_value = app_config::DEFAULT_PROFILE)]
+    profile: String,
+}
+
 /// Main seeder orchestrator
 pub struct Seeder {
     service_manager: Arc<ServiceManager>,
@@ -60,5 +70,6 @@ impl Seeder {
 
 #[tokio::main]
 async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    tui::run_seeder_tui().await
+    let cli = Cli::parse();
+    tui::run_seeder_tui(&cli.profile).await
 }
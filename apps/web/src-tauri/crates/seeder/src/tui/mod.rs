@@ -10,7 +10,7 @@ use state::*;
 use std::sync::Arc;
 
 use crate::Seeder;
-use db_service::{DatabaseConfig, JwtConfig, ServiceManager};
+use db_service::{BackupConfig, DatabaseConfig, JwtConfig, ServiceManager};
 
 #[derive(Clone)]
 pub struct SeederTUI {
@@ -364,39 +364,25 @@ fn handle_key_event(
     }
 }
 
-pub async fn run_seeder_tui() -> Result<(), Box<dyn std::error::Error>> {
-    // Get config directory
-    let config_dir = dirs::config_dir()
-        .ok_or("Could not determine config directory")?
-        .join("meditrack");
-
-    // Check if config exists
-    let config_path = config_dir.join("config.enc");
-    let config_exists = config_path.exists();
-
-    // Prompt for password
-    let password = if config_exists {
-        // Config exists, just ask for password
-        dialoguer::Password::new()
-            .with_prompt("Enter configuration password")
-            .allow_empty_password(false)
-            .interact()?
-    } else {
-        // Config doesn't exist, ask for password with confirmation
-        println!("No configuration found. Creating new configuration.");
-        dialoguer::Password::new()
-            .with_prompt("Create configuration password")
-            .with_confirmation("Confirm password", "Passwords do not match")
-            .allow_empty_password(false)
-            .interact()?
-    };
+pub async fn run_seeder_tui(profile: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // Get the config directory of the profile
+    let config_dir = app_config::ConfigStorage::profile_dir(
+        &app_config::app_config_dir(app_config::APP_IDENTIFIER)
+            .ok_or("Could not determine config directory")?,
+        profile,
+    )?;
+    println!("Using configuration profile {}", profile);
+
+    // Ask only for what the configuration's key source needs
+    let key_source = app_config::cli_tui::prompt_key_source(&config_dir)?;
 
-    // Load configuration with password
-    let app_config = load_config_with_password(&config_dir, &password)?;
+    // Load configuration with the key source
+    let mut app_config = load_config_with_key_source(&config_dir, profile, key_source)?;
+    app_config.database.resolve_sqlite_path(&config_dir);
 
     // Convert app_config DatabaseConfig to db_service DatabaseConfig
     let db_config = DatabaseConfig {
-        url: app_config.database.connection_url(),
+        url: app_config.database.connection_url()?,
         max_connections: app_config.database.max_connections,
         min_connections: app_config.database.min_connections,
         connect_timeout: app_config.database.connect_timeout,
@@ -409,29 +395,42 @@ pub async fn run_seeder_tui() -> Result<(), Box<dyn std::error::Error>> {
         issuer: app_config.jwt.issuer,
         audience: app_config.jwt.audience,
         expiration_hours: app_config.jwt.expiration_hours,
+        // The seeder issues no tokens
+        retired_secrets: Vec::new(),
+        allow_default_secret: true,
+        keys: Vec::new(),
+        signing_key: String::new(),
+    };
+
+    // The seeder never runs the scheduler, so backups need no cipher
+    let backup_config = BackupConfig {
+        dir: config_dir.join("backups"),
+        cipher: None,
     };
 
     // Initialize service manager
-    let service_manager = Arc::new(ServiceManager::init(db_config, jwt_config).await?);
+    let service_manager =
+        Arc::new(ServiceManager::init(db_config, jwt_config, backup_config).await?);
 
     let tui = SeederTUI { service_manager };
     reratui::render(move || tui.clone()).await?;
     Ok(())
 }
 
-fn load_config_with_password(
+fn load_config_with_key_source(
     config_dir: &std::path::Path,
-    password: &str,
+    profile: &str,
+    key_source: app_config::KeySource,
 ) -> Result<app_config::AppConfig, Box<dyn std::error::Error>> {
     use app_config::ConfigStorage;
 
     let storage =
-        ConfigStorage::new_with_password("meditrack", config_dir.to_path_buf(), password)?;
+        ConfigStorage::new_with_key_source("meditrack", config_dir.to_path_buf(), key_source)?;
 
     match storage.load() {
         Ok(config) => Ok(config),
         Err(app_config::ConfigError::NotFound) => {
-            let config = app_config::AppConfig::default();
+            let config = app_config::AppConfig::for_profile(profile);
 
             // Try to save default config for next time
             if let Err(_e) = storage.save(&config) {
@@ -440,9 +439,15 @@ fn load_config_with_password(
 
             Ok(config)
         }
-        Err(app_config::ConfigError::Decryption(_)) => Err(
+        Err(app_config::ConfigError::WrongPassword | app_config::ConfigError::Decryption(_)) => Err(
             "Incorrect password. Please try again or delete the config file to start fresh.".into(),
         ),
+        Err(app_config::ConfigError::Corrupt(reason)) => Err(format!(
+            "The config file is corrupt ({}). Restore it from a backup or delete it to start fresh.",
+            reason
+        )
+        .into()),
+        Err(e @ app_config::ConfigError::NewerVersion { .. }) => Err(e.to_string().into()),
         Err(e) => Err(format!("Failed to load configuration: {:?}", e).into()),
     }
 }
@@ -1,6 +1,8 @@
 use super::super::inventory_item_barcode::dto::InventoryItemBarcodeResponse;
+use super::super::inventory_opening_balance::dto::ImportRowError;
 use super::Id;
 use super::Model;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
 /// DTO for creating a new barcode with item
@@ -128,6 +130,57 @@ pub struct InventoryItemWithStockResponse {
     pub barcodes: Vec<InventoryItemBarcodeResponse>,
 }
 
+/// Item identified from scanner input, with the lot data a GS1 code carries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScannedBarcode {
+    pub item: InventoryItemWithStockResponse,
+    /// GTIN-14 from AI (01); `None` for a plain barcode
+    pub gtin: Option<String>,
+    /// Batch or lot number, AI (10)
+    pub batch_number: Option<String>,
+    /// Expiry date, AI (17)
+    pub expiry_date: Option<NaiveDate>,
+    /// Serial number, AI (21)
+    pub serial_number: Option<String>,
+}
+
+/// Options for importing catalogue items from a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogueImportOptions {
+    /// Original file name, used in logs
+    pub file_name: String,
+    /// Validate and report without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Create manufacturers and medicine forms that don't exist yet
+    #[serde(default = "default_true")]
+    pub create_missing: bool,
+    /// User recorded as creating the imported items' stock
+    #[serde(default)]
+    pub created_by: Option<Id>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Result of a catalogue import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogueImportResult {
+    pub success: bool,
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub created_count: usize,
+    pub error_count: usize,
+    /// Manufacturers created (or, in a dry run, to be created)
+    pub created_manufacturers: Vec<String>,
+    /// Medicine form codes created (or, in a dry run, to be created)
+    pub created_medicine_forms: Vec<String>,
+    pub errors: Vec<ImportRowError>,
+    /// CSV of the rejected rows with an `error` column, ready to fix and re-import
+    pub error_report: Option<String>,
+}
+
 impl From<Model> for InventoryItemResponse {
     fn from(model: Model) -> Self {
         Self {
@@ -23,6 +23,8 @@ pub struct CreateOpeningBalanceDto {
     pub notes: Option<String>,
     #[serde(default)]
     pub allow_override: bool,
+    /// User entering the balance
+    pub entered_by: Id,
 }
 
 /// DTO for updating an existing opening balance entry
@@ -40,6 +42,8 @@ pub struct CreateAdjustmentDto {
     pub unit_price: Decimal,
     pub reason: String,
     pub notes: Option<String>,
+    /// User entering the adjustment
+    pub entered_by: Id,
 }
 
 /// DTO for bulk import row
@@ -77,6 +81,23 @@ impl OpeningBalanceImportRow {
     }
 }
 
+/// Options for importing opening balances from a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpeningBalanceImportOptions {
+    /// Original file name, stored on every imported entry
+    pub file_name: String,
+    /// Entry date for imported entries (defaults to today)
+    pub entry_date: Option<NaiveDate>,
+    /// Validate and report without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Import items that already have an active initial opening balance
+    #[serde(default)]
+    pub allow_override: bool,
+    /// User recorded as entering every imported balance
+    pub entered_by: Id,
+}
+
 /// DTO for creating opening balance with import metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateOpeningBalanceWithImportDto {
@@ -206,6 +227,8 @@ pub struct EntriesByType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportResult {
     pub success: bool,
+    #[serde(default)]
+    pub dry_run: bool,
     pub total_rows: usize,
     pub success_count: usize,
     pub error_count: usize,
@@ -1,5 +1,6 @@
 use super::Id;
 use super::Model;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
 /// DTO for creating a new inventory stock record
@@ -12,19 +13,39 @@ pub struct CreateInventoryStock {
 }
 
 /// DTO for updating inventory stock
+///
+/// `reason` is required whenever `unit_price` changes the current price.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateInventoryStock {
     pub stock_quantity: Option<i32>,
     pub min_stock_level: Option<i32>,
     pub unit_price: Option<f64>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub updated_by: Option<Id>,
 }
 
 /// DTO for stock adjustment
+///
+/// Adjustments sharing an `idempotency_key` are applied only once, so callers
+/// can safely retry. The movements of one sale share its `reference_id`,
+/// which is what its receipt is built from.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdjustStock {
     pub adjustment: i32, // Positive for add, negative for subtract
     pub reason: Option<String>,
     pub adjustment_type: Option<super::super::inventory_stock_history::StockAdjustmentType>,
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    #[serde(default)]
+    pub adjusted_by: Option<Id>,
+    /// Record the adjustment belongs to, e.g. the sale
+    #[serde(default)]
+    pub reference_id: Option<Id>,
+    /// Kind of `reference_id`; defaults to `sale` for sale adjustments
+    #[serde(default)]
+    pub reference_type: Option<String>,
 }
 
 /// DTO for inventory stock response
@@ -54,3 +75,69 @@ impl From<Model> for InventoryStockResponse {
         }
     }
 }
+
+/// How stock is priced in a valuation report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValuationMethod {
+    /// Unit price in effect at the report date
+    #[default]
+    CurrentPrice,
+    /// Moving weighted average of the price paid on every stock receipt
+    WeightedAverageCost,
+}
+
+/// How items are grouped in a valuation report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValuationGroupBy {
+    #[default]
+    Manufacturer,
+    MedicineForm,
+}
+
+/// Query for an inventory valuation report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValuationQuery {
+    /// Stock is valued at the end of this day (UTC)
+    pub as_of: NaiveDate,
+    #[serde(default)]
+    pub method: ValuationMethod,
+    #[serde(default)]
+    pub group_by: ValuationGroupBy,
+}
+
+/// Quantity and value of one item at the report date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemValuation {
+    pub inventory_item_id: Id,
+    pub name: String,
+    pub quantity: i32,
+    pub unit_cost: f64,
+    pub value: f64,
+    /// No price was recorded on or before the report date, so the current
+    /// price was used
+    pub price_estimated: bool,
+}
+
+/// Items sharing a manufacturer or medicine form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValuationGroup {
+    /// Manufacturer or medicine form id; `None` for items without a manufacturer
+    pub id: Option<Id>,
+    pub name: String,
+    pub total_quantity: i64,
+    pub total_value: f64,
+    pub items: Vec<ItemValuation>,
+}
+
+/// Stock value at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValuationReport {
+    pub as_of: NaiveDate,
+    pub method: ValuationMethod,
+    pub group_by: ValuationGroupBy,
+    pub total_quantity: i64,
+    pub total_value: f64,
+    pub groups: Vec<ValuationGroup>,
+}
@@ -76,6 +76,10 @@ pub struct Model {
     /// User who made the adjustment - PostgreSQL UUID (nullable)
     #[sea_orm(column_type = "Uuid", nullable)]
     pub recorded_by: Option<Id>,
+
+    /// Client-supplied key that makes retried adjustments idempotent - VARCHAR(100) (nullable, unique)
+    #[sea_orm(column_type = "String(StringLen::N(100))", nullable)]
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
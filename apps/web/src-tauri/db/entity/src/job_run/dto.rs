@@ -0,0 +1,49 @@
+use super::Id;
+use super::JobRunStatus;
+use super::Model;
+use serde::{Deserialize, Serialize};
+
+/// Response DTO for a job run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRunResponse {
+    pub id: Id,
+    pub job_name: String,
+    pub status: JobRunStatus,
+    pub trigger: String,
+    pub started_at: String,          // ISO 8601 timestamp
+    pub finished_at: Option<String>, // ISO 8601 timestamp
+    pub duration_ms: Option<i64>,
+    pub message: Option<String>,
+}
+
+impl From<Model> for JobRunResponse {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            job_name: model.job_name,
+            status: model.status,
+            trigger: model.trigger,
+            started_at: model.started_at.to_string(),
+            finished_at: model.finished_at.map(|dt| dt.to_string()),
+            duration_ms: model.duration_ms,
+            message: model.message,
+        }
+    }
+}
+
+/// Query filter for job runs
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct JobRunQueryDto {
+    pub job_name: Option<String>,
+    pub status: Option<JobRunStatus>,
+}
+
+/// Current schedule of a registered job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobScheduleResponse {
+    pub job_name: String,
+    pub cron: String,
+    pub enabled: bool,
+    pub is_running: bool,
+    pub next_run_at: Option<String>, // ISO 8601 timestamp
+}
@@ -0,0 +1,63 @@
+pub mod dto;
+
+use super::id::Id;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Job run status enum - PostgreSQL native enum type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "job_run_status")]
+#[serde(rename_all = "snake_case")]
+pub enum JobRunStatus {
+    #[sea_orm(string_value = "running")]
+    Running,
+    #[sea_orm(string_value = "succeeded")]
+    Succeeded,
+    #[sea_orm(string_value = "failed")]
+    Failed,
+    /// The job was due but a previous run was still in progress
+    #[sea_orm(string_value = "skipped")]
+    Skipped,
+}
+
+/// Job run entity - records every execution of a background job
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "job_runs")]
+pub struct Model {
+    /// Primary key - PostgreSQL UUID type
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Uuid")]
+    pub id: Id,
+
+    /// Name of the job that ran - VARCHAR(100)
+    #[sea_orm(column_type = "String(StringLen::N(100))")]
+    pub job_name: String,
+
+    /// Run status - PostgreSQL ENUM type
+    pub status: JobRunStatus,
+
+    /// What started the run ("schedule" or "manual") - VARCHAR(20)
+    #[sea_orm(column_type = "String(StringLen::N(20))")]
+    pub trigger: String,
+
+    /// When the run started - PostgreSQL TIMESTAMPTZ
+    #[sea_orm(column_type = "TimestampWithTimeZone")]
+    pub started_at: DateTimeWithTimeZone,
+
+    /// When the run finished - PostgreSQL TIMESTAMPTZ (nullable while running)
+    #[sea_orm(column_type = "TimestampWithTimeZone", nullable)]
+    pub finished_at: Option<DateTimeWithTimeZone>,
+
+    /// Run duration in milliseconds - BIGINT (nullable while running)
+    #[sea_orm(nullable)]
+    pub duration_ms: Option<i64>,
+
+    /// Summary or error message produced by the run - TEXT (nullable)
+    #[sea_orm(column_type = "Text", nullable)]
+    pub message: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {}
@@ -8,6 +8,7 @@ pub mod inventory_opening_balance;
 pub mod inventory_price_history;
 pub mod inventory_stock;
 pub mod inventory_stock_history;
+pub mod job_run;
 pub mod manufacturer;
 pub mod medicine_form;
 pub mod role;
@@ -43,6 +44,9 @@ pub mod prelude {
     pub use super::inventory_stock_history;
     pub use super::inventory_stock_history::Entity as InventoryStockHistory;
     pub use super::inventory_stock_history::dto as inventory_stock_history_dto;
+    pub use super::job_run;
+    pub use super::job_run::Entity as JobRun;
+    pub use super::job_run::dto as job_run_dto;
     pub use super::manufacturer;
     pub use super::manufacturer::Entity as Manufacturer;
     pub use super::manufacturer::dto as manufacturer_dto;
@@ -43,6 +43,7 @@ pub struct SpecialOrderResponse {
     pub delivery_date: Option<String>,
     pub total_amount: f64,
     pub deposit_paid: Option<f64>,
+    pub deposit_paid_at: Option<String>,
     pub notes: Option<String>,
     pub internal_notes: Option<String>,
     pub created_by: Option<String>,
@@ -67,6 +68,7 @@ impl From<Model> for SpecialOrderResponse {
             deposit_paid: model
                 .deposit_paid
                 .map(|d| d.to_string().parse().unwrap_or(0.0)),
+            deposit_paid_at: model.deposit_paid_at.map(|d| d.to_string()),
             notes: model.notes,
             internal_notes: model.internal_notes,
             created_by: model.created_by.map(|id| id.to_string()),
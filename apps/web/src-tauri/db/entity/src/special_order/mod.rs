@@ -73,6 +73,10 @@ pub struct Model {
     #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
     pub deposit_paid: Option<Decimal>,
 
+    /// When the deposit was collected - PostgreSQL TIMESTAMPTZ (nullable)
+    #[sea_orm(column_type = "TimestampWithTimeZone", nullable)]
+    pub deposit_paid_at: Option<DateTimeWithTimeZone>,
+
     /// Additional notes - TEXT (nullable)
     #[sea_orm(column_type = "Text", nullable)]
     pub notes: Option<String>,
@@ -158,7 +162,8 @@ impl ActiveModelBehavior for ActiveModel {
         }
     }
 
-    /// Called before save - update timestamp on modifications
+    /// Called before save - update timestamp on modifications and record
+    /// when a deposit is collected
     async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
     where
         C: ConnectionTrait,
@@ -166,6 +171,12 @@ impl ActiveModelBehavior for ActiveModel {
         if !insert {
             self.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
         }
+        if let sea_orm::ActiveValue::Set(deposit_paid) = &self.deposit_paid
+            && !self.deposit_paid_at.is_set()
+        {
+            self.deposit_paid_at =
+                sea_orm::ActiveValue::Set(deposit_paid.map(|_| chrono::Utc::now().into()));
+        }
         Ok(self)
     }
 }
@@ -1,6 +1,8 @@
 pub use sea_orm_migration::prelude::*;
 use sea_orm_migration::sea_orm::DatabaseConnection;
 
+mod schema_ext;
+
 mod m20250130_000001_create_enums;
 mod m20250130_000002_create_staff_table;
 mod m20250130_000003_create_roles_table;
@@ -19,6 +21,10 @@ mod m20250131_000006_create_supplier_inventory_items_table;
 mod m20250131_000007_create_settings_table;
 mod m20250131_000008_create_sessions_table;
 mod m20250201_000001_create_inventory_stock_history_table;
+mod m20250202_000001_create_job_runs_table;
+mod m20250203_000001_drop_price_history_trigger;
+mod m20250204_000001_add_stock_history_idempotency_key;
+mod m20250205_000001_add_special_order_deposit_paid_at;
 
 pub struct Migrator;
 
@@ -44,6 +50,10 @@ impl MigratorTrait for Migrator {
             Box::new(m20250131_000006_create_supplier_inventory_items_table::Migration),
             Box::new(m20250131_000007_create_settings_table::Migration),
             Box::new(m20250131_000008_create_sessions_table::Migration),
+            Box::new(m20250202_000001_create_job_runs_table::Migration),
+            Box::new(m20250203_000001_drop_price_history_trigger::Migration),
+            Box::new(m20250204_000001_add_stock_history_idempotency_key::Migration),
+            Box::new(m20250205_000001_add_special_order_deposit_paid_at::Migration),
         ]
     }
 }
@@ -51,3 +61,12 @@ impl MigratorTrait for Migrator {
 pub async fn run_migrations(db: &DatabaseConnection) -> Result<(), DbErr> {
     Migrator::up(db, None).await
 }
+
+/// Name of the latest applied migration, or `None` on an empty database
+///
+/// Backups are stamped with this value so that a dump is only restored into
+/// a database with the same schema.
+pub async fn schema_version(db: &DatabaseConnection) -> Result<Option<String>, DbErr> {
+    let applied = Migrator::get_applied_migrations(db).await?;
+    Ok(applied.last().map(|migration| migration.name().to_string()))
+}
@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+use crate::schema_ext::SchemaManagerExt;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -8,8 +10,7 @@ impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Create employment_status ENUM type
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TYPE employment_status AS ENUM (
                     'active',
@@ -22,8 +23,7 @@ impl MigrationTrait for Migration {
 
         // Create work_schedule ENUM type
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TYPE work_schedule AS ENUM (
                     'full_time',
@@ -36,8 +36,7 @@ impl MigrationTrait for Migration {
 
         // Create user_status ENUM type
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TYPE user_status AS ENUM (
                     'active',
@@ -51,8 +50,7 @@ impl MigrationTrait for Migration {
 
         // Create special_order_status ENUM type
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TYPE special_order_status AS ENUM (
                     'pending',
@@ -68,8 +66,7 @@ impl MigrationTrait for Migration {
 
         // Create stock_adjustment_type ENUM type
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TYPE stock_adjustment_type AS ENUM (
                     'manual_adjustment',
@@ -88,8 +85,7 @@ impl MigrationTrait for Migration {
 
         // Create opening_balance_entry_type ENUM type
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TYPE opening_balance_entry_type AS ENUM (
                     'initial',
@@ -107,33 +103,27 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Drop ENUM types in reverse order
         manager
-            .get_connection()
-            .execute_unprepared("DROP TYPE IF EXISTS opening_balance_entry_type CASCADE;")
+            .execute_postgres("DROP TYPE IF EXISTS opening_balance_entry_type CASCADE;")
             .await?;
 
         manager
-            .get_connection()
-            .execute_unprepared("DROP TYPE IF EXISTS stock_adjustment_type CASCADE;")
+            .execute_postgres("DROP TYPE IF EXISTS stock_adjustment_type CASCADE;")
             .await?;
 
         manager
-            .get_connection()
-            .execute_unprepared("DROP TYPE IF EXISTS special_order_status CASCADE;")
+            .execute_postgres("DROP TYPE IF EXISTS special_order_status CASCADE;")
             .await?;
 
         manager
-            .get_connection()
-            .execute_unprepared("DROP TYPE IF EXISTS user_status CASCADE;")
+            .execute_postgres("DROP TYPE IF EXISTS user_status CASCADE;")
             .await?;
 
         manager
-            .get_connection()
-            .execute_unprepared("DROP TYPE IF EXISTS work_schedule CASCADE;")
+            .execute_postgres("DROP TYPE IF EXISTS work_schedule CASCADE;")
             .await?;
 
         manager
-            .get_connection()
-            .execute_unprepared("DROP TYPE IF EXISTS employment_status CASCADE;")
+            .execute_postgres("DROP TYPE IF EXISTS employment_status CASCADE;")
             .await?;
 
         Ok(())
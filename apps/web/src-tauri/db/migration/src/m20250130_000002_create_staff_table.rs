@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+use crate::schema_ext::SchemaManagerExt;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -139,8 +141,7 @@ impl MigrationTrait for Migration {
 
         // Create trigger to auto-update updated_at
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE OR REPLACE FUNCTION update_updated_at_column()
                 RETURNS TRIGGER AS $$
@@ -164,8 +165,7 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Drop trigger first
         manager
-            .get_connection()
-            .execute_unprepared("DROP TRIGGER IF EXISTS update_staff_updated_at ON staff;")
+            .execute_postgres("DROP TRIGGER IF EXISTS update_staff_updated_at ON staff;")
             .await?;
 
         // Drop table (indexes will be dropped automatically)
@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+use crate::schema_ext::SchemaManagerExt;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -94,8 +96,7 @@ impl MigrationTrait for Migration {
 
         // GIN index for JSONB permissions field
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 "CREATE INDEX idx_roles_permissions ON roles USING GIN (permissions);",
             )
             .await?;
@@ -118,8 +119,7 @@ impl MigrationTrait for Migration {
 
         // Create trigger to auto-update updated_at
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TRIGGER update_roles_updated_at
                     BEFORE UPDATE ON roles
@@ -135,8 +135,7 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Drop trigger first
         manager
-            .get_connection()
-            .execute_unprepared("DROP TRIGGER IF EXISTS update_roles_updated_at ON roles;")
+            .execute_postgres("DROP TRIGGER IF EXISTS update_roles_updated_at ON roles;")
             .await?;
 
         // Drop table (indexes will be dropped automatically)
@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+use crate::schema_ext::SchemaManagerExt;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -208,8 +210,7 @@ impl MigrationTrait for Migration {
 
         // Create trigger to auto-update updated_at
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TRIGGER update_users_updated_at
                     BEFORE UPDATE ON users
@@ -225,8 +226,7 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Drop trigger first
         manager
-            .get_connection()
-            .execute_unprepared("DROP TRIGGER IF EXISTS update_users_updated_at ON users;")
+            .execute_postgres("DROP TRIGGER IF EXISTS update_users_updated_at ON users;")
             .await?;
 
         // Drop table (foreign keys will be dropped automatically)
@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+use crate::schema_ext::SchemaManagerExt;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -81,8 +83,7 @@ impl MigrationTrait for Migration {
 
         // Create trigger to auto-update updated_at for manufacturers
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TRIGGER update_manufacturers_updated_at
                     BEFORE UPDATE ON manufacturers
@@ -98,8 +99,7 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Drop trigger
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 "DROP TRIGGER IF EXISTS update_manufacturers_updated_at ON manufacturers;",
             )
             .await?;
@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+use crate::schema_ext::SchemaManagerExt;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -96,8 +98,7 @@ impl MigrationTrait for Migration {
 
         // Create trigger to auto-update updated_at for medicine_forms
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TRIGGER update_medicine_forms_updated_at
                     BEFORE UPDATE ON medicine_forms
@@ -113,8 +114,7 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Drop trigger
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 "DROP TRIGGER IF EXISTS update_medicine_forms_updated_at ON medicine_forms;",
             )
             .await?;
@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+use crate::schema_ext::SchemaManagerExt;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -122,8 +124,7 @@ impl MigrationTrait for Migration {
 
         // Create trigger to auto-update updated_at
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TRIGGER update_customers_updated_at
                     BEFORE UPDATE ON customers
@@ -139,8 +140,7 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Drop trigger first
         manager
-            .get_connection()
-            .execute_unprepared("DROP TRIGGER IF EXISTS update_customers_updated_at ON customers;")
+            .execute_postgres("DROP TRIGGER IF EXISTS update_customers_updated_at ON customers;")
             .await?;
 
         // Drop table (indexes will be dropped automatically)
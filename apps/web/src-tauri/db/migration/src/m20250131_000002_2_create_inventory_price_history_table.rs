@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+use crate::schema_ext::SchemaManagerExt;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -78,8 +80,7 @@ impl MigrationTrait for Migration {
 
         // Create trigger function to record price changes
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE OR REPLACE FUNCTION record_price_change()
                 RETURNS TRIGGER AS $$
@@ -118,8 +119,7 @@ impl MigrationTrait for Migration {
 
         // Attach trigger to inventory_stock table
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TRIGGER price_history_trigger
                     AFTER UPDATE OF unit_price ON inventory_stock
@@ -135,14 +135,12 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Drop trigger
         manager
-            .get_connection()
-            .execute_unprepared("DROP TRIGGER IF EXISTS price_history_trigger ON inventory_stock;")
+            .execute_postgres("DROP TRIGGER IF EXISTS price_history_trigger ON inventory_stock;")
             .await?;
 
         // Drop function
         manager
-            .get_connection()
-            .execute_unprepared("DROP FUNCTION IF EXISTS record_price_change();")
+            .execute_postgres("DROP FUNCTION IF EXISTS record_price_change();")
             .await?;
 
         // Drop inventory_price_history table
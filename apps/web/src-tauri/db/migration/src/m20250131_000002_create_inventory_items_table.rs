@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+use crate::schema_ext::SchemaManagerExt;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -165,8 +167,7 @@ impl MigrationTrait for Migration {
 
         // Create trigger to auto-update updated_at for inventory_items
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TRIGGER update_inventory_items_updated_at
                     BEFORE UPDATE ON inventory_items
@@ -182,8 +183,7 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Drop trigger
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 "DROP TRIGGER IF EXISTS update_inventory_items_updated_at ON inventory_items;",
             )
             .await?;
@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+use crate::schema_ext::SchemaManagerExt;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -89,8 +91,7 @@ impl MigrationTrait for Migration {
 
         // Create trigger to auto-update updated_at
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TRIGGER update_suppliers_updated_at
                     BEFORE UPDATE ON suppliers
@@ -106,8 +107,7 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Drop trigger first
         manager
-            .get_connection()
-            .execute_unprepared("DROP TRIGGER IF EXISTS update_suppliers_updated_at ON suppliers;")
+            .execute_postgres("DROP TRIGGER IF EXISTS update_suppliers_updated_at ON suppliers;")
             .await?;
 
         // Drop table (indexes will be dropped automatically)
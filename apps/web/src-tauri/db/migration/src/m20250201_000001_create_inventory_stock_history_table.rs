@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+use crate::schema_ext::SchemaManagerExt;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -114,8 +116,7 @@ impl MigrationTrait for Migration {
 
         // Create trigger function to record stock changes
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE OR REPLACE FUNCTION record_stock_change()
                 RETURNS TRIGGER AS $$
@@ -164,8 +165,7 @@ impl MigrationTrait for Migration {
 
         // Attach trigger to inventory_stock table
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TRIGGER stock_history_trigger
                     AFTER UPDATE OF stock_quantity ON inventory_stock
@@ -329,31 +329,24 @@ impl MigrationTrait for Migration {
                             .on_delete(ForeignKeyAction::SetNull)
                             .on_update(ForeignKeyAction::Cascade),
                     )
+                    // Declared with the table so SQLite, which cannot add
+                    // constraints to an existing table, enforces them too
+                    .check(Expr::col(InventoryOpeningBalance::Quantity).gte(0))
+                    .check(Expr::col(InventoryOpeningBalance::UnitPrice).gte(0))
+                    .check(Expr::cust(
+                        "expiry_date IS NULL OR expiry_date > entry_date",
+                    ))
+                    .check(Expr::cust(
+                        "(entry_type = 'initial' AND adjusted_from_id IS NULL) OR \
+                         (entry_type IN ('adjustment', 'correction') AND adjusted_from_id IS NOT NULL)",
+                    ))
                     .to_owned(),
             )
             .await?;
 
-        // Add CHECK constraints
-        manager
-            .get_connection()
-            .execute_unprepared(
-                r#"
-                ALTER TABLE inventory_opening_balances
-                ADD CONSTRAINT check_quantity_non_negative CHECK (quantity >= 0),
-                ADD CONSTRAINT check_unit_price_non_negative CHECK (unit_price >= 0),
-                ADD CONSTRAINT check_valid_expiry_date CHECK (expiry_date IS NULL OR expiry_date > entry_date),
-                ADD CONSTRAINT check_adjustment_requires_reference CHECK (
-                    (entry_type = 'initial' AND adjusted_from_id IS NULL) OR
-                    (entry_type IN ('adjustment', 'correction') AND adjusted_from_id IS NOT NULL)
-                );
-                "#,
-            )
-            .await?;
-
         // Add computed column for total_value
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 ALTER TABLE inventory_opening_balances
                 ADD COLUMN total_value DECIMAL(12,2) GENERATED ALWAYS AS (quantity * unit_price) STORED;
@@ -426,8 +419,7 @@ impl MigrationTrait for Migration {
 
         // Create trigger to auto-update updated_at for inventory_opening_balances
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 CREATE TRIGGER update_opening_balances_updated_at
                     BEFORE UPDATE ON inventory_opening_balances
@@ -439,8 +431,7 @@ impl MigrationTrait for Migration {
 
         // Add comments for documentation
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 r#"
                 COMMENT ON TABLE inventory_opening_balances IS 
                     'Stores opening balance entries for inventory items with full audit trail';
@@ -458,8 +449,7 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         // Drop opening balances trigger
         manager
-            .get_connection()
-            .execute_unprepared(
+            .execute_postgres(
                 "DROP TRIGGER IF EXISTS update_opening_balances_updated_at ON inventory_opening_balances;",
             )
             .await?;
@@ -476,14 +466,12 @@ impl MigrationTrait for Migration {
 
         // Drop stock history trigger
         manager
-            .get_connection()
-            .execute_unprepared("DROP TRIGGER IF EXISTS stock_history_trigger ON inventory_stock;")
+            .execute_postgres("DROP TRIGGER IF EXISTS stock_history_trigger ON inventory_stock;")
             .await?;
 
         // Drop function
         manager
-            .get_connection()
-            .execute_unprepared("DROP FUNCTION IF EXISTS record_stock_change();")
+            .execute_postgres("DROP FUNCTION IF EXISTS record_stock_change();")
             .await?;
 
         // Drop inventory_stock_history table
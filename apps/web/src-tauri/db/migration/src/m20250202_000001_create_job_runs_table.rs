@@ -0,0 +1,114 @@
+use sea_orm_migration::prelude::*;
+
+use crate::schema_ext::SchemaManagerExt;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create job_run_status ENUM type
+        manager
+            .execute_postgres(
+                r#"
+                CREATE TYPE job_run_status AS ENUM (
+                    'running',
+                    'succeeded',
+                    'failed',
+                    'skipped'
+                );
+                "#,
+            )
+            .await?;
+
+        // ========================================
+        // Create job_runs table
+        // ========================================
+        manager
+            .create_table(
+                Table::create()
+                    .table(Alias::new("job_runs"))
+                    .if_not_exists()
+                    .col(ColumnDef::new(JobRun::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(JobRun::JobName).string_len(100).not_null())
+                    .col(
+                        ColumnDef::new(JobRun::Status)
+                            .custom(Alias::new("job_run_status"))
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(JobRun::Trigger)
+                            .string_len(20)
+                            .not_null()
+                            .default("schedule"),
+                    )
+                    .col(
+                        ColumnDef::new(JobRun::StartedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(JobRun::FinishedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(JobRun::DurationMs).big_integer().null())
+                    .col(ColumnDef::new(JobRun::Message).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create composite index on (job_name, started_at DESC)
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE INDEX idx_job_runs_name_time ON job_runs (job_name, started_at DESC);",
+            )
+            .await?;
+
+        // Create index on status for filtering failed runs
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_job_runs_status")
+                    .table(Alias::new("job_runs"))
+                    .col(JobRun::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Drop job_runs table (indexes will be dropped automatically)
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(Alias::new("job_runs"))
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .execute_postgres("DROP TYPE IF EXISTS job_run_status CASCADE;")
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum JobRun {
+    Id,
+    JobName,
+    Status,
+    Trigger,
+    StartedAt,
+    FinishedAt,
+    DurationMs,
+    Message,
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+use crate::schema_ext::SchemaManagerExt;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Price history is written by the services with the acting user and
+        // reason; the trigger always stored NULL for both
+        manager
+            .execute_postgres("DROP TRIGGER IF EXISTS price_history_trigger ON inventory_stock;")
+            .await?;
+
+        manager
+            .execute_postgres("DROP FUNCTION IF EXISTS record_price_change();")
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Restore function to record price changes
+        manager
+            .execute_postgres(
+                r#"
+                CREATE OR REPLACE FUNCTION record_price_change()
+                RETURNS TRIGGER AS $$
+                BEGIN
+                    BEGIN
+                        -- Only record if price actually changed
+                        IF OLD.unit_price IS DISTINCT FROM NEW.unit_price THEN
+                            INSERT INTO inventory_price_history (
+                                id,
+                                inventory_item_id,
+                                unit_price,
+                                recorded_at,
+                                changed_by,
+                                reason
+                            ) VALUES (
+                                gen_random_uuid(),
+                                NEW.inventory_item_id,
+                                NEW.unit_price,
+                                NOW(),
+                                NULL,
+                                NULL
+                            );
+                        END IF;
+                    EXCEPTION
+                        WHEN OTHERS THEN
+                            -- Log error but don't block the stock update
+                            RAISE WARNING 'Failed to record price history: %', SQLERRM;
+                    END;
+                    
+                    RETURN NEW;
+                END;
+                $$ LANGUAGE plpgsql;
+                "#,
+            )
+            .await?;
+
+        // Restore trigger on inventory_stock table
+        manager
+            .execute_postgres(
+                r#"
+                CREATE TRIGGER price_history_trigger
+                    AFTER UPDATE OF unit_price ON inventory_stock
+                    FOR EACH ROW
+                    EXECUTE FUNCTION record_price_change();
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use sea_orm_migration::prelude::*;
+
+use crate::schema_ext::SchemaManagerExt;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Stock history is written by the services in the same transaction as
+        // the stock change; the trigger double-wrote every adjustment
+        manager
+            .execute_postgres("DROP TRIGGER IF EXISTS stock_history_trigger ON inventory_stock;")
+            .await?;
+
+        manager
+            .execute_postgres("DROP FUNCTION IF EXISTS record_stock_change();")
+            .await?;
+
+        // Add idempotency key so retried adjustments are applied only once
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alias::new("inventory_stock_history"))
+                    .add_column(
+                        ColumnDef::new(InventoryStockHistory::IdempotencyKey)
+                            .string_len(100)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create unique index on idempotency_key (NULLs are not compared)
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_stock_history_idempotency_key")
+                    .table(Alias::new("inventory_stock_history"))
+                    .col(InventoryStockHistory::IdempotencyKey)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Drop idempotency key index and column
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_stock_history_idempotency_key")
+                    .table(Alias::new("inventory_stock_history"))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alias::new("inventory_stock_history"))
+                    .drop_column(InventoryStockHistory::IdempotencyKey)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Restore function to record stock changes
+        manager
+            .execute_postgres(
+                r#"
+                CREATE OR REPLACE FUNCTION record_stock_change()
+                RETURNS TRIGGER AS $$
+                BEGIN
+                    BEGIN
+                        -- Only record if stock quantity actually changed
+                        IF OLD.stock_quantity IS DISTINCT FROM NEW.stock_quantity THEN
+                            INSERT INTO inventory_stock_history (
+                                id,
+                                inventory_item_id,
+                                adjustment_type,
+                                quantity_before,
+                                quantity_after,
+                                adjustment_amount,
+                                reason,
+                                reference_id,
+                                reference_type,
+                                recorded_at,
+                                recorded_by
+                            ) VALUES (
+                                gen_random_uuid(),
+                                NEW.inventory_item_id,
+                                'manual_adjustment'::stock_adjustment_type,
+                                OLD.stock_quantity,
+                                NEW.stock_quantity,
+                                NEW.stock_quantity - OLD.stock_quantity,
+                                NULL,
+                                NULL,
+                                NULL,
+                                NOW(),
+                                NULL
+                            );
+                        END IF;
+                    EXCEPTION
+                        WHEN OTHERS THEN
+                            -- Log error but don't block the stock update
+                            RAISE WARNING 'Failed to record stock history: %', SQLERRM;
+                    END;
+                    
+                    RETURN NEW;
+                END;
+                $$ LANGUAGE plpgsql;
+                "#,
+            )
+            .await?;
+
+        // Restore trigger on inventory_stock table
+        manager
+            .execute_postgres(
+                r#"
+                CREATE TRIGGER stock_history_trigger
+                    AFTER UPDATE OF stock_quantity ON inventory_stock
+                    FOR EACH ROW
+                    EXECUTE FUNCTION record_stock_change();
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum InventoryStockHistory {
+    IdempotencyKey,
+}
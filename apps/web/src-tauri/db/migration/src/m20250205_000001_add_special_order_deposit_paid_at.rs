@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // When the deposit was collected, so a reprinted deposit receipt
+        // keeps its original date
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alias::new("special_orders"))
+                    .add_column(
+                        ColumnDef::new(SpecialOrder::DepositPaidAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing deposits: the last update is the best record there is
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE special_orders SET deposit_paid_at = updated_at \
+                 WHERE deposit_paid IS NOT NULL;",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alias::new("special_orders"))
+                    .drop_column(SpecialOrder::DepositPaidAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum SpecialOrder {
+    DepositPaidAt,
+}
@@ -0,0 +1,35 @@
+//! Helpers for migrations that run on both PostgreSQL and SQLite
+
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::DatabaseBackend;
+
+/// Backend-aware extensions for [`SchemaManager`]
+///
+/// PostgreSQL gets native enum types, plpgsql triggers, GIN indexes and table
+/// comments. SQLite has no equivalent for these, so they are skipped there:
+/// enum columns fall back to plain text, `updated_at` is always set by the
+/// services, and price/stock history is recorded by the service layer instead
+/// of triggers. CHECK constraints are not routed through here; declare them
+/// in the `create_table` statement so both backends enforce them.
+#[async_trait::async_trait]
+pub(crate) trait SchemaManagerExt {
+    /// Whether the migration runs against PostgreSQL
+    fn is_postgres(&self) -> bool;
+
+    /// Execute raw SQL only when running against PostgreSQL
+    async fn execute_postgres(&self, sql: &str) -> Result<(), DbErr>;
+}
+
+#[async_trait::async_trait]
+impl SchemaManagerExt for SchemaManager<'_> {
+    fn is_postgres(&self) -> bool {
+        self.get_database_backend() == DatabaseBackend::Postgres
+    }
+
+    async fn execute_postgres(&self, sql: &str) -> Result<(), DbErr> {
+        if self.is_postgres() {
+            self.get_connection().execute_unprepared(sql).await?;
+        }
+        Ok(())
+    }
+}
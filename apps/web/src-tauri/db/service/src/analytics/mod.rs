@@ -0,0 +1,413 @@
+//! Sales and consumption analytics
+//!
+//! Consumption is read from `inventory_stock_history`: sales, damage and
+//! expiry movements within the analysis period count towards an item's
+//! velocity, while any recorded movement resets its dead-stock clock.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use db_entity::id::Id;
+use db_entity::inventory_item::{self, Entity as InventoryItem};
+use db_entity::inventory_stock::Entity as InventoryStock;
+use db_entity::inventory_stock::dto::UpdateInventoryStock;
+use db_entity::inventory_stock_history::{
+    self, Entity as InventoryStockHistory, StockAdjustmentType,
+};
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use tap::{Pipe, TapFallible};
+
+use crate::error::{ServiceError, ServiceResult};
+use crate::inventory::InventoryService;
+use crate::pagination::{PaginationParams, PaginationResult};
+
+/// Cumulative share of consumption value covered by class A items
+const CLASS_A_SHARE: f64 = 0.80;
+/// Cumulative share of consumption value covered by class A and B items
+const CLASS_B_SHARE: f64 = 0.95;
+
+/// ABC class of an item by its share of consumption value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AbcClass {
+    A,
+    B,
+    C,
+}
+
+/// Column item analytics are sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsSortBy {
+    Name,
+    StockQuantity,
+    DailyVelocity,
+    #[default]
+    ConsumptionValue,
+    DaysOfCover,
+    DaysSinceMovement,
+}
+
+/// Analytics query options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemAnalyticsQuery {
+    /// Days of history velocity and ABC class are computed over
+    pub period_days: u32,
+    /// Items with stock and no movement for this many days are dead stock
+    pub dead_stock_days: u32,
+    /// Days of consumption the recommended minimum stock level should cover
+    pub cover_days: u32,
+    /// Only return items of this class
+    pub abc_class: Option<AbcClass>,
+    /// Only return dead stock
+    pub dead_stock_only: bool,
+    pub sort_by: AnalyticsSortBy,
+    pub descending: bool,
+}
+
+impl Default for ItemAnalyticsQuery {
+    fn default() -> Self {
+        Self {
+            period_days: 90,
+            dead_stock_days: 90,
+            cover_days: 14,
+            abc_class: None,
+            dead_stock_only: false,
+            sort_by: AnalyticsSortBy::default(),
+            descending: true,
+        }
+    }
+}
+
+/// Items to set to their recommended minimum stock level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyStockRecommendations {
+    /// Analytics options the recommendations are computed with
+    #[serde(default)]
+    pub query: ItemAnalyticsQuery,
+    pub item_ids: Vec<Id>,
+    /// User recorded as changing the levels
+    pub updated_by: Id,
+}
+
+/// Consumption analytics for one inventory item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemAnalytics {
+    pub inventory_item_id: Id,
+    pub name: String,
+    pub stock_quantity: i32,
+    pub min_stock_level: i32,
+    /// Units sold during the period
+    pub sold: i64,
+    /// Units written off as damaged or expired during the period
+    pub wasted: i64,
+    /// Average units consumed per day over the period
+    pub daily_velocity: f64,
+    /// Units consumed during the period at the current unit price
+    pub consumption_value: f64,
+    pub abc_class: AbcClass,
+    pub last_movement_at: Option<DateTime<FixedOffset>>,
+    /// Days since the last movement, or since the item was created
+    pub days_since_movement: i64,
+    pub is_dead_stock: bool,
+    /// Days the current stock lasts at the current velocity; `None` without
+    /// consumption
+    pub days_of_cover: Option<f64>,
+    /// Stock needed for `cover_days` days at the current velocity
+    pub recommended_min_stock_level: i32,
+}
+
+/// Units consumed by one item during the analysis period
+#[derive(Default)]
+struct Consumption {
+    sold: i64,
+    wasted: i64,
+}
+
+/// Sales and consumption analytics service
+pub struct AnalyticsService {
+    db: Arc<DatabaseConnection>,
+}
+
+impl AnalyticsService {
+    /// Create a new analytics service
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    // ========================================================================
+    // Item Analytics
+    // ========================================================================
+
+    /// Compute analytics for every active item, then filter, sort and page
+    pub async fn item_analytics(
+        &self,
+        query: ItemAnalyticsQuery,
+        pagination: Option<PaginationParams>,
+    ) -> ServiceResult<PaginationResult<ItemAnalytics>> {
+        let mut items = self
+            .compute(&query, Utc::now().fixed_offset())
+            .await?
+            .into_iter()
+            .filter(|item| query.abc_class.is_none_or(|class| item.abc_class == class))
+            .filter(|item| !query.dead_stock_only || item.is_dead_stock)
+            .collect::<Vec<_>>();
+
+        items.sort_by(|a, b| {
+            let ordering = compare(a, b, query.sort_by).then_with(|| a.name.cmp(&b.name));
+            if query.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        let total = items.len() as u64;
+        let (items, page, page_size) = match pagination {
+            Some(pagination) => {
+                let skip = (pagination.page() - 1) * pagination.page_size();
+                let items = items
+                    .into_iter()
+                    .skip(skip as usize)
+                    .take(pagination.page_size() as usize)
+                    .collect();
+                (items, pagination.page(), pagination.page_size())
+            }
+            None => (items, 1, total),
+        };
+
+        Ok(PaginationResult::new(items, total, page, page_size))
+    }
+
+    /// Set `min_stock_level` to the recommendation for each of the request's
+    /// items, as its `updated_by`
+    ///
+    /// Every id is checked before anything changes, and the levels are set in
+    /// one transaction: either all of them change or none. Returns the number
+    /// of items whose level changed.
+    pub async fn apply_min_stock_recommendations(
+        &self,
+        request: ApplyStockRecommendations,
+    ) -> ServiceResult<u64> {
+        let ApplyStockRecommendations {
+            query,
+            item_ids,
+            updated_by,
+        } = request;
+
+        let analytics: HashMap<Id, ItemAnalytics> = self
+            .compute(&query, Utc::now().fixed_offset())
+            .await?
+            .into_iter()
+            .map(|item| (item.inventory_item_id, item))
+            .collect();
+
+        let mut changes = Vec::new();
+        let mut seen = HashSet::new();
+        for id in item_ids {
+            let item = analytics.get(&id).ok_or_else(|| {
+                ServiceError::NotFound(format!("Inventory item not found: {}", id))
+            })?;
+            if seen.insert(id) && item.recommended_min_stock_level != item.min_stock_level {
+                changes.push((id, item.recommended_min_stock_level));
+            }
+        }
+
+        let txn = self.db.begin().await?;
+        for (id, min_stock_level) in &changes {
+            InventoryService::write_stock_update(
+                &txn,
+                *id,
+                UpdateInventoryStock {
+                    stock_quantity: None,
+                    min_stock_level: Some(*min_stock_level),
+                    unit_price: None,
+                    reason: None,
+                    updated_by: Some(updated_by),
+                },
+            )
+            .await?;
+        }
+        txn.commit()
+            .await
+            .tap_err(|e| tracing::error!("Failed to apply stock recommendations: {}", e))?;
+
+        tracing::info!(
+            "Applied {} minimum stock level recommendations",
+            changes.len()
+        );
+        Ok(changes.len() as u64)
+    }
+
+    /// Analytics for every active item as of `now`, unsorted
+    async fn compute(
+        &self,
+        query: &ItemAnalyticsQuery,
+        now: DateTime<FixedOffset>,
+    ) -> ServiceResult<Vec<ItemAnalytics>> {
+        if query.period_days == 0 {
+            return Err(ServiceError::BadRequest(
+                "Analysis period must be at least one day".to_string(),
+            ));
+        }
+
+        let items = InventoryItem::find()
+            .find_also_related(InventoryStock)
+            .filter(inventory_item::Column::IsActive.eq(true))
+            .filter(inventory_item::Column::DeletedAt.is_null())
+            .all(&*self.db)
+            .await
+            .tap_err(|e| tracing::error!("Failed to load items for analytics: {}", e))?;
+
+        let consumption = self
+            .consumption_since(now - Duration::days(i64::from(query.period_days)))
+            .await?;
+        let last_movements = self.last_movements().await?;
+
+        let mut analytics = Vec::with_capacity(items.len());
+        for (item, stock) in items {
+            let Some(stock) = stock else { continue };
+            let consumed = consumption.get(&item.id);
+            let sold = consumed.map(|c| c.sold).unwrap_or_default();
+            let wasted = consumed.map(|c| c.wasted).unwrap_or_default();
+            let daily_velocity = (sold + wasted) as f64 / f64::from(query.period_days);
+
+            let last_movement_at = last_movements.get(&item.id).copied();
+            let days_since_movement =
+                (now - last_movement_at.unwrap_or(item.created_at)).num_days();
+
+            let consumption_value = (stock.unit_price * Decimal::from(sold + wasted))
+                .round_dp(2)
+                .to_f64()
+                .unwrap_or_default();
+
+            analytics.push(ItemAnalytics {
+                inventory_item_id: item.id,
+                name: item.name,
+                stock_quantity: stock.stock_quantity,
+                min_stock_level: stock.min_stock_level,
+                sold,
+                wasted,
+                daily_velocity,
+                consumption_value,
+                abc_class: AbcClass::C,
+                last_movement_at,
+                days_since_movement,
+                is_dead_stock: stock.stock_quantity > 0
+                    && days_since_movement >= i64::from(query.dead_stock_days),
+                days_of_cover: (daily_velocity > 0.0)
+                    .then(|| f64::from(stock.stock_quantity.max(0)) / daily_velocity),
+                recommended_min_stock_level: (daily_velocity * f64::from(query.cover_days)).ceil()
+                    as i32,
+            });
+        }
+
+        classify(&mut analytics);
+        Ok(analytics)
+    }
+
+    /// Units sold and written off per item since `since`
+    async fn consumption_since(
+        &self,
+        since: DateTime<FixedOffset>,
+    ) -> ServiceResult<HashMap<Id, Consumption>> {
+        let mut consumption: HashMap<Id, Consumption> = HashMap::new();
+        let mut entries = InventoryStockHistory::find()
+            .filter(inventory_stock_history::Column::RecordedAt.gte(since))
+            .filter(inventory_stock_history::Column::AdjustmentType.is_in([
+                StockAdjustmentType::Sale,
+                StockAdjustmentType::Damage,
+                StockAdjustmentType::Expiry,
+            ]))
+            .stream(&*self.db)
+            .await?;
+
+        while let Some(entry) = entries.try_next().await? {
+            // Outgoing movements are recorded as negative adjustments
+            let units = -i64::from(entry.adjustment_amount);
+            let item = consumption.entry(entry.inventory_item_id).or_default();
+            match entry.adjustment_type {
+                StockAdjustmentType::Sale => item.sold += units,
+                _ => item.wasted += units,
+            }
+        }
+        Ok(consumption)
+    }
+
+    /// Time of the latest stock movement of each item
+    async fn last_movements(&self) -> ServiceResult<HashMap<Id, DateTime<FixedOffset>>> {
+        InventoryStockHistory::find()
+            .select_only()
+            .column(inventory_stock_history::Column::InventoryItemId)
+            .column_as(
+                Expr::col(inventory_stock_history::Column::RecordedAt).max(),
+                "last_movement_at",
+            )
+            .group_by(inventory_stock_history::Column::InventoryItemId)
+            .into_tuple::<(Id, DateTime<FixedOffset>)>()
+            .all(&*self.db)
+            .await
+            .tap_err(|e| tracing::error!("Failed to load last stock movements: {}", e))?
+            .into_iter()
+            .collect::<HashMap<_, _>>()
+            .pipe(Ok)
+    }
+}
+
+/// Assign ABC classes by cumulative share of consumption value
+fn classify(analytics: &mut [ItemAnalytics]) {
+    let total: f64 = analytics.iter().map(|item| item.consumption_value).sum();
+    if total <= 0.0 {
+        return;
+    }
+
+    let mut order: Vec<usize> = (0..analytics.len()).collect();
+    order.sort_by(|&a, &b| {
+        analytics[b]
+            .consumption_value
+            .total_cmp(&analytics[a].consumption_value)
+    });
+
+    let mut cumulative = 0.0;
+    for index in order {
+        let item = &mut analytics[index];
+        if item.consumption_value <= 0.0 {
+            break;
+        }
+        // An item is classed by the share reached before it is counted, so
+        // the top seller is always class A
+        item.abc_class = if cumulative < CLASS_A_SHARE {
+            AbcClass::A
+        } else if cumulative < CLASS_B_SHARE {
+            AbcClass::B
+        } else {
+            AbcClass::C
+        };
+        cumulative += item.consumption_value / total;
+    }
+}
+
+fn compare(a: &ItemAnalytics, b: &ItemAnalytics, sort_by: AnalyticsSortBy) -> Ordering {
+    match sort_by {
+        AnalyticsSortBy::Name => a.name.cmp(&b.name),
+        AnalyticsSortBy::StockQuantity => a.stock_quantity.cmp(&b.stock_quantity),
+        AnalyticsSortBy::DailyVelocity => a.daily_velocity.total_cmp(&b.daily_velocity),
+        AnalyticsSortBy::ConsumptionValue => a.consumption_value.total_cmp(&b.consumption_value),
+        // Items without consumption have unlimited cover
+        AnalyticsSortBy::DaysOfCover => a
+            .days_of_cover
+            .unwrap_or(f64::INFINITY)
+            .total_cmp(&b.days_of_cover.unwrap_or(f64::INFINITY)),
+        AnalyticsSortBy::DaysSinceMovement => a.days_since_movement.cmp(&b.days_since_movement),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,208 @@
+use db_entity::inventory_stock::dto::AdjustStock;
+
+use super::*;
+use crate::events::EventBus;
+use crate::test_support::{TestItem, create_items, sqlite_db};
+
+/// Item with the given stock and unit price
+fn item(name: &str, stock_quantity: i32, unit_price: f64) -> TestItem<'_> {
+    TestItem {
+        stock_quantity,
+        unit_price,
+        ..TestItem::new(name)
+    }
+}
+
+async fn record(
+    inventory: &InventoryService,
+    item_id: Id,
+    amount: i32,
+    adjustment_type: StockAdjustmentType,
+) {
+    inventory
+        .adjust_stock(
+            item_id,
+            AdjustStock {
+                adjustment: amount,
+                reason: None,
+                adjustment_type: Some(adjustment_type),
+                idempotency_key: None,
+                adjusted_by: None,
+                reference_id: None,
+                reference_type: None,
+            },
+        )
+        .await
+        .unwrap();
+}
+
+fn query(period_days: u32) -> ItemAnalyticsQuery {
+    ItemAnalyticsQuery {
+        period_days,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_velocity_abc_class_and_cover() {
+    let db = sqlite_db().await;
+    let inventory = Arc::new(InventoryService::new(
+        db.clone(),
+        Arc::new(EventBus::default()),
+    ));
+    let ids = create_items(
+        &db,
+        &inventory,
+        &[
+            item("Amoxil", 100, 10.0),
+            item("Brufen", 100, 10.0),
+            item("Cetal", 100, 1.0),
+        ],
+    )
+    .await;
+
+    record(&inventory, ids[0], -90, StockAdjustmentType::Sale).await;
+    record(&inventory, ids[1], -8, StockAdjustmentType::Sale).await;
+    record(&inventory, ids[1], -2, StockAdjustmentType::Expiry).await;
+
+    let analytics = AnalyticsService::new(db.clone());
+    let result = analytics.item_analytics(query(10), None).await.unwrap();
+    let items = result.items();
+    assert_eq!(items.len(), 3);
+
+    // Sorted by consumption value, highest first
+    let amoxil = &items[0];
+    assert_eq!(amoxil.name, "Amoxil");
+    assert_eq!(amoxil.sold, 90);
+    assert_eq!(amoxil.daily_velocity, 9.0);
+    assert_eq!(amoxil.consumption_value, 900.0);
+    assert_eq!(amoxil.abc_class, AbcClass::A);
+    assert_eq!(amoxil.days_of_cover, Some(10.0 / 9.0));
+    assert_eq!(amoxil.recommended_min_stock_level, 126);
+
+    let brufen = &items[1];
+    assert_eq!((brufen.sold, brufen.wasted), (8, 2));
+    assert_eq!(brufen.abc_class, AbcClass::B);
+
+    let cetal = &items[2];
+    assert_eq!(cetal.abc_class, AbcClass::C);
+    assert_eq!(cetal.days_of_cover, None);
+    assert_eq!(cetal.recommended_min_stock_level, 0);
+
+    let only_a = analytics
+        .item_analytics(
+            ItemAnalyticsQuery {
+                abc_class: Some(AbcClass::A),
+                ..query(10)
+            },
+            Some(PaginationParams::new(1, 10)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(only_a.total(), 1);
+}
+
+#[tokio::test]
+async fn test_dead_stock_and_pagination() {
+    let db = sqlite_db().await;
+    let inventory = Arc::new(InventoryService::new(
+        db.clone(),
+        Arc::new(EventBus::default()),
+    ));
+    let ids = create_items(
+        &db,
+        &inventory,
+        &[
+            item("Amoxil", 10, 10.0),
+            item("Brufen", 10, 10.0),
+            item("Cetal", 0, 1.0),
+        ],
+    )
+    .await;
+    record(&inventory, ids[0], -1, StockAdjustmentType::Sale).await;
+
+    // Brufen has not moved since it was created 30 days ago
+    InventoryItem::update_many()
+        .col_expr(
+            inventory_item::Column::CreatedAt,
+            Expr::value(Utc::now().fixed_offset() - Duration::days(30)),
+        )
+        .filter(inventory_item::Column::Id.eq(ids[1]))
+        .exec(&*db)
+        .await
+        .unwrap();
+
+    let analytics = AnalyticsService::new(db.clone());
+    let dead = analytics
+        .item_analytics(
+            ItemAnalyticsQuery {
+                dead_stock_days: 30,
+                dead_stock_only: true,
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    let dead = dead.items();
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0].inventory_item_id, ids[1]);
+    assert_eq!(dead[0].days_since_movement, 30);
+
+    let page = analytics
+        .item_analytics(
+            ItemAnalyticsQuery {
+                sort_by: AnalyticsSortBy::Name,
+                descending: false,
+                ..Default::default()
+            },
+            Some(PaginationParams::new(2, 2)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.total(), 3);
+    assert_eq!(page.total_pages(), 2);
+    assert_eq!(page.items_ref().len(), 1);
+    assert_eq!(page.items_ref()[0].name, "Cetal");
+}
+
+#[tokio::test]
+async fn test_apply_min_stock_recommendations() {
+    let db = sqlite_db().await;
+    let inventory = Arc::new(InventoryService::new(
+        db.clone(),
+        Arc::new(EventBus::default()),
+    ));
+    let ids = create_items(&db, &inventory, &[item("Amoxil", 100, 10.0)]).await;
+    record(&inventory, ids[0], -20, StockAdjustmentType::Sale).await;
+
+    let analytics = AnalyticsService::new(db.clone());
+    let user = Id::new();
+
+    // An unknown id stops the whole batch before anything changes
+    let missing = analytics
+        .apply_min_stock_recommendations(ApplyStockRecommendations {
+            query: query(10),
+            item_ids: vec![ids[0], Id::new()],
+            updated_by: user,
+        })
+        .await;
+    assert!(matches!(missing, Err(ServiceError::NotFound(_))));
+    let stock = inventory.get_by_id(ids[0]).await.unwrap();
+    assert_eq!(stock.min_stock_level, 5);
+
+    // Repeated ids are applied once
+    let updated = analytics
+        .apply_min_stock_recommendations(ApplyStockRecommendations {
+            query: query(10),
+            item_ids: vec![ids[0], ids[0]],
+            updated_by: user,
+        })
+        .await
+        .unwrap();
+    assert_eq!(updated, 1);
+
+    // 2 units a day for 14 days
+    let stock = inventory.get_by_id(ids[0]).await.unwrap();
+    assert_eq!(stock.min_stock_level, 28);
+}
@@ -0,0 +1,686 @@
+//! Database backup and restore
+//!
+//! A backup is a logical dump of the MediTrack tables read through the
+//! `DatabaseConnection`, so it needs no external tools such as `pg_dump` and
+//! works the same on PostgreSQL and SQLite. The archive layout is:
+//!
+//! ```text
+//! MEDITRACK-BACKUP
+//! {"format_version":1,"schema_version":"m2025...", ...}
+//! <payload>
+//! ```
+//!
+//! The payload is the gzip-compressed dump (one JSON line per row), encrypted
+//! when a [`BackupCipher`] is given. The manifest on the second line records
+//! the migration the database was at, the row count of each table, the
+//! SHA-256 checksum of the payload and, for encrypted backups, the key
+//! derivation parameters with the archive's own random salt.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
+use db_entity::prelude::*;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use futures::TryStreamExt;
+use sea_orm::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tap::TapFallible;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+use crate::error::{ServiceError, ServiceResult};
+
+/// First line of every backup archive
+const MAGIC: &[u8] = b"MEDITRACK-BACKUP\n";
+
+/// Archive format written by this version
+const FORMAT_VERSION: u32 = 1;
+
+/// File extension of backups written to the backup directory
+const FILE_EXTENSION: &str = "mtbak";
+
+/// Rows inserted per statement during a restore
+const INSERT_BATCH_SIZE: usize = 200;
+
+/// Length of the random salt of each encrypted archive
+const SALT_LEN: usize = 16;
+
+/// Encrypts and decrypts backup payloads
+///
+/// Implemented by the application on top of `app_config::SecureStorage`.
+pub trait BackupCipher: Send + Sync {
+    /// Encrypt with the key derived with `kdf`
+    fn encrypt(&self, data: &[u8], kdf: &BackupKdf) -> ServiceResult<Vec<u8>>;
+
+    /// Decrypt with the key derived with `kdf`
+    fn decrypt(&self, data: &[u8], kdf: &BackupKdf) -> ServiceResult<Vec<u8>>;
+}
+
+/// Argon2id parameters and salt the key of an encrypted backup is derived
+/// with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupKdf {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+    /// Base64 salt, random for every archive
+    pub salt: String,
+}
+
+impl BackupKdf {
+    /// Default Argon2id parameters with a fresh random salt
+    pub fn generate() -> Self {
+        let salt: [u8; SALT_LEN] = rand::random();
+
+        Self {
+            memory_kib: argon2::Params::DEFAULT_M_COST,
+            iterations: argon2::Params::DEFAULT_T_COST,
+            parallelism: argon2::Params::DEFAULT_P_COST,
+            salt: STANDARD.encode(salt),
+        }
+    }
+
+    /// The decoded salt
+    pub fn salt_bytes(&self) -> ServiceResult<[u8; SALT_LEN]> {
+        STANDARD
+            .decode(&self.salt)
+            .ok()
+            .and_then(|salt| <[u8; SALT_LEN]>::try_from(salt).ok())
+            .ok_or_else(|| ServiceError::BadRequest("Invalid salt in backup manifest".to_string()))
+    }
+}
+
+/// Row count of one table in a backup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupTable {
+    pub name: String,
+    pub rows: u64,
+}
+
+/// Header stored in front of the payload of every backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    /// Latest migration applied to the database that was backed up
+    pub schema_version: String,
+    pub created_at: DateTime<Utc>,
+    pub encrypted: bool,
+    /// Hex SHA-256 of the payload as stored
+    pub checksum: String,
+    pub tables: Vec<BackupTable>,
+    /// Key derivation of an encrypted payload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kdf: Option<BackupKdf>,
+}
+
+/// A backup archive in the backup directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub manifest: BackupManifest,
+}
+
+/// One row of the dump
+#[derive(Serialize, Deserialize)]
+struct DumpLine {
+    table: String,
+    row: Value,
+}
+
+// ============================================================================
+// Tables
+// ============================================================================
+
+/// Dump and restore operations for one table
+#[async_trait]
+trait TableBackup: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Write every row, in primary key order, as a dump line
+    async fn dump(
+        &self,
+        txn: &DatabaseTransaction,
+        out: &mut GzEncoder<Vec<u8>>,
+    ) -> ServiceResult<u64>;
+
+    async fn count(&self, txn: &DatabaseTransaction) -> ServiceResult<u64>;
+
+    async fn insert(&self, txn: &DatabaseTransaction, rows: Vec<Value>) -> ServiceResult<()>;
+}
+
+struct Table<E> {
+    name: String,
+    entity: PhantomData<E>,
+}
+
+#[async_trait]
+impl<E> TableBackup for Table<E>
+where
+    E: EntityTrait + Sync,
+    E::Model: Serialize + DeserializeOwned + IntoActiveModel<E::ActiveModel> + Send + Sync,
+    E::ActiveModel: Send,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn dump(
+        &self,
+        txn: &DatabaseTransaction,
+        out: &mut GzEncoder<Vec<u8>>,
+    ) -> ServiceResult<u64> {
+        let mut select = E::find();
+        for key in E::PrimaryKey::iter() {
+            select = select.order_by_asc(key.into_column());
+        }
+
+        let mut rows = select.stream(txn).await?;
+        let mut count = 0;
+        while let Some(model) = rows.try_next().await? {
+            let line = DumpLine {
+                table: self.name().to_string(),
+                row: serde_json::to_value(&model).map_err(encode_error)?,
+            };
+            serde_json::to_writer(&mut *out, &line).map_err(encode_error)?;
+            out.write_all(b"\n").map_err(encode_error)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    async fn count(&self, txn: &DatabaseTransaction) -> ServiceResult<u64> {
+        Ok(E::find().count(txn).await?)
+    }
+
+    async fn insert(&self, txn: &DatabaseTransaction, rows: Vec<Value>) -> ServiceResult<()> {
+        let models = rows
+            .into_iter()
+            .map(|row| {
+                serde_json::from_value::<E::Model>(row).map(IntoActiveModel::into_active_model)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                ServiceError::BadRequest(format!("Invalid row in table '{}': {}", self.name(), e))
+            })?;
+
+        if !models.is_empty() {
+            E::insert_many(models).exec_without_returning(txn).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Every backed-up table, parents before children so restores satisfy the
+/// foreign keys
+///
+/// Sessions and job runs are left out: they are operational state that a
+/// running application recreates on its own.
+fn tables() -> Vec<Box<dyn TableBackup>> {
+    fn table<E>() -> Box<dyn TableBackup>
+    where
+        E: EntityTrait + Sync,
+        E::Model: Serialize + DeserializeOwned + IntoActiveModel<E::ActiveModel> + Send + Sync,
+        E::ActiveModel: Send,
+    {
+        Box::new(Table::<E> {
+            name: E::default().table_name().to_string(),
+            entity: PhantomData,
+        })
+    }
+
+    vec![
+        table::<Role>(),
+        table::<Staff>(),
+        table::<User>(),
+        table::<Setting>(),
+        table::<Customer>(),
+        table::<Supplier>(),
+        table::<Manufacturer>(),
+        table::<MedicineForm>(),
+        table::<InventoryItem>(),
+        table::<InventoryStock>(),
+        table::<InventoryItemBarcode>(),
+        table::<InventoryPriceHistory>(),
+        table::<InventoryStockHistory>(),
+        table::<InventoryOpeningBalance>(),
+        table::<SupplierInventoryItem>(),
+        table::<SpecialOrder>(),
+        table::<SpecialOrderItem>(),
+    ]
+}
+
+// ============================================================================
+// Backup Service
+// ============================================================================
+
+/// Backup service for dumping the database to archives and restoring them
+pub struct BackupService {
+    db: Arc<DatabaseConnection>,
+    dir: PathBuf,
+}
+
+impl BackupService {
+    /// Create a new backup service writing archives into `dir`
+    pub fn new(db: Arc<DatabaseConnection>, dir: PathBuf) -> Self {
+        Self { db, dir }
+    }
+
+    /// Directory backups are written to
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write a new backup into the backup directory
+    pub async fn create_backup(
+        &self,
+        cipher: Option<&dyn BackupCipher>,
+    ) -> ServiceResult<BackupFile> {
+        let archive = self.create_archive(cipher).await?;
+        let (manifest, _) = read_archive(&archive)?;
+
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| io_error(&self.dir, e))?;
+
+        // Microseconds keep backups taken within the same second apart
+        let stamp = manifest.created_at.format("%Y%m%d-%H%M%S-%6f");
+        let path = self
+            .dir
+            .join(format!("meditrack-{}.{}", stamp, FILE_EXTENSION));
+        let exists = || ServiceError::Conflict(format!("Backup {} already exists", path.display()));
+        if tokio::fs::try_exists(&path)
+            .await
+            .map_err(|e| io_error(&path, e))?
+        {
+            return Err(exists());
+        }
+
+        // Write under a temporary name so a crash never leaves a truncated
+        // backup; a second backup under the same name fails to create it
+        let partial = path.with_extension("partial");
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&partial)
+            .await
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::AlreadyExists => exists(),
+                _ => io_error(&partial, e),
+            })?;
+        file.write_all(&archive)
+            .await
+            .map_err(|e| io_error(&partial, e))?;
+        file.sync_all().await.map_err(|e| io_error(&partial, e))?;
+        drop(file);
+        tokio::fs::rename(&partial, &path)
+            .await
+            .map_err(|e| io_error(&path, e))?;
+
+        tracing::info!("Database backed up to {}", path.display());
+        Ok(BackupFile {
+            path,
+            size: archive.len() as u64,
+            manifest,
+        })
+    }
+
+    /// Dump every table into an in-memory archive
+    ///
+    /// The rows are read in one transaction, so the dump is consistent even
+    /// while the application keeps writing.
+    pub async fn create_archive(
+        &self,
+        cipher: Option<&dyn BackupCipher>,
+    ) -> ServiceResult<Vec<u8>> {
+        let schema_version = self.schema_version().await?;
+
+        let txn = match self.db.get_database_backend() {
+            DbBackend::Postgres => {
+                self.db
+                    .begin_with_config(
+                        Some(IsolationLevel::RepeatableRead),
+                        Some(AccessMode::ReadOnly),
+                    )
+                    .await?
+            }
+            // SQLite transactions are already serializable
+            _ => self.db.begin().await?,
+        };
+
+        let mut out = GzEncoder::new(Vec::new(), Compression::default());
+        let mut counts = Vec::new();
+        for table in tables() {
+            let rows = table
+                .dump(&txn, &mut out)
+                .await
+                .tap_err(|e| tracing::error!("Failed to back up {}: {}", table.name(), e))?;
+            counts.push(BackupTable {
+                name: table.name().to_string(),
+                rows,
+            });
+        }
+        txn.commit().await?;
+
+        let compressed = out.finish().map_err(encode_error)?;
+        let kdf = cipher.map(|_| BackupKdf::generate());
+        let payload = match (cipher, &kdf) {
+            (Some(cipher), Some(kdf)) => cipher.encrypt(&compressed, kdf)?,
+            _ => compressed,
+        };
+
+        let manifest = BackupManifest {
+            format_version: FORMAT_VERSION,
+            schema_version,
+            created_at: Utc::now(),
+            encrypted: cipher.is_some(),
+            checksum: checksum(&payload),
+            tables: counts,
+            kdf,
+        };
+
+        let mut archive = MAGIC.to_vec();
+        serde_json::to_writer(&mut archive, &manifest).map_err(encode_error)?;
+        archive.push(b'\n');
+        archive.extend_from_slice(&payload);
+        Ok(archive)
+    }
+
+    /// Restore an archive into this (empty) database
+    ///
+    /// The archive must have been taken at the same migration as the database,
+    /// and every backed-up table must be empty. All rows are inserted in one
+    /// transaction, so a failed restore leaves the database untouched.
+    pub async fn restore_archive(
+        &self,
+        archive: &[u8],
+        cipher: Option<&dyn BackupCipher>,
+    ) -> ServiceResult<BackupManifest> {
+        let (manifest, payload) = read_archive(archive)?;
+
+        let schema_version = self.schema_version().await?;
+        if manifest.schema_version != schema_version {
+            return Err(ServiceError::BadRequest(format!(
+                "Backup was taken at schema version '{}' but the database is at '{}'",
+                manifest.schema_version, schema_version
+            )));
+        }
+
+        if checksum(payload) != manifest.checksum {
+            return Err(ServiceError::BadRequest(
+                "Backup checksum does not match, the file is corrupted".to_string(),
+            ));
+        }
+
+        let compressed = match (manifest.encrypted, cipher) {
+            (false, _) => payload.to_vec(),
+            (true, Some(cipher)) => {
+                let kdf = manifest.kdf.as_ref().ok_or_else(|| {
+                    ServiceError::BadRequest(
+                        "Backup is encrypted but its key derivation parameters are missing"
+                            .to_string(),
+                    )
+                })?;
+                cipher.decrypt(payload, kdf)?
+            }
+            (true, None) => {
+                return Err(ServiceError::BadRequest(
+                    "Backup is encrypted and no key was given".to_string(),
+                ));
+            }
+        };
+
+        let tables = tables();
+        let txn = self.db.begin().await?;
+
+        for table in &tables {
+            if table.count(&txn).await? > 0 {
+                return Err(ServiceError::Conflict(format!(
+                    "Table '{}' is not empty; backups can only be restored into an empty database",
+                    table.name()
+                )));
+            }
+        }
+
+        let by_name: HashMap<&str, &dyn TableBackup> = tables
+            .iter()
+            .map(|table| (table.name(), table.as_ref()))
+            .collect();
+        let mut restored: HashMap<String, u64> = HashMap::new();
+        let mut batch: Vec<Value> = Vec::with_capacity(INSERT_BATCH_SIZE);
+        let mut current: Option<&dyn TableBackup> = None;
+
+        for line in BufReader::new(GzDecoder::new(compressed.as_slice())).lines() {
+            let line = line.map_err(|e| {
+                ServiceError::BadRequest(format!("Failed to decompress backup: {}", e))
+            })?;
+            let DumpLine { table, row } = serde_json::from_str(&line)
+                .map_err(|e| ServiceError::BadRequest(format!("Invalid backup line: {}", e)))?;
+            let target = *by_name.get(table.as_str()).ok_or_else(|| {
+                ServiceError::BadRequest(format!("Unknown table '{}' in backup", table))
+            })?;
+
+            // Rows arrive grouped by table, so flush whenever the table changes
+            if let Some(previous) = current
+                && (previous.name() != target.name() || batch.len() == INSERT_BATCH_SIZE)
+            {
+                previous.insert(&txn, std::mem::take(&mut batch)).await?;
+            }
+            current = Some(target);
+            batch.push(row);
+            *restored.entry(table).or_default() += 1;
+        }
+        if let Some(current) = current {
+            current.insert(&txn, batch).await?;
+        }
+
+        for table in &manifest.tables {
+            let rows = restored.get(&table.name).copied().unwrap_or_default();
+            if rows != table.rows {
+                return Err(ServiceError::BadRequest(format!(
+                    "Backup lists {} row(s) for '{}' but contains {}",
+                    table.rows, table.name, rows
+                )));
+            }
+        }
+
+        txn.commit().await?;
+
+        tracing::info!(
+            "Restored backup from {} ({} rows)",
+            manifest.created_at,
+            restored.values().sum::<u64>()
+        );
+        Ok(manifest)
+    }
+
+    /// Read and restore a backup file from the backup directory
+    ///
+    /// `path` is relative to the backup directory or one of the paths
+    /// [`Self::list_backups`] returns; files anywhere else are refused.
+    pub async fn restore_backup(
+        &self,
+        path: &Path,
+        cipher: Option<&dyn BackupCipher>,
+    ) -> ServiceResult<BackupManifest> {
+        let path = self.backup_path(path).await?;
+        let archive = tokio::fs::read(&path)
+            .await
+            .map_err(|e| io_error(&path, e))?;
+        self.restore_archive(&archive, cipher).await
+    }
+
+    /// List the backups in the backup directory, newest first
+    pub async fn list_backups(&self) -> ServiceResult<Vec<BackupFile>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(io_error(&self.dir, e)),
+        };
+
+        let mut backups = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| io_error(&self.dir, e))?
+        {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != FILE_EXTENSION) {
+                continue;
+            }
+            match read_file_manifest(&path).await {
+                Ok(manifest) => backups.push(BackupFile {
+                    size: entry.metadata().await.map(|m| m.len()).unwrap_or_default(),
+                    path,
+                    manifest,
+                }),
+                Err(e) => tracing::warn!("Skipping unreadable backup {}: {}", path.display(), e),
+            }
+        }
+
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.manifest.created_at));
+        Ok(backups)
+    }
+
+    /// Delete all but the newest `keep` backups, returning how many were removed
+    pub async fn prune_backups(&self, keep: usize) -> ServiceResult<u64> {
+        let mut removed = 0;
+        for backup in self.list_backups().await?.into_iter().skip(keep) {
+            tokio::fs::remove_file(&backup.path)
+                .await
+                .map_err(|e| io_error(&backup.path, e))?;
+            removed += 1;
+        }
+
+        if removed > 0 {
+            tracing::info!("Removed {} old backup(s)", removed);
+        }
+        Ok(removed)
+    }
+
+    // ========================================================================
+    // Helper Methods
+    // ========================================================================
+
+    /// Resolve `path` to a backup file directly inside the backup directory
+    ///
+    /// Both sides are canonicalized, so `..` components and symbolic links
+    /// cannot reach outside it.
+    async fn backup_path(&self, path: &Path) -> ServiceResult<PathBuf> {
+        let dir = tokio::fs::canonicalize(&self.dir)
+            .await
+            .map_err(|e| io_error(&self.dir, e))?;
+        let path = self.dir.join(path);
+        let resolved = match tokio::fs::canonicalize(&path).await {
+            Ok(resolved) => resolved,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(ServiceError::NotFound(format!(
+                    "Backup {} does not exist",
+                    path.display()
+                )));
+            }
+            Err(e) => return Err(io_error(&path, e)),
+        };
+
+        if resolved.parent() != Some(dir.as_path())
+            || resolved.extension().is_none_or(|ext| ext != FILE_EXTENSION)
+        {
+            return Err(ServiceError::Forbidden(format!(
+                "{} is not a backup in {}",
+                path.display(),
+                self.dir.display()
+            )));
+        }
+        Ok(resolved)
+    }
+
+    async fn schema_version(&self) -> ServiceResult<String> {
+        db_migration::schema_version(&self.db)
+            .await?
+            .ok_or_else(|| ServiceError::Internal("Database has no migrations applied".to_string()))
+    }
+}
+
+/// Split an archive into its manifest and payload
+fn read_archive(archive: &[u8]) -> ServiceResult<(BackupManifest, &[u8])> {
+    let rest = archive
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| ServiceError::BadRequest("Not a MediTrack backup".to_string()))?;
+    let end = rest
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .ok_or_else(|| ServiceError::BadRequest("Backup manifest is missing".to_string()))?;
+
+    let manifest = parse_manifest(&rest[..end])?;
+    Ok((manifest, &rest[end + 1..]))
+}
+
+/// Read only the manifest of a backup file
+async fn read_file_manifest(path: &Path) -> ServiceResult<BackupManifest> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| io_error(path, e))?;
+    let mut reader = tokio::io::BufReader::new(file);
+
+    let mut magic = Vec::new();
+    let mut manifest = Vec::new();
+    reader
+        .read_until(b'\n', &mut magic)
+        .await
+        .map_err(|e| io_error(path, e))?;
+    reader
+        .read_until(b'\n', &mut manifest)
+        .await
+        .map_err(|e| io_error(path, e))?;
+
+    if magic != MAGIC {
+        return Err(ServiceError::BadRequest(
+            "Not a MediTrack backup".to_string(),
+        ));
+    }
+    parse_manifest(manifest.trim_ascii_end())
+}
+
+fn parse_manifest(bytes: &[u8]) -> ServiceResult<BackupManifest> {
+    let manifest: BackupManifest = serde_json::from_slice(bytes)
+        .map_err(|e| ServiceError::BadRequest(format!("Invalid backup manifest: {}", e)))?;
+
+    if manifest.format_version > FORMAT_VERSION {
+        return Err(ServiceError::BadRequest(format!(
+            "Backup format version {} is newer than this application supports",
+            manifest.format_version
+        )));
+    }
+    Ok(manifest)
+}
+
+fn checksum(payload: &[u8]) -> String {
+    Sha256::digest(payload)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn encode_error(e: impl std::fmt::Display) -> ServiceError {
+    ServiceError::Internal(format!("Failed to write backup: {}", e))
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> ServiceError {
+    ServiceError::Internal(format!("Failed to access {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests;
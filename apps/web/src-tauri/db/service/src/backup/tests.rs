@@ -0,0 +1,265 @@
+use super::*;
+use crate::events::EventBus;
+use crate::inventory::InventoryService;
+use crate::test_support::{TestItem, create_admin, create_items, sqlite_db};
+
+/// XORs with the salt, enough to tell encrypted payloads and their keys
+/// apart in tests
+struct SaltCipher;
+
+impl SaltCipher {
+    fn xor(data: &[u8], kdf: &BackupKdf) -> ServiceResult<Vec<u8>> {
+        let salt = kdf.salt_bytes()?;
+        Ok(data
+            .iter()
+            .zip(salt.iter().cycle())
+            .map(|(byte, key)| byte ^ key)
+            .collect())
+    }
+}
+
+impl BackupCipher for SaltCipher {
+    fn encrypt(&self, data: &[u8], kdf: &BackupKdf) -> ServiceResult<Vec<u8>> {
+        Self::xor(data, kdf)
+    }
+
+    fn decrypt(&self, data: &[u8], kdf: &BackupKdf) -> ServiceResult<Vec<u8>> {
+        Self::xor(data, kdf)
+    }
+}
+
+/// `archive` with its manifest changed by `edit`
+fn with_manifest(archive: &[u8], edit: impl FnOnce(&mut BackupManifest)) -> Vec<u8> {
+    let (mut manifest, payload) = read_archive(archive).unwrap();
+    edit(&mut manifest);
+    let mut edited = MAGIC.to_vec();
+    serde_json::to_writer(&mut edited, &manifest).unwrap();
+    edited.push(b'\n');
+    edited.extend_from_slice(payload);
+    edited
+}
+
+/// Database with an admin user and one item ("Panadol", stock 10)
+async fn seeded_db() -> Arc<DatabaseConnection> {
+    let db = sqlite_db().await;
+    create_admin(&db).await;
+
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    create_items(
+        &db,
+        &inventory,
+        &[TestItem {
+            barcodes: &["6221000000011"],
+            ..TestItem::new("Panadol")
+        }],
+    )
+    .await;
+
+    db
+}
+
+fn temp_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("meditrack-backup-{}", Id::new()))
+}
+
+fn rows(manifest: &BackupManifest, table: &str) -> u64 {
+    manifest
+        .tables
+        .iter()
+        .find(|t| t.name == table)
+        .map(|t| t.rows)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_backup_restores_into_empty_database() {
+    let source = BackupService::new(seeded_db().await, temp_dir());
+    let archive = source.create_archive(None).await.unwrap();
+
+    let target_db = sqlite_db().await;
+    let target = BackupService::new(target_db.clone(), temp_dir());
+    let manifest = target.restore_archive(&archive, None).await.unwrap();
+
+    assert!(!manifest.encrypted);
+    assert_eq!(rows(&manifest, "users"), 1);
+    assert_eq!(rows(&manifest, "inventory_items"), 1);
+    assert_eq!(User::find().count(target_db.as_ref()).await.unwrap(), 1);
+
+    let stock = InventoryStock::find()
+        .one(target_db.as_ref())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stock.stock_quantity, 10);
+
+    // The restored database is no longer empty
+    assert!(matches!(
+        target.restore_archive(&archive, None).await,
+        Err(ServiceError::Conflict(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_restore_rejects_corrupted_or_mismatched_archives() {
+    let source = BackupService::new(seeded_db().await, temp_dir());
+    let archive = source.create_archive(None).await.unwrap();
+    let target = BackupService::new(sqlite_db().await, temp_dir());
+
+    let mut corrupted = archive.clone();
+    *corrupted.last_mut().unwrap() ^= 0xff;
+    assert!(matches!(
+        target.restore_archive(&corrupted, None).await,
+        Err(ServiceError::BadRequest(_))
+    ));
+
+    let older = with_manifest(&archive, |manifest| {
+        manifest.schema_version = "m20000101_000001_older".to_string();
+    });
+    let result = target.restore_archive(&older, None).await;
+    assert!(
+        matches!(&result, Err(ServiceError::BadRequest(message)) if message.contains("schema version"))
+    );
+}
+
+#[tokio::test]
+async fn test_encrypted_backup_needs_cipher() {
+    let source = BackupService::new(seeded_db().await, temp_dir());
+    let archive = source.create_archive(Some(&SaltCipher)).await.unwrap();
+    let target = BackupService::new(sqlite_db().await, temp_dir());
+
+    assert!(matches!(
+        target.restore_archive(&archive, None).await,
+        Err(ServiceError::BadRequest(_))
+    ));
+
+    let manifest = target
+        .restore_archive(&archive, Some(&SaltCipher))
+        .await
+        .unwrap();
+    assert!(manifest.encrypted);
+}
+
+#[tokio::test]
+async fn test_encrypted_backups_have_their_own_salt() {
+    let source = BackupService::new(seeded_db().await, temp_dir());
+    let first = source.create_archive(Some(&SaltCipher)).await.unwrap();
+    let second = source.create_archive(Some(&SaltCipher)).await.unwrap();
+
+    let (first_manifest, _) = read_archive(&first).unwrap();
+    let (second_manifest, _) = read_archive(&second).unwrap();
+    let kdf = first_manifest.kdf.unwrap();
+    assert_ne!(kdf.salt, second_manifest.kdf.unwrap().salt);
+    assert_eq!(kdf.salt_bytes().unwrap().len(), SALT_LEN);
+    assert!(
+        read_archive(&source.create_archive(None).await.unwrap())
+            .unwrap()
+            .0
+            .kdf
+            .is_none()
+    );
+
+    // The key is derived from the salt in the manifest
+    let target = BackupService::new(sqlite_db().await, temp_dir());
+    let resalted = with_manifest(&first, |manifest| {
+        manifest.kdf = Some(BackupKdf::generate());
+    });
+    assert!(matches!(
+        target.restore_archive(&resalted, Some(&SaltCipher)).await,
+        Err(ServiceError::BadRequest(_))
+    ));
+    let unsalted = with_manifest(&first, |manifest| manifest.kdf = None);
+    assert!(matches!(
+        target.restore_archive(&unsalted, Some(&SaltCipher)).await,
+        Err(ServiceError::BadRequest(message)) if message.contains("key derivation")
+    ));
+    target
+        .restore_archive(&second, Some(&SaltCipher))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_prune_keeps_newest_backups() {
+    let dir = temp_dir();
+    let backup = BackupService::new(seeded_db().await, dir.clone());
+
+    // Backups are named after the time they were taken
+    let archive = backup.create_archive(None).await.unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    for (index, stamp) in [
+        "20250101-020000-000000",
+        "20250102-020000-000000",
+        "20250103-020000-000000",
+    ]
+    .iter()
+    .enumerate()
+    {
+        let (mut manifest, payload) = read_archive(&archive).unwrap();
+        manifest.created_at += chrono::Duration::days(index as i64);
+        let mut file = MAGIC.to_vec();
+        serde_json::to_writer(&mut file, &manifest).unwrap();
+        file.push(b'\n');
+        file.extend_from_slice(payload);
+        std::fs::write(dir.join(format!("meditrack-{}.mtbak", stamp)), file).unwrap();
+    }
+
+    assert_eq!(backup.list_backups().await.unwrap().len(), 3);
+    assert_eq!(backup.prune_backups(2).await.unwrap(), 1);
+
+    let kept = backup.list_backups().await.unwrap();
+    assert_eq!(kept.len(), 2);
+    assert_eq!(
+        kept[0].path,
+        dir.join("meditrack-20250103-020000-000000.mtbak")
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_restore_backup_reads_only_the_backup_directory() {
+    let source = BackupService::new(seeded_db().await, temp_dir());
+    let file = source.create_backup(None).await.unwrap();
+
+    // A copy outside the backup directory, reached directly or through `..`
+    let outside = temp_dir();
+    std::fs::create_dir_all(&outside).unwrap();
+    let copy = outside.join(file.path.file_name().unwrap());
+    std::fs::copy(&file.path, &copy).unwrap();
+
+    let target = BackupService::new(sqlite_db().await, source.dir().to_path_buf());
+    let escape = Path::new("..")
+        .join(outside.file_name().unwrap())
+        .join(copy.file_name().unwrap());
+    for path in [copy.as_path(), escape.as_path()] {
+        assert!(matches!(
+            target.restore_backup(path, None).await,
+            Err(ServiceError::Forbidden(_))
+        ));
+    }
+    assert!(matches!(
+        target
+            .restore_backup(Path::new("missing.mtbak"), None)
+            .await,
+        Err(ServiceError::NotFound(_))
+    ));
+
+    // A name is looked up in the backup directory
+    let name = Path::new(file.path.file_name().unwrap());
+    target.restore_backup(name, None).await.unwrap();
+
+    std::fs::remove_dir_all(source.dir()).unwrap();
+    std::fs::remove_dir_all(&outside).unwrap();
+}
+
+#[tokio::test]
+async fn test_backups_in_the_same_second_get_their_own_files() {
+    let backup = BackupService::new(seeded_db().await, temp_dir());
+
+    let first = backup.create_backup(None).await.unwrap();
+    let second = backup.create_backup(None).await.unwrap();
+    assert_ne!(first.path, second.path);
+    assert_eq!(backup.list_backups().await.unwrap().len(), 2);
+
+    std::fs::remove_dir_all(backup.dir()).unwrap();
+}
@@ -0,0 +1,329 @@
+//! Database connection diagnostics
+//!
+//! [`test_connection`] opens a single connection, without a pool, so that a
+//! failure is reported at once instead of being retried until the pool times
+//! out. On success it reports the round-trip latency, the server version, the
+//! migrations of [`Migrator`] that are applied or pending and, on PostgreSQL,
+//! which of the [`REQUIRED_EXTENSIONS`] are installed.
+
+use std::fmt;
+use std::io::ErrorKind;
+use std::time::{Duration, Instant};
+
+use db_migration::{Migrator, MigratorTrait};
+use sea_orm::sqlx::{self, Connection, PgConnection, SqliteConnection};
+use serde::Serialize;
+use thiserror::Error;
+
+/// PostgreSQL extensions the schema and `docker/postgres/init` rely on
+pub const REQUIRED_EXTENSIONS: &[&str] = &["uuid-ossp", "pgcrypto", "pg_trgm", "btree_gin"];
+
+/// Why a connection attempt failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionFailure {
+    /// The host name did not resolve
+    Dns,
+    /// Nothing is listening on the host and port
+    Refused,
+    /// The server rejected the user name or password
+    Auth,
+    /// The database (or SQLite file) does not exist
+    MissingDatabase,
+    /// No answer within the connect timeout
+    Timeout,
+    /// Anything else, such as TLS or protocol errors
+    Other,
+}
+
+impl fmt::Display for ConnectionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dns => write!(f, "Host not found"),
+            Self::Refused => write!(f, "Connection refused"),
+            Self::Auth => write!(f, "Authentication failed"),
+            Self::MissingDatabase => write!(f, "Database does not exist"),
+            Self::Timeout => write!(f, "Connection timed out"),
+            Self::Other => write!(f, "Connection failed"),
+        }
+    }
+}
+
+/// Failed connection test
+#[derive(Error, Debug, Clone, Serialize)]
+#[error("{failure}: {message}")]
+pub struct ConnectionError {
+    pub failure: ConnectionFailure,
+    pub message: String,
+}
+
+impl From<sqlx::Error> for ConnectionError {
+    fn from(error: sqlx::Error) -> Self {
+        Self {
+            failure: classify(&error),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Whether a migration of [`Migrator`] has been applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStatus {
+    Applied,
+    Pending,
+    /// Applied by a newer build; this build does not know the migration
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationState {
+    pub name: String,
+    pub status: MigrationStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExtensionState {
+    pub name: String,
+    /// Installed version, `None` when the extension is not installed
+    pub installed: Option<String>,
+    /// Whether the server can install the extension
+    pub available: bool,
+}
+
+/// Successful connection test
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionReport {
+    /// Time to open the connection
+    pub connect_time: Duration,
+    /// Round trip of `SELECT 1`
+    pub latency: Duration,
+    pub server_version: String,
+    pub migrations: Vec<MigrationState>,
+    /// Required extensions; empty on SQLite
+    pub extensions: Vec<ExtensionState>,
+}
+
+impl ConnectionReport {
+    pub fn pending_migrations(&self) -> usize {
+        self.migrations
+            .iter()
+            .filter(|migration| migration.status == MigrationStatus::Pending)
+            .count()
+    }
+
+    pub fn missing_extensions(&self) -> Vec<&str> {
+        self.extensions
+            .iter()
+            .filter(|extension| extension.installed.is_none())
+            .map(|extension| extension.name.as_str())
+            .collect()
+    }
+}
+
+/// Connect to `url` and report on the database behind it
+///
+/// SQLite files are opened read-write without `mode=rwc`, so a missing file
+/// is reported instead of created.
+pub async fn test_connection(
+    url: &str,
+    timeout: Duration,
+) -> Result<ConnectionReport, ConnectionError> {
+    if url.starts_with("sqlite:") {
+        let url = url.replace("mode=rwc", "mode=rw");
+        let started = Instant::now();
+        let mut conn = connect::<SqliteConnection>(&url, timeout).await?;
+        let connect_time = started.elapsed();
+        let report = probe_sqlite(&mut conn, connect_time).await;
+        let _ = conn.close().await;
+        report
+    } else {
+        let started = Instant::now();
+        let mut conn = connect::<PgConnection>(url, timeout).await?;
+        let connect_time = started.elapsed();
+        let report = probe_postgres(&mut conn, connect_time).await;
+        let _ = conn.close().await;
+        report
+    }
+}
+
+async fn connect<C: Connection>(url: &str, timeout: Duration) -> Result<C, ConnectionError> {
+    match tokio::time::timeout(timeout, C::connect(url)).await {
+        Ok(conn) => Ok(conn?),
+        Err(_) => Err(ConnectionError {
+            failure: ConnectionFailure::Timeout,
+            message: format!("no answer within {}s", timeout.as_secs()),
+        }),
+    }
+}
+
+async fn probe_postgres(
+    conn: &mut PgConnection,
+    connect_time: Duration,
+) -> Result<ConnectionReport, ConnectionError> {
+    let started = Instant::now();
+    sqlx::query("SELECT 1").execute(&mut *conn).await?;
+    let latency = started.elapsed();
+
+    let server_version: String = sqlx::query_scalar("SHOW server_version")
+        .fetch_one(&mut *conn)
+        .await?;
+
+    let table = Migrator::migration_table_name().to_string();
+    let has_table: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+        .bind(format!("\"{}\"", table))
+        .fetch_one(&mut *conn)
+        .await?;
+    let applied: Vec<String> = if has_table {
+        sqlx::query_scalar(&format!("SELECT version FROM \"{}\"", table))
+            .fetch_all(&mut *conn)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    let available: Vec<(String, Option<String>)> = sqlx::query_as(
+        "SELECT name::text, installed_version::text FROM pg_available_extensions \
+         WHERE name::text = ANY($1)",
+    )
+    .bind(
+        REQUIRED_EXTENSIONS
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>(),
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    let extensions = REQUIRED_EXTENSIONS
+        .iter()
+        .map(|name| {
+            let found = available.iter().find(|(available, _)| available == name);
+            ExtensionState {
+                name: name.to_string(),
+                installed: found.and_then(|(_, version)| version.clone()),
+                available: found.is_some(),
+            }
+        })
+        .collect();
+
+    Ok(ConnectionReport {
+        connect_time,
+        latency,
+        server_version: format!("PostgreSQL {}", server_version),
+        migrations: migration_states(&applied),
+        extensions,
+    })
+}
+
+async fn probe_sqlite(
+    conn: &mut SqliteConnection,
+    connect_time: Duration,
+) -> Result<ConnectionReport, ConnectionError> {
+    let started = Instant::now();
+    sqlx::query("SELECT 1").execute(&mut *conn).await?;
+    let latency = started.elapsed();
+
+    let server_version: String = sqlx::query_scalar("SELECT sqlite_version()")
+        .fetch_one(&mut *conn)
+        .await?;
+
+    let table = Migrator::migration_table_name().to_string();
+    let has_table: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?)",
+    )
+    .bind(&table)
+    .fetch_one(&mut *conn)
+    .await?;
+    let applied: Vec<String> = if has_table {
+        sqlx::query_scalar(&format!("SELECT version FROM \"{}\"", table))
+            .fetch_all(&mut *conn)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    Ok(ConnectionReport {
+        connect_time,
+        latency,
+        server_version: format!("SQLite {}", server_version),
+        migrations: migration_states(&applied),
+        extensions: Vec::new(),
+    })
+}
+
+/// Status of every migration of [`Migrator`], followed by applied migrations
+/// this build does not know
+fn migration_states(applied: &[String]) -> Vec<MigrationState> {
+    let known: Vec<String> = Migrator::migrations()
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect();
+
+    let mut states: Vec<MigrationState> = known
+        .iter()
+        .map(|name| MigrationState {
+            name: name.clone(),
+            status: if applied.contains(name) {
+                MigrationStatus::Applied
+            } else {
+                MigrationStatus::Pending
+            },
+        })
+        .collect();
+    states.extend(
+        applied
+            .iter()
+            .filter(|name| !known.contains(name))
+            .map(|name| MigrationState {
+                name: name.clone(),
+                status: MigrationStatus::Unknown,
+            }),
+    );
+    states
+}
+
+fn classify(error: &sqlx::Error) -> ConnectionFailure {
+    match error {
+        sqlx::Error::Database(error) => error
+            .code()
+            .map_or(ConnectionFailure::Other, |code| classify_code(&code)),
+        sqlx::Error::Io(error) if error.kind() == ErrorKind::ConnectionRefused => {
+            ConnectionFailure::Refused
+        }
+        sqlx::Error::Io(error) if error.kind() == ErrorKind::TimedOut => ConnectionFailure::Timeout,
+        sqlx::Error::Io(error) if is_lookup_error(error) => ConnectionFailure::Dns,
+        sqlx::Error::PoolTimedOut => ConnectionFailure::Timeout,
+        _ => ConnectionFailure::Other,
+    }
+}
+
+/// Map a PostgreSQL SQLSTATE or SQLite result code
+fn classify_code(code: &str) -> ConnectionFailure {
+    match code {
+        // invalid_password, invalid_authorization_specification
+        "28P01" | "28000" => ConnectionFailure::Auth,
+        // invalid_catalog_name
+        "3D000" => ConnectionFailure::MissingDatabase,
+        // SQLITE_CANTOPEN
+        "14" => ConnectionFailure::MissingDatabase,
+        _ => ConnectionFailure::Other,
+    }
+}
+
+/// Resolver failures carry no dedicated `ErrorKind`, only the message of the
+/// platform's resolver
+fn is_lookup_error(error: &std::io::Error) -> bool {
+    let message = error.to_string().to_lowercase();
+    [
+        "failed to lookup address",
+        "name or service not known",
+        "temporary failure in name resolution",
+        "nodename nor servname",
+        "no such host",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+#[cfg(test)]
+mod tests;
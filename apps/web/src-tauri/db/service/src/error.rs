@@ -28,6 +28,10 @@ pub enum ServiceError {
     #[error("Forbidden: {0}")]
     Forbidden(String),
 
+    /// Configuration that would leave the application open to attack
+    #[error("Insecure configuration: {0}")]
+    InsecureConfig(String),
+
     /// Internal server error
     #[error("Internal error: {0}")]
     Internal(String),
@@ -0,0 +1,202 @@
+use db_entity::id::Id;
+use db_entity::special_order::SpecialOrderStatus;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Number of events buffered per subscriber before the slowest one starts lagging
+const DEFAULT_CAPACITY: usize = 256;
+
+/// Domain events published by services after a change has been committed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+pub enum DomainEvent {
+    /// Stock quantity of an inventory item changed
+    StockChanged {
+        inventory_item_id: Id,
+        quantity_before: i32,
+        quantity_after: i32,
+        min_stock_level: i32,
+    },
+
+    /// Stock of an inventory item dropped to or below its minimum level
+    LowStockReached {
+        inventory_item_id: Id,
+        stock_quantity: i32,
+        min_stock_level: i32,
+    },
+
+    /// A special order moved to a different status
+    SpecialOrderStatusChanged {
+        special_order_id: Id,
+        old_status: SpecialOrderStatus,
+        new_status: SpecialOrderStatus,
+    },
+
+    /// One session (or, when `session_id` is `None`, all sessions) of a user was revoked
+    SessionRevoked { user_id: Id, session_id: Option<Id> },
+
+    /// A setting was created, updated or deleted
+    ///
+    /// `key` is `None` when a whole category was affected.
+    SettingChanged {
+        key: Option<String>,
+        category: Option<String>,
+    },
+}
+
+impl DomainEvent {
+    /// Stable event name, matching the serialized `type` tag
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::StockChanged { .. } => "stock_changed",
+            Self::LowStockReached { .. } => "low_stock_reached",
+            Self::SpecialOrderStatusChanged { .. } => "special_order_status_changed",
+            Self::SessionRevoked { .. } => "session_revoked",
+            Self::SettingChanged { .. } => "setting_changed",
+        }
+    }
+}
+
+/// In-process publish/subscribe bus for domain events
+///
+/// Publishing never blocks and never fails: events published while nobody is
+/// subscribed are dropped, and subscribers that fall too far behind skip the
+/// oldest events.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<DomainEvent>,
+}
+
+impl EventBus {
+    /// Create a new event bus buffering up to `capacity` events per subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// Publish an event to all current subscribers
+    pub fn publish(&self, event: DomainEvent) {
+        tracing::debug!("Publishing domain event: {}", event.name());
+        // An error only means there are no subscribers right now
+        let _ = self.sender.send(event);
+    }
+
+    /// Subscribe to all events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publish a stock change, plus `LowStockReached` if the change crossed the minimum level
+    pub fn publish_stock_change(
+        &self,
+        inventory_item_id: Id,
+        quantity_before: i32,
+        quantity_after: i32,
+        min_stock_level: i32,
+    ) {
+        if quantity_before == quantity_after {
+            return;
+        }
+
+        self.publish(DomainEvent::StockChanged {
+            inventory_item_id,
+            quantity_before,
+            quantity_after,
+            min_stock_level,
+        });
+
+        if quantity_after <= min_stock_level && quantity_before > min_stock_level {
+            self.publish(DomainEvent::LowStockReached {
+                inventory_item_id,
+                stock_quantity: quantity_after,
+                min_stock_level,
+            });
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(receiver: &mut broadcast::Receiver<DomainEvent>) -> Vec<DomainEvent> {
+        std::iter::from_fn(|| receiver.try_recv().ok()).collect()
+    }
+
+    #[test]
+    fn test_publish_without_subscribers_does_not_fail() {
+        let bus = EventBus::default();
+        bus.publish(DomainEvent::SettingChanged {
+            key: Some("app.language".to_string()),
+            category: None,
+        });
+    }
+
+    #[test]
+    fn test_subscribers_receive_events() {
+        let bus = EventBus::default();
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+
+        let event = DomainEvent::SessionRevoked {
+            user_id: Id::new(),
+            session_id: None,
+        };
+        bus.publish(event.clone());
+
+        assert_eq!(drain(&mut first), vec![event.clone()]);
+        assert_eq!(drain(&mut second), vec![event]);
+    }
+
+    #[test]
+    fn test_stock_change_crossing_minimum_reports_low_stock() {
+        let bus = EventBus::default();
+        let mut receiver = bus.subscribe();
+        let item = Id::new();
+
+        bus.publish_stock_change(item, 12, 8, 10);
+
+        let events = drain(&mut receiver);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].name(), "stock_changed");
+        assert_eq!(
+            events[1],
+            DomainEvent::LowStockReached {
+                inventory_item_id: item,
+                stock_quantity: 8,
+                min_stock_level: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn test_stock_change_already_below_minimum_does_not_repeat_low_stock() {
+        let bus = EventBus::default();
+        let mut receiver = bus.subscribe();
+
+        bus.publish_stock_change(Id::new(), 8, 5, 10);
+        bus.publish_stock_change(Id::new(), 5, 5, 10);
+
+        let events = drain(&mut receiver);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name(), "stock_changed");
+    }
+
+    #[test]
+    fn test_event_serialization_is_tagged() {
+        let event = DomainEvent::SettingChanged {
+            key: Some("app.language".to_string()),
+            category: Some("general".to_string()),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "setting_changed");
+        assert_eq!(json["payload"]["key"], "app.language");
+    }
+}
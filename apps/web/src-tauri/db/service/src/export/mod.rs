@@ -0,0 +1,516 @@
+//! Data export to CSV files and a JSON archive
+//!
+//! Rows are streamed from the database and written as they arrive, so large
+//! history tables are never held in memory.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{NaiveDate, NaiveTime};
+use db_entity::prelude::*;
+use futures::TryStreamExt;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tap::TapFallible;
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
+
+use crate::error::{ServiceError, ServiceResult};
+use crate::import::write_csv_record;
+
+/// Identifies the archive format in the JSON export
+const ARCHIVE_FORMAT: &str = "meditrack-export";
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Stock columns appended to each exported inventory item
+const STOCK_COLUMNS: [&str; 4] = [
+    "stock_quantity",
+    "min_stock_level",
+    "unit_price",
+    "last_restocked_at",
+];
+
+/// A table that can be exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportTable {
+    Manufacturers,
+    MedicineForms,
+    /// Catalogue items joined with their stock
+    InventoryItems,
+    Barcodes,
+    PriceHistory,
+    StockHistory,
+    OpeningBalances,
+}
+
+impl ExportTable {
+    /// Every table, in dependency order
+    pub const ALL: [Self; 7] = [
+        Self::Manufacturers,
+        Self::MedicineForms,
+        Self::InventoryItems,
+        Self::Barcodes,
+        Self::PriceHistory,
+        Self::StockHistory,
+        Self::OpeningBalances,
+    ];
+
+    /// Name used for the CSV file and the JSON archive key
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Manufacturers => "manufacturers",
+            Self::MedicineForms => "medicine_forms",
+            Self::InventoryItems => "inventory_items",
+            Self::Barcodes => "barcodes",
+            Self::PriceHistory => "price_history",
+            Self::StockHistory => "stock_history",
+            Self::OpeningBalances => "opening_balances",
+        }
+    }
+}
+
+/// Date range applied to the history tables (both ends inclusive)
+///
+/// Catalogue tables are always exported in full.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ExportFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl ExportFilter {
+    /// Apply the range to a timestamp column
+    fn timestamps<E: EntityTrait>(&self, select: Select<E>, column: E::Column) -> Select<E> {
+        let start_of = |date: NaiveDate| date.and_time(NaiveTime::MIN).and_utc().fixed_offset();
+
+        select
+            .apply_if(self.from, |q, from| q.filter(column.gte(start_of(from))))
+            .apply_if(self.to.and_then(|to| to.succ_opt()), |q, end| {
+                q.filter(column.lt(start_of(end)))
+            })
+    }
+
+    /// Apply the range to a date column
+    fn dates<E: EntityTrait>(&self, select: Select<E>, column: E::Column) -> Select<E> {
+        select
+            .apply_if(self.from, |q, from| q.filter(column.gte(from)))
+            .apply_if(self.to, |q, to| q.filter(column.lte(to)))
+    }
+}
+
+/// Output format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// One CSV file per table
+    Csv,
+    /// A single JSON archive holding every table
+    Json,
+}
+
+/// What to export and how
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Tables to export; all of them when omitted
+    #[serde(default = "all_tables")]
+    pub tables: Vec<ExportTable>,
+    #[serde(default)]
+    pub filter: ExportFilter,
+}
+
+fn all_tables() -> Vec<ExportTable> {
+    ExportTable::ALL.to_vec()
+}
+
+/// Number of rows written for a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedTable {
+    pub table: ExportTable,
+    pub rows: u64,
+}
+
+/// A file written by an export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedFile {
+    pub path: PathBuf,
+    pub tables: Vec<ExportedTable>,
+}
+
+/// Output format of the row writer
+enum Format {
+    Csv,
+    /// `first` tracks whether a separating comma is needed
+    Json {
+        first: bool,
+    },
+}
+
+/// Writes streamed rows as CSV lines or JSON array elements
+struct RowWriter<'w, W> {
+    out: &'w mut W,
+    format: Format,
+    columns: Vec<String>,
+    rows: u64,
+}
+
+impl<'w, W: AsyncWrite + Unpin> RowWriter<'w, W> {
+    async fn csv(out: &'w mut W, columns: Vec<String>) -> ServiceResult<Self> {
+        let mut header = String::new();
+        write_csv_record(&mut header, columns.iter().map(String::as_str));
+        write(out, header.as_bytes()).await?;
+
+        Ok(Self {
+            out,
+            format: Format::Csv,
+            columns,
+            rows: 0,
+        })
+    }
+
+    fn json(out: &'w mut W) -> Self {
+        Self {
+            out,
+            format: Format::Json { first: true },
+            columns: Vec::new(),
+            rows: 0,
+        }
+    }
+
+    async fn row(&mut self, row: Map<String, Value>) -> ServiceResult<()> {
+        self.rows += 1;
+
+        match &mut self.format {
+            Format::Csv => {
+                let cells: Vec<String> = self
+                    .columns
+                    .iter()
+                    .map(|column| match row.get(column) {
+                        None | Some(Value::Null) => String::new(),
+                        Some(Value::String(value)) => value.clone(),
+                        Some(value) => value.to_string(),
+                    })
+                    .collect();
+                let mut line = String::new();
+                write_csv_record(&mut line, cells.iter().map(String::as_str));
+                write(self.out, line.as_bytes()).await
+            }
+            Format::Json { first } => {
+                if !std::mem::take(first) {
+                    write(self.out, b",").await?;
+                }
+                let json = serde_json::to_vec(&row)
+                    .map_err(|e| ServiceError::Internal(format!("Failed to encode row: {}", e)))?;
+                write(self.out, &json).await
+            }
+        }
+    }
+}
+
+/// Export service for getting data out as CSV files or a JSON archive
+pub struct ExportService {
+    db: Arc<DatabaseConnection>,
+}
+
+impl ExportService {
+    /// Create a new export service
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    /// Export into `dir` using the given format
+    ///
+    /// CSV exports write one `<table>.csv` per table, JSON exports a single
+    /// `export.json`.
+    pub async fn export(
+        &self,
+        dir: &Path,
+        options: ExportOptions,
+    ) -> ServiceResult<Vec<ExportedFile>> {
+        match options.format {
+            ExportFormat::Csv => {
+                self.export_csv_files(dir, &options.tables, options.filter)
+                    .await
+            }
+            ExportFormat::Json => {
+                let path = dir.join("export.json");
+                self.export_json_file(&path, &options.tables, options.filter)
+                    .await
+                    .map(|file| vec![file])
+            }
+        }
+    }
+
+    /// Write one CSV file per table into `dir`, named after the table
+    pub async fn export_csv_files(
+        &self,
+        dir: &Path,
+        tables: &[ExportTable],
+        filter: ExportFilter,
+    ) -> ServiceResult<Vec<ExportedFile>> {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| io_error(dir, e))?;
+
+        let mut files = Vec::with_capacity(tables.len());
+        for table in tables {
+            let path = dir.join(format!("{}.csv", table.name()));
+            let file = tokio::fs::File::create(&path)
+                .await
+                .map_err(|e| io_error(&path, e))?;
+            let mut out = BufWriter::new(file);
+
+            let rows = self.write_csv(*table, filter, &mut out).await?;
+            out.flush().await.map_err(|e| io_error(&path, e))?;
+
+            files.push(ExportedFile {
+                path,
+                tables: vec![ExportedTable {
+                    table: *table,
+                    rows,
+                }],
+            });
+        }
+
+        tracing::info!("Exported {} CSV file(s) to {}", files.len(), dir.display());
+        Ok(files)
+    }
+
+    /// Write all `tables` into a single JSON archive at `path`
+    pub async fn export_json_file(
+        &self,
+        path: &Path,
+        tables: &[ExportTable],
+        filter: ExportFilter,
+    ) -> ServiceResult<ExportedFile> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error(parent, e))?;
+        }
+        let file = tokio::fs::File::create(path)
+            .await
+            .map_err(|e| io_error(path, e))?;
+        let mut out = BufWriter::new(file);
+
+        let counts = self.write_json_archive(tables, filter, &mut out).await?;
+        out.flush().await.map_err(|e| io_error(path, e))?;
+
+        tracing::info!("Exported JSON archive to {}", path.display());
+        Ok(ExportedFile {
+            path: path.to_path_buf(),
+            tables: counts,
+        })
+    }
+
+    /// Stream one table as CSV into `out`, returning the number of rows
+    pub async fn write_csv<W: AsyncWrite + Unpin>(
+        &self,
+        table: ExportTable,
+        filter: ExportFilter,
+        out: &mut W,
+    ) -> ServiceResult<u64> {
+        let mut writer = RowWriter::csv(out, Self::columns(table)).await?;
+        self.write_rows(table, filter, &mut writer)
+            .await
+            .tap_err(|e| tracing::error!("Failed to export {}: {}", table.name(), e))?;
+        Ok(writer.rows)
+    }
+
+    /// Stream `tables` as one JSON document into `out`
+    ///
+    /// The document has a `format`/`version` header, the applied filter, one
+    /// array of rows per table under `tables`, and the row counts.
+    pub async fn write_json_archive<W: AsyncWrite + Unpin>(
+        &self,
+        tables: &[ExportTable],
+        filter: ExportFilter,
+        out: &mut W,
+    ) -> ServiceResult<Vec<ExportedTable>> {
+        let header = serde_json::json!({
+            "format": ARCHIVE_FORMAT,
+            "version": ARCHIVE_VERSION,
+            "exported_at": chrono::Utc::now(),
+            "filter": filter,
+        })
+        .to_string();
+        // Re-open the header object to append the streamed tables
+        let header = header.strip_suffix('}').unwrap_or(&header);
+        write(out, header.as_bytes()).await?;
+        write(out, br#","tables":{"#).await?;
+
+        let mut counts = Vec::with_capacity(tables.len());
+        for (index, table) in tables.iter().enumerate() {
+            let separator = if index == 0 { "" } else { "," };
+            write(
+                out,
+                format!(r#"{}"{}":["#, separator, table.name()).as_bytes(),
+            )
+            .await?;
+
+            let mut writer = RowWriter::json(out);
+            self.write_rows(*table, filter, &mut writer)
+                .await
+                .tap_err(|e| tracing::error!("Failed to export {}: {}", table.name(), e))?;
+            counts.push(ExportedTable {
+                table: *table,
+                rows: writer.rows,
+            });
+
+            write(out, b"]").await?;
+        }
+
+        let row_counts: Map<String, Value> = counts
+            .iter()
+            .map(|count| (count.table.name().to_string(), Value::from(count.rows)))
+            .collect();
+        write(
+            out,
+            format!(r#"}},"row_counts":{}}}"#, Value::Object(row_counts)).as_bytes(),
+        )
+        .await?;
+
+        Ok(counts)
+    }
+
+    // ========================================================================
+    // Helper Methods
+    // ========================================================================
+
+    /// Column names of a table, in schema order
+    fn columns(table: ExportTable) -> Vec<String> {
+        fn of<E: EntityTrait>() -> Vec<String> {
+            E::Column::iter()
+                .map(|column| column.as_str().to_owned())
+                .collect()
+        }
+
+        match table {
+            ExportTable::Manufacturers => of::<Manufacturer>(),
+            ExportTable::MedicineForms => of::<MedicineForm>(),
+            ExportTable::InventoryItems => {
+                let mut columns = of::<InventoryItem>();
+                columns.extend(STOCK_COLUMNS.map(str::to_owned));
+                columns
+            }
+            ExportTable::Barcodes => of::<InventoryItemBarcode>(),
+            ExportTable::PriceHistory => of::<InventoryPriceHistory>(),
+            ExportTable::StockHistory => of::<InventoryStockHistory>(),
+            ExportTable::OpeningBalances => of::<InventoryOpeningBalance>(),
+        }
+    }
+
+    /// Stream the rows of a table into `writer`
+    async fn write_rows<W: AsyncWrite + Unpin>(
+        &self,
+        table: ExportTable,
+        filter: ExportFilter,
+        writer: &mut RowWriter<'_, W>,
+    ) -> ServiceResult<()> {
+        match table {
+            ExportTable::Manufacturers => {
+                let select = Manufacturer::find().order_by_asc(manufacturer::Column::Name);
+                self.stream(select, writer).await
+            }
+            ExportTable::MedicineForms => {
+                let select = MedicineForm::find().order_by_asc(medicine_form::Column::DisplayOrder);
+                self.stream(select, writer).await
+            }
+            ExportTable::InventoryItems => self.stream_items_with_stock(writer).await,
+            ExportTable::Barcodes => {
+                let select =
+                    InventoryItemBarcode::find().order_by_asc(inventory_item_barcode::Column::Id);
+                self.stream(select, writer).await
+            }
+            ExportTable::PriceHistory => {
+                let column = inventory_price_history::Column::RecordedAt;
+                let select = filter
+                    .timestamps(InventoryPriceHistory::find(), column)
+                    .order_by_asc(column);
+                self.stream(select, writer).await
+            }
+            ExportTable::StockHistory => {
+                let column = inventory_stock_history::Column::RecordedAt;
+                let select = filter
+                    .timestamps(InventoryStockHistory::find(), column)
+                    .order_by_asc(column);
+                self.stream(select, writer).await
+            }
+            ExportTable::OpeningBalances => {
+                let column = inventory_opening_balance::Column::EntryDate;
+                let select = filter
+                    .dates(InventoryOpeningBalance::find(), column)
+                    .order_by_asc(column);
+                self.stream(select, writer).await
+            }
+        }
+    }
+
+    async fn stream<E, W>(
+        &self,
+        select: Select<E>,
+        writer: &mut RowWriter<'_, W>,
+    ) -> ServiceResult<()>
+    where
+        E: EntityTrait,
+        E::Model: Serialize + Send + Sync,
+        W: AsyncWrite + Unpin,
+    {
+        let mut rows = select.stream(self.db.as_ref()).await?;
+        while let Some(model) = rows.try_next().await? {
+            writer.row(to_map(&model)?).await?;
+        }
+        Ok(())
+    }
+
+    async fn stream_items_with_stock<W: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut RowWriter<'_, W>,
+    ) -> ServiceResult<()> {
+        let mut rows = InventoryItem::find()
+            .find_also_related(InventoryStock)
+            .order_by_asc(inventory_item::Column::Name)
+            .stream(self.db.as_ref())
+            .await?;
+
+        while let Some((item, stock)) = rows.try_next().await? {
+            let mut row = to_map(&item)?;
+            if let Some(stock) = stock {
+                let stock = to_map(&stock)?;
+                for column in STOCK_COLUMNS {
+                    let value = stock.get(column).cloned().unwrap_or_default();
+                    row.insert(column.to_string(), value);
+                }
+            }
+            writer.row(row).await?;
+        }
+        Ok(())
+    }
+}
+
+fn to_map<T: Serialize>(model: &T) -> ServiceResult<Map<String, Value>> {
+    match serde_json::to_value(model) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(ServiceError::Internal(
+            "Exported row is not an object".to_string(),
+        )),
+        Err(e) => Err(ServiceError::Internal(format!(
+            "Failed to encode row: {}",
+            e
+        ))),
+    }
+}
+
+async fn write<W: AsyncWrite + Unpin>(out: &mut W, bytes: &[u8]) -> ServiceResult<()> {
+    out.write_all(bytes)
+        .await
+        .map_err(|e| ServiceError::Internal(format!("Failed to write export: {}", e)))
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> ServiceError {
+    ServiceError::Internal(format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests;
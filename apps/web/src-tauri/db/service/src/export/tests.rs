@@ -0,0 +1,141 @@
+use db_entity::inventory_stock::dto::AdjustStock;
+
+use super::*;
+use crate::events::EventBus;
+use crate::import::ImportSheet;
+use crate::inventory::InventoryService;
+use crate::test_support::{TestItem, create_items, sqlite_db};
+
+/// Database with one item ("Panadol", stock 7 after one adjustment)
+async fn seeded_db() -> Arc<DatabaseConnection> {
+    let db = sqlite_db().await;
+
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    let ids = create_items(
+        &db,
+        &inventory,
+        &[TestItem {
+            barcodes: &["6221000000011"],
+            ..TestItem::new("Panadol, Extra")
+        }],
+    )
+    .await;
+
+    inventory
+        .adjust_stock(
+            ids[0],
+            AdjustStock {
+                adjustment: -3,
+                reason: Some("Sale".to_string()),
+                adjustment_type: None,
+                idempotency_key: None,
+                adjusted_by: None,
+                reference_id: None,
+                reference_type: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    db
+}
+
+#[tokio::test]
+async fn test_csv_export_joins_items_with_stock() {
+    let export = ExportService::new(seeded_db().await);
+    let mut out = Vec::new();
+
+    let rows = export
+        .write_csv(
+            ExportTable::InventoryItems,
+            ExportFilter::default(),
+            &mut out,
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows, 1);
+
+    let sheet = ImportSheet::from_csv(std::str::from_utf8(&out).unwrap()).unwrap();
+    let record = sheet.records().next().unwrap();
+    assert_eq!(record.get(sheet.column(&["name"])), Some("Panadol, Extra"));
+    assert_eq!(record.get(sheet.column(&["stock_quantity"])), Some("7"));
+    assert_eq!(record.get(sheet.column(&["deleted_at"])), None);
+}
+
+#[tokio::test]
+async fn test_csv_export_filters_history_by_date() {
+    let export = ExportService::new(seeded_db().await);
+    let today = chrono::Utc::now().date_naive();
+
+    let mut out = Vec::new();
+    let rows = export
+        .write_csv(
+            ExportTable::StockHistory,
+            ExportFilter {
+                from: Some(today),
+                to: Some(today),
+            },
+            &mut out,
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows, 1);
+
+    let rows = export
+        .write_csv(
+            ExportTable::StockHistory,
+            ExportFilter {
+                from: today.succ_opt(),
+                to: None,
+            },
+            &mut Vec::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows, 0);
+}
+
+#[tokio::test]
+async fn test_json_archive_contains_every_table() {
+    let export = ExportService::new(seeded_db().await);
+    let mut out = Vec::new();
+
+    let counts = export
+        .write_json_archive(&ExportTable::ALL, ExportFilter::default(), &mut out)
+        .await
+        .unwrap();
+    assert_eq!(counts.len(), ExportTable::ALL.len());
+
+    let archive: Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(archive["format"], ARCHIVE_FORMAT);
+    assert_eq!(archive["tables"]["barcodes"][0]["barcode"], "6221000000011");
+    assert_eq!(archive["tables"]["inventory_items"][0]["stock_quantity"], 7);
+    assert_eq!(archive["tables"]["manufacturers"], Value::Array(vec![]));
+    assert_eq!(archive["row_counts"]["stock_history"], 1);
+}
+
+#[tokio::test]
+async fn test_export_writes_one_csv_per_table() {
+    let export = ExportService::new(seeded_db().await);
+    let dir = std::env::temp_dir().join(format!("meditrack-export-{}", Id::new()));
+
+    let files = export
+        .export(
+            &dir,
+            ExportOptions {
+                format: ExportFormat::Csv,
+                tables: vec![ExportTable::Barcodes, ExportTable::StockHistory],
+                filter: ExportFilter::default(),
+            },
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].path, dir.join("barcodes.csv"));
+    assert_eq!(files[1].tables[0].rows, 1);
+    let barcodes = std::fs::read_to_string(&files[0].path).unwrap();
+    assert!(barcodes.contains("6221000000011"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,376 @@
+//! Tabular import files
+//!
+//! Importers work on an [`ImportSheet`]: a header row plus data rows of plain
+//! strings. CSV is parsed here; spreadsheet formats are decoded by the caller
+//! into the same shape with [`ImportSheet::new`].
+
+use chrono::NaiveDate;
+use db_entity::inventory_opening_balance::dto::ImportRowError;
+use rust_decimal::Decimal;
+
+use crate::error::{ServiceError, ServiceResult};
+
+/// Header row plus data rows read from an import file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSheet {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl ImportSheet {
+    /// Build a sheet from raw rows, skipping rows where every cell is blank
+    pub fn new(headers: Vec<String>, rows: Vec<Vec<String>>) -> Self {
+        let headers = headers.iter().map(|h| normalize_header(h)).collect();
+        let rows = rows
+            .into_iter()
+            .filter(|row| row.iter().any(|cell| !cell.trim().is_empty()))
+            .collect();
+
+        Self { headers, rows }
+    }
+
+    /// Parse CSV content (RFC 4180 quoting, `,` or `;` delimited)
+    ///
+    /// The delimiter is taken from the header line, since spreadsheet
+    /// programs in Arabic and European locales export with `;`.
+    pub fn from_csv(content: &str) -> ServiceResult<Self> {
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        let header_line = content.lines().next().unwrap_or_default();
+        let delimiter = if header_line.matches(';').count() > header_line.matches(',').count() {
+            ';'
+        } else {
+            ','
+        };
+
+        let mut records = parse_csv(content, delimiter)?.into_iter();
+        let headers = records
+            .next()
+            .ok_or_else(|| ServiceError::BadRequest("Import file is empty".to_string()))?;
+
+        Ok(Self::new(headers, records.collect()))
+    }
+
+    /// Normalized header names (lowercase, `snake_case`)
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// Number of data rows
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Whether the sheet has no data rows
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Index of the first column whose header matches one of `aliases`
+    pub fn column(&self, aliases: &[&str]) -> Option<usize> {
+        self.headers
+            .iter()
+            .position(|header| aliases.iter().any(|alias| header == alias))
+    }
+
+    /// CSV of the rows named in `errors`, each followed by its error message
+    ///
+    /// The report keeps the sheet's columns so it can be corrected and
+    /// imported again; the extra `row` and `error` columns are ignored.
+    pub fn error_report(&self, errors: &[ImportRowError]) -> String {
+        let mut report = String::new();
+        let header = std::iter::once("row")
+            .chain(self.headers.iter().map(String::as_str))
+            .chain(std::iter::once("error"));
+        write_csv_record(&mut report, header);
+
+        for error in errors {
+            let row_number = error.row_number.to_string();
+            let cells = self
+                .rows
+                .get(error.row_number.wrapping_sub(2))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let record = std::iter::once(row_number.as_str())
+                .chain(
+                    (0..self.headers.len())
+                        .map(|index| cells.get(index).map(String::as_str).unwrap_or_default()),
+                )
+                .chain(std::iter::once(error.error_message.as_str()));
+            write_csv_record(&mut report, record);
+        }
+
+        report
+    }
+
+    /// Iterate over data rows
+    pub fn records(&self) -> impl Iterator<Item = ImportRecord<'_>> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(index, cells)| ImportRecord {
+                // Spreadsheet row number: the header is row 1
+                row_number: index + 2,
+                cells,
+            })
+    }
+}
+
+/// A single data row of an [`ImportSheet`]
+#[derive(Debug, Clone, Copy)]
+pub struct ImportRecord<'a> {
+    row_number: usize,
+    cells: &'a [String],
+}
+
+impl<'a> ImportRecord<'a> {
+    /// Row number as shown in a spreadsheet (the header is row 1)
+    pub fn row_number(&self) -> usize {
+        self.row_number
+    }
+
+    /// Trimmed cell value, or `None` when the column is missing or the cell is blank
+    pub fn get(&self, column: Option<usize>) -> Option<&'a str> {
+        column
+            .and_then(|index| self.cells.get(index))
+            .map(|cell| cell.trim())
+            .filter(|cell| !cell.is_empty())
+    }
+
+    /// Required cell value
+    pub fn required(&self, column: Option<usize>, name: &str) -> Result<&'a str, String> {
+        self.get(column)
+            .ok_or_else(|| format!("{} is required", name))
+    }
+}
+
+// ============================================================================
+// Cell Parsing
+// ============================================================================
+
+/// Parse a whole number, accepting spreadsheet-style `12.0`
+pub fn parse_i32(value: &str, name: &str) -> Result<i32, String> {
+    let value = value.trim();
+    value
+        .parse::<i32>()
+        .ok()
+        .or_else(|| {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.fract() == 0.0 && *n >= i32::MIN as f64 && *n <= i32::MAX as f64)
+                .map(|n| n as i32)
+        })
+        .ok_or_else(|| format!("{} must be a whole number, got '{}'", name, value))
+}
+
+/// Parse a decimal amount
+pub fn parse_decimal(value: &str, name: &str) -> Result<Decimal, String> {
+    value
+        .trim()
+        .parse::<Decimal>()
+        .map_err(|_| format!("{} must be a number, got '{}'", name, value))
+}
+
+/// Parse a yes/no flag
+pub fn parse_bool(value: &str, name: &str) -> Result<bool, String> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "y" | "نعم" => Ok(true),
+        "0" | "false" | "no" | "n" | "لا" => Ok(false),
+        other => Err(format!("{} must be yes or no, got '{}'", name, other)),
+    }
+}
+
+/// Parse a date as `YYYY-MM-DD`, `DD/MM/YYYY` or `MM/YYYY` (end of month)
+pub fn parse_date(value: &str, name: &str) -> Result<NaiveDate, String> {
+    let value = value.trim();
+    let invalid = || format!("{} must be a date (YYYY-MM-DD), got '{}'", name, value);
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%d/%m/%Y") {
+        return Ok(date);
+    }
+
+    // Expiry dates are often printed as month/year only
+    let (month, year) = value.split_once('/').ok_or_else(invalid)?;
+    let month: u32 = month.parse().map_err(|_| invalid())?;
+    let year: i32 = year.parse().map_err(|_| invalid())?;
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .filter(|_| (1..=12).contains(&month))
+        .and_then(|date| date.pred_opt())
+        .ok_or_else(invalid)
+}
+
+// ============================================================================
+// CSV
+// ============================================================================
+
+/// Normalize a header for matching: trimmed, lowercase, `_`-separated
+fn normalize_header(header: &str) -> String {
+    header
+        .trim()
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn parse_csv(content: &str, delimiter: char) -> ServiceResult<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(ServiceError::BadRequest(
+            "Import file has an unterminated quoted field".to_string(),
+        ));
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// Append one CSV line, quoting fields that need it
+pub(crate) fn write_csv_record<'a>(out: &mut String, fields: impl IntoIterator<Item = &'a str>) {
+    for (index, field) in fields.into_iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_with_quotes_and_crlf() {
+        let sheet = ImportSheet::from_csv(
+            "\u{feff}Item Code,Quantity,Notes\r\n123,5,\"Shelf A, top\"\r\n456,7,\"Says \"\"hi\"\"\nthen leaves\"\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(sheet.headers(), ["item_code", "quantity", "notes"]);
+        assert_eq!(sheet.len(), 2);
+
+        let records: Vec<_> = sheet.records().collect();
+        let notes = sheet.column(&["notes"]);
+        assert_eq!(records[0].get(notes), Some("Shelf A, top"));
+        assert_eq!(records[1].get(notes), Some("Says \"hi\"\nthen leaves"));
+        assert_eq!(records[1].row_number(), 3);
+    }
+
+    #[test]
+    fn test_csv_semicolon_delimiter_and_blank_rows() {
+        let sheet = ImportSheet::from_csv("code;qty\n1;2\n;\n3;4").unwrap();
+
+        assert_eq!(sheet.len(), 2);
+        let qty = sheet.column(&["quantity", "qty"]);
+        let values: Vec<_> = sheet.records().map(|r| r.get(qty)).collect();
+        assert_eq!(values, [Some("2"), Some("4")]);
+    }
+
+    #[test]
+    fn test_csv_unterminated_quote_is_rejected() {
+        assert!(ImportSheet::from_csv("a,b\n\"1,2\n").is_err());
+    }
+
+    #[test]
+    fn test_missing_cells_are_none() {
+        let sheet = ImportSheet::from_csv("a,b,c\n1\n").unwrap();
+        let record = sheet.records().next().unwrap();
+
+        assert_eq!(record.get(sheet.column(&["a"])), Some("1"));
+        assert_eq!(record.get(sheet.column(&["c"])), None);
+        assert_eq!(record.get(sheet.column(&["missing"])), None);
+        assert!(record.required(sheet.column(&["c"]), "C").is_err());
+    }
+
+    #[test]
+    fn test_error_report_round_trips() {
+        let sheet = ImportSheet::from_csv("Name,Notes\nA,ok\nB,\"x, y\"\n").unwrap();
+        let report = sheet.error_report(&[ImportRowError {
+            row_number: 3,
+            item_code: "B".to_string(),
+            error_message: "Bad \"form\"".to_string(),
+        }]);
+
+        assert_eq!(
+            report,
+            "row,name,notes,error\r\n3,B,\"x, y\",\"Bad \"\"form\"\"\"\r\n"
+        );
+
+        let reparsed = ImportSheet::from_csv(&report).unwrap();
+        let record = reparsed.records().next().unwrap();
+        assert_eq!(record.get(reparsed.column(&["notes"])), Some("x, y"));
+    }
+
+    #[test]
+    fn test_parse_cells() {
+        assert_eq!(parse_i32("12.0", "Quantity"), Ok(12));
+        assert!(parse_i32("1.5", "Quantity").is_err());
+        assert_eq!(parse_decimal("9.75", "Price"), Ok(Decimal::new(975, 2)));
+        assert_eq!(parse_bool("Yes", "Flag"), Ok(true));
+        assert!(parse_bool("maybe", "Flag").is_err());
+        assert_eq!(
+            parse_date("2027-03-15", "Expiry"),
+            Ok(NaiveDate::from_ymd_opt(2027, 3, 15).unwrap())
+        );
+        assert_eq!(
+            parse_date("15/03/2027", "Expiry"),
+            Ok(NaiveDate::from_ymd_opt(2027, 3, 15).unwrap())
+        );
+        assert_eq!(
+            parse_date("02/2028", "Expiry"),
+            Ok(NaiveDate::from_ymd_opt(2028, 2, 29).unwrap())
+        );
+        assert!(parse_date("13/2028", "Expiry").is_err());
+    }
+}
@@ -0,0 +1,531 @@
+use std::collections::{HashMap, HashSet};
+
+use db_entity::id::Id;
+use db_entity::inventory_item::dto::{
+    CatalogueImportOptions, CatalogueImportResult, CreateBarcodeInput, CreateInventoryItemWithStock,
+};
+use db_entity::inventory_item::{self, Entity as InventoryItem};
+use db_entity::inventory_item_barcode::Entity as InventoryItemBarcode;
+use db_entity::inventory_opening_balance::dto::ImportRowError;
+use db_entity::manufacturer::{self, Entity as Manufacturer};
+use db_entity::medicine_form::{self, Entity as MedicineForm};
+use sea_orm::*;
+use tap::TapFallible;
+
+use super::InventoryService;
+use crate::error::{ServiceError, ServiceResult};
+use crate::import::{ImportRecord, ImportSheet, parse_bool, parse_decimal, parse_i32};
+
+/// Accepted header names for each import column
+const NAME_COLUMNS: &[&str] = &["name", "item_name", "trade_name"];
+const GENERIC_NAME_COLUMNS: &[&str] = &["generic_name", "generic", "active_ingredient"];
+const CONCENTRATION_COLUMNS: &[&str] = &["concentration", "strength"];
+const FORM_COLUMNS: &[&str] = &["medicine_form", "form", "dosage_form"];
+const MANUFACTURER_COLUMNS: &[&str] = &["manufacturer", "company"];
+const BARCODE_COLUMNS: &[&str] = &["barcodes", "barcode"];
+const PRESCRIPTION_COLUMNS: &[&str] = &["requires_prescription", "prescription", "rx"];
+const CONTROLLED_COLUMNS: &[&str] = &["is_controlled", "controlled"];
+const STORAGE_COLUMNS: &[&str] = &["storage_instructions", "storage"];
+const NOTES_COLUMNS: &[&str] = &["notes", "note"];
+const QUANTITY_COLUMNS: &[&str] = &["stock_quantity", "quantity", "qty"];
+const MIN_STOCK_COLUMNS: &[&str] = &["min_stock_level", "min_stock", "reorder_level"];
+const UNIT_PRICE_COLUMNS: &[&str] = &["unit_price", "price"];
+
+/// Column positions in the import sheet
+struct Columns {
+    name: Option<usize>,
+    generic_name: Option<usize>,
+    concentration: Option<usize>,
+    form: Option<usize>,
+    manufacturer: Option<usize>,
+    barcodes: Option<usize>,
+    requires_prescription: Option<usize>,
+    is_controlled: Option<usize>,
+    storage_instructions: Option<usize>,
+    notes: Option<usize>,
+    stock_quantity: Option<usize>,
+    min_stock_level: Option<usize>,
+    unit_price: Option<usize>,
+}
+
+impl Columns {
+    fn resolve(sheet: &ImportSheet) -> ServiceResult<Self> {
+        let columns = Self {
+            name: sheet.column(NAME_COLUMNS),
+            generic_name: sheet.column(GENERIC_NAME_COLUMNS),
+            concentration: sheet.column(CONCENTRATION_COLUMNS),
+            form: sheet.column(FORM_COLUMNS),
+            manufacturer: sheet.column(MANUFACTURER_COLUMNS),
+            barcodes: sheet.column(BARCODE_COLUMNS),
+            requires_prescription: sheet.column(PRESCRIPTION_COLUMNS),
+            is_controlled: sheet.column(CONTROLLED_COLUMNS),
+            storage_instructions: sheet.column(STORAGE_COLUMNS),
+            notes: sheet.column(NOTES_COLUMNS),
+            stock_quantity: sheet.column(QUANTITY_COLUMNS),
+            min_stock_level: sheet.column(MIN_STOCK_COLUMNS),
+            unit_price: sheet.column(UNIT_PRICE_COLUMNS),
+        };
+
+        let missing: Vec<&str> = [
+            ("name", columns.name),
+            ("concentration", columns.concentration),
+            ("medicine_form", columns.form),
+            ("unit_price", columns.unit_price),
+        ]
+        .into_iter()
+        .filter(|(_, column)| column.is_none())
+        .map(|(name, _)| name)
+        .collect();
+
+        if !missing.is_empty() {
+            return Err(ServiceError::BadRequest(format!(
+                "Import file is missing required column(s): {}",
+                missing.join(", ")
+            )));
+        }
+
+        Ok(columns)
+    }
+}
+
+/// Existing catalogue data that rows are resolved and checked against
+///
+/// Forms and manufacturers that don't exist yet are given an id when first
+/// referenced, so later rows resolve to the same new record.
+struct Catalogue {
+    /// Lowercase code, English and Arabic name to form id and code
+    forms: HashMap<String, (Id, String)>,
+    /// Lowercase name and short name to manufacturer id
+    manufacturers: HashMap<String, Id>,
+    /// (lowercase name, normalized concentration, form code) of known items
+    items: HashSet<(String, String, String)>,
+    barcodes: HashSet<String>,
+    /// Barcodes of the valid rows so far, with the row they are on
+    sheet_barcodes: HashMap<String, usize>,
+    /// Forms to create, by id and code
+    new_forms: Vec<(Id, String)>,
+    /// Manufacturers to create, by id and name
+    new_manufacturers: Vec<(Id, String)>,
+}
+
+impl Catalogue {
+    async fn load<C: ConnectionTrait>(conn: &C) -> ServiceResult<Self> {
+        let mut forms = HashMap::new();
+        let mut codes = HashMap::new();
+        for form in MedicineForm::find().all(conn).await? {
+            let code = form.code.to_uppercase();
+            for key in [&form.code, &form.name_en, &form.name_ar] {
+                forms.insert(key.trim().to_lowercase(), (form.id, code.clone()));
+            }
+            codes.insert(form.id, code);
+        }
+
+        let mut manufacturers = HashMap::new();
+        for manufacturer in Manufacturer::find().all(conn).await? {
+            if let Some(short_name) = &manufacturer.short_name {
+                manufacturers.insert(short_name.trim().to_lowercase(), manufacturer.id);
+            }
+            manufacturers.insert(manufacturer.name.trim().to_lowercase(), manufacturer.id);
+        }
+
+        let items = InventoryItem::find()
+            .filter(inventory_item::Column::DeletedAt.is_null())
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|item| {
+                let code = codes
+                    .get(&item.medicine_form_id)
+                    .cloned()
+                    .unwrap_or_default();
+                duplicate_key(&item.name, &item.concentration, &code)
+            })
+            .collect();
+
+        let barcodes = InventoryItemBarcode::find()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|barcode| barcode.barcode)
+            .collect();
+
+        Ok(Self {
+            forms,
+            manufacturers,
+            items,
+            barcodes,
+            sheet_barcodes: HashMap::new(),
+            new_forms: Vec::new(),
+            new_manufacturers: Vec::new(),
+        })
+    }
+
+    /// Resolve a form by code or name, returning its id and code
+    fn form(&mut self, value: &str, create_missing: bool) -> Result<(Id, String), String> {
+        if let Some(form) = self.forms.get(&value.to_lowercase()) {
+            return Ok(form.clone());
+        }
+        if !create_missing {
+            return Err(format!("Unknown medicine form '{}'", value));
+        }
+
+        let code = form_code(value);
+        if code.is_empty() {
+            return Err(format!("Invalid medicine form '{}'", value));
+        }
+        let form = match self.forms.get(&code.to_lowercase()) {
+            Some(form) => form.clone(),
+            None => {
+                let id = Id::new();
+                self.new_forms.push((id, code.clone()));
+                (id, code.clone())
+            }
+        };
+        self.forms.insert(value.to_lowercase(), form.clone());
+        self.forms.insert(code.to_lowercase(), form.clone());
+
+        Ok(form)
+    }
+
+    /// Resolve a manufacturer by name or short name
+    fn manufacturer(&mut self, value: &str, create_missing: bool) -> Result<Id, String> {
+        if let Some(id) = self.manufacturers.get(&value.to_lowercase()) {
+            return Ok(*id);
+        }
+        if !create_missing {
+            return Err(format!("Unknown manufacturer '{}'", value));
+        }
+
+        let id = Id::new();
+        self.new_manufacturers.push((id, value.to_string()));
+        self.manufacturers.insert(value.to_lowercase(), id);
+
+        Ok(id)
+    }
+}
+
+impl InventoryService {
+    /// Import catalogue items with their stock from a sheet
+    ///
+    /// Every row is validated and reported. Rows that duplicate an existing
+    /// item (same name, concentration and form) or reuse a barcode are
+    /// rejected. With `dry_run` nothing is written; otherwise all valid rows,
+    /// and any manufacturers and forms they need, are created in one
+    /// transaction.
+    pub async fn import_catalogue(
+        &self,
+        sheet: &ImportSheet,
+        options: CatalogueImportOptions,
+    ) -> ServiceResult<CatalogueImportResult> {
+        let columns = Columns::resolve(sheet)?;
+        let mut catalogue = Catalogue::load(self.db.as_ref()).await?;
+
+        let mut valid = Vec::new();
+        let mut errors = Vec::new();
+
+        for record in sheet.records() {
+            match Self::validate_catalogue_row(&record, &columns, &mut catalogue, &options) {
+                Ok(item) => valid.push(item),
+                Err(error_message) => errors.push(ImportRowError {
+                    row_number: record.row_number(),
+                    item_code: record.get(columns.name).unwrap_or_default().to_string(),
+                    error_message,
+                }),
+            }
+        }
+
+        // Only create what a valid row actually uses
+        let new_forms: Vec<(Id, String)> = catalogue
+            .new_forms
+            .into_iter()
+            .filter(|(id, _)| valid.iter().any(|item| item.medicine_form_id == *id))
+            .collect();
+        let new_manufacturers: Vec<(Id, String)> = catalogue
+            .new_manufacturers
+            .into_iter()
+            .filter(|(id, _)| valid.iter().any(|item| item.manufacturer_id == Some(*id)))
+            .collect();
+
+        let result = CatalogueImportResult {
+            success: errors.is_empty() && !valid.is_empty(),
+            dry_run: options.dry_run,
+            total_rows: sheet.len(),
+            created_count: valid.len(),
+            error_count: errors.len(),
+            created_manufacturers: new_manufacturers.iter().map(|(_, n)| n.clone()).collect(),
+            created_medicine_forms: new_forms.iter().map(|(_, code)| code.clone()).collect(),
+            error_report: (!errors.is_empty()).then(|| sheet.error_report(&errors)),
+            errors,
+        };
+
+        if options.dry_run || valid.is_empty() {
+            tracing::info!(
+                "Validated catalogue import '{}': {} valid, {} invalid",
+                options.file_name,
+                result.created_count,
+                result.error_count
+            );
+            return Ok(result);
+        }
+
+        self.commit_catalogue(valid, new_forms, new_manufacturers, options.created_by)
+            .await
+            .tap_ok(|_| {
+                tracing::info!(
+                    "Imported {} catalogue items from '{}'",
+                    result.created_count,
+                    options.file_name
+                )
+            })
+            .tap_err(|e| {
+                tracing::error!(
+                    "Failed to import catalogue from '{}': {}",
+                    options.file_name,
+                    e
+                )
+            })?;
+
+        Ok(result)
+    }
+
+    // ========================================================================
+    // Catalogue Import Helpers
+    // ========================================================================
+
+    /// Parse and validate one row, collecting every problem in it
+    fn validate_catalogue_row(
+        record: &ImportRecord<'_>,
+        columns: &Columns,
+        catalogue: &mut Catalogue,
+        options: &CatalogueImportOptions,
+    ) -> Result<CreateInventoryItemWithStock, String> {
+        let mut problems = Vec::new();
+
+        let name = record.required(columns.name, "Name");
+        let concentration = record.required(columns.concentration, "Concentration");
+        let form = record
+            .required(columns.form, "Medicine form")
+            .and_then(|value| catalogue.form(value, options.create_missing));
+        let manufacturer = record
+            .get(columns.manufacturer)
+            .map(|value| catalogue.manufacturer(value, options.create_missing))
+            .transpose();
+
+        let barcodes = record
+            .get(columns.barcodes)
+            .map(split_barcodes)
+            .unwrap_or_default();
+        for barcode in &barcodes {
+            if catalogue.barcodes.contains(*barcode) {
+                problems.push(format!("Barcode '{}' is already in use", barcode));
+            } else if let Some(row) = catalogue.sheet_barcodes.get(*barcode) {
+                problems.push(format!("Barcode '{}' is also on row {}", barcode, row));
+            }
+        }
+
+        let flag = |column, name| {
+            record
+                .get(column)
+                .map(|value| parse_bool(value, name))
+                .transpose()
+                .map(Option::unwrap_or_default)
+        };
+        let requires_prescription = flag(columns.requires_prescription, "Requires prescription");
+        let is_controlled = flag(columns.is_controlled, "Controlled");
+
+        let count = |column, name| {
+            record
+                .get(column)
+                .map(|value| parse_i32(value, name))
+                .transpose()
+                .map(Option::unwrap_or_default)
+                .and_then(|count| match count {
+                    c if c < 0 => Err(format!("{} cannot be negative", name)),
+                    c => Ok(c),
+                })
+        };
+        let stock_quantity = count(columns.stock_quantity, "Stock quantity");
+        let min_stock_level = count(columns.min_stock_level, "Min stock level");
+
+        let unit_price = record
+            .required(columns.unit_price, "Unit price")
+            .and_then(|value| parse_decimal(value, "Unit price"))
+            .and_then(|price| match price {
+                p if p.is_sign_negative() => Err("Unit price cannot be negative".to_string()),
+                p => f64::try_from(p).map_err(|_| "Unit price is out of range".to_string()),
+            });
+
+        let (
+            Ok(name),
+            Ok(concentration),
+            Ok((medicine_form_id, form_code)),
+            Ok(manufacturer_id),
+            Ok(requires_prescription),
+            Ok(is_controlled),
+            Ok(stock_quantity),
+            Ok(min_stock_level),
+            Ok(unit_price),
+        ) = (
+            name.map_err(|e| problems.push(e)),
+            concentration.map_err(|e| problems.push(e)),
+            form.map_err(|e| problems.push(e)),
+            manufacturer.map_err(|e| problems.push(e)),
+            requires_prescription.map_err(|e| problems.push(e)),
+            is_controlled.map_err(|e| problems.push(e)),
+            stock_quantity.map_err(|e| problems.push(e)),
+            min_stock_level.map_err(|e| problems.push(e)),
+            unit_price.map_err(|e| problems.push(e)),
+        )
+        else {
+            return Err(problems.join("; "));
+        };
+        if !problems.is_empty() {
+            return Err(problems.join("; "));
+        }
+
+        let key = duplicate_key(name, concentration, &form_code);
+        if !catalogue.items.insert(key) {
+            return Err(format!(
+                "Duplicate item: '{} {}' ({}) already exists",
+                name, concentration, form_code
+            ));
+        }
+        // Later rows are checked against this one too
+        catalogue.sheet_barcodes.extend(
+            barcodes
+                .iter()
+                .map(|barcode| ((*barcode).to_owned(), record.row_number())),
+        );
+
+        Ok(CreateInventoryItemWithStock {
+            name: name.to_string(),
+            generic_name: record.get(columns.generic_name).map(str::to_string),
+            concentration: concentration.to_string(),
+            medicine_form_id,
+            manufacturer_id,
+            requires_prescription,
+            is_controlled,
+            storage_instructions: record.get(columns.storage_instructions).map(str::to_string),
+            notes: record.get(columns.notes).map(str::to_string),
+            barcodes: barcodes
+                .iter()
+                .enumerate()
+                .map(|(index, barcode)| CreateBarcodeInput {
+                    barcode: (*barcode).to_owned(),
+                    barcode_type: None,
+                    is_primary: index == 0,
+                    description: None,
+                })
+                .collect(),
+            stock_quantity,
+            min_stock_level,
+            unit_price,
+        })
+    }
+
+    /// Create new forms and manufacturers, then the items, in one transaction
+    async fn commit_catalogue(
+        &self,
+        items: Vec<CreateInventoryItemWithStock>,
+        new_forms: Vec<(Id, String)>,
+        new_manufacturers: Vec<(Id, String)>,
+        created_by: Option<Id>,
+    ) -> ServiceResult<()> {
+        let now = chrono::Utc::now();
+        let txn = self.db.begin().await?;
+
+        let mut display_order = MedicineForm::find()
+            .select_only()
+            .column_as(medicine_form::Column::DisplayOrder.max(), "max")
+            .into_tuple::<Option<i32>>()
+            .one(&txn)
+            .await?
+            .flatten()
+            .unwrap_or_default();
+
+        for (id, code) in new_forms {
+            display_order += 1;
+            let name = code_to_name(&code);
+
+            medicine_form::ActiveModel {
+                id: Set(id),
+                code: Set(code),
+                name_en: Set(name.clone()),
+                // Translated later from the medicine forms screen
+                name_ar: Set(name),
+                display_order: Set(display_order),
+                is_active: Set(true),
+                created_at: Set(now.into()),
+                updated_at: Set(now.into()),
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        for (id, name) in new_manufacturers {
+            manufacturer::ActiveModel {
+                id: Set(id),
+                name: Set(name),
+                short_name: Set(None),
+                country: Set(None),
+                phone: Set(None),
+                email: Set(None),
+                website: Set(None),
+                notes: Set(Some("Created by catalogue import".to_string())),
+                is_active: Set(true),
+                created_at: Set(now.into()),
+                updated_at: Set(now.into()),
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        for item in items {
+            Self::insert_item_with_stock(&txn, item, created_by).await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+}
+
+/// Key used to detect duplicate items
+fn duplicate_key(name: &str, concentration: &str, form_code: &str) -> (String, String, String) {
+    (
+        name.trim().to_lowercase(),
+        concentration.trim().to_lowercase().replace(' ', ""),
+        form_code.to_uppercase(),
+    )
+}
+
+/// Barcodes are separated by `|` or `/` within one cell; a barcode repeated
+/// in the cell is kept once, where it first appears
+fn split_barcodes(value: &str) -> Vec<&str> {
+    let mut seen = HashSet::new();
+    value
+        .split(['|', '/'])
+        .map(str::trim)
+        .filter(|barcode| !barcode.is_empty() && seen.insert(*barcode))
+        .collect()
+}
+
+/// Code for a new medicine form, e.g. "Oral drops" -> "ORAL_DROPS"
+fn form_code(value: &str) -> String {
+    value
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+        .to_uppercase()
+}
+
+/// Display name for a new medicine form, e.g. "ORAL_DROPS" -> "Oral drops"
+fn code_to_name(code: &str) -> String {
+    let name = code.replace('_', " ").to_lowercase();
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
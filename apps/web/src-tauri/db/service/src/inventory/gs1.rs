@@ -0,0 +1,436 @@
+//! GS1 element strings from scanned DataMatrix and GS1-128 codes
+//!
+//! The data of a GS1 code is a run of application identifiers (AIs), each
+//! followed by its value: `01` GTIN, `10` batch, `17` expiry, `21` serial and
+//! so on. Fixed-length values run straight into the next AI; variable-length
+//! ones end at FNC1, which scanners send as the ASCII group separator (GS).
+//! Codes typed from the printed text, with the AIs in parentheses, are
+//! accepted too.
+
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use db_entity::inventory_item::dto::ScannedBarcode;
+use db_entity::inventory_item_barcode::{self, Entity as InventoryItemBarcode};
+use sea_orm::*;
+
+use super::InventoryService;
+use crate::error::{ServiceError, ServiceResult};
+
+/// FNC1 as sent by scanners
+const GS: char = '\u{1D}';
+
+/// Symbology identifiers of GS1 DataMatrix, GS1-128, GS1 QR Code and GS1
+/// DataBar; scanners can be set to send one before the data
+const GS1_SYMBOLOGIES: [&str; 4] = ["]d2", "]C1", "]Q3", "]e0"];
+
+/// Length of an AI's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Length {
+    Fixed(usize),
+    /// Up to this many characters, ended by FNC1 unless last
+    Variable(usize),
+}
+
+/// Scanner input, told apart by its content
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Scan<'a> {
+    /// An ordinary barcode, such as an EAN-13, to match as is
+    Plain(&'a str),
+    Gs1(Gs1Data),
+}
+
+/// The elements of a GS1 code used for stock; others are skipped
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(super) struct Gs1Data {
+    pub(super) gtin: Option<String>,
+    pub(super) batch_number: Option<String>,
+    pub(super) expiry_date: Option<NaiveDate>,
+    pub(super) serial_number: Option<String>,
+}
+
+/// Read scanner input as a GS1 element string, or as a plain barcode when it
+/// is not one
+///
+/// Input is GS1 when it starts with a GS1 symbology identifier or a
+/// parenthesised AI, contains FNC1, or begins with a GTIN (01).
+pub(super) fn parse(input: &str) -> ServiceResult<Scan<'_>> {
+    // A leading FNC1 only marks the code as GS1; a trailing one ends nothing
+    let input = input.trim_matches(is_padding);
+    let (data, gs1_symbology) = match input.get(..3) {
+        Some(identifier) if identifier.starts_with(']') => (
+            input[3..].trim_matches(is_padding),
+            GS1_SYMBOLOGIES.contains(&identifier),
+        ),
+        _ => (input, false),
+    };
+
+    let elements = if data.starts_with('(') {
+        parse_bracketed(data)?
+    } else if gs1_symbology
+        || data.contains(GS)
+        || (data.starts_with("01") && data.len() >= 16 && data.get(2..16).is_some_and(valid_gtin))
+    {
+        parse_element_string(data)?
+    } else {
+        return Ok(Scan::Plain(data));
+    };
+
+    let mut gs1 = Gs1Data::default();
+    for (ai, value) in elements {
+        match ai {
+            "01" => {
+                if !valid_gtin(value) {
+                    return Err(ServiceError::BadRequest(format!("Invalid GTIN: {}", value)));
+                }
+                gs1.gtin = Some(value.to_string());
+            }
+            "10" => gs1.batch_number = Some(value.to_string()),
+            "17" => {
+                let expiry = parse_date(value).ok_or_else(|| {
+                    ServiceError::BadRequest(format!("Invalid GS1 expiry date: {}", value))
+                })?;
+                gs1.expiry_date = Some(expiry);
+            }
+            "21" => gs1.serial_number = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    Ok(Scan::Gs1(gs1))
+}
+
+fn is_padding(c: char) -> bool {
+    c == GS || c.is_whitespace()
+}
+
+/// Split AIs and values from the data of a GS1 code
+fn parse_element_string(data: &str) -> ServiceResult<Vec<(&str, &str)>> {
+    if !data.is_ascii() {
+        return Err(ServiceError::BadRequest(
+            "GS1 data can only contain ASCII characters".to_string(),
+        ));
+    }
+
+    let mut elements = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let (ai_len, length) = application_identifier(rest).ok_or_else(|| {
+            ServiceError::BadRequest(format!("Unknown GS1 application identifier in: {}", rest))
+        })?;
+        let (ai, data) = rest.split_at(ai_len);
+
+        let end = match length {
+            Length::Fixed(len) if data.len() < len => {
+                return Err(ServiceError::BadRequest(format!(
+                    "GS1 element ({}) needs {} characters",
+                    ai, len
+                )));
+            }
+            Length::Fixed(len) => len,
+            Length::Variable(max) => {
+                let end = data.find(GS).unwrap_or(data.len());
+                if end > max {
+                    return Err(ServiceError::BadRequest(format!(
+                        "GS1 element ({}) is longer than {} characters; is a group separator missing?",
+                        ai, max
+                    )));
+                }
+                end
+            }
+        };
+        let (value, next) = data.split_at(end);
+        elements.push((ai, value));
+        // Some encoders put FNC1 after fixed-length values as well
+        rest = next.strip_prefix(GS).unwrap_or(next);
+    }
+    Ok(elements)
+}
+
+/// Split AIs and values from the human readable text, e.g.
+/// `(01)09506000134352(17)261231(10)AB12`
+fn parse_bracketed(data: &str) -> ServiceResult<Vec<(&str, &str)>> {
+    let invalid = || ServiceError::BadRequest(format!("Invalid GS1 text: {}", data));
+
+    data.strip_prefix('(')
+        .ok_or_else(invalid)?
+        .split('(')
+        .map(|element| {
+            let (ai, value) = element.split_once(')').ok_or_else(invalid)?;
+            let valid = match application_identifier(ai) {
+                Some((ai_len, Length::Fixed(len))) => ai_len == ai.len() && value.len() == len,
+                Some((ai_len, Length::Variable(max))) => {
+                    ai_len == ai.len() && !value.is_empty() && value.len() <= max
+                }
+                None => false,
+            };
+            if valid && value.is_ascii() {
+                Ok((ai, value))
+            } else {
+                Err(ServiceError::BadRequest(format!(
+                    "Invalid GS1 element ({}){}",
+                    ai, value
+                )))
+            }
+        })
+        .collect()
+}
+
+/// Length of the AI at the start of `data` and of its value
+///
+/// Covers the AIs found on medicine packs and their shipping cases, as given
+/// in the GS1 General Specifications.
+fn application_identifier(data: &str) -> Option<(usize, Length)> {
+    let prefix = |len: usize| -> Option<u32> {
+        data.get(..len)
+            .filter(|ai| ai.bytes().all(|b| b.is_ascii_digit()))?
+            .parse()
+            .ok()
+    };
+
+    let spec = match prefix(2)? {
+        0 => (2, Length::Fixed(18)),
+        1..=3 => (2, Length::Fixed(14)),
+        10 | 21 | 22 => (2, Length::Variable(20)),
+        11..=13 | 15..=17 => (2, Length::Fixed(6)),
+        20 => (2, Length::Fixed(2)),
+        30 | 37 => (2, Length::Variable(8)),
+        31..=36 => prefix(4).map(|_| (4, Length::Fixed(6)))?,
+        90 => (2, Length::Variable(30)),
+        91..=99 => (2, Length::Variable(90)),
+        _ => match prefix(3)? {
+            240 | 241 | 250 | 251 | 253 | 400 | 401 | 403 => (3, Length::Variable(30)),
+            242 => (3, Length::Variable(6)),
+            243 | 254 | 420 | 710..=716 => (3, Length::Variable(20)),
+            402 => (3, Length::Fixed(17)),
+            410..=417 => (3, Length::Fixed(13)),
+            421 => (3, Length::Variable(12)),
+            422 | 424 | 426 => (3, Length::Fixed(3)),
+            423 | 425 => (3, Length::Variable(15)),
+            _ => match prefix(4)? {
+                7003 => (4, Length::Fixed(10)),
+                7004 => (4, Length::Variable(4)),
+                7006 => (4, Length::Fixed(6)),
+                _ => return None,
+            },
+        },
+    };
+    Some(spec)
+}
+
+/// Whether `gtin` is 14 digits with a correct check digit
+fn valid_gtin(gtin: &str) -> bool {
+    let digits: Vec<u32> = gtin.chars().filter_map(|c| c.to_digit(10)).collect();
+    if gtin.len() != 14 || digits.len() != 14 {
+        return false;
+    }
+
+    // Weights alternate 3, 1 from the digit next to the check digit
+    let sum: u32 = digits[..13]
+        .iter()
+        .enumerate()
+        .map(|(index, digit)| if index % 2 == 0 { digit * 3 } else { *digit })
+        .sum();
+    (10 - sum % 10) % 10 == digits[13]
+}
+
+/// Parse a GS1 `YYMMDD` date
+///
+/// The century puts the year within 49 years before and 50 years after the
+/// current one. Day `00` means the last day of the month.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    if value.len() != 6 || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let year: i32 = value[..2].parse().ok()?;
+    let month: u32 = value[2..4].parse().ok()?;
+    let day: u32 = value[4..].parse().ok()?;
+
+    let current = Utc::now().year();
+    let mut year = current - current % 100 + year;
+    if year - current > 50 {
+        year -= 100;
+    } else if current - year >= 50 {
+        year += 100;
+    }
+
+    if day == 0 {
+        NaiveDate::from_ymd_opt(year, month, 1)?
+            .checked_add_months(Months::new(1))?
+            .pred_opt()
+    } else {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+}
+
+/// Forms a GTIN-14 can be stored in: as is, and without its leading zeros as
+/// an EAN-13, UPC-A or EAN-8
+fn gtin_forms(gtin: &str) -> Vec<&str> {
+    [14, 13, 12, 8]
+        .into_iter()
+        .filter_map(|len| {
+            let (zeros, barcode) = gtin.split_at(gtin.len().checked_sub(len)?);
+            zeros.bytes().all(|b| b == b'0').then_some(barcode)
+        })
+        .collect()
+}
+
+impl InventoryService {
+    // ========================================================================
+    // Scanning
+    // ========================================================================
+
+    /// Identify the item behind scanner input
+    ///
+    /// A GS1 code is resolved by its GTIN, matching barcodes stored as
+    /// GTIN-14, EAN-13, UPC-A or EAN-8, and returns the batch, expiry and
+    /// serial it carries. Anything else is looked up as a plain barcode.
+    pub async fn scan_barcode(&self, input: &str) -> ServiceResult<ScannedBarcode> {
+        let gs1 = match parse(input)? {
+            Scan::Plain(barcode) => {
+                return Ok(ScannedBarcode {
+                    item: self.get_by_barcode(barcode).await?,
+                    gtin: None,
+                    batch_number: None,
+                    expiry_date: None,
+                    serial_number: None,
+                });
+            }
+            Scan::Gs1(gs1) => gs1,
+        };
+        let gtin = gs1
+            .gtin
+            .ok_or_else(|| ServiceError::BadRequest("GS1 code has no GTIN (01)".to_string()))?;
+
+        let barcode = InventoryItemBarcode::find()
+            .filter(inventory_item_barcode::Column::Barcode.is_in(gtin_forms(&gtin)))
+            .one(&*self.db)
+            .await?
+            .ok_or_else(|| {
+                ServiceError::NotFound(format!("Inventory item not found with GTIN: {}", gtin))
+            })?;
+        let item = self.get_by_barcode(&barcode.barcode).await?;
+
+        tracing::debug!(
+            "Scanned GTIN {} (batch {:?}, expiry {:?})",
+            gtin,
+            gs1.batch_number,
+            gs1.expiry_date
+        );
+
+        Ok(ScannedBarcode {
+            item,
+            gtin: Some(gtin),
+            batch_number: gs1.batch_number,
+            expiry_date: gs1.expiry_date,
+            serial_number: gs1.serial_number,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gs1(input: &str) -> Gs1Data {
+        match parse(input).unwrap() {
+            Scan::Gs1(gs1) => gs1,
+            Scan::Plain(barcode) => panic!("read as plain barcode: {}", barcode),
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    #[test]
+    fn test_parse_element_string_with_separators() {
+        let expected = Gs1Data {
+            gtin: Some("09506000134352".to_string()),
+            batch_number: Some("AB12".to_string()),
+            expiry_date: date(2027, 12, 31),
+            serial_number: Some("SN9876".to_string()),
+        };
+
+        // DataMatrix with symbology identifier; the batch ends with FNC1
+        assert_eq!(
+            gs1("]d201095060001343521727123110AB12\u{1D}21SN9876"),
+            expected
+        );
+        // FNC1 after fixed-length values too
+        assert_eq!(
+            gs1("]d20109506000134352\u{1D}17271231\u{1D}10AB12\u{1D}21SN9876"),
+            expected
+        );
+        // Leading FNC1, no identifier, fields in another order
+        assert_eq!(
+            gs1("\u{1D}01095060001343521727123121SN9876\u{1D}10AB12\r\n"),
+            expected
+        );
+        // GTIN and more data, without any separator or identifier
+        assert_eq!(
+            gs1("010950600013435210AB12").batch_number.as_deref(),
+            Some("AB12")
+        );
+        // Text from the printed label
+        assert_eq!(
+            gs1("(01)09506000134352(17)271231(10)AB12(21)SN9876"),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_plain_and_invalid_input() {
+        assert_eq!(
+            parse("6221234567890").unwrap(),
+            Scan::Plain("6221234567890")
+        );
+        assert_eq!(parse(" INT-001 ").unwrap(), Scan::Plain("INT-001"));
+        // Another symbology's identifier is dropped
+        assert_eq!(
+            parse("]E06221234567890").unwrap(),
+            Scan::Plain("6221234567890")
+        );
+
+        for input in [
+            // Wrong check digit
+            "]d20109506000134353",
+            // Batch over 20 characters: the separator is missing
+            "]d2010950600013435210ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+            // Month 13
+            "]d2010950600013435217271301",
+            // Unknown AI
+            "]d20109506000134352\u{1D}89123",
+            "(01)0950600013435",
+        ] {
+            assert!(
+                matches!(parse(input), Err(ServiceError::BadRequest(_))),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_date_century_and_month_end() {
+        assert_eq!(parse_date("270315"), date(2027, 3, 15));
+        assert_eq!(parse_date("280200"), date(2028, 2, 29));
+        assert_eq!(parse_date("271200"), date(2027, 12, 31));
+        assert_eq!(parse_date("991231"), date(1999, 12, 31));
+        assert_eq!(parse_date("270230"), None);
+    }
+
+    #[test]
+    fn test_gtin_forms() {
+        assert_eq!(
+            gtin_forms("09506000134352"),
+            vec!["09506000134352", "9506000134352"]
+        );
+        assert_eq!(
+            gtin_forms("00000096385074"),
+            vec![
+                "00000096385074",
+                "0000096385074",
+                "000096385074",
+                "96385074"
+            ]
+        );
+    }
+}
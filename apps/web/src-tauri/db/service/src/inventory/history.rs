@@ -0,0 +1,76 @@
+//! Stock and price history recording for `inventory_stock` changes
+//!
+//! History is written here, inside the caller's transaction, rather than by
+//! database triggers, so every row carries the acting user and reason and the
+//! same code runs on PostgreSQL and SQLite.
+
+use db_entity::id::Id;
+use db_entity::inventory_price_history;
+use db_entity::inventory_stock_history::{self, StockAdjustmentType};
+use rust_decimal::Decimal;
+use sea_orm::*;
+use tap::TapFallible;
+
+use crate::error::ServiceResult;
+
+/// Record a manual stock quantity change made by `recorded_by`
+pub(crate) async fn record_stock_change<C: ConnectionTrait>(
+    conn: &C,
+    inventory_item_id: Id,
+    quantity_before: i32,
+    quantity_after: i32,
+    recorded_by: Option<Id>,
+    reason: Option<String>,
+) -> ServiceResult<()> {
+    if quantity_before == quantity_after {
+        return Ok(());
+    }
+
+    inventory_stock_history::ActiveModel {
+        id: Set(Id::new()),
+        inventory_item_id: Set(inventory_item_id),
+        adjustment_type: Set(StockAdjustmentType::ManualAdjustment),
+        quantity_before: Set(quantity_before),
+        quantity_after: Set(quantity_after),
+        adjustment_amount: Set(quantity_after - quantity_before),
+        reason: Set(reason),
+        reference_id: Set(None),
+        reference_type: Set(None),
+        recorded_at: Set(chrono::Utc::now().into()),
+        recorded_by: Set(recorded_by),
+        idempotency_key: Set(None),
+    }
+    .insert(conn)
+    .await
+    .tap_err(|e| tracing::error!("Failed to record stock history: {}", e))?;
+
+    Ok(())
+}
+
+/// Record a unit price change made by `changed_by` for `reason`
+pub(crate) async fn record_price_change<C: ConnectionTrait>(
+    conn: &C,
+    inventory_item_id: Id,
+    price_before: Decimal,
+    price_after: Decimal,
+    changed_by: Option<Id>,
+    reason: &str,
+) -> ServiceResult<()> {
+    if price_before == price_after {
+        return Ok(());
+    }
+
+    inventory_price_history::ActiveModel {
+        id: Set(Id::new()),
+        inventory_item_id: Set(inventory_item_id),
+        unit_price: Set(price_after),
+        recorded_at: Set(chrono::Utc::now().into()),
+        changed_by: Set(changed_by),
+        reason: Set(Some(reason.to_string())),
+    }
+    .insert(conn)
+    .await
+    .tap_err(|e| tracing::error!("Failed to record price history: {}", e))?;
+
+    Ok(())
+}
@@ -1,8 +1,13 @@
+pub(crate) mod history;
 pub mod medicine_forms;
 pub mod opening_balances;
 pub mod price_history;
 pub mod stock_history;
 
+mod catalogue_import;
+mod gs1;
+mod valuation;
+
 use std::sync::Arc;
 
 use db_entity::id::Id;
@@ -15,7 +20,7 @@ use db_entity::inventory_item_barcode::dto::InventoryItemBarcodeResponse;
 use db_entity::inventory_item_barcode::{self, Entity as InventoryItemBarcode};
 use db_entity::inventory_stock::dto::{AdjustStock, InventoryStockResponse, UpdateInventoryStock};
 use db_entity::inventory_stock::{self, Entity as InventoryStock};
-use db_entity::inventory_stock_history::{self};
+use db_entity::inventory_stock_history::{self, Entity as InventoryStockHistory};
 use rust_decimal::Decimal;
 use sea_orm::sea_query::Expr;
 use sea_orm::*;
@@ -23,16 +28,21 @@ use serde::{Deserialize, Serialize};
 use tap::{Pipe, Tap, TapFallible};
 
 use crate::error::{ServiceError, ServiceResult};
+use crate::events::EventBus;
 
 /// Inventory service for managing medicine catalog and stock
 pub struct InventoryService {
     db: Arc<DatabaseConnection>,
+    events: Arc<EventBus>,
 }
 
 impl InventoryService {
+    /// Reference type of stock movements that belong to a sale
+    pub const SALE_REFERENCE_TYPE: &'static str = "sale";
+
     /// Create a new inventory service
-    pub fn new(db: Arc<DatabaseConnection>) -> Self {
-        Self { db }
+    pub fn new(db: Arc<DatabaseConnection>, events: Arc<EventBus>) -> Self {
+        Self { db, events }
     }
 
     // ========================================================================
@@ -103,18 +113,12 @@ impl InventoryService {
         })
     }
 
-    // ========================================================================
-    // CRUD Operations (Catalog + Stock Combined)
-    // ========================================================================
-
-    /// Create a new inventory item with stock
-    pub async fn create(
-        &self,
+    /// Insert an item, its barcodes and its stock row on `conn`
+    pub(crate) async fn insert_item_with_stock<C: ConnectionTrait>(
+        conn: &C,
         dto: CreateInventoryItemWithStock,
         created_by: Option<Id>,
-    ) -> ServiceResult<InventoryItemWithStockResponse> {
-        let txn = self.db.begin().await?;
-
+    ) -> ServiceResult<(inventory_item::Model, inventory_stock::Model)> {
         let now = chrono::Utc::now();
         let item_id = Id::new();
 
@@ -139,7 +143,7 @@ impl InventoryService {
         };
 
         let item = item
-            .insert(&txn)
+            .insert(conn)
             .await
             .tap_ok(|_| tracing::info!("Created inventory item: {}", item_id))
             .tap_err(|e| tracing::error!("Failed to create inventory item: {}", e))?;
@@ -158,7 +162,7 @@ impl InventoryService {
             };
 
             barcode
-                .insert(&txn)
+                .insert(conn)
                 .await
                 .tap_ok(|_| tracing::info!("Created barcode for item: {}", item_id))
                 .tap_err(|e| tracing::error!("Failed to create barcode: {}", e))?;
@@ -185,11 +189,26 @@ impl InventoryService {
         };
 
         let stock = stock
-            .insert(&txn)
+            .insert(conn)
             .await
             .tap_ok(|_| tracing::info!("Created inventory stock: {}", stock_id))
             .tap_err(|e| tracing::error!("Failed to create inventory stock: {}", e))?;
 
+        Ok((item, stock))
+    }
+
+    // ========================================================================
+    // CRUD Operations (Catalog + Stock Combined)
+    // ========================================================================
+
+    /// Create a new inventory item with stock
+    pub async fn create(
+        &self,
+        dto: CreateInventoryItemWithStock,
+        created_by: Option<Id>,
+    ) -> ServiceResult<InventoryItemWithStockResponse> {
+        let txn = self.db.begin().await?;
+        let (item, stock) = Self::insert_item_with_stock(&txn, dto, created_by).await?;
         txn.commit().await?;
 
         // Build combined response
@@ -361,9 +380,33 @@ impl InventoryService {
         inventory_item_id: Id,
         dto: UpdateInventoryStock,
     ) -> ServiceResult<InventoryStockResponse> {
+        let txn = self.db.begin().await?;
+        let (old_quantity, stock) = Self::write_stock_update(&txn, inventory_item_id, dto).await?;
+        txn.commit().await?;
+
+        self.events.publish_stock_change(
+            inventory_item_id,
+            old_quantity,
+            stock.stock_quantity,
+            stock.min_stock_level,
+        );
+
+        Ok(InventoryStockResponse::from(stock))
+    }
+
+    /// Apply `dto` to the stock of an item on `conn`, recording the quantity
+    /// and price changes
+    ///
+    /// Returns the quantity before the update with the updated stock; the
+    /// caller publishes the stock change once `conn` commits.
+    pub(crate) async fn write_stock_update<C: ConnectionTrait>(
+        conn: &C,
+        inventory_item_id: Id,
+        dto: UpdateInventoryStock,
+    ) -> ServiceResult<(i32, inventory_stock::Model)> {
         let stock = InventoryStock::find()
             .filter(inventory_stock::Column::InventoryItemId.eq(inventory_item_id))
-            .one(&*self.db)
+            .one(conn)
             .await?
             .ok_or_else(|| {
                 ServiceError::NotFound(format!(
@@ -372,6 +415,8 @@ impl InventoryService {
                 ))
             })?;
 
+        let old_quantity = stock.stock_quantity;
+        let old_price = stock.unit_price;
         let mut stock: inventory_stock::ActiveModel = stock.into();
 
         if let Some(stock_quantity) = dto.stock_quantity {
@@ -383,16 +428,28 @@ impl InventoryService {
         if let Some(min_stock_level) = dto.min_stock_level {
             stock.min_stock_level = Set(min_stock_level);
         }
-        if let Some(unit_price) = dto.unit_price {
-            let price = Decimal::try_from(unit_price)
-                .map_err(|e| ServiceError::BadRequest(format!("Invalid unit price: {}", e)))?;
+        let new_price = dto
+            .unit_price
+            .map(|unit_price| {
+                Decimal::try_from(unit_price)
+                    .map_err(|e| ServiceError::BadRequest(format!("Invalid unit price: {}", e)))
+            })
+            .transpose()?
+            .filter(|price| *price != old_price);
+        let reason = dto.reason.as_deref().map(str::trim).unwrap_or_default();
+        if new_price.is_some() && reason.is_empty() {
+            return Err(ServiceError::BadRequest(
+                "A reason is required when changing the unit price".to_string(),
+            ));
+        }
+        if let Some(price) = new_price {
             stock.unit_price = Set(price);
         }
 
         stock.updated_at = Set(chrono::Utc::now().into());
 
         let stock = stock
-            .update(&*self.db)
+            .update(conn)
             .await
             .tap_ok(|_| tracing::info!("Updated stock for item: {}", inventory_item_id))
             .tap_err(|e| {
@@ -403,18 +460,53 @@ impl InventoryService {
                 )
             })?;
 
-        Ok(InventoryStockResponse::from(stock))
+        history::record_stock_change(
+            conn,
+            inventory_item_id,
+            old_quantity,
+            stock.stock_quantity,
+            dto.updated_by,
+            dto.reason.clone(),
+        )
+        .await?;
+        history::record_price_change(
+            conn,
+            inventory_item_id,
+            old_price,
+            stock.unit_price,
+            dto.updated_by,
+            reason,
+        )
+        .await?;
+
+        Ok((old_quantity, stock))
     }
 
     /// Adjust stock (add or subtract)
+    ///
+    /// The stock row is locked (`SELECT ... FOR UPDATE` on PostgreSQL) and the
+    /// update is guarded by the quantity that was read, so concurrent
+    /// adjustments never lose an update. Stock and history are written in one
+    /// transaction. A repeated `idempotency_key` returns the current stock
+    /// without applying the adjustment again. A sale adjustment with a
+    /// `reference_id` is recorded as part of that sale.
     pub async fn adjust_stock(
         &self,
         inventory_item_id: Id,
         dto: AdjustStock,
     ) -> ServiceResult<InventoryStockResponse> {
+        if dto.reference_type.is_some() && dto.reference_id.is_none() {
+            return Err(ServiceError::BadRequest(
+                "A reference type needs a reference id".to_string(),
+            ));
+        }
+
+        let txn = self.db.begin().await?;
+
         let stock = InventoryStock::find()
             .filter(inventory_stock::Column::InventoryItemId.eq(inventory_item_id))
-            .one(&*self.db)
+            .lock_exclusive()
+            .one(&txn)
             .await?
             .ok_or_else(|| {
                 ServiceError::NotFound(format!(
@@ -423,47 +515,77 @@ impl InventoryService {
                 ))
             })?;
 
-        let old_quantity = stock.stock_quantity;
-        let new_quantity = stock.stock_quantity + dto.adjustment;
+        if let Some(key) = &dto.idempotency_key {
+            let previous = InventoryStockHistory::find()
+                .filter(inventory_stock_history::Column::IdempotencyKey.eq(key.as_str()))
+                .one(&txn)
+                .await?;
 
-        if new_quantity < 0 {
-            return Err(ServiceError::BadRequest(
-                "Stock quantity cannot be negative".to_string(),
-            ));
+            if let Some(previous) = previous {
+                if previous.inventory_item_id != inventory_item_id {
+                    return Err(ServiceError::Conflict(format!(
+                        "Idempotency key '{}' was already used for another item",
+                        key
+                    )));
+                }
+
+                tracing::info!(
+                    "Skipped duplicate stock adjustment for item {} (key: {})",
+                    inventory_item_id,
+                    key
+                );
+                return Ok(InventoryStockResponse::from(stock));
+            }
         }
 
-        let mut stock: inventory_stock::ActiveModel = stock.into();
-        stock.stock_quantity = Set(new_quantity);
+        let old_quantity = stock.stock_quantity;
+        let new_quantity = stock
+            .stock_quantity
+            .checked_add(dto.adjustment)
+            .filter(|quantity| *quantity >= 0)
+            .ok_or_else(|| {
+                ServiceError::BadRequest("Stock quantity cannot be negative".to_string())
+            })?;
+
+        let now: sea_orm::prelude::DateTimeWithTimeZone = chrono::Utc::now().into();
+        let mut update = InventoryStock::update_many()
+            .col_expr(
+                inventory_stock::Column::StockQuantity,
+                Expr::value(new_quantity),
+            )
+            .col_expr(inventory_stock::Column::UpdatedAt, Expr::value(now))
+            .filter(inventory_stock::Column::Id.eq(stock.id))
+            .filter(inventory_stock::Column::StockQuantity.eq(old_quantity));
 
         if dto.adjustment > 0 {
-            stock.last_restocked_at = Set(Some(chrono::Utc::now().into()));
+            update = update.col_expr(inventory_stock::Column::LastRestockedAt, Expr::value(now));
         }
 
-        stock.updated_at = Set(chrono::Utc::now().into());
+        let result = update.exec(&txn).await.tap_err(|e| {
+            tracing::error!(
+                "Failed to adjust stock for item {}: {}",
+                inventory_item_id,
+                e
+            )
+        })?;
 
-        let stock = stock
-            .update(&*self.db)
-            .await
-            .tap_ok(|_| {
-                tracing::info!(
-                    "Adjusted stock for item {}: {} (reason: {:?})",
-                    inventory_item_id,
-                    dto.adjustment,
-                    dto.reason
-                )
-            })
-            .tap_err(|e| {
-                tracing::error!(
-                    "Failed to adjust stock for item {}: {}",
-                    inventory_item_id,
-                    e
-                )
-            })?;
+        // Only reachable without row locks (SQLite): another writer got there first
+        if result.rows_affected == 0 {
+            return Err(ServiceError::Conflict(format!(
+                "Stock for item {} was changed concurrently, please retry",
+                inventory_item_id
+            )));
+        }
 
         // Get adjustment type or default to ManualAdjustment
         let adjustment_type = dto
             .adjustment_type
             .unwrap_or(inventory_stock_history::StockAdjustmentType::ManualAdjustment);
+        let reference_type = dto.reference_type.clone().or_else(|| {
+            (dto.reference_id.is_some()
+                && adjustment_type == inventory_stock_history::StockAdjustmentType::Sale)
+                .then(|| Self::SALE_REFERENCE_TYPE.to_string())
+        });
 
         // Create stock history record using type-safe SeaORM
         let history = inventory_stock_history::ActiveModel {
@@ -474,14 +596,15 @@ impl InventoryService {
             quantity_after: Set(new_quantity),
             adjustment_amount: Set(dto.adjustment),
             reason: Set(dto.reason.clone()),
-            reference_id: Set(None),
-            reference_type: Set(None),
-            recorded_at: Set(chrono::Utc::now().into()),
-            recorded_by: Set(None),
+            reference_id: Set(dto.reference_id),
+            reference_type: Set(reference_type),
+            recorded_at: Set(now),
+            recorded_by: Set(dto.adjusted_by),
+            idempotency_key: Set(dto.idempotency_key.clone()),
         };
 
         history
-            .insert(&*self.db)
+            .insert(&txn)
             .await
             .tap_ok(|_| {
                 tracing::info!(
@@ -492,6 +615,29 @@ impl InventoryService {
             })
             .tap_err(|e| tracing::error!("Failed to create stock history record: {}", e))?;
 
+        txn.commit().await.tap_ok(|_| {
+            tracing::info!(
+                "Adjusted stock for item {}: {} (reason: {:?})",
+                inventory_item_id,
+                dto.adjustment,
+                dto.reason
+            )
+        })?;
+
+        self.events.publish_stock_change(
+            inventory_item_id,
+            old_quantity,
+            new_quantity,
+            stock.min_stock_level,
+        );
+
+        let mut stock = stock;
+        stock.stock_quantity = new_quantity;
+        stock.updated_at = now;
+        if dto.adjustment > 0 {
+            stock.last_restocked_at = Some(now);
+        }
+
         Ok(InventoryStockResponse::from(stock))
     }
 
@@ -845,3 +991,6 @@ pub struct InventoryStatistics {
     pub out_of_stock_count: u64,
     pub total_inventory_value: f64,
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,566 @@
+use std::collections::{HashMap, HashSet};
+
+use db_entity::id::Id;
+use db_entity::inventory_opening_balance::dto::{
+    ImportResult, ImportRowError, OpeningBalanceImportOptions, OpeningBalanceImportRow,
+};
+use db_entity::inventory_opening_balance::{self, OpeningBalanceEntryType};
+use db_entity::inventory_stock_history::{self, StockAdjustmentType};
+use db_entity::{inventory_item, inventory_item_barcode, inventory_stock};
+use rust_decimal::Decimal;
+use sea_orm::*;
+use tap::TapFallible;
+
+use super::OpeningBalanceService;
+use crate::error::{ServiceError, ServiceResult};
+use crate::import::{ImportRecord, ImportSheet, parse_date, parse_decimal, parse_i32};
+
+/// Stock history reference type for rows written by an import batch
+const IMPORT_REFERENCE_TYPE: &str = "opening_balance_import";
+
+/// Accepted header names for each import column
+const ITEM_CODE_COLUMNS: &[&str] = &["item_code", "barcode", "code", "item", "name"];
+const QUANTITY_COLUMNS: &[&str] = &["quantity", "qty"];
+const UNIT_PRICE_COLUMNS: &[&str] = &["unit_price", "price", "cost"];
+const BATCH_NUMBER_COLUMNS: &[&str] = &["batch_number", "batch", "lot"];
+const EXPIRY_DATE_COLUMNS: &[&str] = &["expiry_date", "expiry", "expiration_date"];
+const NOTES_COLUMNS: &[&str] = &["notes", "note"];
+
+/// Column positions in the import sheet
+struct Columns {
+    item_code: Option<usize>,
+    quantity: Option<usize>,
+    unit_price: Option<usize>,
+    batch_number: Option<usize>,
+    expiry_date: Option<usize>,
+    notes: Option<usize>,
+}
+
+impl Columns {
+    fn resolve(sheet: &ImportSheet) -> ServiceResult<Self> {
+        let columns = Self {
+            item_code: sheet.column(ITEM_CODE_COLUMNS),
+            quantity: sheet.column(QUANTITY_COLUMNS),
+            unit_price: sheet.column(UNIT_PRICE_COLUMNS),
+            batch_number: sheet.column(BATCH_NUMBER_COLUMNS),
+            expiry_date: sheet.column(EXPIRY_DATE_COLUMNS),
+            notes: sheet.column(NOTES_COLUMNS),
+        };
+
+        let missing: Vec<&str> = [
+            ("item_code", columns.item_code),
+            ("quantity", columns.quantity),
+            ("unit_price", columns.unit_price),
+        ]
+        .into_iter()
+        .filter(|(_, column)| column.is_none())
+        .map(|(name, _)| name)
+        .collect();
+
+        if !missing.is_empty() {
+            return Err(ServiceError::BadRequest(format!(
+                "Import file is missing required column(s): {}",
+                missing.join(", ")
+            )));
+        }
+
+        Ok(columns)
+    }
+}
+
+/// Resolves `item_code` values to active inventory items
+struct ItemLookup {
+    by_barcode: HashMap<String, Id>,
+    by_name: HashMap<String, Vec<Id>>,
+}
+
+impl ItemLookup {
+    async fn load<C: ConnectionTrait>(conn: &C) -> ServiceResult<Self> {
+        let items = inventory_item::Entity::find()
+            .filter(inventory_item::Column::IsActive.eq(true))
+            .filter(inventory_item::Column::DeletedAt.is_null())
+            .all(conn)
+            .await?;
+
+        let active: HashSet<Id> = items.iter().map(|item| item.id).collect();
+
+        let by_barcode = inventory_item_barcode::Entity::find()
+            .all(conn)
+            .await?
+            .into_iter()
+            .filter(|barcode| active.contains(&barcode.inventory_item_id))
+            .map(|barcode| (barcode.barcode, barcode.inventory_item_id))
+            .collect();
+
+        let mut by_name: HashMap<String, Vec<Id>> = HashMap::new();
+        for item in items {
+            by_name
+                .entry(item.name.trim().to_lowercase())
+                .or_default()
+                .push(item.id);
+        }
+
+        Ok(Self {
+            by_barcode,
+            by_name,
+        })
+    }
+
+    /// Resolve by exact barcode first, then by case-insensitive name
+    fn resolve(&self, code: &str) -> Result<Id, String> {
+        if let Some(id) = self.by_barcode.get(code) {
+            return Ok(*id);
+        }
+
+        match self.by_name.get(&code.to_lowercase()).map(Vec::as_slice) {
+            Some([id]) => Ok(*id),
+            Some(ids) if ids.len() > 1 => Err(format!(
+                "'{}' matches {} items by name; use a barcode instead",
+                code,
+                ids.len()
+            )),
+            _ => Err(format!("No active item with barcode or name '{}'", code)),
+        }
+    }
+}
+
+/// A validated import row ready to be written
+struct ValidRow {
+    inventory_item_id: Id,
+    row: OpeningBalanceImportRow,
+}
+
+/// Per-item stock change produced by an import
+struct ItemTotal {
+    quantity: i32,
+    unit_price: Decimal,
+}
+
+impl OpeningBalanceService {
+    /// Import opening balances from a sheet
+    ///
+    /// Every row is validated and reported. With `dry_run` nothing is written;
+    /// otherwise all valid rows are committed in one transaction under a new
+    /// `import_batch_id`, and each item's stock is set to the total quantity
+    /// imported for it.
+    pub async fn import(
+        &self,
+        sheet: &ImportSheet,
+        options: OpeningBalanceImportOptions,
+    ) -> ServiceResult<ImportResult> {
+        let columns = Columns::resolve(sheet)?;
+        let lookup = ItemLookup::load(self.db.as_ref()).await?;
+
+        let existing: HashSet<Id> = if options.allow_override {
+            HashSet::new()
+        } else {
+            inventory_opening_balance::Entity::find()
+                .filter(inventory_opening_balance::Column::IsActive.eq(true))
+                .filter(
+                    inventory_opening_balance::Column::EntryType
+                        .eq(OpeningBalanceEntryType::Initial),
+                )
+                .all(self.db.as_ref())
+                .await?
+                .into_iter()
+                .map(|balance| balance.inventory_item_id)
+                .collect()
+        };
+
+        let mut valid = Vec::new();
+        let mut errors = Vec::new();
+        let mut seen = HashSet::new();
+
+        for record in sheet.records() {
+            match Self::validate_import_row(&record, &columns, &lookup) {
+                Ok(row) if existing.contains(&row.inventory_item_id) => {
+                    errors.push(ImportRowError {
+                        row_number: record.row_number(),
+                        item_code: row.row.item_code,
+                        error_message:
+                            "Item already has an opening balance; use an adjustment instead"
+                                .to_string(),
+                    });
+                }
+                Ok(row) if !seen.insert((row.inventory_item_id, row.row.batch_number.clone())) => {
+                    errors.push(ImportRowError {
+                        row_number: record.row_number(),
+                        item_code: row.row.item_code,
+                        error_message: "Duplicate item and batch number in this file".to_string(),
+                    });
+                }
+                Ok(row) => valid.push(row),
+                Err(error_message) => errors.push(ImportRowError {
+                    row_number: record.row_number(),
+                    item_code: record
+                        .get(columns.item_code)
+                        .unwrap_or_default()
+                        .to_string(),
+                    error_message,
+                }),
+            }
+        }
+
+        let mut result = ImportResult {
+            success: errors.is_empty() && !valid.is_empty(),
+            dry_run: options.dry_run,
+            total_rows: sheet.len(),
+            success_count: valid.len(),
+            error_count: errors.len(),
+            import_batch_id: None,
+            errors,
+        };
+
+        if options.dry_run || valid.is_empty() {
+            tracing::info!(
+                "Validated opening balance import '{}': {} valid, {} invalid",
+                options.file_name,
+                result.success_count,
+                result.error_count
+            );
+            return Ok(result);
+        }
+
+        let import_batch_id = self.commit_import(valid, &options).await.tap_err(|e| {
+            tracing::error!(
+                "Failed to import opening balances from '{}': {}",
+                options.file_name,
+                e
+            )
+        })?;
+        result.import_batch_id = Some(import_batch_id);
+
+        Ok(result)
+    }
+
+    /// Revert every active entry of an import batch
+    ///
+    /// Each item's stock is reduced by what the import added, so sales made
+    /// since the import are kept. Fails if that would make any stock negative.
+    /// Prices are left as they are. Returns the number of reverted entries.
+    pub async fn revert_import_batch(
+        &self,
+        import_batch_id: Id,
+        reverted_by: Id,
+    ) -> ServiceResult<u64> {
+        let txn = self.db.begin().await?;
+
+        let balances = inventory_opening_balance::Entity::find()
+            .filter(inventory_opening_balance::Column::ImportBatchId.eq(import_batch_id))
+            .filter(inventory_opening_balance::Column::IsActive.eq(true))
+            .all(&txn)
+            .await?;
+
+        if balances.is_empty() {
+            return Err(ServiceError::NotFound(format!(
+                "No active opening balances for import batch: {}",
+                import_batch_id
+            )));
+        }
+
+        let changes = inventory_stock_history::Entity::find()
+            .filter(inventory_stock_history::Column::ReferenceId.eq(import_batch_id))
+            .filter(inventory_stock_history::Column::ReferenceType.eq(IMPORT_REFERENCE_TYPE))
+            .all(&txn)
+            .await?;
+
+        let mut stock_changes = Vec::new();
+        for change in changes {
+            let stock = Self::lock_stock(&txn, change.inventory_item_id).await?;
+            let imported = change.quantity_after - change.quantity_before;
+            let quantity_after = stock.stock_quantity - imported;
+
+            if quantity_after < 0 {
+                return Err(ServiceError::Conflict(format!(
+                    "Cannot revert import: stock of item {} is already below the imported quantity",
+                    change.inventory_item_id
+                )));
+            }
+
+            stock_changes.push((stock.clone(), quantity_after));
+
+            let mut stock: inventory_stock::ActiveModel = stock.into();
+            stock.stock_quantity = Set(quantity_after);
+            stock.updated_at = Set(chrono::Utc::now().into());
+            let stock = stock.update(&txn).await?;
+
+            Self::insert_import_history(
+                &txn,
+                import_batch_id,
+                stock.inventory_item_id,
+                stock.stock_quantity + imported,
+                stock.stock_quantity,
+                reverted_by,
+                "Opening balance import reverted".to_string(),
+            )
+            .await?;
+        }
+
+        let reverted = inventory_opening_balance::Entity::update_many()
+            .col_expr(inventory_opening_balance::Column::IsActive, false.into())
+            .col_expr(
+                inventory_opening_balance::Column::UpdatedAt,
+                chrono::Utc::now().fixed_offset().into(),
+            )
+            .filter(inventory_opening_balance::Column::ImportBatchId.eq(import_batch_id))
+            .filter(inventory_opening_balance::Column::IsActive.eq(true))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+
+        txn.commit().await?;
+
+        for (stock, quantity_after) in stock_changes {
+            self.events.publish_stock_change(
+                stock.inventory_item_id,
+                stock.stock_quantity,
+                quantity_after,
+                stock.min_stock_level,
+            );
+        }
+
+        tracing::info!(
+            "Reverted import batch {}: {} opening balance(s)",
+            import_batch_id,
+            reverted
+        );
+
+        Ok(reverted)
+    }
+
+    // ========================================================================
+    // Import Helpers
+    // ========================================================================
+
+    /// Parse and validate one row, collecting every problem in it
+    fn validate_import_row(
+        record: &ImportRecord<'_>,
+        columns: &Columns,
+        lookup: &ItemLookup,
+    ) -> Result<ValidRow, String> {
+        let item = record
+            .required(columns.item_code, "Item code")
+            .and_then(|code| lookup.resolve(code).map(|id| (code, id)));
+
+        let quantity = record
+            .required(columns.quantity, "Quantity")
+            .and_then(|value| parse_i32(value, "Quantity"))
+            .and_then(|quantity| match quantity {
+                q if q < 0 => Err("Quantity cannot be negative".to_string()),
+                q => Ok(q),
+            });
+
+        let unit_price = record
+            .required(columns.unit_price, "Unit price")
+            .and_then(|value| parse_decimal(value, "Unit price"))
+            .and_then(|price| match price {
+                p if p.is_sign_negative() => Err("Unit price cannot be negative".to_string()),
+                p => Ok(p),
+            });
+
+        let expiry_date = record
+            .get(columns.expiry_date)
+            .map(|value| parse_date(value, "Expiry date"))
+            .transpose();
+
+        match (item, quantity, unit_price, expiry_date) {
+            (Ok((item_code, inventory_item_id)), Ok(quantity), Ok(unit_price), Ok(expiry_date)) => {
+                Ok(ValidRow {
+                    inventory_item_id,
+                    row: OpeningBalanceImportRow {
+                        item_code: item_code.to_string(),
+                        quantity,
+                        unit_price,
+                        batch_number: record.get(columns.batch_number).map(str::to_string),
+                        expiry_date,
+                        notes: record.get(columns.notes).map(str::to_string),
+                    },
+                })
+            }
+            (item, quantity, unit_price, expiry_date) => Err([
+                item.err(),
+                quantity.err(),
+                unit_price.err(),
+                expiry_date.err(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("; ")),
+        }
+    }
+
+    /// Write validated rows and the resulting stock changes in one transaction
+    async fn commit_import(
+        &self,
+        rows: Vec<ValidRow>,
+        options: &OpeningBalanceImportOptions,
+    ) -> ServiceResult<Id> {
+        let import_batch_id = Id::new();
+        let entered_by = options.entered_by;
+        let entry_date = options
+            .entry_date
+            .unwrap_or_else(|| chrono::Local::now().date_naive());
+        let now = chrono::Utc::now();
+
+        let txn = self.db.begin().await?;
+
+        let mut totals: Vec<(Id, ItemTotal)> = Vec::new();
+        for ValidRow {
+            inventory_item_id,
+            row,
+        } in rows
+        {
+            match totals.iter_mut().find(|(id, _)| *id == inventory_item_id) {
+                Some((_, total)) => {
+                    total.quantity += row.quantity;
+                    total.unit_price = row.unit_price;
+                }
+                None => totals.push((
+                    inventory_item_id,
+                    ItemTotal {
+                        quantity: row.quantity,
+                        unit_price: row.unit_price,
+                    },
+                )),
+            }
+
+            let dto = row.into_dto(
+                inventory_item_id,
+                import_batch_id,
+                options.file_name.clone(),
+                entry_date,
+            );
+
+            inventory_opening_balance::ActiveModel {
+                id: Set(Id::new()),
+                inventory_item_id: Set(dto.inventory_item_id),
+                entered_by: Set(entered_by),
+                adjusted_from_id: Set(None),
+                quantity: Set(dto.quantity),
+                unit_price: Set(dto.unit_price),
+                batch_number: Set(dto.batch_number),
+                expiry_date: Set(dto.expiry_date),
+                entry_date: Set(dto.entry_date),
+                entry_type: Set(dto.entry_type),
+                reason: Set(dto.reason),
+                notes: Set(dto.notes),
+                import_batch_id: Set(dto.import_batch_id),
+                import_file_name: Set(dto.import_file_name),
+                is_active: Set(true),
+                is_verified: Set(false),
+                verified_by: Set(None),
+                verified_at: Set(None),
+                created_at: Set(now.into()),
+                updated_at: Set(now.into()),
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        let reason = format!("Opening balance import: {}", options.file_name);
+        let mut stock_changes = Vec::new();
+
+        for (inventory_item_id, total) in totals {
+            let stock = Self::lock_stock(&txn, inventory_item_id).await?;
+            let quantity_before = stock.stock_quantity;
+            let price_before = stock.unit_price;
+            stock_changes.push((stock.clone(), total.quantity));
+
+            let mut stock: inventory_stock::ActiveModel = stock.into();
+            stock.stock_quantity = Set(total.quantity);
+            stock.unit_price = Set(total.unit_price);
+            stock.last_restocked_at = Set(Some(now.into()));
+            stock.updated_at = Set(now.into());
+            stock.update(&txn).await?;
+
+            Self::insert_import_history(
+                &txn,
+                import_batch_id,
+                inventory_item_id,
+                quantity_before,
+                total.quantity,
+                entered_by,
+                reason.clone(),
+            )
+            .await?;
+
+            crate::inventory::history::record_price_change(
+                &txn,
+                inventory_item_id,
+                price_before,
+                total.unit_price,
+                Some(entered_by),
+                &reason,
+            )
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        for (stock, quantity_after) in stock_changes {
+            self.events.publish_stock_change(
+                stock.inventory_item_id,
+                stock.stock_quantity,
+                quantity_after,
+                stock.min_stock_level,
+            );
+        }
+
+        tracing::info!(
+            "Imported opening balances from '{}' as batch {}",
+            options.file_name,
+            import_batch_id
+        );
+
+        Ok(import_batch_id)
+    }
+
+    /// Find an item's stock row, locking it for the rest of the transaction
+    async fn lock_stock(
+        txn: &DatabaseTransaction,
+        inventory_item_id: Id,
+    ) -> ServiceResult<inventory_stock::Model> {
+        inventory_stock::Entity::find()
+            .filter(inventory_stock::Column::InventoryItemId.eq(inventory_item_id))
+            .lock_exclusive()
+            .one(txn)
+            .await?
+            .ok_or_else(|| {
+                ServiceError::NotFound(format!(
+                    "Stock record not found for item: {}",
+                    inventory_item_id
+                ))
+            })
+    }
+
+    /// Record a stock change made by an import batch (or its revert)
+    async fn insert_import_history(
+        txn: &DatabaseTransaction,
+        import_batch_id: Id,
+        inventory_item_id: Id,
+        quantity_before: i32,
+        quantity_after: i32,
+        recorded_by: Id,
+        reason: String,
+    ) -> ServiceResult<()> {
+        inventory_stock_history::ActiveModel {
+            id: Set(Id::new()),
+            inventory_item_id: Set(inventory_item_id),
+            adjustment_type: Set(StockAdjustmentType::OpeningBalance),
+            quantity_before: Set(quantity_before),
+            quantity_after: Set(quantity_after),
+            adjustment_amount: Set(quantity_after - quantity_before),
+            reason: Set(Some(reason)),
+            reference_id: Set(Some(import_batch_id)),
+            reference_type: Set(Some(IMPORT_REFERENCE_TYPE.to_string())),
+            recorded_at: Set(chrono::Utc::now().into()),
+            recorded_by: Set(Some(recorded_by)),
+            idempotency_key: Set(None),
+        }
+        .insert(txn)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+mod import;
+
 use std::sync::Arc;
 
 use db_entity::id::Id;
@@ -14,17 +16,19 @@ use sea_orm::*;
 use tap::TapFallible;
 
 use crate::error::{ServiceError, ServiceResult};
+use crate::events::EventBus;
 use crate::pagination::{PaginationParams, PaginationResult};
 
 /// Opening balance service for managing initial stock quantities
 pub struct OpeningBalanceService {
     db: Arc<DatabaseConnection>,
+    events: Arc<EventBus>,
 }
 
 impl OpeningBalanceService {
     /// Create a new opening balance service
-    pub fn new(db: Arc<DatabaseConnection>) -> Self {
-        Self { db }
+    pub fn new(db: Arc<DatabaseConnection>, events: Arc<EventBus>) -> Self {
+        Self { db, events }
     }
 
     // ========================================================================
@@ -111,6 +115,8 @@ impl OpeningBalanceService {
                 ))
             })?;
 
+        let price_before = stock.unit_price;
+
         // Update stock
         let mut stock: db_entity::inventory_stock::ActiveModel = stock.into();
         stock.stock_quantity = Set(balance.quantity);
@@ -129,6 +135,17 @@ impl OpeningBalanceService {
             })
             .tap_err(|e| tracing::error!("Failed to update stock: {}", e))?;
 
+        // Quantity history is written by create_stock_history_entry
+        super::history::record_price_change(
+            txn,
+            balance.inventory_item_id,
+            price_before,
+            balance.unit_price,
+            Some(balance.entered_by),
+            balance.reason.as_deref().unwrap_or("Opening balance"),
+        )
+        .await?;
+
         Ok(())
     }
 
@@ -151,6 +168,7 @@ impl OpeningBalanceService {
             reference_type: Set(Some("opening_balance".to_string())),
             recorded_at: Set(chrono::Utc::now().into()),
             recorded_by: Set(Some(balance.entered_by)),
+            idempotency_key: Set(None),
         };
 
         history
@@ -239,7 +257,6 @@ impl OpeningBalanceService {
     pub async fn create(
         &self,
         dto: CreateOpeningBalanceDto,
-        entered_by: Id,
     ) -> ServiceResult<OpeningBalanceResponse> {
         let txn = self.db.begin().await?;
 
@@ -270,7 +287,7 @@ impl OpeningBalanceService {
         let balance = inventory_opening_balance::ActiveModel {
             id: Set(Id::new()),
             inventory_item_id: Set(dto.inventory_item_id),
-            entered_by: Set(entered_by),
+            entered_by: Set(dto.entered_by),
             adjusted_from_id: Set(None),
             quantity: Set(dto.quantity),
             unit_price: Set(dto.unit_price),
@@ -312,6 +329,13 @@ impl OpeningBalanceService {
 
         txn.commit().await?;
 
+        self.events.publish_stock_change(
+            balance.inventory_item_id,
+            quantity_before,
+            balance.quantity,
+            current_stock.min_stock_level,
+        );
+
         self.build_response(balance).await
     }
 
@@ -492,11 +516,22 @@ impl OpeningBalanceService {
                 ))
             })?;
 
+        let quantity_before = current_stock.stock_quantity;
         let mut stock: db_entity::inventory_stock::ActiveModel = current_stock.into();
         stock.stock_quantity = Set(0); // Reset to zero
         stock.updated_at = Set(chrono::Utc::now().into());
         stock.update(&txn).await?;
 
+        super::history::record_stock_change(
+            &txn,
+            balance.inventory_item_id,
+            quantity_before,
+            0,
+            None,
+            Some(format!("Opening balance rejected: {}", reason)),
+        )
+        .await?;
+
         // Mark balance as inactive
         let mut balance: inventory_opening_balance::ActiveModel = balance.into();
         balance.is_active = Set(false);
@@ -518,7 +553,6 @@ impl OpeningBalanceService {
     pub async fn create_adjustment(
         &self,
         dto: CreateAdjustmentDto,
-        entered_by: Id,
     ) -> ServiceResult<OpeningBalanceResponse> {
         let txn = self.db.begin().await?;
 
@@ -552,7 +586,7 @@ impl OpeningBalanceService {
         let adjustment = inventory_opening_balance::ActiveModel {
             id: Set(Id::new()),
             inventory_item_id: Set(original.inventory_item_id),
-            entered_by: Set(entered_by),
+            entered_by: Set(dto.entered_by),
             adjusted_from_id: Set(Some(dto.original_balance_id)),
             quantity: Set(dto.quantity),
             unit_price: Set(dto.unit_price),
@@ -594,6 +628,13 @@ impl OpeningBalanceService {
 
         txn.commit().await?;
 
+        self.events.publish_stock_change(
+            adjustment.inventory_item_id,
+            quantity_before,
+            adjustment.quantity,
+            current_stock.min_stock_level,
+        );
+
         self.build_response(adjustment).await
     }
 
@@ -672,6 +713,36 @@ impl OpeningBalanceService {
         Ok(responses)
     }
 
+    /// Get active opening balances whose batches expire within the given number of days
+    pub async fn get_expiring_within(
+        &self,
+        days: i64,
+    ) -> ServiceResult<Vec<OpeningBalanceResponse>> {
+        let today = chrono::Local::now().date_naive();
+        let until = today + chrono::Duration::days(days);
+
+        let balances = OpeningBalance::find()
+            .filter(inventory_opening_balance::Column::IsActive.eq(true))
+            .filter(inventory_opening_balance::Column::ExpiryDate.between(today, until))
+            .order_by_asc(inventory_opening_balance::Column::ExpiryDate)
+            .all(self.db.as_ref())
+            .await
+            .tap_err(|e| {
+                tracing::error!(
+                    "Failed to get opening balances expiring within {} days: {}",
+                    days,
+                    e
+                )
+            })?;
+
+        let mut responses = Vec::new();
+        for balance in balances {
+            responses.push(self.build_response(balance).await?);
+        }
+
+        Ok(responses)
+    }
+
     // ========================================================================
     // Statistics
     // ========================================================================
@@ -738,3 +809,6 @@ impl OpeningBalanceService {
         })
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,227 @@
+use db_entity::inventory_opening_balance::dto::OpeningBalanceImportOptions;
+use db_entity::prelude::*;
+
+use super::*;
+use crate::import::ImportSheet;
+use crate::inventory::InventoryService;
+use crate::test_support::{TestItem, create_admin, create_items, sqlite_db};
+
+struct Fixture {
+    db: Arc<DatabaseConnection>,
+    service: OpeningBalanceService,
+    admin_id: Id,
+    panadol_id: Id,
+    brufen_id: Id,
+}
+
+/// Item without stock, for opening balances to fill
+fn item<'a>(name: &'a str, barcodes: &'a [&'a str]) -> TestItem<'a> {
+    TestItem {
+        barcodes,
+        stock_quantity: 0,
+        unit_price: 10.0,
+        ..TestItem::new(name)
+    }
+}
+
+async fn fixture() -> Fixture {
+    let db = sqlite_db().await;
+    let admin = create_admin(&db).await;
+
+    let events = Arc::new(EventBus::default());
+    let inventory = InventoryService::new(db.clone(), events.clone());
+    let ids = create_items(
+        &db,
+        &inventory,
+        &[
+            item("Panadol", &["6221000000011"]),
+            item("Brufen", &["6221000000028"]),
+        ],
+    )
+    .await;
+
+    Fixture {
+        service: OpeningBalanceService::new(db.clone(), events),
+        db,
+        admin_id: admin.id,
+        panadol_id: ids[0],
+        brufen_id: ids[1],
+    }
+}
+
+fn options(dry_run: bool, entered_by: Id) -> OpeningBalanceImportOptions {
+    OpeningBalanceImportOptions {
+        file_name: "opening.csv".to_string(),
+        entry_date: None,
+        dry_run,
+        allow_override: false,
+        entered_by,
+    }
+}
+
+async fn stock_quantity(db: &DatabaseConnection, item_id: Id) -> i32 {
+    InventoryStock::find()
+        .filter(inventory_stock::Column::InventoryItemId.eq(item_id))
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap()
+        .stock_quantity
+}
+
+#[tokio::test]
+async fn test_import_dry_run_reports_row_errors() {
+    let f = fixture().await;
+    let sheet = ImportSheet::from_csv(
+        "Barcode,Qty,Price,Expiry\n\
+         6221000000011,20,12.50,03/2027\n\
+         unknown,5,1,\n\
+         brufen,abc,-1,not a date\n",
+    )
+    .unwrap();
+
+    let result = f
+        .service
+        .import(&sheet, options(true, f.admin_id))
+        .await
+        .unwrap();
+
+    assert!(result.dry_run);
+    assert!(!result.success);
+    assert_eq!(result.total_rows, 3);
+    assert_eq!(result.success_count, 1);
+    assert_eq!(result.error_count, 2);
+    assert_eq!(result.import_batch_id, None);
+    assert_eq!(result.errors[0].row_number, 3);
+    assert_eq!(result.errors[1].item_code, "brufen");
+    assert_eq!(result.errors[1].error_message.matches("; ").count(), 2);
+
+    assert_eq!(stock_quantity(&f.db, f.panadol_id).await, 0);
+}
+
+#[tokio::test]
+async fn test_import_missing_required_column_is_rejected() {
+    let f = fixture().await;
+    let sheet = ImportSheet::from_csv("barcode,qty\n6221000000011,20\n").unwrap();
+
+    let result = f.service.import(&sheet, options(false, f.admin_id)).await;
+
+    assert!(matches!(result, Err(ServiceError::BadRequest(_))));
+}
+
+#[tokio::test]
+async fn test_import_commits_batch_and_revert_restores_stock() {
+    let f = fixture().await;
+    let sheet = ImportSheet::from_csv(
+        "item_code,quantity,unit_price,batch_number\n\
+         6221000000011,20,12.50,A1\n\
+         6221000000011,5,13.00,A2\n\
+         Brufen,8,7.25,\n",
+    )
+    .unwrap();
+
+    let result = f
+        .service
+        .import(&sheet, options(false, f.admin_id))
+        .await
+        .unwrap();
+    let batch_id = result.import_batch_id.unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.success_count, 3);
+    assert_eq!(
+        f.service.get_by_import_batch(batch_id).await.unwrap().len(),
+        3
+    );
+    assert_eq!(stock_quantity(&f.db, f.panadol_id).await, 25);
+    assert_eq!(stock_quantity(&f.db, f.brufen_id).await, 8);
+
+    // A second import of the same items is refused
+    let again = f
+        .service
+        .import(&sheet, options(false, f.admin_id))
+        .await
+        .unwrap();
+    assert_eq!(again.error_count, 3);
+    assert_eq!(again.import_batch_id, None);
+
+    let reverted = f
+        .service
+        .revert_import_batch(batch_id, f.admin_id)
+        .await
+        .unwrap();
+
+    assert_eq!(reverted, 3);
+    assert_eq!(stock_quantity(&f.db, f.panadol_id).await, 0);
+    assert_eq!(stock_quantity(&f.db, f.brufen_id).await, 0);
+    assert!(matches!(
+        f.service.revert_import_batch(batch_id, f.admin_id).await,
+        Err(ServiceError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_sqlite_enforces_opening_balance_checks() {
+    let f = fixture().await;
+    let entry_date = chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+    let valid = || inventory_opening_balance::ActiveModel {
+        id: Set(Id::new()),
+        inventory_item_id: Set(f.panadol_id),
+        entered_by: Set(f.admin_id),
+        adjusted_from_id: Set(None),
+        quantity: Set(10),
+        unit_price: Set(Decimal::new(1250, 2)),
+        batch_number: Set(None),
+        expiry_date: Set(chrono::NaiveDate::from_ymd_opt(2027, 1, 1)),
+        entry_date: Set(entry_date),
+        entry_type: Set(OpeningBalanceEntryType::Initial),
+        reason: Set(None),
+        notes: Set(None),
+        import_batch_id: Set(None),
+        import_file_name: Set(None),
+        is_active: Set(true),
+        is_verified: Set(false),
+        verified_by: Set(None),
+        verified_at: Set(None),
+        created_at: Set(chrono::Utc::now().into()),
+        updated_at: Set(chrono::Utc::now().into()),
+    };
+
+    let initial = valid().insert(f.db.as_ref()).await.unwrap();
+
+    let violations = [
+        inventory_opening_balance::ActiveModel {
+            quantity: Set(-1),
+            ..valid()
+        },
+        inventory_opening_balance::ActiveModel {
+            unit_price: Set(Decimal::new(-1, 2)),
+            ..valid()
+        },
+        inventory_opening_balance::ActiveModel {
+            expiry_date: Set(Some(entry_date)),
+            ..valid()
+        },
+        // Initial entries reference nothing, adjustments their original entry
+        inventory_opening_balance::ActiveModel {
+            adjusted_from_id: Set(Some(initial.id)),
+            ..valid()
+        },
+        inventory_opening_balance::ActiveModel {
+            entry_type: Set(OpeningBalanceEntryType::Adjustment),
+            ..valid()
+        },
+    ];
+    for balance in violations {
+        assert!(balance.insert(f.db.as_ref()).await.is_err());
+    }
+
+    inventory_opening_balance::ActiveModel {
+        entry_type: Set(OpeningBalanceEntryType::Adjustment),
+        adjusted_from_id: Set(Some(initial.id)),
+        ..valid()
+    }
+    .insert(f.db.as_ref())
+    .await
+    .unwrap();
+}
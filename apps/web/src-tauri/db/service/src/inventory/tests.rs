@@ -0,0 +1,415 @@
+use std::sync::Arc;
+
+use db_entity::inventory_item::dto::CatalogueImportOptions;
+use db_entity::inventory_price_history;
+use db_entity::inventory_stock::dto::{ValuationGroupBy, ValuationMethod, ValuationQuery};
+use db_entity::prelude::*;
+use sea_orm::prelude::DateTimeWithTimeZone;
+
+use super::medicine_forms::MedicineFormsService;
+use super::*;
+use crate::import::ImportSheet;
+use crate::test_support::{TestItem, create_items, sqlite_db};
+
+#[tokio::test]
+async fn test_sqlite_records_stock_and_price_history() {
+    let db = sqlite_db().await;
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    let item_id = create_items(&db, &inventory, &[TestItem::new("Paracetamol")]).await[0];
+
+    inventory
+        .update_stock(
+            item_id,
+            UpdateInventoryStock {
+                stock_quantity: Some(25),
+                min_stock_level: None,
+                unit_price: Some(15.0),
+                reason: Some("Supplier price increase".to_string()),
+                updated_by: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    let stock_history = InventoryStockHistory::find()
+        .filter(inventory_stock_history::Column::InventoryItemId.eq(item_id))
+        .all(&*db)
+        .await
+        .unwrap();
+    assert_eq!(stock_history.len(), 1);
+    assert_eq!(stock_history[0].quantity_before, 10);
+    assert_eq!(stock_history[0].quantity_after, 25);
+    assert_eq!(stock_history[0].adjustment_amount, 15);
+
+    let price_history = InventoryPriceHistory::find()
+        .filter(inventory_price_history::Column::InventoryItemId.eq(item_id))
+        .all(&*db)
+        .await
+        .unwrap();
+    assert_eq!(price_history.len(), 1);
+    assert_eq!(price_history[0].unit_price, Decimal::new(150, 1));
+    assert_eq!(
+        price_history[0].reason.as_deref(),
+        Some("Supplier price increase")
+    );
+}
+
+#[tokio::test]
+async fn test_price_change_without_reason_is_rejected() {
+    let db = sqlite_db().await;
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    let item_id = create_items(&db, &inventory, &[TestItem::new("Paracetamol")]).await[0];
+
+    let result = inventory
+        .update_stock(
+            item_id,
+            UpdateInventoryStock {
+                stock_quantity: None,
+                min_stock_level: None,
+                unit_price: Some(15.0),
+                reason: Some("  ".to_string()),
+                updated_by: None,
+            },
+        )
+        .await;
+    assert!(matches!(result, Err(ServiceError::BadRequest(_))));
+
+    // Re-submitting the current price is not a change and needs no reason
+    inventory
+        .update_stock(
+            item_id,
+            UpdateInventoryStock {
+                stock_quantity: None,
+                min_stock_level: Some(3),
+                unit_price: Some(12.5),
+                reason: None,
+                updated_by: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    let price_history = InventoryPriceHistory::find()
+        .filter(inventory_price_history::Column::InventoryItemId.eq(item_id))
+        .count(&*db)
+        .await
+        .unwrap();
+    assert_eq!(price_history, 0);
+}
+
+fn adjustment(amount: i32, key: Option<&str>) -> AdjustStock {
+    AdjustStock {
+        adjustment: amount,
+        reason: Some("Sale".to_string()),
+        adjustment_type: Some(inventory_stock_history::StockAdjustmentType::Sale),
+        idempotency_key: key.map(str::to_string),
+        adjusted_by: None,
+        reference_id: None,
+        reference_type: None,
+    }
+}
+
+#[tokio::test]
+async fn test_adjust_stock_writes_single_history_entry() {
+    let db = sqlite_db().await;
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    let item_id = create_items(&db, &inventory, &[TestItem::new("Paracetamol")]).await[0];
+
+    let stock = inventory
+        .adjust_stock(item_id, adjustment(-3, None))
+        .await
+        .unwrap();
+    assert_eq!(stock.stock_quantity, 7);
+
+    let history = InventoryStockHistory::find()
+        .filter(inventory_stock_history::Column::InventoryItemId.eq(item_id))
+        .all(&*db)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].quantity_before, 10);
+    assert_eq!(history[0].quantity_after, 7);
+}
+
+#[tokio::test]
+async fn test_adjust_stock_with_repeated_idempotency_key_applies_once() {
+    let db = sqlite_db().await;
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    let item_id = create_items(&db, &inventory, &[TestItem::new("Paracetamol")]).await[0];
+
+    for _ in 0..2 {
+        let stock = inventory
+            .adjust_stock(item_id, adjustment(-4, Some("sale-42")))
+            .await
+            .unwrap();
+        assert_eq!(stock.stock_quantity, 6);
+    }
+
+    let history = InventoryStockHistory::find()
+        .filter(inventory_stock_history::Column::InventoryItemId.eq(item_id))
+        .count(&*db)
+        .await
+        .unwrap();
+    assert_eq!(history, 1);
+}
+
+#[tokio::test]
+async fn test_adjust_stock_rejects_negative_result() {
+    let db = sqlite_db().await;
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    let item_id = create_items(&db, &inventory, &[TestItem::new("Paracetamol")]).await[0];
+
+    let result = inventory.adjust_stock(item_id, adjustment(-11, None)).await;
+    assert!(matches!(result, Err(ServiceError::BadRequest(_))));
+
+    let stock = inventory.get_by_id(item_id).await.unwrap();
+    assert_eq!(stock.stock_quantity, 10);
+}
+
+const CATALOGUE_CSV: &str = "\
+name,concentration,form,manufacturer,barcode,price,qty,rx
+Amoxil,250mg/5ml,Syrup,Pharco,6221000000035,45,12,yes
+paracetamol,500 mg,Tablet,,,10,,
+Brufen,400mg,TAB,Abbott,6221000000035,-3,,maybe
+Augmentin,1g,tab,Pharco,6221000000042|6221000000059|6221000000042,120.5,4,no
+Flagyl,500mg,tab,,6221000000066,20,,
+Flagyl,250mg,tab,,6221000000059/6221000000066,15,,
+";
+
+fn catalogue_options(dry_run: bool) -> CatalogueImportOptions {
+    CatalogueImportOptions {
+        file_name: "catalogue.csv".to_string(),
+        dry_run,
+        create_missing: true,
+        created_by: None,
+    }
+}
+
+#[tokio::test]
+async fn test_catalogue_import_dry_run_reports_without_writing() {
+    let db = sqlite_db().await;
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    create_items(&db, &inventory, &[TestItem::new("Paracetamol")]).await;
+    let sheet = ImportSheet::from_csv(CATALOGUE_CSV).unwrap();
+
+    let result = inventory
+        .import_catalogue(&sheet, catalogue_options(true))
+        .await
+        .unwrap();
+
+    assert_eq!(result.created_count, 3);
+    assert_eq!(result.error_count, 3);
+    assert_eq!(result.created_medicine_forms, ["SYRUP"]);
+    assert_eq!(result.created_manufacturers, ["Pharco"]);
+    assert!(result.errors[0].error_message.starts_with("Duplicate item"));
+    // Reused barcode, negative price and bad flag are reported together
+    assert_eq!(result.errors[1].error_message.matches("; ").count(), 2);
+    // Barcodes of earlier rows in the file
+    assert_eq!(result.errors[2].row_number, 7);
+    assert_eq!(
+        result.errors[2].error_message,
+        "Barcode '6221000000059' is also on row 5; Barcode '6221000000066' is also on row 6"
+    );
+
+    let report = ImportSheet::from_csv(result.error_report.as_deref().unwrap()).unwrap();
+    assert_eq!(report.len(), 3);
+    assert!(report.column(&["error"]).is_some());
+
+    assert_eq!(InventoryItem::find().count(&*db).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_catalogue_import_creates_items_forms_and_manufacturers() {
+    let db = sqlite_db().await;
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    create_items(&db, &inventory, &[TestItem::new("Paracetamol")]).await;
+    let sheet = ImportSheet::from_csv(CATALOGUE_CSV).unwrap();
+
+    let result = inventory
+        .import_catalogue(&sheet, catalogue_options(false))
+        .await
+        .unwrap();
+    assert_eq!(result.created_count, 3);
+
+    let amoxil = inventory.get_by_barcode("6221000000035").await.unwrap();
+    assert_eq!(amoxil.name, "Amoxil");
+    assert_eq!(amoxil.stock_quantity, 12);
+    assert!(amoxil.requires_prescription);
+
+    // The barcode repeated within the cell is added once
+    let augmentin = inventory.get_by_barcode("6221000000059").await.unwrap();
+    assert_eq!(augmentin.barcodes.len(), 2);
+    assert_eq!(augmentin.manufacturer_id, amoxil.manufacturer_id);
+
+    let syrup = MedicineFormsService::new(db.clone())
+        .get_by_code("SYRUP")
+        .await
+        .unwrap();
+    assert_eq!(amoxil.medicine_form_id, syrup.id);
+
+    // Importing the same file again only produces duplicates
+    let again = inventory
+        .import_catalogue(&sheet, catalogue_options(false))
+        .await
+        .unwrap();
+    assert_eq!(again.created_count, 0);
+    assert_eq!(InventoryItem::find().count(&*db).await.unwrap(), 4);
+}
+
+/// Noon (UTC) `days` days before today
+fn days_ago(days: i64) -> DateTimeWithTimeZone {
+    let date = chrono::Utc::now().date_naive() - chrono::Duration::days(days);
+    date.and_hms_opt(12, 0, 0).unwrap().and_utc().fixed_offset()
+}
+
+fn valuation_query(as_of_days_ago: i64, method: ValuationMethod) -> ValuationQuery {
+    ValuationQuery {
+        as_of: days_ago(as_of_days_ago).date_naive(),
+        method,
+        group_by: ValuationGroupBy::MedicineForm,
+    }
+}
+
+/// Item created five days ago with 10 units, prices of 10 (four days ago)
+/// and 20 (two days ago), and receipts of 10 units three days and one day ago
+async fn item_with_history(db: &Arc<DatabaseConnection>, inventory: &InventoryService) -> Id {
+    let item_id = create_items(db, inventory, &[TestItem::new("Paracetamol")]).await[0];
+    InventoryItem::update_many()
+        .col_expr(inventory_item::Column::CreatedAt, Expr::value(days_ago(5)))
+        .filter(inventory_item::Column::Id.eq(item_id))
+        .exec(&**db)
+        .await
+        .unwrap();
+
+    for (days, price) in [(4, 10), (2, 20)] {
+        inventory_price_history::ActiveModel {
+            id: Set(Id::new()),
+            inventory_item_id: Set(item_id),
+            unit_price: Set(Decimal::from(price)),
+            recorded_at: Set(days_ago(days)),
+            changed_by: Set(None),
+            reason: Set(Some("Supplier price".to_string())),
+        }
+        .insert(&**db)
+        .await
+        .unwrap();
+    }
+
+    for days in [3, 1] {
+        let receipt = AdjustStock {
+            adjustment: 10,
+            reason: Some("Delivery".to_string()),
+            adjustment_type: Some(inventory_stock_history::StockAdjustmentType::OrderArrival),
+            idempotency_key: None,
+            adjusted_by: None,
+            reference_id: None,
+            reference_type: None,
+        };
+        inventory.adjust_stock(item_id, receipt).await.unwrap();
+        // Backdate the entry just written; earlier ones are already older
+        InventoryStockHistory::update_many()
+            .col_expr(
+                inventory_stock_history::Column::RecordedAt,
+                Expr::value(days_ago(days)),
+            )
+            .filter(inventory_stock_history::Column::InventoryItemId.eq(item_id))
+            .filter(inventory_stock_history::Column::RecordedAt.gt(days_ago(1)))
+            .exec(&**db)
+            .await
+            .unwrap();
+    }
+
+    item_id
+}
+
+#[tokio::test]
+async fn test_valuation_rebuilds_past_quantities() {
+    let db = sqlite_db().await;
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    let item_id = item_with_history(&db, &inventory).await;
+
+    let before_created = inventory
+        .get_valuation(valuation_query(6, ValuationMethod::CurrentPrice))
+        .await
+        .unwrap();
+    assert!(before_created.groups.is_empty());
+
+    // Before the first receipt the item held its initial 10 units
+    let report = inventory
+        .get_valuation(valuation_query(4, ValuationMethod::CurrentPrice))
+        .await
+        .unwrap();
+    assert_eq!(report.total_quantity, 10);
+    assert_eq!(report.total_value, 100.0);
+    assert_eq!(report.groups[0].name, "Tablet");
+    assert_eq!(report.groups[0].items[0].inventory_item_id, item_id);
+
+    let report = inventory
+        .get_valuation(valuation_query(2, ValuationMethod::CurrentPrice))
+        .await
+        .unwrap();
+    assert_eq!(report.total_quantity, 20);
+    assert_eq!(report.total_value, 400.0);
+}
+
+#[tokio::test]
+async fn test_valuation_weighted_average_cost() {
+    let db = sqlite_db().await;
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    item_with_history(&db, &inventory).await;
+
+    // 20 units at 10, then 10 more at 20
+    let report = inventory
+        .get_valuation(valuation_query(0, ValuationMethod::WeightedAverageCost))
+        .await
+        .unwrap();
+    let item = &report.groups[0].items[0];
+    assert_eq!(item.quantity, 30);
+    assert_eq!(item.unit_cost, 13.33);
+    assert_eq!(report.total_value, 400.0);
+
+    let report = inventory
+        .get_valuation(valuation_query(0, ValuationMethod::CurrentPrice))
+        .await
+        .unwrap();
+    assert_eq!(report.total_value, 600.0);
+}
+
+#[tokio::test]
+async fn test_scan_gs1_code_resolves_item_by_gtin() {
+    let db = sqlite_db().await;
+    let inventory = InventoryService::new(db.clone(), Arc::new(EventBus::default()));
+    let item_id = create_items(&db, &inventory, &[TestItem::new("Paracetamol")]).await[0];
+    // Stored as the EAN-13 printed on the pack
+    inventory
+        .add_barcode(item_id, "9506000134352".to_string(), None, true, None, None)
+        .await
+        .unwrap();
+
+    let scanned = inventory
+        .scan_barcode("]d2010950600013435217271231\u{1D}10AB12\u{1D}21SN9876")
+        .await
+        .unwrap();
+    assert_eq!(scanned.item.id, item_id);
+    assert_eq!(scanned.gtin.as_deref(), Some("09506000134352"));
+    assert_eq!(scanned.batch_number.as_deref(), Some("AB12"));
+    assert_eq!(
+        scanned.expiry_date,
+        chrono::NaiveDate::from_ymd_opt(2027, 12, 31)
+    );
+    assert_eq!(scanned.serial_number.as_deref(), Some("SN9876"));
+
+    // A plain scan of the same pack still works, without lot data
+    let scanned = inventory.scan_barcode("9506000134352").await.unwrap();
+    assert_eq!(scanned.item.id, item_id);
+    assert_eq!(scanned.batch_number, None);
+
+    assert!(matches!(
+        inventory.scan_barcode("(01)05012345678900(10)X1").await,
+        Err(ServiceError::NotFound(_))
+    ));
+    assert!(matches!(
+        inventory.scan_barcode("(10)X1(17)271231").await,
+        Err(ServiceError::BadRequest(_))
+    ));
+}
@@ -0,0 +1,294 @@
+//! Inventory valuation at a point in time
+//!
+//! Quantities are rebuilt from `inventory_stock_history` and priced from
+//! `inventory_price_history`, so a report for a past date does not depend on
+//! the current stock levels of items that have changed since.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{NaiveDate, NaiveTime};
+use db_entity::id::Id;
+use db_entity::inventory_item::{self, Entity as InventoryItem};
+use db_entity::inventory_price_history::{self, Entity as InventoryPriceHistory};
+use db_entity::inventory_stock::Entity as InventoryStock;
+use db_entity::inventory_stock::dto::{
+    ItemValuation, ValuationGroup, ValuationGroupBy, ValuationMethod, ValuationQuery,
+    ValuationReport,
+};
+use db_entity::inventory_stock_history::{self, Entity as InventoryStockHistory};
+use db_entity::manufacturer::Entity as Manufacturer;
+use db_entity::medicine_form::Entity as MedicineForm;
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::*;
+use tap::{Pipe, Tap, TapFallible};
+
+use super::InventoryService;
+use crate::error::{ServiceError, ServiceResult};
+
+/// Group name for items without a manufacturer
+const NO_MANUFACTURER: &str = "No manufacturer";
+
+/// Group name for items whose medicine form cannot be found
+const NO_FORM: &str = "No form";
+
+/// Prices recorded for one item before the report cutoff, oldest first
+#[derive(Default)]
+struct PriceTimeline(Vec<(DateTimeWithTimeZone, Decimal)>);
+
+impl PriceTimeline {
+    /// Price in effect at `at`, if one was recorded by then
+    fn at(&self, at: DateTimeWithTimeZone) -> Option<Decimal> {
+        self.0
+            .iter()
+            .take_while(|(recorded_at, _)| *recorded_at <= at)
+            .last()
+            .map(|(_, price)| *price)
+    }
+
+    fn last(&self) -> Option<Decimal> {
+        self.0.last().map(|(_, price)| *price)
+    }
+}
+
+/// Quantity and moving average cost of an item while replaying its history
+struct ItemState {
+    quantity: i32,
+    average_cost: Decimal,
+}
+
+impl InventoryService {
+    // ========================================================================
+    // Valuation
+    // ========================================================================
+
+    /// Value the stock held at the end of `query.as_of` (UTC)
+    ///
+    /// Each item's quantity is the last recorded `quantity_after` before the
+    /// cutoff; items without history before it fall back to the
+    /// `quantity_before` of their first later change, or to their current
+    /// stock. With [`ValuationMethod::WeightedAverageCost`] the unit cost is a
+    /// moving average over every stock receipt, priced at the unit price in
+    /// effect when it was received. Items with no stock are left out.
+    pub async fn get_valuation(&self, query: ValuationQuery) -> ServiceResult<ValuationReport> {
+        let cutoff = query
+            .as_of
+            .succ_opt()
+            .map(start_of_day)
+            .ok_or_else(|| ServiceError::BadRequest("Invalid valuation date".to_string()))?;
+
+        // Items that existed at the cutoff, with their current stock as fallback
+        let items = InventoryItem::find()
+            .find_also_related(InventoryStock)
+            .filter(inventory_item::Column::CreatedAt.lt(cutoff))
+            .filter(
+                Condition::any()
+                    .add(inventory_item::Column::DeletedAt.is_null())
+                    .add(inventory_item::Column::DeletedAt.gte(cutoff)),
+            )
+            .all(&*self.db)
+            .await
+            .tap_err(|e| tracing::error!("Failed to load items for valuation: {}", e))?;
+
+        let prices = self.price_timelines(cutoff).await?;
+        let repriced_later: HashSet<Id> = InventoryPriceHistory::find()
+            .select_only()
+            .column(inventory_price_history::Column::InventoryItemId)
+            .filter(inventory_price_history::Column::RecordedAt.gte(cutoff))
+            .distinct()
+            .into_tuple::<Id>()
+            .all(&*self.db)
+            .await?
+            .into_iter()
+            .collect();
+
+        let current_prices: HashMap<Id, Decimal> = items
+            .iter()
+            .filter_map(|(item, stock)| stock.as_ref().map(|stock| (item.id, stock.unit_price)))
+            .collect();
+        let price_at = |item_id: Id, at: DateTimeWithTimeZone| {
+            prices
+                .get(&item_id)
+                .and_then(|timeline| timeline.at(at))
+                .or_else(|| current_prices.get(&item_id).copied())
+                .unwrap_or_default()
+        };
+
+        // Replay the history before the cutoff
+        let mut states: HashMap<Id, ItemState> = HashMap::new();
+        let mut history = InventoryStockHistory::find()
+            .filter(inventory_stock_history::Column::RecordedAt.lt(cutoff))
+            .order_by_asc(inventory_stock_history::Column::RecordedAt)
+            .order_by_asc(inventory_stock_history::Column::Id)
+            .stream(&*self.db)
+            .await?;
+        while let Some(entry) = history.try_next().await? {
+            let price = price_at(entry.inventory_item_id, entry.recorded_at);
+            let state = states
+                .entry(entry.inventory_item_id)
+                .or_insert_with(|| ItemState {
+                    quantity: entry.quantity_before,
+                    average_cost: price,
+                });
+
+            let held = entry.quantity_before.max(0);
+            let received = entry.adjustment_amount;
+            if received > 0 && held + received > 0 {
+                state.average_cost = (state.average_cost * Decimal::from(held)
+                    + price * Decimal::from(received))
+                    / Decimal::from(held + received);
+            }
+            state.quantity = entry.quantity_after;
+        }
+        drop(history);
+
+        // Items first changed after the cutoff held their pre-change quantity
+        let mut later = InventoryStockHistory::find()
+            .filter(inventory_stock_history::Column::RecordedAt.gte(cutoff))
+            .order_by_asc(inventory_stock_history::Column::RecordedAt)
+            .order_by_asc(inventory_stock_history::Column::Id)
+            .stream(&*self.db)
+            .await?;
+        while let Some(entry) = later.try_next().await? {
+            states
+                .entry(entry.inventory_item_id)
+                .or_insert_with(|| ItemState {
+                    quantity: entry.quantity_before,
+                    average_cost: price_at(entry.inventory_item_id, cutoff),
+                });
+        }
+        drop(later);
+
+        let group_names = self.valuation_group_names(query.group_by).await?;
+        // Group totals are summed as decimals and converted once at the end
+        let mut groups: HashMap<Option<Id>, (ValuationGroup, Decimal)> = HashMap::new();
+
+        for (item, stock) in &items {
+            let (quantity, average_cost) = match states.get(&item.id) {
+                Some(state) => (state.quantity, state.average_cost),
+                None => (
+                    stock.as_ref().map(|s| s.stock_quantity).unwrap_or_default(),
+                    price_at(item.id, cutoff),
+                ),
+            };
+            if quantity == 0 {
+                continue;
+            }
+
+            let unit_cost = match query.method {
+                ValuationMethod::CurrentPrice => price_at(item.id, cutoff),
+                ValuationMethod::WeightedAverageCost => average_cost,
+            };
+            let value = unit_cost * Decimal::from(quantity);
+            let price_estimated = repriced_later.contains(&item.id)
+                && prices.get(&item.id).and_then(PriceTimeline::last).is_none();
+
+            let (group_id, ungrouped) = match query.group_by {
+                ValuationGroupBy::Manufacturer => (item.manufacturer_id, NO_MANUFACTURER),
+                ValuationGroupBy::MedicineForm => (Some(item.medicine_form_id), NO_FORM),
+            };
+            let group_name = group_id
+                .and_then(|id| group_names.get(&id).cloned())
+                .unwrap_or_else(|| ungrouped.to_string());
+
+            let (group, total_value) = groups.entry(group_id).or_insert_with(|| {
+                let group = ValuationGroup {
+                    id: group_id,
+                    name: group_name,
+                    total_quantity: 0,
+                    total_value: 0.0,
+                    items: Vec::new(),
+                };
+                (group, Decimal::ZERO)
+            });
+            group.total_quantity += i64::from(quantity);
+            *total_value += value;
+            group.items.push(ItemValuation {
+                inventory_item_id: item.id,
+                name: item.name.clone(),
+                quantity,
+                unit_cost: Self::decimal_to_f64(&unit_cost.round_dp(2))?,
+                value: Self::decimal_to_f64(&value.round_dp(2))?,
+                price_estimated,
+            });
+        }
+
+        let mut total_value = Decimal::ZERO;
+        let mut report_groups = Vec::with_capacity(groups.len());
+        for (mut group, group_value) in groups.into_values() {
+            group.total_value = Self::decimal_to_f64(&group_value.round_dp(2))?;
+            group.items.sort_by(|a, b| a.name.cmp(&b.name));
+            total_value += group_value;
+            report_groups.push(group);
+        }
+        report_groups.sort_by(|a, b| a.name.cmp(&b.name));
+
+        ValuationReport {
+            as_of: query.as_of,
+            method: query.method,
+            group_by: query.group_by,
+            total_quantity: report_groups.iter().map(|g| g.total_quantity).sum(),
+            total_value: Self::decimal_to_f64(&total_value.round_dp(2))?,
+            groups: report_groups,
+        }
+        .tap(|report| {
+            tracing::debug!(
+                "Valued stock at {} as of {} ({:?})",
+                report.total_value,
+                report.as_of,
+                report.method
+            )
+        })
+        .pipe(Ok)
+    }
+
+    /// Prices recorded before `cutoff`, per item
+    async fn price_timelines(
+        &self,
+        cutoff: DateTimeWithTimeZone,
+    ) -> ServiceResult<HashMap<Id, PriceTimeline>> {
+        let mut timelines: HashMap<Id, PriceTimeline> = HashMap::new();
+        let mut prices = InventoryPriceHistory::find()
+            .filter(inventory_price_history::Column::RecordedAt.lt(cutoff))
+            .order_by_asc(inventory_price_history::Column::RecordedAt)
+            .order_by_asc(inventory_price_history::Column::Id)
+            .stream(&*self.db)
+            .await?;
+
+        while let Some(price) = prices.try_next().await? {
+            timelines
+                .entry(price.inventory_item_id)
+                .or_default()
+                .0
+                .push((price.recorded_at, price.unit_price));
+        }
+        Ok(timelines)
+    }
+
+    /// Names of the manufacturers or medicine forms items are grouped by
+    async fn valuation_group_names(
+        &self,
+        group_by: ValuationGroupBy,
+    ) -> ServiceResult<HashMap<Id, String>> {
+        let names = match group_by {
+            ValuationGroupBy::Manufacturer => Manufacturer::find()
+                .all(&*self.db)
+                .await?
+                .into_iter()
+                .map(|m| (m.id, m.name))
+                .collect(),
+            ValuationGroupBy::MedicineForm => MedicineForm::find()
+                .all(&*self.db)
+                .await?
+                .into_iter()
+                .map(|f| (f.id, f.name_en))
+                .collect(),
+        };
+        Ok(names)
+    }
+}
+
+fn start_of_day(date: NaiveDate) -> DateTimeWithTimeZone {
+    date.and_time(NaiveTime::MIN).and_utc().fixed_offset()
+}
@@ -2,10 +2,24 @@ use argon2::password_hash::SaltString;
 use argon2::{Argon2, PasswordHasher};
 use chrono::{Duration, Utc};
 use db_entity::id::Id;
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub use jwt_keys::{DEFAULT_SECRET, JwtKey, JwtKeyAlgorithm, JwtKeyError, jwks};
+
+/// Secret replaced by a rotation, still accepted for verification until
+/// `valid_until`
+#[derive(Debug, Clone)]
+pub struct RetiredSecret {
+    pub secret: String,
+    /// End of the grace period (Unix timestamp, like [`Claims::exp`])
+    pub valid_until: i64,
+}
+
 /// JWT Claims structure for authentication tokens
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
@@ -46,14 +60,38 @@ pub enum JwtError {
 
     #[error("Argon2 error: {0}")]
     Argon2Error(String),
+
+    #[error("JWT key error: {0}")]
+    Key(String),
+}
+
+impl From<JwtKeyError> for JwtError {
+    fn from(error: JwtKeyError) -> Self {
+        Self::Key(error.0)
+    }
+}
+
+/// Key pair of the key set, ready to verify tokens naming its `kid`
+struct VerifyingKey {
+    kid: String,
+    decoding_key: DecodingKey,
+    validation: Validation,
+    valid_until: Option<i64>,
 }
 
 /// JWT Service for creating and validating tokens
 /// Uses jsonwebtoken crate with Argon2-derived keys for enhanced security
 pub struct JwtService {
     encoding_key: EncodingKey,
+    /// Header of issued tokens: HS256, or the algorithm and `kid` of the
+    /// signing key pair
+    header: Header,
     decoding_key: DecodingKey,
     validation: Validation,
+    /// Keys of retired secrets and the end of their grace periods
+    retired: Vec<(DecodingKey, i64)>,
+    /// Key pairs tokens with a `kid` are verified with
+    keys: Vec<VerifyingKey>,
     issuer: String,
     audience: String,
     expiration_hours: i64,
@@ -95,14 +133,69 @@ impl JwtService {
 
         Ok(Self {
             encoding_key,
+            header: Header::default(),
             decoding_key,
             validation,
+            retired: Vec::new(),
+            keys: Vec::new(),
             issuer,
             audience,
             expiration_hours,
         })
     }
 
+    /// Also accept tokens signed with `retired` secrets until their grace
+    /// periods end
+    pub fn with_retired_secrets(mut self, retired: Vec<RetiredSecret>) -> Result<Self, JwtError> {
+        self.retired = retired
+            .into_iter()
+            .map(|retired| {
+                let derived_key = Self::derive_key(&retired.secret)?;
+                Ok((DecodingKey::from_secret(&derived_key), retired.valid_until))
+            })
+            .collect::<Result<_, JwtError>>()?;
+        Ok(self)
+    }
+
+    /// Verify tokens with the key pairs of `keys`, and sign them with the one
+    /// whose `kid` is `signing_key` instead of the secret
+    ///
+    /// An empty `signing_key` keeps signing with the secret. Once a key pair
+    /// signs, tokens without a `kid` are only accepted from retired secrets
+    /// during their grace periods; retiring the secret when switching to a
+    /// key pair keeps the sessions from before the switch valid for a while.
+    pub fn with_key_set(mut self, keys: Vec<JwtKey>, signing_key: &str) -> Result<Self, JwtError> {
+        self.keys = keys
+            .iter()
+            .map(|key| {
+                let (algorithm, decoding_key) = key.verifying()?;
+                let mut validation = self.validation.clone();
+                validation.algorithms = vec![algorithm];
+                Ok(VerifyingKey {
+                    kid: key.kid().to_string(),
+                    decoding_key,
+                    validation,
+                    valid_until: key.valid_until,
+                })
+            })
+            .collect::<Result<_, JwtError>>()?;
+
+        if !signing_key.is_empty() {
+            let key = keys
+                .iter()
+                .find(|key| key.kid() == signing_key)
+                .ok_or_else(|| {
+                    JwtError::Key(format!("Signing key {} is not in the key set", signing_key))
+                })?;
+            let (algorithm, encoding_key) = key.signing()?;
+            self.encoding_key = encoding_key;
+            self.header = Header::new(algorithm);
+            self.header.kid = Some(signing_key.to_string());
+        }
+
+        Ok(self)
+    }
+
     /// Derive a cryptographically strong key from the secret using Argon2
     fn derive_key(secret: &str) -> Result<Vec<u8>, JwtError> {
         let argon2 = Argon2::default();
@@ -165,24 +258,61 @@ impl JwtService {
             aud: self.audience.clone(),
         };
 
-        let token = encode(&Header::default(), &claims, &self.encoding_key)?;
+        let token = encode(&self.header, &claims, &self.encoding_key)?;
         Ok(token)
     }
 
     /// Verify and decode a JWT token
     pub fn verify_token(&self, token: &str) -> Result<Claims, JwtError> {
-        let token_data =
-            decode::<Claims>(token, &self.decoding_key, &self.validation).map_err(|e| {
-                match e.kind() {
-                    jsonwebtoken::errors::ErrorKind::ExpiredSignature => JwtError::Expired,
-                    jsonwebtoken::errors::ErrorKind::ImmatureSignature => JwtError::NotYetValid,
-                    _ => JwtError::Invalid,
-                }
-            })?;
+        let now = Utc::now().timestamp();
+
+        // Tokens naming a key pair are only checked against that key
+        let header = decode_header(token).map_err(|_| JwtError::Invalid)?;
+        if let Some(kid) = header.kid {
+            let key = self
+                .keys
+                .iter()
+                .find(|key| key.kid == kid)
+                .filter(|key| key.valid_until.is_none_or(|valid_until| valid_until > now))
+                .ok_or(JwtError::Invalid)?;
+            return decode::<Claims>(token, &key.decoding_key, &key.validation)
+                .map(|token_data| token_data.claims)
+                .map_err(Self::verification_error);
+        }
+
+        // The secret stops verifying once a key pair signs, or it could go on
+        // minting valid tokens after it was meant to be phased out
+        let mut result = if self.header.kid.is_none() {
+            decode::<Claims>(token, &self.decoding_key, &self.validation)
+        } else {
+            Err(ErrorKind::InvalidSignature.into())
+        };
+
+        // Tokens signed before a rotation stay valid during its grace period
+        for (decoding_key, _) in self
+            .retired
+            .iter()
+            .filter(|(_, valid_until)| *valid_until > now)
+        {
+            if !matches!(&result, Err(e) if *e.kind() == ErrorKind::InvalidSignature) {
+                break;
+            }
+            result = decode::<Claims>(token, decoding_key, &self.validation);
+        }
+
+        let token_data = result.map_err(Self::verification_error)?;
 
         Ok(token_data.claims)
     }
 
+    fn verification_error(e: jsonwebtoken::errors::Error) -> JwtError {
+        match e.kind() {
+            ErrorKind::ExpiredSignature => JwtError::Expired,
+            ErrorKind::ImmatureSignature => JwtError::NotYetValid,
+            _ => JwtError::Invalid,
+        }
+    }
+
     /// Refresh a token (generate new token with same user info but new timestamps)
     pub fn refresh_token(&self, token: &str) -> Result<String, JwtError> {
         let claims = self.verify_token(token)?;
@@ -321,6 +451,163 @@ mod tests {
         assert_eq!(claims.email, email);
     }
 
+    #[test]
+    fn test_retired_secrets() {
+        let user_id = Id::new();
+        let old_token = create_test_service()
+            .generate_token(user_id, "test@example.com".to_string(), "admin".to_string())
+            .expect("Failed to generate token");
+        let rotated = |valid_until: i64| {
+            JwtService::new(
+                "rotated_secret_key_67890".to_string(),
+                "meditrack".to_string(),
+                "meditrack-app".to_string(),
+                24,
+            )
+            .and_then(|service| {
+                service.with_retired_secrets(vec![RetiredSecret {
+                    secret: "test_secret_key_12345".to_string(),
+                    valid_until,
+                }])
+            })
+            .expect("Failed to create JWT service")
+        };
+
+        // During the grace period old tokens verify and refresh to new ones
+        let service = rotated((Utc::now() + Duration::hours(1)).timestamp());
+        let claims = service
+            .verify_token(&old_token)
+            .expect("Failed to verify token signed with the retired secret");
+        assert_eq!(claims.sub, user_id.to_string());
+        let refreshed_token = service
+            .refresh_token(&old_token)
+            .expect("Failed to refresh token");
+        assert!(
+            create_test_service()
+                .verify_token(&refreshed_token)
+                .is_err()
+        );
+        assert!(service.verify_token(&refreshed_token).is_ok());
+
+        // Afterwards they are rejected
+        let service = rotated((Utc::now() - Duration::hours(1)).timestamp());
+        assert!(matches!(
+            service.verify_token(&old_token),
+            Err(JwtError::Invalid)
+        ));
+    }
+
+    #[test]
+    fn test_key_set() {
+        let user_id = Id::new();
+        let hs256_token = create_test_service()
+            .generate_token(user_id, "test@example.com".to_string(), "admin".to_string())
+            .expect("Failed to generate token");
+
+        for algorithm in [JwtKeyAlgorithm::EdDsa, JwtKeyAlgorithm::Rs256] {
+            let key = JwtKey::generate(algorithm).expect("Failed to generate key");
+            let service = create_test_service()
+                .with_key_set(vec![key.clone()], key.kid())
+                .expect("Failed to use key set");
+
+            let token = service
+                .generate_token(user_id, "test@example.com".to_string(), "admin".to_string())
+                .expect("Failed to generate token");
+            let header = decode_header(&token).unwrap();
+            assert_eq!(header.kid.as_deref(), Some(key.kid()));
+            assert_eq!(format!("{:?}", header.alg), algorithm.to_string());
+            assert_eq!(
+                service.verify_token(&token).unwrap().sub,
+                user_id.to_string()
+            );
+            assert!(create_test_service().verify_token(&token).is_err());
+
+            // Sessions from before the switch only work while the secret is
+            // retired
+            assert!(matches!(
+                service.verify_token(&hs256_token),
+                Err(JwtError::Invalid)
+            ));
+            for (valid_until, accepted) in
+                [(Duration::hours(1), true), (Duration::hours(-1), false)]
+            {
+                let service = create_test_service()
+                    .with_retired_secrets(vec![RetiredSecret {
+                        secret: "test_secret_key_12345".to_string(),
+                        valid_until: (Utc::now() + valid_until).timestamp(),
+                    }])
+                    .and_then(|service| service.with_key_set(vec![key.clone()], key.kid()))
+                    .expect("Failed to use key set");
+                assert_eq!(service.verify_token(&hs256_token).is_ok(), accepted);
+            }
+
+            // A verifier holding only the JWKS accepts the token
+            let jwks = jwks(std::slice::from_ref(&key));
+            let jwk = jwks.find(key.kid()).unwrap();
+            let mut validation = Validation::new(header.alg);
+            validation.set_audience(&["meditrack-app"]);
+            let claims =
+                decode::<Claims>(&token, &DecodingKey::from_jwk(jwk).unwrap(), &validation)
+                    .unwrap()
+                    .claims;
+            assert_eq!(claims.sub, user_id.to_string());
+        }
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let user_id = Id::new();
+        let mut old_key = JwtKey::generate(JwtKeyAlgorithm::EdDsa).unwrap();
+        let old_token = create_test_service()
+            .with_key_set(vec![old_key.clone()], old_key.kid())
+            .and_then(|service| {
+                service.generate_token(user_id, "test@example.com".to_string(), "admin".to_string())
+            })
+            .expect("Failed to generate token");
+
+        let new_key = JwtKey::generate(JwtKeyAlgorithm::EdDsa).unwrap();
+        let rotated = |old_key: &JwtKey| {
+            create_test_service()
+                .with_key_set(vec![old_key.clone(), new_key.clone()], new_key.kid())
+                .expect("Failed to use key set")
+        };
+
+        // The retired key verifies during its grace period, without its
+        // private half
+        old_key.retire((Utc::now() + Duration::hours(1)).timestamp());
+        let service = rotated(&old_key);
+        assert!(service.verify_token(&old_token).is_ok());
+        let refreshed_token = service.refresh_token(&old_token).unwrap();
+        assert_eq!(
+            decode_header(&refreshed_token).unwrap().kid.as_deref(),
+            Some(new_key.kid())
+        );
+        assert!(matches!(
+            create_test_service().with_key_set(vec![old_key.clone()], old_key.kid()),
+            Err(JwtError::Key(_))
+        ));
+
+        old_key.retire((Utc::now() - Duration::hours(1)).timestamp());
+        let service = rotated(&old_key);
+        assert!(matches!(
+            service.verify_token(&old_token),
+            Err(JwtError::Invalid)
+        ));
+
+        // Keys of other installations are unknown
+        assert!(matches!(
+            create_test_service()
+                .with_key_set(vec![new_key.clone()], "")
+                .unwrap()
+                .verify_token(&old_token),
+            Err(JwtError::Invalid)
+        ));
+        assert!(matches!(
+            create_test_service().with_key_set(Vec::new(), new_key.kid()),
+            Err(JwtError::Key(_))
+        ));
+    }
+
     #[test]
     fn test_different_secrets_produce_different_keys() {
         let key1 = JwtService::derive_key("secret1").expect("Failed to derive key");
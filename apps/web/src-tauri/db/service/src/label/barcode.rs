@@ -0,0 +1,234 @@
+//! Code 128 barcode encoding
+//!
+//! Barcodes are printed as Code 128, which every retail scanner reads and
+//! which encodes any printable ASCII value, so EAN/UPC numbers and internal
+//! codes print the same way and scan back to the stored string. Numeric
+//! values use code set C (two digits per symbol) for a shorter barcode,
+//! switching to code set B for an odd last digit; everything else uses code
+//! set B.
+
+use crate::error::{ServiceError, ServiceResult};
+
+/// Bar and space widths, in modules, of symbol values 0..=105
+const PATTERNS: [[u8; 6]; 106] = [
+    [2, 1, 2, 2, 2, 2],
+    [2, 2, 2, 1, 2, 2],
+    [2, 2, 2, 2, 2, 1],
+    [1, 2, 1, 2, 2, 3],
+    [1, 2, 1, 3, 2, 2],
+    [1, 3, 1, 2, 2, 2],
+    [1, 2, 2, 2, 1, 3],
+    [1, 2, 2, 3, 1, 2],
+    [1, 3, 2, 2, 1, 2],
+    [2, 2, 1, 2, 1, 3],
+    [2, 2, 1, 3, 1, 2],
+    [2, 3, 1, 2, 1, 2],
+    [1, 1, 2, 2, 3, 2],
+    [1, 2, 2, 1, 3, 2],
+    [1, 2, 2, 2, 3, 1],
+    [1, 1, 3, 2, 2, 2],
+    [1, 2, 3, 1, 2, 2],
+    [1, 2, 3, 2, 2, 1],
+    [2, 2, 3, 2, 1, 1],
+    [2, 2, 1, 1, 3, 2],
+    [2, 2, 1, 2, 3, 1],
+    [2, 1, 3, 2, 1, 2],
+    [2, 2, 3, 1, 1, 2],
+    [3, 1, 2, 1, 3, 1],
+    [3, 1, 1, 2, 2, 2],
+    [3, 2, 1, 1, 2, 2],
+    [3, 2, 1, 2, 2, 1],
+    [3, 1, 2, 2, 1, 2],
+    [3, 2, 2, 1, 1, 2],
+    [3, 2, 2, 2, 1, 1],
+    [2, 1, 2, 1, 2, 3],
+    [2, 1, 2, 3, 2, 1],
+    [2, 3, 2, 1, 2, 1],
+    [1, 1, 1, 3, 2, 3],
+    [1, 3, 1, 1, 2, 3],
+    [1, 3, 1, 3, 2, 1],
+    [1, 1, 2, 3, 1, 3],
+    [1, 3, 2, 1, 1, 3],
+    [1, 3, 2, 3, 1, 1],
+    [2, 1, 1, 3, 1, 3],
+    [2, 3, 1, 1, 1, 3],
+    [2, 3, 1, 3, 1, 1],
+    [1, 1, 2, 1, 3, 3],
+    [1, 1, 2, 3, 3, 1],
+    [1, 3, 2, 1, 3, 1],
+    [1, 1, 3, 1, 2, 3],
+    [1, 1, 3, 3, 2, 1],
+    [1, 3, 3, 1, 2, 1],
+    [3, 1, 3, 1, 2, 1],
+    [2, 1, 1, 3, 3, 1],
+    [2, 3, 1, 1, 3, 1],
+    [2, 1, 3, 1, 1, 3],
+    [2, 1, 3, 3, 1, 1],
+    [2, 1, 3, 1, 3, 1],
+    [3, 1, 1, 1, 2, 3],
+    [3, 1, 1, 3, 2, 1],
+    [3, 3, 1, 1, 2, 1],
+    [3, 1, 2, 1, 1, 3],
+    [3, 1, 2, 3, 1, 1],
+    [3, 3, 2, 1, 1, 1],
+    [3, 1, 4, 1, 1, 1],
+    [2, 2, 1, 4, 1, 1],
+    [4, 3, 1, 1, 1, 1],
+    [1, 1, 1, 2, 2, 4],
+    [1, 1, 1, 4, 2, 2],
+    [1, 2, 1, 1, 2, 4],
+    [1, 2, 1, 4, 2, 1],
+    [1, 4, 1, 1, 2, 2],
+    [1, 4, 1, 2, 2, 1],
+    [1, 1, 2, 2, 1, 4],
+    [1, 1, 2, 4, 1, 2],
+    [1, 2, 2, 1, 1, 4],
+    [1, 2, 2, 4, 1, 1],
+    [1, 4, 2, 1, 1, 2],
+    [1, 4, 2, 2, 1, 1],
+    [2, 4, 1, 2, 1, 1],
+    [2, 2, 1, 1, 1, 4],
+    [4, 1, 3, 1, 1, 1],
+    [2, 4, 1, 1, 1, 2],
+    [1, 3, 4, 1, 1, 1],
+    [1, 1, 1, 2, 4, 2],
+    [1, 2, 1, 1, 4, 2],
+    [1, 2, 1, 2, 4, 1],
+    [1, 1, 4, 2, 1, 2],
+    [1, 2, 4, 1, 1, 2],
+    [1, 2, 4, 2, 1, 1],
+    [4, 1, 1, 2, 1, 2],
+    [4, 2, 1, 1, 1, 2],
+    [4, 2, 1, 2, 1, 1],
+    [2, 1, 2, 1, 4, 1],
+    [2, 1, 4, 1, 2, 1],
+    [4, 1, 2, 1, 2, 1],
+    [1, 1, 1, 1, 4, 3],
+    [1, 1, 1, 3, 4, 1],
+    [1, 3, 1, 1, 4, 1],
+    [1, 1, 4, 1, 1, 3],
+    [1, 1, 4, 3, 1, 1],
+    [4, 1, 1, 1, 1, 3],
+    [4, 1, 1, 3, 1, 1],
+    [1, 1, 3, 1, 4, 1],
+    [1, 1, 4, 1, 3, 1],
+    [3, 1, 1, 1, 4, 1],
+    [4, 1, 1, 1, 3, 1],
+    [2, 1, 1, 4, 1, 2],
+    [2, 1, 1, 2, 1, 4],
+    [2, 1, 1, 2, 3, 2],
+];
+
+/// Stop symbol, including the final bar
+const STOP: [u8; 7] = [2, 3, 3, 1, 1, 1, 2];
+
+/// Switch to code set B, in code set C
+const CODE_B: usize = 100;
+const START_B: usize = 104;
+const START_C: usize = 105;
+
+/// Modules of quiet zone required on each side of the bars
+pub(super) const QUIET_ZONE: u32 = 10;
+
+/// Encoded barcode as alternating bar and space widths, starting with a bar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Code128 {
+    pub widths: Vec<u8>,
+}
+
+impl Code128 {
+    /// Encode `value`, which must be non-empty printable ASCII
+    pub(super) fn encode(value: &str) -> ServiceResult<Self> {
+        if value.is_empty() || !value.bytes().all(|b| (b' '..=b'~').contains(&b)) {
+            return Err(ServiceError::BadRequest(format!(
+                "Barcode cannot be printed as Code 128: {:?}",
+                value
+            )));
+        }
+
+        let numeric = value.len() >= 2 && value.bytes().all(|b| b.is_ascii_digit());
+        let mut symbols = if numeric {
+            let digits = value.as_bytes();
+            let mut symbols = vec![START_C];
+            let pairs = digits.chunks_exact(2);
+            let last = pairs.remainder().first().copied();
+            symbols.extend(pairs.map(|pair| usize::from((pair[0] - b'0') * 10 + (pair[1] - b'0'))));
+            if let Some(digit) = last {
+                symbols.extend([CODE_B, usize::from(digit - b' ')]);
+            }
+            symbols
+        } else {
+            let mut symbols = vec![START_B];
+            symbols.extend(value.bytes().map(|b| usize::from(b - b' ')));
+            symbols
+        };
+
+        // Start symbol weighted 1, then each data symbol by its position
+        let checksum = symbols
+            .iter()
+            .enumerate()
+            .map(|(position, &symbol)| position.max(1) * symbol)
+            .sum::<usize>()
+            % 103;
+        symbols.push(checksum);
+
+        let mut widths: Vec<u8> = symbols
+            .iter()
+            .flat_map(|&symbol| PATTERNS[symbol])
+            .collect();
+        widths.extend(STOP);
+        Ok(Self { widths })
+    }
+
+    /// Total width of the bars and spaces in modules, without quiet zones
+    pub(super) fn modules(&self) -> u32 {
+        self.widths.iter().map(|&width| u32::from(width)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_are_distinct_with_even_bar_width() {
+        for (value, pattern) in PATTERNS.iter().enumerate() {
+            assert_eq!(pattern.iter().sum::<u8>(), 11, "symbol {}", value);
+            // Code 128's self-check: bar modules add up to an even number
+            assert_eq!(
+                (pattern[0] + pattern[2] + pattern[4]) % 2,
+                0,
+                "symbol {}",
+                value
+            );
+            assert!(!PATTERNS[..value].contains(pattern), "symbol {}", value);
+        }
+        assert_eq!(STOP.iter().sum::<u8>(), 13);
+    }
+
+    #[test]
+    fn test_encode_picks_code_set_and_checksum() {
+        // Start B, "P" "J" "J" "1" "2" "3" "C", check symbol 55, stop
+        let code = Code128::encode("PJJ123C").unwrap();
+        assert_eq!(&code.widths[..6], &PATTERNS[START_B]);
+        assert_eq!(&code.widths[6..12], &PATTERNS[48]);
+        assert_eq!(&code.widths[48..54], &PATTERNS[55]);
+        assert_eq!(code.modules(), 11 * 9 + 13);
+
+        // Digits pack two per symbol
+        let code = Code128::encode("622123456789").unwrap();
+        assert_eq!(&code.widths[..6], &PATTERNS[START_C]);
+        assert_eq!(&code.widths[6..12], &PATTERNS[62]);
+        assert_eq!(code.modules(), 11 * 8 + 13);
+
+        // An odd last digit switches to code set B
+        let code = Code128::encode("6221234567890").unwrap();
+        assert_eq!(&code.widths[..6], &PATTERNS[START_C]);
+        assert_eq!(&code.widths[42..48], &PATTERNS[CODE_B]);
+        assert_eq!(&code.widths[48..54], &PATTERNS[16]);
+        assert_eq!(code.modules(), 11 * 10 + 13);
+
+        assert!(Code128::encode("").is_err());
+        assert!(Code128::encode("دواء").is_err());
+    }
+}
@@ -0,0 +1,485 @@
+//! Shelf and barcode labels
+//!
+//! A label template sets the label size and which of an item's name,
+//! concentration, price and primary barcode appear on it, top to bottom.
+//! Labels are rendered as a PDF with one page per label, sized to the label,
+//! which roll label printers print as is.
+
+mod barcode;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use db_entity::id::Id;
+use db_entity::inventory_item::dto::InventoryItemWithStockResponse;
+use db_entity::inventory_item::{self, Entity as InventoryItem};
+use db_entity::inventory_price_history::{self, Entity as PriceHistory};
+use db_entity::setting::dto::SetSettingDto;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use tap::TapFallible;
+
+use self::barcode::{Code128, QUIET_ZONE};
+use crate::error::{ServiceError, ServiceResult};
+use crate::inventory::InventoryService;
+use crate::report::ReportService;
+use crate::report::pdf::{Align, PdfWriter};
+use crate::settings::SettingsService;
+
+/// Blank border around the label content, in millimetres
+const MARGIN_MM: f32 = 1.5;
+
+/// Item detail printed on a label
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelField {
+    Name,
+    Concentration,
+    Price,
+    /// Primary barcode as Code 128, with its value printed underneath
+    Barcode,
+}
+
+/// Label size and layout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelTemplate {
+    pub name: String,
+    pub width_mm: f32,
+    pub height_mm: f32,
+    /// Fields from top to bottom; the barcode takes the height left over
+    pub fields: Vec<LabelField>,
+    /// Base text size in points; prices print larger
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+}
+
+fn default_font_size() -> f32 {
+    8.0
+}
+
+impl LabelTemplate {
+    /// Templates available without configuration
+    pub fn built_in() -> Vec<Self> {
+        vec![
+            Self {
+                name: "shelf".to_string(),
+                width_mm: 50.0,
+                height_mm: 30.0,
+                fields: vec![
+                    LabelField::Name,
+                    LabelField::Concentration,
+                    LabelField::Price,
+                    LabelField::Barcode,
+                ],
+                font_size: 8.0,
+            },
+            Self {
+                name: "barcode".to_string(),
+                width_mm: 38.0,
+                height_mm: 25.0,
+                fields: vec![LabelField::Name, LabelField::Barcode],
+                font_size: 7.0,
+            },
+        ]
+    }
+
+    fn validate(&self) -> ServiceResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(ServiceError::BadRequest(
+                "Label template name is required".to_string(),
+            ));
+        }
+        if !(10.0..=200.0).contains(&self.width_mm) || !(10.0..=200.0).contains(&self.height_mm) {
+            return Err(ServiceError::BadRequest(format!(
+                "Label size must be between 10 and 200 mm, got {} x {} mm",
+                self.width_mm, self.height_mm
+            )));
+        }
+        if !(4.0..=24.0).contains(&self.font_size) {
+            return Err(ServiceError::BadRequest(format!(
+                "Label font size must be between 4 and 24 pt, got {}",
+                self.font_size
+            )));
+        }
+        if self.fields.is_empty() {
+            return Err(ServiceError::BadRequest(
+                "Label template needs at least one field".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Items to print labels for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "query", rename_all = "snake_case")]
+pub enum LabelSelection {
+    /// The given items, in order
+    Items(Vec<Id>),
+    /// Active items whose price was recorded at or after the given time
+    PriceChangedSince(DateTime<Utc>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelOptions {
+    /// Name of the template to use
+    pub template: String,
+    pub selection: LabelSelection,
+    /// Labels printed per item, from 1 to [`MAX_LABEL_COPIES`]
+    #[serde(default = "default_copies")]
+    pub copies: u32,
+}
+
+/// Most labels printed per item in one job, so a mistyped count cannot fill
+/// the printer queue
+pub const MAX_LABEL_COPIES: u32 = 100;
+
+fn default_copies() -> u32 {
+    1
+}
+
+impl LabelOptions {
+    /// Labels printed per item, clamped to 1..=[`MAX_LABEL_COPIES`]
+    pub fn copies(&self) -> usize {
+        self.copies.clamp(1, MAX_LABEL_COPIES) as usize
+    }
+}
+
+/// Item details printed on a label
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Label {
+    pub inventory_item_id: Id,
+    pub name: String,
+    pub concentration: String,
+    pub price: f64,
+    /// Primary barcode, or the first one when none is marked primary
+    pub barcode: Option<String>,
+}
+
+impl From<InventoryItemWithStockResponse> for Label {
+    fn from(item: InventoryItemWithStockResponse) -> Self {
+        let barcode = item
+            .barcodes
+            .iter()
+            .find(|barcode| barcode.is_primary)
+            .or(item.barcodes.first())
+            .map(|barcode| barcode.barcode.clone());
+
+        Self {
+            inventory_item_id: item.id,
+            name: item.name,
+            concentration: item.concentration,
+            price: item.unit_price,
+            barcode,
+        }
+    }
+}
+
+/// Labels written to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedLabels {
+    pub path: PathBuf,
+    /// Number of labels, including copies
+    pub labels: usize,
+    pub size: u64,
+}
+
+// ============================================================================
+// Label Service
+// ============================================================================
+
+/// Shelf and barcode label service
+pub struct LabelService {
+    db: Arc<DatabaseConnection>,
+    inventory: Arc<InventoryService>,
+    settings: Arc<SettingsService>,
+    report: Arc<ReportService>,
+}
+
+impl LabelService {
+    /// Setting key holding the custom label templates as a JSON array
+    pub const TEMPLATES_SETTING: &'static str = "labels.templates";
+
+    /// Create a new label service
+    pub fn new(
+        db: Arc<DatabaseConnection>,
+        inventory: Arc<InventoryService>,
+        settings: Arc<SettingsService>,
+        report: Arc<ReportService>,
+    ) -> Self {
+        Self {
+            db,
+            inventory,
+            settings,
+            report,
+        }
+    }
+
+    // ========================================================================
+    // Templates
+    // ========================================================================
+
+    /// Built-in templates followed by custom ones; a custom template replaces
+    /// the built-in template of the same name
+    pub async fn templates(&self) -> ServiceResult<Vec<LabelTemplate>> {
+        let custom = self.custom_templates().await?;
+        let mut templates: Vec<LabelTemplate> = LabelTemplate::built_in()
+            .into_iter()
+            .filter(|template| !custom.iter().any(|other| other.name == template.name))
+            .collect();
+        templates.extend(custom);
+        Ok(templates)
+    }
+
+    /// Get a template by name
+    pub async fn template(&self, name: &str) -> ServiceResult<LabelTemplate> {
+        self.templates()
+            .await?
+            .into_iter()
+            .find(|template| template.name == name)
+            .ok_or_else(|| ServiceError::NotFound(format!("Label template not found: {}", name)))
+    }
+
+    /// Create or replace a custom template
+    pub async fn save_template(&self, template: LabelTemplate) -> ServiceResult<LabelTemplate> {
+        template.validate()?;
+
+        let mut custom = self.custom_templates().await?;
+        match custom.iter_mut().find(|other| other.name == template.name) {
+            Some(existing) => *existing = template.clone(),
+            None => custom.push(template.clone()),
+        }
+        self.store_templates(custom).await?;
+
+        tracing::info!("Saved label template: {}", template.name);
+        Ok(template)
+    }
+
+    /// Delete a custom template
+    pub async fn delete_template(&self, name: &str) -> ServiceResult<()> {
+        let mut custom = self.custom_templates().await?;
+        let count = custom.len();
+        custom.retain(|template| template.name != name);
+        if custom.len() == count {
+            return Err(ServiceError::NotFound(format!(
+                "Custom label template not found: {}",
+                name
+            )));
+        }
+        self.store_templates(custom).await?;
+
+        tracing::info!("Deleted label template: {}", name);
+        Ok(())
+    }
+
+    async fn custom_templates(&self) -> ServiceResult<Vec<LabelTemplate>> {
+        match self.settings.get(Self::TEMPLATES_SETTING).await {
+            Ok(setting) => serde_json::from_value(setting.value).map_err(|e| {
+                ServiceError::Internal(format!(
+                    "Invalid {} setting: {}",
+                    Self::TEMPLATES_SETTING,
+                    e
+                ))
+            }),
+            Err(ServiceError::NotFound(_)) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn store_templates(&self, templates: Vec<LabelTemplate>) -> ServiceResult<()> {
+        let value = serde_json::to_value(templates)
+            .map_err(|e| ServiceError::Internal(format!("Failed to encode templates: {}", e)))?;
+        self.settings
+            .set(SetSettingDto {
+                key: Self::TEMPLATES_SETTING.to_string(),
+                value,
+                category: Some("labels".to_string()),
+                description: None,
+                updated_by: None,
+            })
+            .await?;
+        Ok(())
+    }
+
+    // ========================================================================
+    // Labels
+    // ========================================================================
+
+    /// Collect the details of the selected items
+    pub async fn labels(&self, selection: LabelSelection) -> ServiceResult<Vec<Label>> {
+        let ids = match selection {
+            LabelSelection::Items(ids) => ids,
+            LabelSelection::PriceChangedSince(since) => self.price_changed_since(since).await?,
+        };
+
+        let mut labels = Vec::with_capacity(ids.len());
+        for id in ids {
+            labels.push(Label::from(self.inventory.get_by_id(id).await?));
+        }
+        Ok(labels)
+    }
+
+    /// Active items with a price recorded at or after `since`, by name
+    async fn price_changed_since(&self, since: DateTime<Utc>) -> ServiceResult<Vec<Id>> {
+        let changed: Vec<Id> = PriceHistory::find()
+            .select_only()
+            .column(inventory_price_history::Column::InventoryItemId)
+            .distinct()
+            .filter(inventory_price_history::Column::RecordedAt.gte(since))
+            .into_tuple()
+            .all(&*self.db)
+            .await?;
+
+        InventoryItem::find()
+            .select_only()
+            .column(inventory_item::Column::Id)
+            .filter(inventory_item::Column::Id.is_in(changed))
+            .filter(inventory_item::Column::IsActive.eq(true))
+            .filter(inventory_item::Column::DeletedAt.is_null())
+            .order_by_asc(inventory_item::Column::Name)
+            .into_tuple()
+            .all(&*self.db)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Render the selected labels, returning the PDF and the label count
+    pub async fn render(&self, options: LabelOptions) -> ServiceResult<(Vec<u8>, usize)> {
+        let template = self.template(&options.template).await?;
+        let labels = self.labels(options.selection).await?;
+        if labels.is_empty() {
+            return Err(ServiceError::BadRequest(
+                "No items selected for labels".to_string(),
+            ));
+        }
+
+        let font = self.report.pdf_font().await?;
+        let mut writer = PdfWriter::new(&font, false)?;
+        let copies = options.copies();
+        for label in &labels {
+            for _ in 0..copies {
+                render_label(&mut writer, &template, label);
+            }
+        }
+        Ok((writer.finish(&font), labels.len() * copies))
+    }
+
+    /// Render the selected labels and write them to `path`
+    pub async fn save(&self, options: LabelOptions, path: &Path) -> ServiceResult<RenderedLabels> {
+        let (content, labels) = self.render(options).await?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error(parent, e))?;
+        }
+        tokio::fs::write(path, &content)
+            .await
+            .map_err(|e| io_error(path, e))
+            .tap_err(|e| tracing::error!("Failed to write labels: {}", e))?;
+
+        tracing::info!("Saved {} labels to {}", labels, path.display());
+        Ok(RenderedLabels {
+            path: path.to_path_buf(),
+            labels,
+            size: content.len() as u64,
+        })
+    }
+}
+
+// ============================================================================
+// Layout
+// ============================================================================
+
+/// Draw one label on its own page
+fn render_label(writer: &mut PdfWriter<'_>, template: &LabelTemplate, label: &Label) {
+    let (width, height) = (points(template.width_mm), points(template.height_mm));
+    let margin = points(MARGIN_MM);
+    let size = |field: LabelField| match field {
+        LabelField::Name | LabelField::Barcode => template.font_size,
+        LabelField::Concentration => template.font_size * 0.9,
+        LabelField::Price => template.font_size * 1.5,
+    };
+
+    let text_height: f32 = template
+        .fields
+        .iter()
+        .filter(|&&field| field != LabelField::Barcode)
+        .map(|&field| size(field) * 1.25)
+        .sum();
+    let barcode_height = (height - 2.0 * margin - text_height).max(0.0);
+
+    let mut y = height - margin;
+    for &field in &template.fields {
+        let size = size(field);
+        let text = match field {
+            LabelField::Name => label.name.clone(),
+            LabelField::Concentration => label.concentration.clone(),
+            LabelField::Price => format!("{:.2}", label.price),
+            LabelField::Barcode => {
+                if let Some(value) = &label.barcode {
+                    let bottom = y - barcode_height;
+                    draw_barcode(writer, value, (margin, width - margin), (bottom, y), size);
+                }
+                y -= barcode_height;
+                continue;
+            }
+        };
+        writer.text(&text, size, 0.0, width, Align::Center, y - size);
+        y -= size * 1.25;
+    }
+
+    writer.end_page(width, height);
+}
+
+/// Draw `value` as Code 128 bars across `x0..x1` in the band `y0..y1`, with
+/// the value printed underneath at `size` points
+fn draw_barcode(
+    writer: &mut PdfWriter<'_>,
+    value: &str,
+    (x0, x1): (f32, f32),
+    (y0, y1): (f32, f32),
+    size: f32,
+) {
+    let code = match Code128::encode(value) {
+        Ok(code) => code,
+        Err(e) => {
+            tracing::warn!("Skipping barcode on label: {}", e);
+            return;
+        }
+    };
+
+    let caption = size * 1.25;
+    let bar_height = y1 - y0 - caption;
+    if bar_height <= 0.0 {
+        return;
+    }
+
+    let module = (x1 - x0) / (code.modules() + 2 * QUIET_ZONE) as f32;
+    let mut x = x0 + QUIET_ZONE as f32 * module;
+    let content = writer.content();
+    for (index, &widths) in code.widths.iter().enumerate() {
+        let bar_width = f32::from(widths) * module;
+        // Even positions are bars, odd positions spaces
+        if index % 2 == 0 {
+            content.rect(x, y0 + caption, bar_width, bar_height);
+        }
+        x += bar_width;
+    }
+    content.fill_nonzero();
+
+    writer.text(value, size, x0, x1, Align::Center, y0 + size * 0.25);
+}
+
+/// Millimetres to PDF points
+fn points(mm: f32) -> f32 {
+    mm * 72.0 / 25.4
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> ServiceError {
+    ServiceError::Internal(format!("Failed to access {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests;
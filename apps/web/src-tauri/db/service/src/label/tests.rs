@@ -0,0 +1,184 @@
+use super::*;
+use crate::test_support::{TestItem, TestServices, change_price, create_items, services};
+
+fn label_service(services: &TestServices) -> LabelService {
+    LabelService::new(
+        services.db.clone(),
+        services.inventory.clone(),
+        services.settings.clone(),
+        services.report.clone(),
+    )
+}
+
+fn template(name: &str, width_mm: f32) -> LabelTemplate {
+    LabelTemplate {
+        name: name.to_string(),
+        width_mm,
+        height_mm: 20.0,
+        fields: vec![LabelField::Price, LabelField::Barcode],
+        font_size: 8.0,
+    }
+}
+
+#[tokio::test]
+async fn test_custom_templates_replace_built_ins() {
+    let services = services().await;
+    let label = label_service(&services);
+
+    let names: Vec<String> = label
+        .templates()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+    assert_eq!(names, vec!["shelf", "barcode"]);
+
+    label.save_template(template("shelf", 60.0)).await.unwrap();
+    label.save_template(template("price", 30.0)).await.unwrap();
+    label.save_template(template("price", 35.0)).await.unwrap();
+
+    let templates = label.templates().await.unwrap();
+    let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["barcode", "shelf", "price"]);
+    assert_eq!(label.template("shelf").await.unwrap().width_mm, 60.0);
+    assert_eq!(label.template("price").await.unwrap().width_mm, 35.0);
+
+    // Deleting the custom shelf template brings back the built-in one
+    label.delete_template("shelf").await.unwrap();
+    assert_eq!(label.template("shelf").await.unwrap().width_mm, 50.0);
+    assert!(matches!(
+        label.delete_template("barcode").await,
+        Err(ServiceError::NotFound(_))
+    ));
+    assert!(matches!(
+        label.save_template(template("tiny", 5.0)).await,
+        Err(ServiceError::BadRequest(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_labels_after_price_change() {
+    let services = services().await;
+    let label = label_service(&services);
+    let inventory = &services.inventory;
+    let ids = create_items(
+        &services.db,
+        inventory,
+        &[
+            TestItem {
+                barcodes: &["INT-001", "6221234567890"],
+                ..TestItem::new("Paracetamol")
+            },
+            TestItem {
+                barcodes: &["5012345678900"],
+                ..TestItem::new("Amoxicillin")
+            },
+            TestItem::new("Ibuprofen"),
+        ],
+    )
+    .await;
+
+    let since = Utc::now();
+    change_price(inventory, ids[0], 15.0).await;
+    change_price(inventory, ids[2], 9.75).await;
+
+    let labels = label
+        .labels(LabelSelection::PriceChangedSince(since))
+        .await
+        .unwrap();
+    let summary: Vec<(&str, f64, Option<&str>)> = labels
+        .iter()
+        .map(|label| (label.name.as_str(), label.price, label.barcode.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Ibuprofen", 9.75, None),
+            ("Paracetamol", 15.0, Some("6221234567890")),
+        ]
+    );
+
+    let labels = label
+        .labels(LabelSelection::Items(vec![ids[1]]))
+        .await
+        .unwrap();
+    assert_eq!(labels[0].barcode.as_deref(), Some("5012345678900"));
+}
+
+#[test]
+fn test_label_copies_are_clamped() {
+    let options = |copies| LabelOptions {
+        template: "shelf".to_string(),
+        selection: LabelSelection::Items(vec![]),
+        copies,
+    };
+
+    assert_eq!(options(0).copies(), 1);
+    assert_eq!(options(3).copies(), 3);
+    assert_eq!(options(u32::MAX).copies(), MAX_LABEL_COPIES as usize);
+}
+
+#[tokio::test]
+async fn test_save_labels_as_pdf() {
+    let services = services().await;
+    let label = label_service(&services);
+    let ids = create_items(
+        &services.db,
+        &services.inventory,
+        &[TestItem {
+            barcodes: &["6221234567890"],
+            ..TestItem::new("Paracetamol")
+        }],
+    )
+    .await;
+
+    let options = LabelOptions {
+        template: "shelf".to_string(),
+        selection: LabelSelection::Items(ids),
+        copies: 3,
+    };
+    // Rendering needs a real font; skip where the system has none of ours
+    let Some(font) = crate::report::FONT_CANDIDATES
+        .iter()
+        .find(|path| Path::new(path).is_file())
+    else {
+        return;
+    };
+    services
+        .settings
+        .set(SetSettingDto {
+            key: ReportService::PDF_FONT_SETTING.to_string(),
+            value: serde_json::json!(font),
+            category: Some("reports".to_string()),
+            description: None,
+            updated_by: None,
+        })
+        .await
+        .unwrap();
+
+    let path = std::env::temp_dir()
+        .join(format!("meditrack-labels-{}", Id::new()))
+        .join("labels.pdf");
+    let saved = label.save(options, &path).await.unwrap();
+    assert_eq!(saved.labels, 3);
+
+    let content = std::fs::read(&path).unwrap();
+    assert!(content.starts_with(b"%PDF-"));
+    let pdf = String::from_utf8_lossy(&content);
+    assert_eq!(pdf.matches("/Type /Page\n").count(), 3);
+    // 50 x 30 mm
+    assert!(pdf.contains("/MediaBox [0 0 141.73228 85.03937]"));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    let empty = LabelOptions {
+        template: "shelf".to_string(),
+        selection: LabelSelection::Items(Vec::new()),
+        copies: 1,
+    };
+    assert!(matches!(
+        label.render(empty).await,
+        Err(ServiceError::BadRequest(_))
+    ));
+}
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use derive_getters::Getters;
@@ -6,10 +7,20 @@ use typed_builder::TypedBuilder;
 
 use db_migration::run_migrations;
 
+mod analytics;
+mod backup;
+mod diagnostics;
+mod events;
+mod export;
+mod import;
 mod inventory;
+mod label;
 mod manufacturer;
 mod onboarding;
+mod receipt;
+mod report;
 mod role;
+mod scheduler;
 mod session;
 mod settings;
 mod staff;
@@ -19,11 +30,20 @@ mod error;
 pub use error::{ServiceError, ServiceResult};
 
 mod jwt;
-pub use jwt::{Claims, JwtError, JwtService};
+pub use jwt::{
+    Claims, DEFAULT_SECRET as DEFAULT_JWT_SECRET, JwtError, JwtKey, JwtKeyAlgorithm, JwtService,
+    RetiredSecret, jwks,
+};
 
 mod pagination;
 pub use pagination::{PaginationParams, PaginationResult};
 
+#[cfg(test)]
+mod test_support;
+
+// Export Event bus
+pub use events::{DomainEvent, EventBus};
+
 // Export Staff service
 pub use staff::{StaffService, StaffStatistics};
 
@@ -60,6 +80,50 @@ pub use inventory::opening_balances::OpeningBalanceService;
 // Export Role service
 pub use role::RoleService;
 
+// Export Export service
+pub use export::{
+    ExportFilter, ExportFormat, ExportOptions, ExportService, ExportTable, ExportedFile,
+    ExportedTable,
+};
+
+// Export Analytics service
+pub use analytics::{
+    AbcClass, AnalyticsService, AnalyticsSortBy, ApplyStockRecommendations, ItemAnalytics,
+    ItemAnalyticsQuery,
+};
+
+// Export Report service
+pub use report::{
+    ColumnAlign, PharmacyHeader, RenderedReport, ReportColumn, ReportDataset, ReportField,
+    ReportFormat, ReportKind, ReportLanguage, ReportOptions, ReportService, ReportTable,
+};
+
+// Export Label service
+pub use label::{
+    Label, LabelField, LabelOptions, LabelSelection, LabelService, LabelTemplate, MAX_LABEL_COPIES,
+    RenderedLabels,
+};
+
+// Export Receipt service
+pub use receipt::{
+    Receipt, ReceiptLine, ReceiptOptions, ReceiptPaper, ReceiptService, ReceiptSource,
+};
+
+// Export Backup service
+pub use backup::{BackupCipher, BackupFile, BackupKdf, BackupManifest, BackupService, BackupTable};
+
+// Export Import sheet
+pub use import::ImportSheet;
+
+// Export Connection diagnostics
+pub use diagnostics::{
+    ConnectionError, ConnectionFailure, ConnectionReport, ExtensionState, MigrationState,
+    MigrationStatus, REQUIRED_EXTENSIONS, test_connection,
+};
+
+// Export Job Scheduler
+pub use scheduler::{CronSchedule, JobScheduler, ScheduledJob};
+
 /// Database connection configuration
 pub struct DatabaseConfig {
     pub url: String,
@@ -75,6 +139,23 @@ pub struct JwtConfig {
     pub issuer: String,
     pub audience: String,
     pub expiration_hours: i64,
+    /// Secrets replaced by a rotation that still verify tokens
+    pub retired_secrets: Vec<RetiredSecret>,
+    /// Accept [`DEFAULT_JWT_SECRET`], for development profiles
+    pub allow_default_secret: bool,
+    /// Key pairs that verify tokens naming their `kid`
+    pub keys: Vec<JwtKey>,
+    /// `kid` of the key pair tokens are signed with; empty to sign with the
+    /// secret (HS256)
+    pub signing_key: String,
+}
+
+/// Database backup configuration
+pub struct BackupConfig {
+    /// Directory backups are written to
+    pub dir: PathBuf,
+    /// Cipher for scheduled backups, used when encryption is turned on
+    pub cipher: Option<Arc<dyn BackupCipher>>,
 }
 
 /// Service manager containing all application services
@@ -84,6 +165,10 @@ pub struct ServiceManager {
     #[builder(setter(into))]
     db: Arc<DatabaseConnection>,
 
+    /// Domain event bus shared by all services
+    #[builder(setter(into))]
+    events: Arc<EventBus>,
+
     /// Staff service
     #[builder(setter(into))]
     staff: Arc<StaffService>,
@@ -128,17 +213,56 @@ pub struct ServiceManager {
     #[builder(setter(into))]
     opening_balance: Arc<OpeningBalanceService>,
 
+    /// Data export service
+    #[builder(setter(into))]
+    export: Arc<ExportService>,
+
+    /// Database backup service
+    #[builder(setter(into))]
+    backup: Arc<BackupService>,
+
+    /// Sales and consumption analytics service
+    #[builder(setter(into))]
+    analytics: Arc<AnalyticsService>,
+
+    /// Printable report service
+    #[builder(setter(into))]
+    report: Arc<ReportService>,
+
+    /// Shelf and barcode label service
+    #[builder(setter(into))]
+    label: Arc<LabelService>,
+
+    /// Sale and deposit receipt service
+    #[builder(setter(into))]
+    receipt: Arc<ReceiptService>,
+
     /// Role service
     #[builder(setter(into))]
     role: Arc<RoleService>,
+
+    /// Background job scheduler
+    #[builder(setter(into))]
+    scheduler: Arc<JobScheduler>,
 }
 
 impl ServiceManager {
-    /// Initialize service manager with database and JWT configuration
+    /// Initialize service manager with database, JWT and backup configuration
+    ///
+    /// Refuses [`DEFAULT_JWT_SECRET`] unless the JWT configuration allows it.
     pub async fn init(
         db_config: DatabaseConfig,
         jwt_config: JwtConfig,
+        backup_config: BackupConfig,
     ) -> Result<Self, ServiceError> {
+        // Anyone can sign tokens with the published default secret
+        if jwt_config.secret == DEFAULT_JWT_SECRET && !jwt_config.allow_default_secret {
+            return Err(ServiceError::InsecureConfig(
+                "jwt.secret is the published default; replace it with `meditrack config generate-jwt-secret --save`"
+                    .to_string(),
+            ));
+        }
+
         // Build database connection options
         let mut opt = ConnectOptions::new(db_config.url);
         opt.max_connections(db_config.max_connections)
@@ -170,9 +294,12 @@ impl ServiceManager {
             jwt_config.audience,
             jwt_config.expiration_hours,
         )
-        .expect("Failed to create JWT service");
+        .and_then(|service| service.with_retired_secrets(jwt_config.retired_secrets))
+        .and_then(|service| service.with_key_set(jwt_config.keys, &jwt_config.signing_key))
+        .map_err(|e| ServiceError::Internal(format!("Failed to create JWT service: {}", e)))?;
 
         let db = Arc::new(db);
+        let events = Arc::new(EventBus::default());
         let staff = Arc::new(StaffService::new(db.clone()));
         let jwt_service = Arc::new(jwt_service);
         let user = Arc::new(UserService::new(
@@ -181,18 +308,55 @@ impl ServiceManager {
             jwt_service.clone(),
         ));
         let onboarding = Arc::new(OnboardingService::new(user.clone()));
-        let session = Arc::new(SessionService::new(db.clone()));
-        let settings = Arc::new(SettingsService::new(db.clone()));
-        let inventory = Arc::new(InventoryService::new(db.clone()));
+        let session = Arc::new(SessionService::new(db.clone(), events.clone()));
+        let settings = Arc::new(SettingsService::new(db.clone(), events.clone()));
+        let inventory = Arc::new(InventoryService::new(db.clone(), events.clone()));
         let manufacturer = Arc::new(ManufacturerService::new(db.clone()));
         let medicine_forms = Arc::new(MedicineFormsService::new(db.clone()));
         let price_history = Arc::new(PriceHistoryService::new(db.clone()));
         let stock_history = Arc::new(StockHistoryService::new(db.clone()));
-        let opening_balance = Arc::new(OpeningBalanceService::new(db.clone()));
+        let opening_balance = Arc::new(OpeningBalanceService::new(db.clone(), events.clone()));
+        let export = Arc::new(ExportService::new(db.clone()));
+        let backup = Arc::new(BackupService::new(db.clone(), backup_config.dir));
+        let analytics = Arc::new(AnalyticsService::new(db.clone()));
+        let report = Arc::new(ReportService::new(
+            inventory.clone(),
+            opening_balance.clone(),
+            settings.clone(),
+        ));
+        let label = Arc::new(LabelService::new(
+            db.clone(),
+            inventory.clone(),
+            settings.clone(),
+            report.clone(),
+        ));
+        let receipt = Arc::new(ReceiptService::new(
+            db.clone(),
+            settings.clone(),
+            report.clone(),
+        ));
         let role = Arc::new(RoleService::new(db.clone()));
 
+        // Register background jobs (the scheduler is started by the application)
+        let mut scheduler = JobScheduler::new(db.clone(), settings.clone());
+        scheduler.register(Arc::new(scheduler::SessionCleanupJob::new(session.clone())));
+        scheduler.register(Arc::new(scheduler::LowStockCheckJob::new(
+            inventory.clone(),
+        )));
+        scheduler.register(Arc::new(scheduler::NearExpiryAlertJob::new(
+            opening_balance.clone(),
+            settings.clone(),
+        )));
+        scheduler.register(Arc::new(scheduler::DatabaseBackupJob::new(
+            backup.clone(),
+            settings.clone(),
+            backup_config.cipher,
+        )));
+        let scheduler = Arc::new(scheduler);
+
         Ok(Self::builder()
             .db(db.clone())
+            .events(events)
             .staff(staff)
             .user(user)
             .onboarding(onboarding)
@@ -204,7 +368,14 @@ impl ServiceManager {
             .price_history(price_history)
             .stock_history(stock_history)
             .opening_balance(opening_balance)
+            .export(export)
+            .backup(backup)
+            .analytics(analytics)
+            .report(report)
+            .label(label)
+            .receipt(receipt)
             .role(role)
+            .scheduler(scheduler)
             .build())
     }
 }
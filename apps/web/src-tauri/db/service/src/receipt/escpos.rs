@@ -0,0 +1,294 @@
+//! ESC/POS command encoding
+//!
+//! Only the commands every thermal receipt printer understands are used:
+//! initialise, code page, alignment, emphasis, double size, feed and cut.
+//! Text is sent in code page 864, which has ASCII in its lower half and the
+//! Arabic letters in their contextual forms in the upper half: Arabic is
+//! shaped and put in visual order here, since printers do neither. Other
+//! characters print as `?`.
+
+use unicode_bidi::BidiInfo;
+
+const ESC: u8 = 0x1B;
+const GS: u8 = 0x1D;
+const LF: u8 = b'\n';
+
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Justify {
+    Left = 0,
+    Center = 1,
+}
+
+/// Builds an ESC/POS byte stream for a printer `columns` characters wide
+pub(super) struct EscPos {
+    buf: Vec<u8>,
+    columns: usize,
+    /// Characters are twice as wide
+    double: bool,
+}
+
+impl EscPos {
+    /// `code_page` is the printer's number for code page 864 in `ESC t`
+    pub(super) fn new(columns: usize, code_page: u8) -> Self {
+        Self {
+            // ESC @: reset the printer to its defaults; ESC t n: code page
+            buf: vec![ESC, b'@', ESC, b't', code_page],
+            columns,
+            double: false,
+        }
+    }
+
+    /// ESC a n: justification of the following lines
+    pub(super) fn justify(&mut self, justify: Justify) -> &mut Self {
+        self.buf.extend([ESC, b'a', justify as u8]);
+        self
+    }
+
+    /// ESC E n: emphasized (bold) text
+    pub(super) fn bold(&mut self, on: bool) -> &mut Self {
+        self.buf.extend([ESC, b'E', u8::from(on)]);
+        self
+    }
+
+    /// GS ! n: double width and height
+    pub(super) fn double(&mut self, on: bool) -> &mut Self {
+        self.buf.extend([GS, b'!', if on { 0x11 } else { 0x00 }]);
+        self.double = on;
+        self
+    }
+
+    /// Print `text`, cut to the line width
+    pub(super) fn line(&mut self, text: &str) -> &mut Self {
+        let text = encode(text, self.width());
+        self.raw_line(&text)
+    }
+
+    /// Print `left` and `right` at either end of a line, cutting `left` short
+    /// when both do not fit
+    pub(super) fn pair(&mut self, left: &str, right: &str) -> &mut Self {
+        let right = encode(right, self.width());
+        let room = self.width().saturating_sub(right.len() + 1);
+        let left = encode(left, room);
+        let padding = self.width().saturating_sub(left.len() + right.len());
+
+        let mut line = left;
+        line.resize(line.len() + padding, b' ');
+        line.extend(right);
+        self.raw_line(&line)
+    }
+
+    /// Print a dashed rule across the line
+    pub(super) fn rule(&mut self) -> &mut Self {
+        let rule = "-".repeat(self.width());
+        self.line(&rule)
+    }
+
+    /// ESC d n: feed `lines` blank lines
+    pub(super) fn feed(&mut self, lines: u8) -> &mut Self {
+        self.buf.extend([ESC, b'd', lines]);
+        self
+    }
+
+    /// GS V 66 n: feed to the cutter and cut, leaving one uncut point
+    pub(super) fn cut(&mut self) -> &mut Self {
+        self.buf.extend([GS, b'V', 66, 0]);
+        self
+    }
+
+    pub(super) fn finish(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.buf)
+    }
+
+    fn raw_line(&mut self, text: &[u8]) -> &mut Self {
+        self.buf.extend(text);
+        self.buf.push(LF);
+        self
+    }
+
+    /// Characters per line at the current size
+    fn width(&self) -> usize {
+        if self.double {
+            self.columns / 2
+        } else {
+            self.columns
+        }
+    }
+}
+
+// ============================================================================
+// Code Page 864
+// ============================================================================
+
+/// `text` in code page 864 and visual order, at most `width` characters
+///
+/// Every byte prints one character, so the length of the result is its width
+/// on paper.
+pub(super) fn encode(text: &str, width: usize) -> Vec<u8> {
+    // Vowel marks have no room on a receipt line
+    let text: String = text
+        .chars()
+        .filter(|c| !('\u{064B}'..='\u{0652}').contains(c) && *c != '\u{0670}')
+        .take(width)
+        .collect();
+
+    let bidi = BidiInfo::new(&text, None);
+    let mut bytes = Vec::with_capacity(text.len());
+    for paragraph in &bidi.paragraphs {
+        let (levels, runs) = bidi.visual_runs(paragraph, paragraph.range.clone());
+        for run in runs {
+            let mut shaped = shape(&text[run.clone()]);
+            if levels[run.start].is_rtl() {
+                shaped.reverse();
+            }
+            bytes.extend(shaped);
+        }
+    }
+    bytes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Joining {
+    /// Joins neither neighbour (hamza)
+    None,
+    /// Joins the letter before it only (alef, dal, reh, waw, ...)
+    Right,
+    /// Joins the letters on both sides
+    Dual,
+}
+
+/// Arabic letter with its code page 864 bytes in isolated, final, initial
+/// and medial form
+#[derive(Debug, Clone, Copy)]
+struct Letter {
+    joining: Joining,
+    forms: [u8; 4],
+}
+
+const LAM: char = '\u{0644}';
+
+/// Code page 864 has only some forms of each letter; the missing final form
+/// prints as the isolated one and the missing medial form as the initial one
+fn letter(c: char) -> Option<Letter> {
+    let dual = |isolated, fin, initial, medial| Letter {
+        joining: Joining::Dual,
+        forms: [isolated, fin, initial, medial],
+    };
+    let right = |isolated, fin| Letter {
+        joining: Joining::Right,
+        forms: [isolated, fin, isolated, fin],
+    };
+
+    Some(match c {
+        '\u{0621}' => Letter {
+            joining: Joining::None,
+            forms: [0xC1; 4],
+        },
+        '\u{0622}' => right(0xC2, 0xA2),
+        '\u{0623}' => right(0xC3, 0xA5),
+        '\u{0624}' => right(0xC4, 0xC4),
+        // Alef with hamza below has no glyph; plain alef is closest
+        '\u{0625}' | '\u{0627}' => right(0xC7, 0xA8),
+        '\u{0626}' => dual(0xE9, 0xF5, 0xC6, 0xC6),
+        '\u{0628}' => dual(0xA9, 0xA9, 0xC8, 0xC8),
+        '\u{0629}' => right(0xC9, 0xC9),
+        '\u{062A}' => dual(0xAA, 0xAA, 0xCA, 0xCA),
+        '\u{062B}' => dual(0xAB, 0xAB, 0xCB, 0xCB),
+        '\u{062C}' => dual(0xAD, 0xAD, 0xCC, 0xCC),
+        '\u{062D}' => dual(0xAE, 0xAE, 0xCD, 0xCD),
+        '\u{062E}' => dual(0xAF, 0xAF, 0xCE, 0xCE),
+        '\u{062F}' => right(0xCF, 0xCF),
+        '\u{0630}' => right(0xD0, 0xD0),
+        '\u{0631}' => right(0xD1, 0xD1),
+        '\u{0632}' => right(0xD2, 0xD2),
+        '\u{0633}' => dual(0xBC, 0xBC, 0xD3, 0xD3),
+        '\u{0634}' => dual(0xBD, 0xBD, 0xD4, 0xD4),
+        '\u{0635}' => dual(0xBE, 0xBE, 0xD5, 0xD5),
+        '\u{0636}' => dual(0xEB, 0xEB, 0xD6, 0xD6),
+        '\u{0637}' => dual(0xD7, 0xD7, 0xD7, 0xD7),
+        '\u{0638}' => dual(0xD8, 0xD8, 0xD8, 0xD8),
+        '\u{0639}' => dual(0xDF, 0xC5, 0xD9, 0xEC),
+        '\u{063A}' => dual(0xEE, 0xED, 0xDA, 0xF7),
+        '\u{0640}' => dual(0xE0, 0xE0, 0xE0, 0xE0),
+        '\u{0641}' => dual(0xBA, 0xBA, 0xE1, 0xE1),
+        '\u{0642}' => dual(0xF8, 0xF8, 0xE2, 0xE2),
+        '\u{0643}' => dual(0xFC, 0xFC, 0xE3, 0xE3),
+        LAM => dual(0xFB, 0xFB, 0xE4, 0xE4),
+        '\u{0645}' => dual(0xEF, 0xEF, 0xE5, 0xE5),
+        '\u{0646}' => dual(0xF2, 0xF2, 0xE6, 0xE6),
+        '\u{0647}' => dual(0xF3, 0xF3, 0xE7, 0xF4),
+        '\u{0648}' => right(0xE8, 0xE8),
+        '\u{0649}' => right(0xE9, 0xF5),
+        '\u{064A}' => dual(0xFD, 0xF6, 0xEA, 0xEA),
+        _ => return None,
+    })
+}
+
+/// Lam-alef ligature in isolated and final form for the alef after a lam
+fn lam_alef(alef: char) -> Option<[u8; 2]> {
+    match alef {
+        '\u{0622}' => Some([0xF9, 0xFA]),
+        '\u{0623}' => Some([0x99, 0x9A]),
+        '\u{0625}' | '\u{0627}' => Some([0x9D, 0x9E]),
+        _ => None,
+    }
+}
+
+/// Characters other than Arabic letters; control characters would be read
+/// as commands
+fn symbol(c: char) -> u8 {
+    match c {
+        ' '..='~' => c as u8,
+        '\u{0660}'..='\u{0669}' => 0xB0 + (c as u32 - 0x0660) as u8,
+        '\u{060C}' => 0xAC,
+        '\u{061B}' => 0xBB,
+        '\u{061F}' => 0xBF,
+        '\u{066A}' => b'%',
+        '\u{00A0}' => b' ',
+        '\u{00B0}' => 0x80,
+        '\u{00D7}' => 0xDE,
+        '\u{00F7}' => 0xDD,
+        _ => b'?',
+    }
+}
+
+/// Code page 864 bytes of a run of text in logical order, each Arabic letter
+/// in the form its neighbours call for
+fn shape(text: &str) -> Vec<u8> {
+    let chars: Vec<char> = text.chars().collect();
+    let joins_next = |i: usize| letter(chars[i]).is_some_and(|l| l.joining == Joining::Dual);
+
+    let mut bytes = Vec::with_capacity(chars.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let after_joining = i > 0 && joins_next(i - 1);
+
+        if chars[i] == LAM
+            && let Some(ligature) = chars.get(i + 1).and_then(|alef| lam_alef(*alef))
+        {
+            bytes.push(ligature[usize::from(after_joining)]);
+            i += 2;
+            continue;
+        }
+
+        bytes.push(match letter(chars[i]) {
+            Some(current) => {
+                let previous = after_joining && current.joining != Joining::None;
+                let next = current.joining == Joining::Dual
+                    && chars
+                        .get(i + 1)
+                        .and_then(|next| letter(*next))
+                        .is_some_and(|next| next.joining != Joining::None);
+                let form = match (previous, next) {
+                    (false, false) => 0,
+                    (true, false) => 1,
+                    (false, true) => 2,
+                    (true, true) => 3,
+                };
+                current.forms[form]
+            }
+            None => symbol(chars[i]),
+        });
+        i += 1;
+    }
+    bytes
+}
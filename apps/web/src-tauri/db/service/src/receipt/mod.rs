@@ -0,0 +1,451 @@
+//! Sale and special order deposit receipts
+//!
+//! Receipts are rebuilt from stored records every time, so reprinting a sale
+//! or a deposit receipt gives the same lines and totals as the original. A
+//! sale is the set of `sale` stock movements sharing a `reference_id`, or a
+//! single `sale` movement by its own id. Output is ESC/POS for thermal
+//! receipt printers, with Arabic names printed through code page 864.
+
+mod escpos;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{DateTime, Local, Utc};
+use db_entity::customer::Entity as Customer;
+use db_entity::id::Id;
+use db_entity::inventory_item::Entity as InventoryItem;
+use db_entity::inventory_price_history::{self, Entity as PriceHistory};
+use db_entity::inventory_stock::{self, Entity as InventoryStock};
+use db_entity::inventory_stock_history::{
+    self, Entity as InventoryStockHistory, StockAdjustmentType,
+};
+use db_entity::special_order::{self, Entity as SpecialOrder};
+use db_entity::special_order_item::{self, Entity as SpecialOrderItem};
+use rust_decimal::Decimal;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use tap::TapFallible;
+
+use self::escpos::{EscPos, Justify};
+use crate::error::{ServiceError, ServiceResult};
+use crate::inventory::InventoryService;
+use crate::report::ReportService;
+use crate::settings::SettingsService;
+
+/// Record a receipt is printed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum ReceiptSource {
+    /// A sale, by its reference id or the id of its only stock movement
+    Sale(Id),
+    /// The deposit collected on a special order
+    SpecialOrderDeposit(Id),
+}
+
+/// Paper width of the receipt printer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptPaper {
+    Mm58,
+    #[default]
+    Mm80,
+}
+
+impl ReceiptPaper {
+    /// Characters per line in the printer's standard font
+    fn columns(self) -> usize {
+        match self {
+            Self::Mm58 => 32,
+            Self::Mm80 => 48,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptOptions {
+    pub source: ReceiptSource,
+    #[serde(default)]
+    pub paper: ReceiptPaper,
+    /// Mark the receipt as a copy of one printed before
+    #[serde(default)]
+    pub reprint: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptLine {
+    pub description: String,
+    pub quantity: i32,
+    pub unit_price: Decimal,
+    pub total: Decimal,
+}
+
+/// Contents of a receipt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Receipt {
+    pub source: ReceiptSource,
+    /// Special order number, or the sale id
+    pub number: String,
+    pub issued_at: DateTime<Utc>,
+    pub customer: Option<String>,
+    pub lines: Vec<ReceiptLine>,
+    pub total: Decimal,
+    /// Deposit collected, on deposit receipts
+    pub paid: Option<Decimal>,
+    /// Amount left to pay after the deposit
+    pub balance_due: Option<Decimal>,
+}
+
+/// Pharmacy details printed at the top of a receipt
+struct ReceiptHeader {
+    name: String,
+    address: Option<String>,
+    phone: Option<String>,
+    tax_id: Option<String>,
+}
+
+// ============================================================================
+// Receipt Service
+// ============================================================================
+
+/// Sale and deposit receipt service
+pub struct ReceiptService {
+    db: Arc<DatabaseConnection>,
+    settings: Arc<SettingsService>,
+    report: Arc<ReportService>,
+}
+
+impl ReceiptService {
+    /// Setting key of the pharmacy tax registration number
+    pub const TAX_ID_SETTING: &'static str = "pharmacy.tax_id";
+
+    /// Setting key of the printer's `ESC t` number for code page 864
+    pub const CODE_PAGE_SETTING: &'static str = "receipt.code_page";
+
+    /// `ESC t` number of code page 864 on Epson printers
+    pub const DEFAULT_CODE_PAGE: u8 = 37;
+
+    /// Reference type of stock movements that belong to a sale
+    pub const SALE_REFERENCE_TYPE: &'static str = InventoryService::SALE_REFERENCE_TYPE;
+
+    /// Create a new receipt service
+    pub fn new(
+        db: Arc<DatabaseConnection>,
+        settings: Arc<SettingsService>,
+        report: Arc<ReportService>,
+    ) -> Self {
+        Self {
+            db,
+            settings,
+            report,
+        }
+    }
+
+    /// Collect the contents of a receipt
+    pub async fn receipt(&self, source: ReceiptSource) -> ServiceResult<Receipt> {
+        match source {
+            ReceiptSource::Sale(id) => self.sale_receipt(id).await,
+            ReceiptSource::SpecialOrderDeposit(id) => self.deposit_receipt(id).await,
+        }
+    }
+
+    /// Render a receipt as ESC/POS commands
+    pub async fn render(&self, options: &ReceiptOptions) -> ServiceResult<(Receipt, Vec<u8>)> {
+        let receipt = self.receipt(options.source).await?;
+        let header = self.header().await;
+        let code_page = self.code_page().await;
+        let content = render_escpos(&receipt, &header, options, code_page);
+        Ok((receipt, content))
+    }
+
+    /// Render a receipt and write the ESC/POS commands to `path`
+    pub async fn save(&self, options: &ReceiptOptions, path: &Path) -> ServiceResult<Receipt> {
+        let (receipt, content) = self.render(options).await?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error(parent, e))?;
+        }
+        tokio::fs::write(path, &content)
+            .await
+            .map_err(|e| io_error(path, e))
+            .tap_err(|e| tracing::error!("Failed to write receipt: {}", e))?;
+
+        tracing::info!("Saved receipt {} to {}", receipt.number, path.display());
+        Ok(receipt)
+    }
+
+    /// Printers number their code pages differently, so the number is a
+    /// setting
+    async fn code_page(&self) -> u8 {
+        self.settings
+            .get_number(Self::CODE_PAGE_SETTING)
+            .await
+            .ok()
+            .map(|dto| dto.value)
+            .filter(|value| (0.0..=255.0).contains(value) && value.fract() == 0.0)
+            .map_or(Self::DEFAULT_CODE_PAGE, |value| value as u8)
+    }
+
+    async fn header(&self) -> ReceiptHeader {
+        let pharmacy = self.report.pharmacy_header().await;
+        let tax_id = self
+            .settings
+            .get_string(Self::TAX_ID_SETTING)
+            .await
+            .ok()
+            .map(|dto| dto.value.trim().to_string())
+            .filter(|value| !value.is_empty());
+
+        ReceiptHeader {
+            name: pharmacy.name.en,
+            address: pharmacy.address,
+            phone: pharmacy.phone,
+            tax_id,
+        }
+    }
+
+    // ========================================================================
+    // Sales
+    // ========================================================================
+
+    async fn sale_receipt(&self, sale_id: Id) -> ServiceResult<Receipt> {
+        let movements = InventoryStockHistory::find()
+            .filter(inventory_stock_history::Column::AdjustmentType.eq(StockAdjustmentType::Sale))
+            .filter(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(inventory_stock_history::Column::ReferenceId.eq(sale_id))
+                            .add(
+                                inventory_stock_history::Column::ReferenceType
+                                    .eq(Self::SALE_REFERENCE_TYPE),
+                            ),
+                    )
+                    .add(inventory_stock_history::Column::Id.eq(sale_id)),
+            )
+            .order_by_asc(inventory_stock_history::Column::RecordedAt)
+            .find_also_related(InventoryItem)
+            .all(&*self.db)
+            .await?;
+
+        let Some(issued_at) = movements.first().map(|(movement, _)| movement.recorded_at) else {
+            return Err(ServiceError::NotFound(format!(
+                "Sale not found: {}",
+                sale_id
+            )));
+        };
+
+        let mut lines = Vec::with_capacity(movements.len());
+        for (movement, item) in movements {
+            let unit_price = self
+                .price_at(movement.inventory_item_id, movement.recorded_at)
+                .await?;
+            let quantity = -movement.adjustment_amount;
+            lines.push(ReceiptLine {
+                description: item
+                    .map(|item| describe(&item.name, &item.concentration))
+                    .unwrap_or_default(),
+                quantity,
+                unit_price,
+                total: unit_price * Decimal::from(quantity),
+            });
+        }
+
+        Ok(Receipt {
+            source: ReceiptSource::Sale(sale_id),
+            number: sale_id.to_string(),
+            issued_at: issued_at.with_timezone(&Utc),
+            customer: None,
+            total: lines.iter().map(|line| line.total).sum(),
+            lines,
+            paid: None,
+            balance_due: None,
+        })
+    }
+
+    /// Unit price of an item when it was sold: the last price recorded up to
+    /// then, or the current price when none was
+    async fn price_at(
+        &self,
+        inventory_item_id: Id,
+        at: sea_orm::prelude::DateTimeWithTimeZone,
+    ) -> ServiceResult<Decimal> {
+        let recorded = PriceHistory::find()
+            .filter(inventory_price_history::Column::InventoryItemId.eq(inventory_item_id))
+            .filter(inventory_price_history::Column::RecordedAt.lte(at))
+            .order_by_desc(inventory_price_history::Column::RecordedAt)
+            .one(&*self.db)
+            .await?;
+        if let Some(entry) = recorded {
+            return Ok(entry.unit_price);
+        }
+
+        let stock = InventoryStock::find()
+            .filter(inventory_stock::Column::InventoryItemId.eq(inventory_item_id))
+            .one(&*self.db)
+            .await?;
+        Ok(stock.map(|stock| stock.unit_price).unwrap_or_default())
+    }
+
+    // ========================================================================
+    // Special Order Deposits
+    // ========================================================================
+
+    async fn deposit_receipt(&self, order_id: Id) -> ServiceResult<Receipt> {
+        let (order, customer) = SpecialOrder::find_by_id(order_id)
+            .filter(special_order::Column::DeletedAt.is_null())
+            .find_also_related(Customer)
+            .one(&*self.db)
+            .await?
+            .ok_or_else(|| {
+                ServiceError::NotFound(format!("Special order not found: {}", order_id))
+            })?;
+
+        let paid = order
+            .deposit_paid
+            .filter(|deposit| *deposit > Decimal::ZERO)
+            .ok_or_else(|| {
+                ServiceError::BadRequest(format!(
+                    "No deposit has been collected for order {}",
+                    order.order_number
+                ))
+            })?;
+
+        let items = SpecialOrderItem::find()
+            .filter(special_order_item::Column::SpecialOrderId.eq(order_id))
+            .order_by_asc(special_order_item::Column::CreatedAt)
+            .find_also_related(InventoryItem)
+            .all(&*self.db)
+            .await?;
+
+        let lines = items
+            .into_iter()
+            .map(|(line, item)| {
+                let description = match item {
+                    Some(item) => describe(&item.name, &item.concentration),
+                    None => describe(
+                        line.custom_item_name.as_deref().unwrap_or_default(),
+                        line.custom_concentration.as_deref().unwrap_or_default(),
+                    ),
+                };
+                ReceiptLine {
+                    description,
+                    quantity: line.quantity,
+                    unit_price: line.unit_price,
+                    total: line.unit_price * Decimal::from(line.quantity),
+                }
+            })
+            .collect();
+
+        Ok(Receipt {
+            source: ReceiptSource::SpecialOrderDeposit(order_id),
+            number: order.order_number,
+            // Later changes to the order must not move the receipt's date
+            issued_at: order
+                .deposit_paid_at
+                .unwrap_or(order.created_at)
+                .with_timezone(&Utc),
+            customer: customer.map(|customer| customer.full_name),
+            lines,
+            total: order.total_amount,
+            paid: Some(paid),
+            balance_due: Some((order.total_amount - paid).max(Decimal::ZERO)),
+        })
+    }
+}
+
+// ============================================================================
+// Layout
+// ============================================================================
+
+fn render_escpos(
+    receipt: &Receipt,
+    header: &ReceiptHeader,
+    options: &ReceiptOptions,
+    code_page: u8,
+) -> Vec<u8> {
+    let mut printer = EscPos::new(options.paper.columns(), code_page);
+
+    printer
+        .justify(Justify::Center)
+        .double(true)
+        .bold(true)
+        .line(&header.name)
+        .bold(false)
+        .double(false);
+    for detail in [&header.address, &header.phone].into_iter().flatten() {
+        printer.line(detail);
+    }
+    if let Some(tax_id) = &header.tax_id {
+        printer.line(&format!("Tax ID: {}", tax_id));
+    }
+    printer.rule();
+
+    let title = match receipt.source {
+        ReceiptSource::Sale(_) => "SALES RECEIPT",
+        ReceiptSource::SpecialOrderDeposit(_) => "DEPOSIT RECEIPT",
+    };
+    printer.bold(true).line(title).bold(false);
+    if options.reprint {
+        printer.line("** REPRINT **");
+    }
+
+    printer.justify(Justify::Left);
+    match receipt.source {
+        ReceiptSource::Sale(_) => printer.line(&format!("Sale: {}", receipt.number)),
+        ReceiptSource::SpecialOrderDeposit(_) => {
+            printer.line(&format!("Order: {}", receipt.number))
+        }
+    };
+    let issued_at = receipt.issued_at.with_timezone(&Local);
+    printer.line(&format!("Date: {}", issued_at.format("%Y-%m-%d %H:%M")));
+    if let Some(customer) = &receipt.customer {
+        printer.line(&format!("Customer: {}", customer));
+    }
+    printer.rule();
+
+    for line in &receipt.lines {
+        printer.line(&line.description).pair(
+            &format!("  {} x {}", line.quantity, money(line.unit_price)),
+            &money(line.total),
+        );
+    }
+    printer.rule();
+
+    printer
+        .bold(true)
+        .pair("TOTAL", &money(receipt.total))
+        .bold(false);
+    if let Some(paid) = receipt.paid {
+        printer.pair("Deposit paid", &money(paid));
+    }
+    if let Some(balance_due) = receipt.balance_due {
+        printer.pair("Balance due", &money(balance_due));
+    }
+    printer.rule();
+
+    printer
+        .justify(Justify::Center)
+        .line("Thank you")
+        .feed(3)
+        .cut()
+        .finish()
+}
+
+/// Item name followed by its concentration
+fn describe(name: &str, concentration: &str) -> String {
+    format!("{} {}", name, concentration).trim().to_string()
+}
+
+fn money(value: Decimal) -> String {
+    format!("{:.2}", value.round_dp(2))
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> ServiceError {
+    ServiceError::Internal(format!("Failed to access {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests;
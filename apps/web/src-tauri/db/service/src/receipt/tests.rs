@@ -0,0 +1,367 @@
+use db_entity::customer;
+use db_entity::inventory_stock::dto::AdjustStock;
+use db_entity::setting::dto::SetSettingDto;
+use db_entity::special_order::SpecialOrderStatus;
+
+use super::*;
+use crate::test_support::{TestItem, TestServices, change_price, create_items, services};
+
+/// Receipt service with the pharmacy name, phone and tax id set
+async fn receipt_service(services: &TestServices) -> ReceiptService {
+    for (key, value) in [
+        (ReportService::NAME_EN_SETTING, "Nile Pharmacy"),
+        (ReportService::PHONE_SETTING, "0223456789"),
+        (ReceiptService::TAX_ID_SETTING, "123-456-789"),
+    ] {
+        services
+            .settings
+            .set(SetSettingDto {
+                key: key.to_string(),
+                value: serde_json::json!(value),
+                category: Some("pharmacy".to_string()),
+                description: None,
+                updated_by: None,
+            })
+            .await
+            .unwrap();
+    }
+
+    ReceiptService::new(
+        services.db.clone(),
+        services.settings.clone(),
+        services.report.clone(),
+    )
+}
+
+/// Item with 50 in stock at the given unit price
+fn item(name: &str, unit_price: f64) -> TestItem<'_> {
+    TestItem {
+        stock_quantity: 50,
+        unit_price,
+        ..TestItem::new(name)
+    }
+}
+
+/// Sell `quantity` of an item as part of `sale_id`, returning the id of the
+/// stock movement
+async fn sell(services: &TestServices, item_id: Id, quantity: i32, sale_id: Option<Id>) -> Id {
+    services
+        .inventory
+        .adjust_stock(
+            item_id,
+            AdjustStock {
+                adjustment: -quantity,
+                reason: None,
+                adjustment_type: Some(StockAdjustmentType::Sale),
+                idempotency_key: None,
+                adjusted_by: None,
+                reference_id: sale_id,
+                reference_type: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    InventoryStockHistory::find()
+        .filter(inventory_stock_history::Column::InventoryItemId.eq(item_id))
+        .order_by_desc(inventory_stock_history::Column::RecordedAt)
+        .one(&*services.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .id
+}
+
+/// Creates a special order with a linked and a custom item
+async fn create_special_order(
+    services: &TestServices,
+    order_number: &str,
+    item_id: Id,
+    deposit: Option<i64>,
+) -> Id {
+    let now: sea_orm::prelude::DateTimeWithTimeZone = Utc::now().into();
+    let customer = customer::ActiveModel {
+        id: Set(Id::new()),
+        full_name: Set("Mona Adel".to_string()),
+        phone_number: Set("01000000000".to_string()),
+        alt_phone_number: Set(None),
+        email: Set(None),
+        address: Set(None),
+        date_of_birth: Set(None),
+        national_id: Set(None),
+        notes: Set(None),
+        is_active: Set(true),
+        created_by: Set(None),
+        updated_by: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+        deleted_at: Set(None),
+    }
+    .insert(&*services.db)
+    .await
+    .unwrap();
+
+    let order = special_order::ActiveModel {
+        id: Set(Id::new()),
+        customer_id: Set(customer.id),
+        supplier_id: Set(None),
+        order_number: Set(order_number.to_string()),
+        status: Set(SpecialOrderStatus::Ordered),
+        order_date: Set(now.date_naive()),
+        expected_arrival_date: Set(None),
+        actual_arrival_date: Set(None),
+        delivery_date: Set(None),
+        total_amount: Set(Decimal::new(120, 0)),
+        deposit_paid: Set(deposit.map(|deposit| Decimal::new(deposit, 0))),
+        deposit_paid_at: NotSet,
+        notes: Set(None),
+        internal_notes: Set(None),
+        created_by: Set(None),
+        updated_by: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+        deleted_at: Set(None),
+    }
+    .insert(&*services.db)
+    .await
+    .unwrap();
+
+    for (inventory_item_id, custom_item_name, quantity, unit_price) in [
+        (Some(item_id), None, 2, 25),
+        (None, Some("Insulin pen"), 1, 70),
+    ] {
+        special_order_item::ActiveModel {
+            id: Set(Id::new()),
+            special_order_id: Set(order.id),
+            inventory_item_id: Set(inventory_item_id),
+            custom_item_name: Set(custom_item_name.map(str::to_string)),
+            custom_concentration: Set(None),
+            custom_form: Set(None),
+            quantity: Set(quantity),
+            unit_price: Set(Decimal::new(unit_price, 0)),
+            notes: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(&*services.db)
+        .await
+        .unwrap();
+    }
+    order.id
+}
+
+fn options(source: ReceiptSource) -> ReceiptOptions {
+    ReceiptOptions {
+        source,
+        paper: ReceiptPaper::Mm80,
+        reprint: false,
+    }
+}
+
+/// Printable text of an ESC/POS stream, one entry per line
+fn text_lines(content: &[u8]) -> Vec<String> {
+    let mut text = Vec::new();
+    let mut bytes = content.iter().copied();
+    while let Some(byte) = bytes.next() {
+        // Skip the arguments of the commands the receipt uses
+        let arguments = match (byte, bytes.clone().next()) {
+            (0x1B, Some(b'@')) => 1,
+            (0x1B, _) | (0x1D, Some(b'!')) => 2,
+            (0x1D, _) => 3,
+            _ => {
+                text.push(byte);
+                continue;
+            }
+        };
+        bytes.nth(arguments - 1);
+    }
+    String::from_utf8(text)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_sale_receipt_uses_price_at_time_of_sale() {
+    let services = services().await;
+    let receipts = receipt_service(&services).await;
+    let ids = create_items(
+        &services.db,
+        &services.inventory,
+        &[item("Paracetamol", 12.5), item("Ibuprofen", 8.0)],
+    )
+    .await;
+
+    change_price(&services.inventory, ids[0], 15.0).await;
+    let sale_id = sell(&services, ids[0], 2, None).await;
+    change_price(&services.inventory, ids[0], 20.0).await;
+
+    let (receipt, content) = receipts
+        .render(&options(ReceiptSource::Sale(sale_id)))
+        .await
+        .unwrap();
+    assert_eq!(receipt.lines.len(), 1);
+    assert_eq!(receipt.lines[0].description, "Paracetamol 500mg");
+    assert_eq!(receipt.total, Decimal::new(30, 0));
+
+    assert!(content.starts_with(&[0x1B, b'@']));
+    assert!(content.ends_with(&[0x1D, b'V', 66, 0]));
+    let lines = text_lines(&content);
+    assert!(lines.contains(&"Nile Pharmacy".to_string()));
+    assert!(lines.contains(&"Tax ID: 123-456-789".to_string()));
+    assert!(lines.contains(&"SALES RECEIPT".to_string()));
+    assert!(lines.contains(&format!("  2 x 15.00{}30.00", " ".repeat(48 - 16))));
+
+    assert!(matches!(
+        receipts.receipt(ReceiptSource::Sale(Id::new())).await,
+        Err(ServiceError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_sale_receipt_groups_movements_by_reference() {
+    let services = services().await;
+    let receipts = receipt_service(&services).await;
+    let ids = create_items(
+        &services.db,
+        &services.inventory,
+        &[item("Paracetamol", 12.5), item("Ibuprofen", 8.0)],
+    )
+    .await;
+    let sale_id = Id::new();
+
+    for (item_id, quantity) in [(ids[0], 2), (ids[1], 3)] {
+        let movement = sell(&services, item_id, quantity, Some(sale_id)).await;
+        let movement = InventoryStockHistory::find_by_id(movement)
+            .one(&*services.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            movement.reference_type.as_deref(),
+            Some(ReceiptService::SALE_REFERENCE_TYPE)
+        );
+    }
+    // Another sale of the same item is not part of the receipt
+    sell(&services, ids[0], 1, Some(Id::new())).await;
+
+    let receipt = receipts
+        .receipt(ReceiptSource::Sale(sale_id))
+        .await
+        .unwrap();
+    let lines: Vec<(&str, i32)> = receipt
+        .lines
+        .iter()
+        .map(|line| (line.description.as_str(), line.quantity))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![("Paracetamol 500mg", 2), ("Ibuprofen 500mg", 3)]
+    );
+    assert_eq!(receipt.total, Decimal::new(49, 0));
+}
+
+#[tokio::test]
+async fn test_deposit_receipt_reprint_to_file() {
+    let services = services().await;
+    let receipts = receipt_service(&services).await;
+    let ids = create_items(
+        &services.db,
+        &services.inventory,
+        &[item("Paracetamol", 12.5)],
+    )
+    .await;
+    let order_id = create_special_order(&services, "SO-2026-0042", ids[0], Some(50)).await;
+
+    let path = std::env::temp_dir()
+        .join(format!("meditrack-receipt-{}", Id::new()))
+        .join("receipt.bin");
+    let receipt = receipts
+        .save(
+            &ReceiptOptions {
+                source: ReceiptSource::SpecialOrderDeposit(order_id),
+                paper: ReceiptPaper::Mm58,
+                reprint: true,
+            },
+            &path,
+        )
+        .await
+        .unwrap();
+    assert_eq!(receipt.number, "SO-2026-0042");
+    assert_eq!(receipt.customer.as_deref(), Some("Mona Adel"));
+    assert_eq!(receipt.paid, Some(Decimal::new(50, 0)));
+    assert_eq!(receipt.balance_due, Some(Decimal::new(70, 0)));
+    let descriptions: Vec<&str> = receipt
+        .lines
+        .iter()
+        .map(|line| line.description.as_str())
+        .collect();
+    assert_eq!(descriptions, vec!["Paracetamol 500mg", "Insulin pen"]);
+
+    let lines = text_lines(&std::fs::read(&path).unwrap());
+    assert!(lines.contains(&"** REPRINT **".to_string()));
+    assert!(lines.contains(&"Order: SO-2026-0042".to_string()));
+    assert!(lines.contains(&format!("Balance due{}70.00", " ".repeat(32 - 16))));
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    // Changing the order later keeps the date the deposit was collected
+    special_order::ActiveModel {
+        id: Set(order_id),
+        notes: Set(Some("Customer called".to_string())),
+        ..Default::default()
+    }
+    .update(&*services.db)
+    .await
+    .unwrap();
+    let reprint = receipts
+        .receipt(ReceiptSource::SpecialOrderDeposit(order_id))
+        .await
+        .unwrap();
+    assert_eq!(reprint.issued_at, receipt.issued_at);
+
+    let unpaid = create_special_order(&services, "SO-2026-0043", ids[0], None).await;
+    assert!(matches!(
+        receipts
+            .receipt(ReceiptSource::SpecialOrderDeposit(unpaid))
+            .await,
+        Err(ServiceError::BadRequest(_))
+    ));
+}
+
+#[test]
+fn test_arabic_is_shaped_in_code_page_864() {
+    // صيدلية النيل: letters joined to their neighbours, in visual order
+    assert_eq!(
+        escpos::encode("صيدلية النيل", 48),
+        vec![
+            0xFB, 0xEA, 0xE6, 0xE4, 0xC7, b' ', 0xC9, 0xEA, 0xE4, 0xCF, 0xEA, 0xD5
+        ]
+    );
+    // Lam-alef ligature; vowel marks are dropped
+    assert_eq!(escpos::encode("سَلام", 48), vec![0xEF, 0x9E, 0xD3]);
+    // Arabic inside a left-to-right line
+    let mut mixed = b"Panadol ".to_vec();
+    mixed.extend([0xFB, 0xE8, 0xCF, 0xA8, 0xE6, 0xC8]);
+    assert_eq!(escpos::encode("Panadol بنادول", 48), mixed);
+    assert_eq!(escpos::encode("Ω 10", 3), b"? 1".to_vec());
+}
+
+#[tokio::test]
+async fn test_receipt_selects_code_page() {
+    let services = services().await;
+    let receipts = receipt_service(&services).await;
+    let ids = create_items(
+        &services.db,
+        &services.inventory,
+        &[item("Paracetamol", 12.5)],
+    )
+    .await;
+    let sale_id = sell(&services, ids[0], 1, None).await;
+
+    let (_, content) = receipts
+        .render(&options(ReceiptSource::Sale(sale_id)))
+        .await
+        .unwrap();
+    assert!(content.starts_with(&[0x1B, b'@', 0x1B, b't', ReceiptService::DEFAULT_CODE_PAGE]));
+}
@@ -0,0 +1,159 @@
+//! HTML report rendering
+//!
+//! Each page is a `<section>` that starts on a new sheet when printed; the
+//! document direction follows the report language so browsers mirror the
+//! layout for Arabic.
+
+use std::fmt::Write;
+
+use super::{
+    Block, ColumnAlign, Page, PharmacyHeader, ReportDataset, ReportLanguage, generated_label,
+    no_data_label, page_label, table_title,
+};
+
+const STYLE: &str = "\
+@page { size: A4; margin: 15mm; }
+body { font-family: \"Noto Naskh Arabic\", Tahoma, Arial, sans-serif; font-size: 10pt; color: #111; margin: 0; }
+.page { display: flex; flex-direction: column; min-height: 267mm; break-after: page; }
+.page:last-child { break-after: auto; }
+.pharmacy { text-align: center; border-bottom: 1px solid #444; padding-bottom: 4mm; margin-bottom: 4mm; }
+.pharmacy h1 { font-size: 16pt; margin: 0; }
+.pharmacy p { font-size: 9pt; margin: 1mm 0 0; color: #444; }
+h2 { font-size: 13pt; margin: 0 0 1mm; }
+h3 { font-size: 11pt; margin: 4mm 0 1mm; }
+.generated { font-size: 8pt; color: #666; margin: 0 0 3mm; }
+table { width: 100%; border-collapse: collapse; }
+th, td { padding: 1mm 2mm; text-align: start; border-bottom: 1px solid #ddd; }
+th { background: #eee; }
+.end { text-align: end; }
+.summary th { background: none; width: 40%; font-weight: normal; color: #444; }
+.empty { text-align: center; color: #666; }
+footer { margin-top: auto; padding-top: 4mm; text-align: center; font-size: 8pt; color: #666; }
+";
+
+/// Render `pages` of `dataset` as a standalone HTML document
+pub(super) fn render(
+    dataset: &ReportDataset,
+    header: &PharmacyHeader,
+    pages: &[Page<'_>],
+    language: ReportLanguage,
+) -> String {
+    let dir = if language.is_rtl() { "rtl" } else { "ltr" };
+    let title = escape(language.pick(&dataset.title));
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"{}\" dir=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n",
+        language.code(),
+        dir,
+        title,
+        STYLE
+    );
+
+    for (index, page) in pages.iter().enumerate() {
+        out.push_str("<section class=\"page\">\n");
+        render_header(&mut out, header, language);
+        let _ = writeln!(out, "<h2>{}</h2>", title);
+        let _ = writeln!(
+            out,
+            "<p class=\"generated\">{}</p>",
+            escape(&generated_label(language, dataset.generated_at))
+        );
+
+        for block in &page.blocks {
+            match block {
+                Block::Summary(fields) => {
+                    out.push_str("<table class=\"summary\">\n");
+                    for field in fields.iter() {
+                        let _ = writeln!(
+                            out,
+                            "<tr><th>{}</th><td>{}</td></tr>",
+                            escape(language.pick(&field.label)),
+                            escape(&field.value)
+                        );
+                    }
+                    out.push_str("</table>\n");
+                }
+                Block::Table {
+                    table,
+                    rows,
+                    continued,
+                } => {
+                    if let Some(title) = table_title(language, table, *continued) {
+                        let _ = writeln!(out, "<h3>{}</h3>", escape(&title));
+                    }
+                    out.push_str("<table>\n<thead><tr>");
+                    for column in &table.columns {
+                        let _ = write!(
+                            out,
+                            "<th{}>{}</th>",
+                            class(column.align),
+                            escape(language.pick(&column.label))
+                        );
+                    }
+                    out.push_str("</tr></thead>\n<tbody>\n");
+                    if rows.is_empty() {
+                        let _ = writeln!(
+                            out,
+                            "<tr><td class=\"empty\" colspan=\"{}\">{}</td></tr>",
+                            table.columns.len().max(1),
+                            no_data_label(language)
+                        );
+                    }
+                    for row in rows.iter() {
+                        out.push_str("<tr>");
+                        for (column, cell) in table.columns.iter().zip(row) {
+                            let _ = write!(out, "<td{}>{}</td>", class(column.align), escape(cell));
+                        }
+                        out.push_str("</tr>\n");
+                    }
+                    out.push_str("</tbody>\n</table>\n");
+                }
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "<footer>{}</footer>\n</section>",
+            page_label(language, index + 1, pages.len())
+        );
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn render_header(out: &mut String, header: &PharmacyHeader, language: ReportLanguage) {
+    out.push_str("<header class=\"pharmacy\">\n");
+    let _ = writeln!(out, "<h1>{}</h1>", escape(language.pick(&header.name)));
+    let details = header.details(language);
+    if !details.is_empty() {
+        let _ = writeln!(out, "<p>{}</p>", escape(&details));
+    }
+    out.push_str("</header>\n");
+}
+
+fn class(align: ColumnAlign) -> &'static str {
+    match align {
+        ColumnAlign::Start => "",
+        ColumnAlign::End => " class=\"end\"",
+    }
+}
+
+/// Escape text for use in HTML content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,606 @@
+//! Printable reports
+//!
+//! Report datasets (a title, summary fields and tables, every label in
+//! English and Arabic) are laid out into pages under the pharmacy header and
+//! rendered as HTML or PDF. Arabic reports are laid out right to left.
+
+mod html;
+pub(crate) mod pdf;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use db_entity::inventory_stock::dto::ValuationQuery;
+use db_entity::setting::MultilingualDescription;
+use serde::{Deserialize, Serialize};
+use tap::TapFallible;
+
+use crate::error::{ServiceError, ServiceResult};
+use crate::inventory::InventoryService;
+use crate::inventory::opening_balances::OpeningBalanceService;
+use crate::settings::SettingsService;
+
+/// Table rows, summary fields and table headings that fit on one page
+const ROWS_PER_PAGE: usize = 32;
+
+/// Fonts tried for PDF output when `reports.pdf_font_path` is not set; each
+/// covers both Latin and Arabic script
+pub(crate) const FONT_CANDIDATES: &[&str] = &[
+    "C:\\Windows\\Fonts\\arial.ttf",
+    "C:\\Windows\\Fonts\\tahoma.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/noto/NotoSansArabic-Regular.ttf",
+];
+
+/// English and Arabic text
+fn text(en: &str, ar: &str) -> MultilingualDescription {
+    MultilingualDescription {
+        en: en.to_string(),
+        ar: ar.to_string(),
+    }
+}
+
+// ============================================================================
+// Report Model
+// ============================================================================
+
+/// Language a report is rendered in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportLanguage {
+    #[default]
+    En,
+    Ar,
+}
+
+impl ReportLanguage {
+    /// Pick this language's variant of `text`
+    pub fn pick<'a>(&self, text: &'a MultilingualDescription) -> &'a str {
+        match self {
+            Self::En => &text.en,
+            Self::Ar => &text.ar,
+        }
+    }
+
+    pub fn is_rtl(&self) -> bool {
+        matches!(self, Self::Ar)
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Ar => "ar",
+        }
+    }
+}
+
+/// Output format of a rendered report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Html,
+    Pdf,
+}
+
+/// Pharmacy details printed at the top of every page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PharmacyHeader {
+    pub name: MultilingualDescription,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub license_number: Option<String>,
+}
+
+impl PharmacyHeader {
+    /// Address, phone and license number on one line
+    fn details(&self, language: ReportLanguage) -> String {
+        let license = self.license_number.as_ref().map(|number| {
+            let label = language.pick(&text("License", "ترخيص")).to_string();
+            format!("{} {}", label, number)
+        });
+        [self.address.clone(), self.phone.clone(), license]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+/// Horizontal alignment of a column, relative to the reading direction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnAlign {
+    #[default]
+    Start,
+    End,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportColumn {
+    pub label: MultilingualDescription,
+    #[serde(default)]
+    pub align: ColumnAlign,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportTable {
+    pub title: Option<MultilingualDescription>,
+    pub columns: Vec<ReportColumn>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Labelled value in a report summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportField {
+    pub label: MultilingualDescription,
+    pub value: String,
+}
+
+/// Data of a report, independent of its output format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportDataset {
+    pub title: MultilingualDescription,
+    pub generated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub summary: Vec<ReportField>,
+    #[serde(default)]
+    pub tables: Vec<ReportTable>,
+}
+
+/// Report to render
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "query", rename_all = "snake_case")]
+pub enum ReportKind {
+    InventoryStatistics,
+    LowStock,
+    OpeningBalanceStatistics,
+    Valuation(ValuationQuery),
+    /// A dataset assembled by the caller
+    Dataset(ReportDataset),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportOptions {
+    pub report: ReportKind,
+    pub format: ReportFormat,
+    #[serde(default)]
+    pub language: ReportLanguage,
+}
+
+/// Report written to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedReport {
+    pub path: PathBuf,
+    pub format: ReportFormat,
+    pub pages: usize,
+    pub size: u64,
+}
+
+// ============================================================================
+// Page Layout
+// ============================================================================
+
+/// Content of one page, shared by the HTML and PDF renderers
+#[derive(Debug)]
+struct Page<'a> {
+    blocks: Vec<Block<'a>>,
+}
+
+#[derive(Debug)]
+enum Block<'a> {
+    Summary(&'a [ReportField]),
+    /// Rows of a table; `continued` when earlier rows are on a previous page
+    Table {
+        table: &'a ReportTable,
+        rows: &'a [Vec<String>],
+        continued: bool,
+    },
+}
+
+/// Split a dataset into pages of at most [`ROWS_PER_PAGE`] lines
+///
+/// The summary counts a line per field plus one for spacing; a table counts
+/// two lines for its title and column headings, repeated on every page it
+/// spans. Empty tables still take a line for their "no data" row.
+fn paginate(dataset: &ReportDataset) -> Vec<Page<'_>> {
+    let mut pages = vec![Page { blocks: Vec::new() }];
+    let mut used = 0;
+
+    if !dataset.summary.is_empty() {
+        pages[0].blocks.push(Block::Summary(&dataset.summary));
+        used = dataset.summary.len() + 1;
+    }
+
+    for table in &dataset.tables {
+        let mut rows = table.rows.as_slice();
+        let mut continued = false;
+        loop {
+            // A heading needs at least one row below it
+            if used + 3 > ROWS_PER_PAGE {
+                pages.push(Page { blocks: Vec::new() });
+                used = 0;
+            }
+            let take = rows.len().min(ROWS_PER_PAGE - used - 2);
+            let (page_rows, rest) = rows.split_at(take);
+            pages.last_mut().unwrap().blocks.push(Block::Table {
+                table,
+                rows: page_rows,
+                continued,
+            });
+            used += 2 + take.max(1);
+
+            if rest.is_empty() {
+                break;
+            }
+            rows = rest;
+            continued = true;
+        }
+    }
+    pages
+}
+
+/// Footer text of page `number` (1-based) of `total`
+fn page_label(language: ReportLanguage, number: usize, total: usize) -> String {
+    match language {
+        ReportLanguage::En => format!("Page {} of {}", number, total),
+        ReportLanguage::Ar => format!("صفحة {} من {}", number, total),
+    }
+}
+
+fn generated_label(language: ReportLanguage, at: DateTime<Utc>) -> String {
+    format!(
+        "{}: {}",
+        language.pick(&text("Generated", "تاريخ الإصدار")),
+        at.format("%Y-%m-%d %H:%M UTC")
+    )
+}
+
+fn no_data_label(language: ReportLanguage) -> &'static str {
+    match language {
+        ReportLanguage::En => "No data",
+        ReportLanguage::Ar => "لا توجد بيانات",
+    }
+}
+
+/// Table title, marked as continued on later pages
+fn table_title(language: ReportLanguage, table: &ReportTable, continued: bool) -> Option<String> {
+    let title = language.pick(table.title.as_ref()?);
+    Some(if continued {
+        format!("{} ({})", title, language.pick(&text("continued", "تابع")))
+    } else {
+        title.to_string()
+    })
+}
+
+// ============================================================================
+// Report Service
+// ============================================================================
+
+/// Report rendering service
+pub struct ReportService {
+    inventory: Arc<InventoryService>,
+    opening_balance: Arc<OpeningBalanceService>,
+    settings: Arc<SettingsService>,
+}
+
+impl ReportService {
+    /// Setting keys of the pharmacy header
+    pub const NAME_EN_SETTING: &'static str = "pharmacy.name_en";
+    pub const NAME_AR_SETTING: &'static str = "pharmacy.name_ar";
+    pub const ADDRESS_SETTING: &'static str = "pharmacy.address";
+    pub const PHONE_SETTING: &'static str = "pharmacy.phone";
+    pub const LICENSE_SETTING: &'static str = "pharmacy.license_number";
+
+    /// Setting key of the TrueType font embedded in PDF reports
+    pub const PDF_FONT_SETTING: &'static str = "reports.pdf_font_path";
+
+    /// Create a new report service
+    pub fn new(
+        inventory: Arc<InventoryService>,
+        opening_balance: Arc<OpeningBalanceService>,
+        settings: Arc<SettingsService>,
+    ) -> Self {
+        Self {
+            inventory,
+            opening_balance,
+            settings,
+        }
+    }
+
+    /// Render a report and write it to `path`
+    pub async fn save(&self, options: ReportOptions, path: &Path) -> ServiceResult<RenderedReport> {
+        let format = options.format;
+        let (content, pages) = self.render(options).await?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error(parent, e))?;
+        }
+        tokio::fs::write(path, &content)
+            .await
+            .map_err(|e| io_error(path, e))
+            .tap_err(|e| tracing::error!("Failed to write report: {}", e))?;
+
+        tracing::info!("Saved {}-page report to {}", pages, path.display());
+        Ok(RenderedReport {
+            path: path.to_path_buf(),
+            format,
+            pages,
+            size: content.len() as u64,
+        })
+    }
+
+    /// Render a report, returning its content and page count
+    pub async fn render(&self, options: ReportOptions) -> ServiceResult<(Vec<u8>, usize)> {
+        let dataset = self.dataset(options.report).await?;
+        let header = self.pharmacy_header().await;
+        let pages = paginate(&dataset);
+        let count = pages.len();
+
+        let content = match options.format {
+            ReportFormat::Html => {
+                html::render(&dataset, &header, &pages, options.language).into_bytes()
+            }
+            ReportFormat::Pdf => {
+                let font = self.pdf_font().await?;
+                pdf::render(&dataset, &header, &pages, options.language, &font)?
+            }
+        };
+        Ok((content, count))
+    }
+
+    /// Pharmacy details from settings; missing names fall back to a placeholder
+    pub async fn pharmacy_header(&self) -> PharmacyHeader {
+        let name_en = self.setting(Self::NAME_EN_SETTING).await;
+        let name_ar = self.setting(Self::NAME_AR_SETTING).await;
+        let name = match (name_en, name_ar) {
+            (Some(en), Some(ar)) => MultilingualDescription { en, ar },
+            (Some(name), None) | (None, Some(name)) => text(&name, &name),
+            (None, None) => text("Pharmacy", "الصيدلية"),
+        };
+
+        PharmacyHeader {
+            name,
+            address: self.setting(Self::ADDRESS_SETTING).await,
+            phone: self.setting(Self::PHONE_SETTING).await,
+            license_number: self.setting(Self::LICENSE_SETTING).await,
+        }
+    }
+
+    async fn setting(&self, key: &str) -> Option<String> {
+        self.settings
+            .get_string(key)
+            .await
+            .ok()
+            .map(|dto| dto.value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// TrueType font for PDF output
+    pub(crate) async fn pdf_font(&self) -> ServiceResult<Vec<u8>> {
+        let path = match self.setting(Self::PDF_FONT_SETTING).await {
+            Some(path) => PathBuf::from(path),
+            None => FONT_CANDIDATES
+                .iter()
+                .map(PathBuf::from)
+                .find(|path| path.is_file())
+                .ok_or_else(|| {
+                    ServiceError::BadRequest(format!(
+                        "No font found for PDF reports; set {} to a TrueType font",
+                        Self::PDF_FONT_SETTING
+                    ))
+                })?,
+        };
+
+        tokio::fs::read(&path).await.map_err(|e| {
+            ServiceError::BadRequest(format!("Failed to read font {}: {}", path.display(), e))
+        })
+    }
+
+    // ========================================================================
+    // Datasets
+    // ========================================================================
+
+    /// Collect the data of a report
+    pub async fn dataset(&self, report: ReportKind) -> ServiceResult<ReportDataset> {
+        match report {
+            ReportKind::InventoryStatistics => self.inventory_statistics().await,
+            ReportKind::LowStock => self.low_stock().await,
+            ReportKind::OpeningBalanceStatistics => self.opening_balance_statistics().await,
+            ReportKind::Valuation(query) => self.valuation(query).await,
+            ReportKind::Dataset(dataset) => Ok(dataset),
+        }
+    }
+
+    async fn inventory_statistics(&self) -> ServiceResult<ReportDataset> {
+        let stats = self.inventory.get_statistics().await?;
+
+        Ok(ReportDataset {
+            title: text("Inventory Statistics", "إحصائيات المخزون"),
+            generated_at: Utc::now(),
+            summary: vec![
+                field(text("Total items", "إجمالي الأصناف"), stats.total_items),
+                field(text("Active items", "الأصناف النشطة"), stats.active_items),
+                field(
+                    text("Inactive items", "الأصناف غير النشطة"),
+                    stats.inactive_items,
+                ),
+                field(text("Low stock", "مخزون منخفض"), stats.low_stock_count),
+                field(
+                    text("Out of stock", "نفد من المخزون"),
+                    stats.out_of_stock_count,
+                ),
+                field(
+                    text("Inventory value", "قيمة المخزون"),
+                    money(stats.total_inventory_value),
+                ),
+            ],
+            tables: Vec::new(),
+        })
+    }
+
+    async fn low_stock(&self) -> ServiceResult<ReportDataset> {
+        let items = self.inventory.get_low_stock().await?;
+
+        Ok(ReportDataset {
+            title: text("Low Stock Items", "الأصناف منخفضة المخزون"),
+            generated_at: Utc::now(),
+            summary: vec![field(text("Items", "الأصناف"), items.len())],
+            tables: vec![ReportTable {
+                title: None,
+                columns: vec![
+                    column(text("Item", "الصنف"), ColumnAlign::Start),
+                    column(text("Concentration", "التركيز"), ColumnAlign::Start),
+                    column(text("Manufacturer", "الشركة المصنعة"), ColumnAlign::Start),
+                    column(text("Stock", "المخزون"), ColumnAlign::End),
+                    column(text("Minimum", "الحد الأدنى"), ColumnAlign::End),
+                ],
+                rows: items
+                    .into_iter()
+                    .map(|item| {
+                        vec![
+                            item.name,
+                            item.concentration,
+                            item.manufacturer_name.unwrap_or_default(),
+                            item.stock_quantity.to_string(),
+                            item.min_stock_level.to_string(),
+                        ]
+                    })
+                    .collect(),
+            }],
+        })
+    }
+
+    async fn opening_balance_statistics(&self) -> ServiceResult<ReportDataset> {
+        let stats = self.opening_balance.get_statistics().await?;
+        let by_type = &stats.entries_by_type;
+
+        Ok(ReportDataset {
+            title: text("Opening Balance Statistics", "إحصائيات الأرصدة الافتتاحية"),
+            generated_at: Utc::now(),
+            summary: vec![
+                field(text("Total entries", "إجمالي القيود"), stats.total_entries),
+                field(
+                    text("Total value", "القيمة الإجمالية"),
+                    money(stats.total_value),
+                ),
+                field(text("Verified", "تم التحقق"), stats.verified_entries),
+                field(
+                    text("Pending verification", "بانتظار التحقق"),
+                    stats.pending_verification,
+                ),
+                field(
+                    text("Items with opening balance", "أصناف لها رصيد افتتاحي"),
+                    stats.items_with_opening_balance,
+                ),
+                field(
+                    text("Latest entry", "آخر قيد"),
+                    stats
+                        .latest_entry_date
+                        .map(|date| date.to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                ),
+            ],
+            tables: vec![ReportTable {
+                title: Some(text("Entries by type", "القيود حسب النوع")),
+                columns: vec![
+                    column(text("Type", "النوع"), ColumnAlign::Start),
+                    column(text("Entries", "القيود"), ColumnAlign::End),
+                ],
+                rows: vec![
+                    vec!["Initial / افتتاحي".to_string(), by_type.initial.to_string()],
+                    vec![
+                        "Adjustment / تسوية".to_string(),
+                        by_type.adjustment.to_string(),
+                    ],
+                    vec![
+                        "Correction / تصحيح".to_string(),
+                        by_type.correction.to_string(),
+                    ],
+                    vec![
+                        "Reconciliation / مطابقة".to_string(),
+                        by_type.reconciliation.to_string(),
+                    ],
+                ],
+            }],
+        })
+    }
+
+    async fn valuation(&self, query: ValuationQuery) -> ServiceResult<ReportDataset> {
+        let report = self.inventory.get_valuation(query).await?;
+
+        Ok(ReportDataset {
+            title: text("Inventory Valuation", "تقييم المخزون"),
+            generated_at: Utc::now(),
+            summary: vec![
+                field(text("As of", "حتى تاريخ"), report.as_of),
+                field(
+                    text("Total quantity", "إجمالي الكمية"),
+                    report.total_quantity,
+                ),
+                field(
+                    text("Total value", "القيمة الإجمالية"),
+                    money(report.total_value),
+                ),
+            ],
+            tables: report
+                .groups
+                .into_iter()
+                .map(|group| ReportTable {
+                    title: Some(text(&group.name, &group.name)),
+                    columns: vec![
+                        column(text("Item", "الصنف"), ColumnAlign::Start),
+                        column(text("Quantity", "الكمية"), ColumnAlign::End),
+                        column(text("Unit cost", "تكلفة الوحدة"), ColumnAlign::End),
+                        column(text("Value", "القيمة"), ColumnAlign::End),
+                    ],
+                    rows: group
+                        .items
+                        .into_iter()
+                        .map(|item| {
+                            vec![
+                                item.name,
+                                item.quantity.to_string(),
+                                money(item.unit_cost),
+                                money(item.value),
+                            ]
+                        })
+                        .chain(std::iter::once(vec![
+                            String::new(),
+                            group.total_quantity.to_string(),
+                            String::new(),
+                            money(group.total_value),
+                        ]))
+                        .collect(),
+                })
+                .collect(),
+        })
+    }
+}
+
+fn field(label: MultilingualDescription, value: impl ToString) -> ReportField {
+    ReportField {
+        label,
+        value: value.to_string(),
+    }
+}
+
+fn column(label: MultilingualDescription, align: ColumnAlign) -> ReportColumn {
+    ReportColumn { label, align }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> ServiceError {
+    ServiceError::Internal(format!("Failed to access {}: {}", path.display(), e))
+}
+
+fn money(value: f64) -> String {
+    format!("{:.2}", value)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,509 @@
+//! PDF report rendering
+//!
+//! Text is reordered with the Unicode bidi algorithm and shaped with
+//! rustybuzz, so Arabic is joined and laid out right to left. The font is
+//! embedded whole as a CID-keyed TrueType font addressed by glyph ID, with a
+//! ToUnicode map so text can still be searched and copied.
+//!
+//! [`PdfWriter`] is shared with other printable documents, such as labels.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str};
+use rustybuzz::ttf_parser::GlyphId;
+use rustybuzz::{Direction, Face, UnicodeBuffer};
+use unicode_bidi::{BidiInfo, Level};
+
+use super::{
+    Block, ColumnAlign, Page, PharmacyHeader, ReportDataset, ReportLanguage, generated_label,
+    no_data_label, page_label, table_title,
+};
+use crate::error::{ServiceError, ServiceResult};
+
+/// A4 in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 40.0;
+const ROW_HEIGHT: f32 = 16.0;
+/// Horizontal padding inside table cells
+const CELL_PADDING: f32 = 4.0;
+
+const FONT_NAME: Name<'static> = Name(b"F1");
+
+/// Glyph produced by shaping, with its shaped advance in font units
+struct Glyph {
+    id: u16,
+    advance: i32,
+    /// Text the glyph stands for, for the ToUnicode map
+    text: String,
+}
+
+/// Horizontal placement of text within its bounds
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// Render `pages` of `dataset` as a PDF document using the TrueType `font`
+pub(super) fn render(
+    dataset: &ReportDataset,
+    header: &PharmacyHeader,
+    pages: &[Page<'_>],
+    language: ReportLanguage,
+    font: &[u8],
+) -> ServiceResult<Vec<u8>> {
+    let mut writer = PdfWriter::new(font, language.is_rtl())?;
+    for (index, page) in pages.iter().enumerate() {
+        render_page(
+            &mut writer,
+            dataset,
+            header,
+            page,
+            language,
+            (index + 1, pages.len()),
+        );
+        writer.end_page(PAGE_WIDTH, PAGE_HEIGHT);
+    }
+    Ok(writer.finish(font))
+}
+
+/// Lay out one report page; `number` is the 1-based page number and total
+fn render_page(
+    writer: &mut PdfWriter<'_>,
+    dataset: &ReportDataset,
+    header: &PharmacyHeader,
+    page: &Page<'_>,
+    language: ReportLanguage,
+    number: (usize, usize),
+) {
+    let (left, right) = (MARGIN, PAGE_WIDTH - MARGIN);
+    let start = if language.is_rtl() {
+        Align::Right
+    } else {
+        Align::Left
+    };
+
+    // Pharmacy header
+    let mut y = PAGE_HEIGHT - MARGIN;
+    writer.text(
+        language.pick(&header.name),
+        16.0,
+        left,
+        right,
+        Align::Center,
+        y - 16.0,
+    );
+    y -= 22.0;
+    let details = header.details(language);
+    if !details.is_empty() {
+        writer.text(&details, 9.0, left, right, Align::Center, y - 9.0);
+        y -= 14.0;
+    }
+    writer.rule(left, right, y - 2.0, 0.8);
+    y -= 12.0;
+
+    writer.text(
+        language.pick(&dataset.title),
+        13.0,
+        left,
+        right,
+        start,
+        y - 13.0,
+    );
+    y -= 18.0;
+    let generated = generated_label(language, dataset.generated_at);
+    writer.text(&generated, 8.0, left, right, start, y - 8.0);
+    y -= 18.0;
+
+    for block in &page.blocks {
+        match block {
+            Block::Summary(fields) => {
+                let split = left + (right - left) * 0.4;
+                for field in fields.iter() {
+                    let baseline = y - 12.0;
+                    let label = language.pick(&field.label);
+                    if language.is_rtl() {
+                        writer.text(label, 9.0, right - (split - left), right, start, baseline);
+                        writer.text(
+                            &field.value,
+                            9.0,
+                            left,
+                            right - (split - left),
+                            start,
+                            baseline,
+                        );
+                    } else {
+                        writer.text(label, 9.0, left, split, start, baseline);
+                        writer.text(&field.value, 9.0, split, right, start, baseline);
+                    }
+                    y -= ROW_HEIGHT;
+                }
+                y -= ROW_HEIGHT;
+            }
+            Block::Table {
+                table,
+                rows,
+                continued,
+            } => {
+                if let Some(title) = table_title(language, table, *continued) {
+                    writer.text(&title, 11.0, left, right, start, y - 12.0);
+                }
+                y -= ROW_HEIGHT;
+
+                let columns = column_bounds(table.columns.len(), left, right, language);
+                writer.content().set_fill_gray(0.92);
+                writer
+                    .content()
+                    .rect(left, y - ROW_HEIGHT, right - left, ROW_HEIGHT);
+                writer.content().fill_nonzero();
+                writer.content().set_fill_gray(0.0);
+                for (column, (x0, x1)) in table.columns.iter().zip(&columns) {
+                    let align = column_align(language, column.align);
+                    writer.text(language.pick(&column.label), 9.0, *x0, *x1, align, y - 11.5);
+                }
+                y -= ROW_HEIGHT;
+
+                if rows.is_empty() {
+                    let label = no_data_label(language);
+                    writer.text(label, 9.0, left, right, Align::Center, y - 11.5);
+                    writer.rule(left, right, y - ROW_HEIGHT, 0.3);
+                    y -= ROW_HEIGHT;
+                }
+                for row in rows.iter() {
+                    for ((column, cell), (x0, x1)) in table.columns.iter().zip(row).zip(&columns) {
+                        let align = column_align(language, column.align);
+                        writer.text(cell, 9.0, *x0, *x1, align, y - 11.5);
+                    }
+                    writer.rule(left, right, y - ROW_HEIGHT, 0.3);
+                    y -= ROW_HEIGHT;
+                }
+            }
+        }
+    }
+
+    let footer = page_label(language, number.0, number.1);
+    writer.text(&footer, 8.0, left, right, Align::Center, MARGIN - 12.0);
+}
+
+/// Physical alignment of a column in the report's reading direction
+fn column_align(language: ReportLanguage, align: ColumnAlign) -> Align {
+    match (align, language.is_rtl()) {
+        (ColumnAlign::Start, false) | (ColumnAlign::End, true) => Align::Left,
+        (ColumnAlign::Start, true) | (ColumnAlign::End, false) => Align::Right,
+    }
+}
+
+/// Writes pages of shaped text and vector graphics into a PDF document
+/// embedding a single TrueType font
+pub(crate) struct PdfWriter<'a> {
+    face: Face<'a>,
+    /// Right-to-left base direction for bidi reordering
+    rtl: bool,
+    /// Glyphs shown anywhere in the document, with the text they stand for
+    used: BTreeMap<u16, String>,
+    content: Content,
+    /// Finished pages: size in points and content stream
+    pages: Vec<(f32, f32, Vec<u8>)>,
+}
+
+impl<'a> PdfWriter<'a> {
+    pub(crate) fn new(font: &'a [u8], rtl: bool) -> ServiceResult<Self> {
+        let face = Face::from_slice(font, 0).ok_or_else(|| {
+            ServiceError::BadRequest("Unsupported font for PDF output".to_string())
+        })?;
+        Ok(Self {
+            face,
+            rtl,
+            used: BTreeMap::new(),
+            content: Content::new(),
+            pages: Vec::new(),
+        })
+    }
+
+    /// Content stream of the current page, for drawing graphics
+    pub(crate) fn content(&mut self) -> &mut Content {
+        &mut self.content
+    }
+
+    /// Finish the current page with the given size in points
+    pub(crate) fn end_page(&mut self, width: f32, height: f32) {
+        let content = std::mem::replace(&mut self.content, Content::new());
+        self.pages.push((width, height, content.finish()));
+    }
+
+    pub(crate) fn rule(&mut self, x0: f32, x1: f32, y: f32, width: f32) {
+        self.content.set_line_width(width);
+        self.content.move_to(x0, y);
+        self.content.line_to(x1, y);
+        self.content.stroke();
+    }
+
+    /// Show `text` on `baseline` within `x0..x1`, shortened to fit
+    pub(crate) fn text(
+        &mut self,
+        text: &str,
+        size: f32,
+        x0: f32,
+        x1: f32,
+        align: Align,
+        baseline: f32,
+    ) {
+        let (x0, x1) = (x0 + CELL_PADDING, x1 - CELL_PADDING);
+        let glyphs = self.fit(text, size, x1 - x0);
+        if glyphs.is_empty() {
+            return;
+        }
+
+        let width = self.width(&glyphs, size);
+        let x = match align {
+            Align::Left => x0,
+            Align::Right => x1 - width,
+            Align::Center => x0 + (x1 - x0 - width) / 2.0,
+        };
+        // The viewer advances by each glyph's default width; adjust that to
+        // the shaped advance (kerning, joining marks)
+        let scale = 1000.0 / self.units_per_em();
+        let adjustments: Vec<f32> = glyphs
+            .iter()
+            .map(|glyph| self.glyph_width(glyph.id) - glyph.advance as f32 * scale)
+            .collect();
+
+        self.content.begin_text();
+        self.content.set_font(FONT_NAME, size);
+        self.content
+            .set_text_matrix([1.0, 0.0, 0.0, 1.0, x, baseline]);
+        {
+            let mut shown = self.content.show_positioned();
+            let mut items = shown.items();
+            for (glyph, adjustment) in glyphs.iter().zip(adjustments) {
+                items.show(Str(&glyph.id.to_be_bytes()));
+                if adjustment.abs() > f32::EPSILON {
+                    items.adjust(adjustment);
+                }
+            }
+        }
+        self.content.end_text();
+
+        for glyph in glyphs {
+            self.used.entry(glyph.id).or_insert(glyph.text);
+        }
+    }
+
+    /// Shape `text`, dropping characters until it fits in `max_width`
+    fn fit(&self, text: &str, size: f32, max_width: f32) -> Vec<Glyph> {
+        let text = text.replace(['\n', '\r', '\t'], " ");
+        let glyphs = self.shape(&text);
+        if self.width(&glyphs, size) <= max_width {
+            return glyphs;
+        }
+
+        let mut chars: Vec<char> = text.chars().collect();
+        while chars.pop().is_some() {
+            let shortened = format!("{}…", chars.iter().collect::<String>().trim_end());
+            let glyphs = self.shape(&shortened);
+            if self.width(&glyphs, size) <= max_width {
+                return glyphs;
+            }
+        }
+        Vec::new()
+    }
+
+    /// Shape `text` into glyphs in visual (left to right) order
+    fn shape(&self, text: &str) -> Vec<Glyph> {
+        let base = if self.rtl { Level::rtl() } else { Level::ltr() };
+        let bidi = BidiInfo::new(text, Some(base));
+
+        let mut glyphs = Vec::new();
+        for paragraph in &bidi.paragraphs {
+            let (levels, runs) = bidi.visual_runs(paragraph, paragraph.range.clone());
+            for run in runs {
+                let run_text = &text[run.clone()];
+                let mut buffer = UnicodeBuffer::new();
+                buffer.push_str(run_text);
+                buffer.guess_segment_properties();
+                buffer.set_direction(if levels[run.start].is_rtl() {
+                    Direction::RightToLeft
+                } else {
+                    Direction::LeftToRight
+                });
+                let shaped = rustybuzz::shape(&self.face, &[], buffer);
+
+                // Each glyph maps back to the text of its cluster
+                let mut clusters: Vec<usize> = shaped
+                    .glyph_infos()
+                    .iter()
+                    .map(|info| info.cluster as usize)
+                    .collect();
+                clusters.sort_unstable();
+                clusters.dedup();
+
+                for (info, position) in shaped.glyph_infos().iter().zip(shaped.glyph_positions()) {
+                    let start = info.cluster as usize;
+                    let end = clusters
+                        .iter()
+                        .find(|&&cluster| cluster > start)
+                        .copied()
+                        .unwrap_or(run_text.len());
+                    glyphs.push(Glyph {
+                        id: info.glyph_id as u16,
+                        advance: position.x_advance,
+                        text: run_text[start..end].to_string(),
+                    });
+                }
+            }
+        }
+        glyphs
+    }
+
+    /// Width of shaped glyphs in points
+    fn width(&self, glyphs: &[Glyph], size: f32) -> f32 {
+        let units: i32 = glyphs.iter().map(|glyph| glyph.advance).sum();
+        units as f32 * size / self.units_per_em()
+    }
+
+    fn units_per_em(&self) -> f32 {
+        self.face.units_per_em() as f32
+    }
+
+    /// Default advance of a glyph in thousandths of an em
+    fn glyph_width(&self, id: u16) -> f32 {
+        let advance = self.face.glyph_hor_advance(GlyphId(id)).unwrap_or(0);
+        f32::from(advance) * 1000.0 / self.units_per_em()
+    }
+
+    /// Assemble the document from the finished pages
+    pub(crate) fn finish(self, font: &[u8]) -> Vec<u8> {
+        let mut next = 1;
+        let mut alloc = || {
+            let id = Ref::new(next);
+            next += 1;
+            id
+        };
+
+        let catalog_id = alloc();
+        let page_tree_id = alloc();
+        let font_id = alloc();
+        let cid_font_id = alloc();
+        let descriptor_id = alloc();
+        let font_file_id = alloc();
+        let cmap_id = alloc();
+        let page_ids: Vec<(Ref, Ref)> = self.pages.iter().map(|_| (alloc(), alloc())).collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().map(|(page, _)| *page))
+            .count(page_ids.len() as i32);
+
+        for ((page_id, content_id), (width, height, content)) in page_ids.iter().zip(&self.pages) {
+            let mut page = pdf.page(*page_id);
+            page.media_box(Rect::new(0.0, 0.0, *width, *height));
+            page.parent(page_tree_id);
+            page.contents(*content_id);
+            page.resources().fonts().pair(FONT_NAME, font_id);
+            page.finish();
+
+            pdf.stream(*content_id, &deflate(content))
+                .filter(Filter::FlateDecode);
+        }
+
+        let system_info = SystemInfo {
+            registry: Str(b"Adobe"),
+            ordering: Str(b"Identity"),
+            supplement: 0,
+        };
+        let base_font = Name(b"ReportFont");
+
+        pdf.type0_font(font_id)
+            .base_font(base_font)
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid_font_id)
+            .to_unicode(cmap_id);
+
+        let mut cid_font = pdf.cid_font(cid_font_id);
+        cid_font
+            .subtype(CidFontType::Type2)
+            .base_font(base_font)
+            .system_info(system_info)
+            .font_descriptor(descriptor_id)
+            .cid_to_gid_map_predefined(Name(b"Identity"));
+        {
+            let mut widths = cid_font.widths();
+            for &id in self.used.keys() {
+                widths.consecutive(id, [self.glyph_width(id)]);
+            }
+        }
+        cid_font.finish();
+
+        let scale = 1000.0 / self.units_per_em();
+        let bbox = self.face.global_bounding_box();
+        let ascent = f32::from(self.face.ascender()) * scale;
+        pdf.font_descriptor(descriptor_id)
+            .name(base_font)
+            .flags(FontFlags::NON_SYMBOLIC)
+            .bbox(Rect::new(
+                f32::from(bbox.x_min) * scale,
+                f32::from(bbox.y_min) * scale,
+                f32::from(bbox.x_max) * scale,
+                f32::from(bbox.y_max) * scale,
+            ))
+            .italic_angle(0.0)
+            .ascent(ascent)
+            .descent(f32::from(self.face.descender()) * scale)
+            .cap_height(
+                self.face
+                    .capital_height()
+                    .map(|height| f32::from(height) * scale)
+                    .unwrap_or(ascent),
+            )
+            .stem_v(80.0)
+            .font_file2(font_file_id);
+
+        pdf.stream(font_file_id, &deflate(font))
+            .filter(Filter::FlateDecode);
+
+        let mut cmap = UnicodeCmap::new(Name(b"Custom"), system_info);
+        for (&id, text) in &self.used {
+            if !text.is_empty() {
+                cmap.pair_with_multiple(id, text.chars());
+            }
+        }
+        pdf.cmap(cmap_id, &cmap.finish());
+
+        pdf.finish()
+    }
+}
+
+/// Horizontal bounds of each column, the first column twice as wide as the
+/// others and placed on the reading-direction start side
+fn column_bounds(count: usize, left: f32, right: f32, language: ReportLanguage) -> Vec<(f32, f32)> {
+    let unit = (right - left) / (count as f32 + 1.0);
+    let mut bounds = Vec::with_capacity(count);
+    let mut x = left;
+    for index in 0..count {
+        let width = if index == 0 { unit * 2.0 } else { unit };
+        bounds.push((x, x + width));
+        x += width;
+    }
+
+    if language.is_rtl() {
+        // Mirror around the page centre
+        for (x0, x1) in &mut bounds {
+            (*x0, *x1) = (left + right - *x1, left + right - *x0);
+        }
+    }
+    bounds
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    // Writing to a Vec cannot fail
+    encoder.write_all(data).expect("compress into memory");
+    encoder.finish().expect("compress into memory")
+}
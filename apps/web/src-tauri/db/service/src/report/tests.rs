@@ -0,0 +1,177 @@
+use db_entity::setting::dto::SetSettingDto;
+
+use super::*;
+use crate::events::EventBus;
+use crate::test_support::sqlite_db;
+
+async fn report_service() -> (ReportService, Arc<SettingsService>) {
+    let db = sqlite_db().await;
+
+    let events = Arc::new(EventBus::default());
+    let settings = Arc::new(SettingsService::new(db.clone(), events.clone()));
+    let service = ReportService::new(
+        Arc::new(InventoryService::new(db.clone(), events.clone())),
+        Arc::new(OpeningBalanceService::new(db.clone(), events)),
+        settings.clone(),
+    );
+    (service, settings)
+}
+
+async fn set(settings: &SettingsService, key: &str, value: &str) {
+    settings
+        .set(SetSettingDto {
+            key: key.to_string(),
+            value: serde_json::json!(value),
+            category: Some("pharmacy".to_string()),
+            description: None,
+            updated_by: None,
+        })
+        .await
+        .unwrap();
+}
+
+/// Dataset with one table of `rows` rows
+fn dataset(rows: usize) -> ReportDataset {
+    ReportDataset {
+        title: text("Stock <Count>", "جرد المخزون"),
+        generated_at: Utc::now(),
+        summary: vec![field(text("Items", "الأصناف"), rows)],
+        tables: vec![ReportTable {
+            title: Some(text("Items", "الأصناف")),
+            columns: vec![
+                column(text("Item", "الصنف"), ColumnAlign::Start),
+                column(text("Quantity", "الكمية"), ColumnAlign::End),
+            ],
+            rows: (1..=rows)
+                .map(|n| vec![format!("Item {}", n), n.to_string()])
+                .collect(),
+        }],
+    }
+}
+
+fn html_options(report: ReportKind, language: ReportLanguage) -> ReportOptions {
+    ReportOptions {
+        report,
+        format: ReportFormat::Html,
+        language,
+    }
+}
+
+#[test]
+fn test_paginate_repeats_table_heading_on_each_page() {
+    let dataset = dataset(60);
+    let pages = paginate(&dataset);
+
+    // Summary (2 lines) + heading (2) + 28 rows, then heading + 30 rows, then 2
+    assert_eq!(pages.len(), 3);
+    let rows: Vec<(usize, bool)> = pages
+        .iter()
+        .flat_map(|page| &page.blocks)
+        .filter_map(|block| match block {
+            Block::Table {
+                rows, continued, ..
+            } => Some((rows.len(), *continued)),
+            Block::Summary(_) => None,
+        })
+        .collect();
+    assert_eq!(rows, vec![(28, false), (30, true), (2, true)]);
+}
+
+#[tokio::test]
+async fn test_html_report_in_arabic_is_right_to_left() {
+    let (service, settings) = report_service().await;
+    set(&settings, ReportService::NAME_EN_SETTING, "Nile Pharmacy").await;
+    set(&settings, ReportService::NAME_AR_SETTING, "صيدلية النيل").await;
+    set(&settings, ReportService::PHONE_SETTING, "0223456789").await;
+
+    let (content, pages) = service
+        .render(html_options(
+            ReportKind::Dataset(dataset(40)),
+            ReportLanguage::Ar,
+        ))
+        .await
+        .unwrap();
+    let html = String::from_utf8(content).unwrap();
+
+    assert_eq!(pages, 2);
+    assert!(html.contains("<html lang=\"ar\" dir=\"rtl\">"));
+    assert_eq!(html.matches("<h1>صيدلية النيل</h1>").count(), 2);
+    assert!(html.contains("<p>0223456789</p>"));
+    assert!(html.contains("<th class=\"end\">الكمية</th>"));
+    assert!(html.contains("الأصناف (تابع)"));
+    assert!(html.contains("صفحة 2 من 2"));
+
+    let (content, _) = service
+        .render(html_options(
+            ReportKind::Dataset(dataset(0)),
+            ReportLanguage::En,
+        ))
+        .await
+        .unwrap();
+    let html = String::from_utf8(content).unwrap();
+    assert!(html.contains("dir=\"ltr\""));
+    assert!(html.contains("<h2>Stock &lt;Count&gt;</h2>"));
+    assert!(html.contains("No data"));
+}
+
+#[tokio::test]
+async fn test_save_inventory_statistics_report() {
+    let (service, _) = report_service().await;
+    let path = std::env::temp_dir()
+        .join(format!("meditrack-report-{}", db_entity::id::Id::new()))
+        .join("statistics.html");
+
+    let report = service
+        .save(
+            html_options(ReportKind::InventoryStatistics, ReportLanguage::En),
+            &path,
+        )
+        .await
+        .unwrap();
+    assert_eq!(report.pages, 1);
+
+    let html = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(report.size, html.len() as u64);
+    assert!(html.contains("Inventory Statistics"));
+    // Placeholder header until the pharmacy name is configured
+    assert!(html.contains("<h1>Pharmacy</h1>"));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn test_pdf_report_embeds_configured_font() {
+    let (service, settings) = report_service().await;
+    let options = ReportOptions {
+        report: ReportKind::Dataset(dataset(40)),
+        format: ReportFormat::Pdf,
+        language: ReportLanguage::Ar,
+    };
+
+    set(
+        &settings,
+        ReportService::PDF_FONT_SETTING,
+        "/missing/font.ttf",
+    )
+    .await;
+    assert!(matches!(
+        service.render(options.clone()).await,
+        Err(ServiceError::BadRequest(_))
+    ));
+
+    // Rendering needs a real font; skip where the system has none of ours
+    let Some(font) = FONT_CANDIDATES
+        .iter()
+        .find(|path| Path::new(path).is_file())
+    else {
+        return;
+    };
+    set(&settings, ReportService::PDF_FONT_SETTING, font).await;
+
+    let (content, pages) = service.render(options).await.unwrap();
+    assert_eq!(pages, 2);
+    assert!(content.starts_with(b"%PDF-"));
+    let pdf = String::from_utf8_lossy(&content);
+    assert_eq!(pdf.matches("/Type /Page\n").count(), 2);
+    assert!(pdf.contains("/FontFile2"));
+}
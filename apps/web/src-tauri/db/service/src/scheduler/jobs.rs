@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::backup::{BackupCipher, BackupService};
+use crate::error::{ServiceError, ServiceResult};
+use crate::inventory::InventoryService;
+use crate::inventory::opening_balances::OpeningBalanceService;
+use crate::session::SessionService;
+use crate::settings::SettingsService;
+
+/// A unit of background work that the scheduler can run
+#[async_trait]
+pub trait ScheduledJob: Send + Sync {
+    /// Unique job name, also used in the `scheduler.<name>.*` setting keys
+    fn name(&self) -> &'static str;
+
+    /// Cron expression used when no `scheduler.<name>.cron` setting exists
+    fn default_cron(&self) -> &'static str;
+
+    /// Run the job once, returning a short summary for the run log
+    async fn run(&self) -> ServiceResult<String>;
+}
+
+// ============================================================================
+// Session Cleanup
+// ============================================================================
+
+/// Removes expired user sessions
+pub struct SessionCleanupJob {
+    session: Arc<SessionService>,
+}
+
+impl SessionCleanupJob {
+    pub fn new(session: Arc<SessionService>) -> Self {
+        Self { session }
+    }
+}
+
+#[async_trait]
+impl ScheduledJob for SessionCleanupJob {
+    fn name(&self) -> &'static str {
+        "session_cleanup"
+    }
+
+    fn default_cron(&self) -> &'static str {
+        "*/15 * * * *"
+    }
+
+    async fn run(&self) -> ServiceResult<String> {
+        let removed = self.session.cleanup_expired_sessions().await?;
+        Ok(format!("Removed {} expired session(s)", removed))
+    }
+}
+
+// ============================================================================
+// Low Stock Check
+// ============================================================================
+
+/// Checks for items at or below their minimum stock level
+pub struct LowStockCheckJob {
+    inventory: Arc<InventoryService>,
+}
+
+impl LowStockCheckJob {
+    pub fn new(inventory: Arc<InventoryService>) -> Self {
+        Self { inventory }
+    }
+}
+
+#[async_trait]
+impl ScheduledJob for LowStockCheckJob {
+    fn name(&self) -> &'static str {
+        "low_stock_check"
+    }
+
+    fn default_cron(&self) -> &'static str {
+        "0 * * * *"
+    }
+
+    async fn run(&self) -> ServiceResult<String> {
+        let items = self.inventory.get_low_stock().await?;
+
+        if !items.is_empty() {
+            tracing::warn!("{} item(s) at or below minimum stock level", items.len());
+        }
+
+        Ok(format!(
+            "{} item(s) at or below minimum stock level",
+            items.len()
+        ))
+    }
+}
+
+// ============================================================================
+// Near-Expiry Alerts
+// ============================================================================
+
+/// Flags stock batches that are about to expire
+pub struct NearExpiryAlertJob {
+    opening_balance: Arc<OpeningBalanceService>,
+    settings: Arc<SettingsService>,
+}
+
+impl NearExpiryAlertJob {
+    /// Setting key for the alert window in days
+    pub const DAYS_SETTING: &'static str = "scheduler.near_expiry_alert.days";
+
+    /// Alert window used when the setting is missing
+    pub const DEFAULT_DAYS: i64 = 90;
+
+    pub fn new(
+        opening_balance: Arc<OpeningBalanceService>,
+        settings: Arc<SettingsService>,
+    ) -> Self {
+        Self {
+            opening_balance,
+            settings,
+        }
+    }
+}
+
+#[async_trait]
+impl ScheduledJob for NearExpiryAlertJob {
+    fn name(&self) -> &'static str {
+        "near_expiry_alert"
+    }
+
+    fn default_cron(&self) -> &'static str {
+        "0 8 * * *"
+    }
+
+    async fn run(&self) -> ServiceResult<String> {
+        let days = self
+            .settings
+            .get_number(Self::DAYS_SETTING)
+            .await
+            .map(|dto| dto.value as i64)
+            .unwrap_or(Self::DEFAULT_DAYS);
+
+        let batches = self.opening_balance.get_expiring_within(days).await?;
+
+        if !batches.is_empty() {
+            tracing::warn!(
+                "{} stock batch(es) expiring within {} days",
+                batches.len(),
+                days
+            );
+        }
+
+        Ok(format!(
+            "{} stock batch(es) expiring within {} days",
+            batches.len(),
+            days
+        ))
+    }
+}
+
+// ============================================================================
+// Database Backup
+// ============================================================================
+
+/// Backs up the database and removes backups beyond the retention count
+pub struct DatabaseBackupJob {
+    backup: Arc<BackupService>,
+    settings: Arc<SettingsService>,
+    cipher: Option<Arc<dyn BackupCipher>>,
+}
+
+impl DatabaseBackupJob {
+    /// Setting key for the number of backups to keep
+    pub const KEEP_SETTING: &'static str = "scheduler.database_backup.keep";
+
+    /// Setting key that turns on encryption of scheduled backups
+    pub const ENCRYPT_SETTING: &'static str = "scheduler.database_backup.encrypt";
+
+    /// Backups kept when the setting is missing
+    pub const DEFAULT_KEEP: usize = 7;
+
+    pub fn new(
+        backup: Arc<BackupService>,
+        settings: Arc<SettingsService>,
+        cipher: Option<Arc<dyn BackupCipher>>,
+    ) -> Self {
+        Self {
+            backup,
+            settings,
+            cipher,
+        }
+    }
+}
+
+#[async_trait]
+impl ScheduledJob for DatabaseBackupJob {
+    fn name(&self) -> &'static str {
+        "database_backup"
+    }
+
+    fn default_cron(&self) -> &'static str {
+        "0 2 * * *"
+    }
+
+    async fn run(&self) -> ServiceResult<String> {
+        let keep = self
+            .settings
+            .get_number(Self::KEEP_SETTING)
+            .await
+            .map(|dto| dto.value.max(1.0) as usize)
+            .unwrap_or(Self::DEFAULT_KEEP);
+        let encrypt = self
+            .settings
+            .get_bool(Self::ENCRYPT_SETTING)
+            .await
+            .map(|dto| dto.value)
+            .unwrap_or(false);
+
+        let cipher = match (encrypt, &self.cipher) {
+            (false, _) => None,
+            (true, Some(cipher)) => Some(cipher.as_ref()),
+            (true, None) => {
+                return Err(ServiceError::Internal(
+                    "Backup encryption is enabled but no key is configured".to_string(),
+                ));
+            }
+        };
+
+        let file = self.backup.create_backup(cipher).await?;
+        let removed = self.backup.prune_backups(keep).await?;
+
+        Ok(format!(
+            "Backed up to {}, removed {} old backup(s)",
+            file.path.display(),
+            removed
+        ))
+    }
+}
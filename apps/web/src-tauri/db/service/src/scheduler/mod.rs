@@ -0,0 +1,386 @@
+mod jobs;
+mod schedule;
+
+pub use jobs::{
+    DatabaseBackupJob, LowStockCheckJob, NearExpiryAlertJob, ScheduledJob, SessionCleanupJob,
+};
+pub use schedule::CronSchedule;
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use chrono::{Local, Timelike, Utc};
+use db_entity::id::Id;
+use db_entity::job_run::dto::{JobRunQueryDto, JobRunResponse, JobScheduleResponse};
+use db_entity::job_run::{self, Entity as JobRun, JobRunStatus};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use tap::TapFallible;
+use tokio::task::JoinHandle;
+
+use crate::error::{ServiceError, ServiceResult};
+use crate::pagination::{PaginationParams, PaginationResult};
+use crate::settings::SettingsService;
+
+/// Trigger recorded for runs started by the schedule
+const TRIGGER_SCHEDULE: &str = "schedule";
+
+/// Trigger recorded for runs started on demand
+const TRIGGER_MANUAL: &str = "manual";
+
+/// Background job scheduler
+///
+/// Jobs are registered once at start-up and run on cron-like schedules read
+/// from the settings table (`scheduler.<job>.cron` and `scheduler.<job>.enabled`),
+/// falling back to each job's default schedule. Every run is recorded in the
+/// `job_runs` table, and a job that is still running when it becomes due again
+/// is skipped rather than started a second time.
+pub struct JobScheduler {
+    db: Arc<DatabaseConnection>,
+    settings: Arc<SettingsService>,
+    jobs: Vec<Arc<dyn ScheduledJob>>,
+    running: Mutex<HashSet<&'static str>>,
+    started: AtomicBool,
+}
+
+/// Marks a job as running until dropped
+struct RunningGuard<'a> {
+    running: &'a Mutex<HashSet<&'static str>>,
+    name: &'static str,
+}
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(self.name);
+        }
+    }
+}
+
+impl JobScheduler {
+    /// Create a new scheduler with no registered jobs
+    pub fn new(db: Arc<DatabaseConnection>, settings: Arc<SettingsService>) -> Self {
+        Self {
+            db,
+            settings,
+            jobs: Vec::new(),
+            running: Mutex::new(HashSet::new()),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Register a job (must happen before the scheduler is started)
+    pub fn register(&mut self, job: Arc<dyn ScheduledJob>) {
+        if self.jobs.iter().any(|j| j.name() == job.name()) {
+            tracing::warn!("Job '{}' is already registered, ignoring", job.name());
+            return;
+        }
+        self.jobs.push(job);
+    }
+
+    /// Start the scheduler loop on the Tokio runtime
+    ///
+    /// Returns `None` if the scheduler was already started.
+    pub fn start(self: &Arc<Self>) -> Option<JoinHandle<()>> {
+        if self.started.swap(true, Ordering::SeqCst) {
+            tracing::warn!("Job scheduler already started");
+            return None;
+        }
+
+        let scheduler = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            scheduler.mark_interrupted_runs().await;
+
+            tracing::info!("Job scheduler started with {} job(s)", scheduler.jobs.len());
+
+            loop {
+                // Sleep until the start of the next minute
+                let now = Local::now();
+                let elapsed_ms =
+                    u64::from(now.second()) * 1000 + u64::from(now.timestamp_subsec_millis());
+                tokio::time::sleep(std::time::Duration::from_millis(
+                    60_000u64.saturating_sub(elapsed_ms).max(1),
+                ))
+                .await;
+
+                scheduler.tick().await;
+            }
+        }))
+    }
+
+    /// Run every job that is due in the current minute
+    async fn tick(self: &Arc<Self>) {
+        let now = Local::now();
+
+        for job in &self.jobs {
+            let Ok((schedule, enabled)) = self
+                .resolve_schedule(job.as_ref())
+                .await
+                .tap_err(|e| tracing::error!("Skipping job '{}': {}", job.name(), e))
+            else {
+                continue;
+            };
+            if !enabled || !schedule.matches(&now) {
+                continue;
+            }
+
+            let scheduler = Arc::clone(self);
+            let job = Arc::clone(job);
+            tokio::spawn(async move {
+                let _ = scheduler.execute(job, TRIGGER_SCHEDULE).await;
+            });
+        }
+    }
+
+    // ========================================================================
+    // Manual Execution
+    // ========================================================================
+
+    /// Run a job immediately, regardless of its schedule
+    pub async fn run_now(&self, job_name: &str) -> ServiceResult<JobRunResponse> {
+        let job = self
+            .jobs
+            .iter()
+            .find(|j| j.name() == job_name)
+            .cloned()
+            .ok_or_else(|| ServiceError::NotFound(format!("Job '{}' not found", job_name)))?;
+
+        let run = self.execute(job, TRIGGER_MANUAL).await?;
+        if run.status == JobRunStatus::Skipped {
+            return Err(ServiceError::Conflict(format!(
+                "Job '{}' is already running",
+                job_name
+            )));
+        }
+
+        Ok(run)
+    }
+
+    // ========================================================================
+    // Query Operations
+    // ========================================================================
+
+    /// List recorded job runs, newest first
+    pub async fn list_runs(
+        &self,
+        query: JobRunQueryDto,
+        pagination: Option<PaginationParams>,
+    ) -> ServiceResult<PaginationResult<JobRunResponse>> {
+        let mut select = JobRun::find();
+
+        if let Some(job_name) = query.job_name {
+            select = select.filter(job_run::Column::JobName.eq(job_name));
+        }
+        if let Some(status) = query.status {
+            select = select.filter(job_run::Column::Status.eq(status));
+        }
+
+        let total = select.clone().count(self.db.as_ref()).await?;
+        let select = select.order_by_desc(job_run::Column::StartedAt);
+
+        let (items, page, page_size) = if let Some(pagination) = pagination {
+            let page = pagination.page();
+            let page_size = pagination.page_size();
+            let items = select
+                .paginate(self.db.as_ref(), page_size)
+                .fetch_page(page - 1)
+                .await?;
+            (items, page, page_size)
+        } else {
+            let items = select.all(self.db.as_ref()).await?;
+            (items, 1u64, total)
+        };
+
+        Ok(PaginationResult::new(
+            items.into_iter().map(Into::into).collect(),
+            total,
+            page,
+            page_size,
+        ))
+    }
+
+    /// Get the effective schedule of every registered job
+    pub async fn get_schedules(&self) -> ServiceResult<Vec<JobScheduleResponse>> {
+        let now = Local::now();
+        let running = self
+            .running
+            .lock()
+            .map(|running| running.clone())
+            .unwrap_or_default();
+
+        let mut schedules = Vec::with_capacity(self.jobs.len());
+        for job in &self.jobs {
+            let (schedule, enabled) = self.resolve_schedule(job.as_ref()).await?;
+            schedules.push(JobScheduleResponse {
+                job_name: job.name().to_string(),
+                cron: schedule.expression().to_string(),
+                enabled,
+                is_running: running.contains(job.name()),
+                next_run_at: enabled
+                    .then(|| schedule.next_after(now))
+                    .flatten()
+                    .map(|dt| dt.with_timezone(&Utc).to_string()),
+            });
+        }
+
+        Ok(schedules)
+    }
+
+    // ========================================================================
+    // Helper Methods
+    // ========================================================================
+
+    /// Resolve a job's schedule and enabled flag from settings
+    ///
+    /// An invalid configured schedule falls back to the job's default; an
+    /// invalid default is reported as an internal error.
+    async fn resolve_schedule(
+        &self,
+        job: &dyn ScheduledJob,
+    ) -> ServiceResult<(CronSchedule, bool)> {
+        let enabled = self
+            .settings
+            .get_bool(&format!("scheduler.{}.enabled", job.name()))
+            .await
+            .map(|dto| dto.value)
+            .unwrap_or(true);
+
+        let configured = self
+            .settings
+            .get_string(&format!("scheduler.{}.cron", job.name()))
+            .await
+            .ok()
+            .and_then(|dto| {
+                CronSchedule::parse(&dto.value)
+                    .tap_err(|e| {
+                        tracing::warn!("Ignoring invalid schedule for job '{}': {}", job.name(), e)
+                    })
+                    .ok()
+            });
+
+        let schedule = match configured {
+            Some(schedule) => schedule,
+            None => CronSchedule::parse(job.default_cron()).map_err(|e| {
+                ServiceError::Internal(format!(
+                    "Invalid default schedule of job '{}': {}",
+                    job.name(),
+                    e
+                ))
+            })?,
+        };
+
+        Ok((schedule, enabled))
+    }
+
+    /// Run a job once and record the outcome
+    async fn execute(
+        &self,
+        job: Arc<dyn ScheduledJob>,
+        trigger: &str,
+    ) -> ServiceResult<JobRunResponse> {
+        let name = job.name();
+
+        let guard = {
+            let mut running = self
+                .running
+                .lock()
+                .map_err(|_| ServiceError::Internal("Job scheduler state poisoned".to_string()))?;
+            running.insert(name).then_some(RunningGuard {
+                running: &self.running,
+                name,
+            })
+        };
+
+        let Some(_guard) = guard else {
+            tracing::warn!("Skipping job '{}': previous run still in progress", name);
+            let now = Utc::now().into();
+            let skipped = job_run::ActiveModel {
+                id: Set(Id::new()),
+                job_name: Set(name.to_string()),
+                status: Set(JobRunStatus::Skipped),
+                trigger: Set(trigger.to_string()),
+                started_at: Set(now),
+                finished_at: Set(Some(now)),
+                duration_ms: Set(Some(0)),
+                message: Set(Some("Previous run still in progress".to_string())),
+            }
+            .insert(self.db.as_ref())
+            .await
+            .tap_err(|e| tracing::error!("Failed to record skipped run of '{}': {}", name, e))?;
+            return Ok(skipped.into());
+        };
+
+        let run = job_run::ActiveModel {
+            id: Set(Id::new()),
+            job_name: Set(name.to_string()),
+            status: Set(JobRunStatus::Running),
+            trigger: Set(trigger.to_string()),
+            started_at: Set(Utc::now().into()),
+            finished_at: Set(None),
+            duration_ms: Set(None),
+            message: Set(None),
+        }
+        .insert(self.db.as_ref())
+        .await
+        .tap_err(|e| tracing::error!("Failed to record start of job '{}': {}", name, e))?;
+
+        tracing::info!("Running job '{}' ({})", name, trigger);
+        let started = Instant::now();
+        let result = job.run().await;
+        let duration_ms = started.elapsed().as_millis() as i64;
+
+        let (status, message) = match result {
+            Ok(summary) => {
+                tracing::info!("Job '{}' succeeded in {}ms: {}", name, duration_ms, summary);
+                (JobRunStatus::Succeeded, summary)
+            }
+            Err(e) => {
+                tracing::error!("Job '{}' failed after {}ms: {}", name, duration_ms, e);
+                (JobRunStatus::Failed, e.to_string())
+            }
+        };
+
+        let mut active: job_run::ActiveModel = run.into();
+        active.status = Set(status);
+        active.finished_at = Set(Some(Utc::now().into()));
+        active.duration_ms = Set(Some(duration_ms));
+        active.message = Set(Some(message));
+
+        let finished = active
+            .update(self.db.as_ref())
+            .await
+            .tap_err(|e| tracing::error!("Failed to record result of job '{}': {}", name, e))?;
+
+        Ok(finished.into())
+    }
+
+    /// Fail runs left in `running` state by a previous process
+    async fn mark_interrupted_runs(&self) {
+        let result = JobRun::update_many()
+            .col_expr(job_run::Column::Status, Expr::value(JobRunStatus::Failed))
+            .col_expr(
+                job_run::Column::FinishedAt,
+                Expr::value(chrono::DateTime::<chrono::FixedOffset>::from(Utc::now())),
+            )
+            .col_expr(
+                job_run::Column::Message,
+                Expr::value("Interrupted before completion"),
+            )
+            .filter(job_run::Column::Status.eq(JobRunStatus::Running))
+            .exec(self.db.as_ref())
+            .await;
+
+        match result {
+            Ok(res) if res.rows_affected > 0 => {
+                tracing::warn!(
+                    "Marked {} interrupted job run(s) as failed",
+                    res.rows_affected
+                )
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to mark interrupted job runs: {}", e),
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike};
+
+use crate::error::{ServiceError, ServiceResult};
+
+/// Upper bound for `next_after` searches (a little over four years, so that
+/// schedules such as "0 0 29 2 *" still resolve)
+const MAX_SEARCH_MINUTES: i64 = 4 * 366 * 24 * 60;
+
+/// Cron-like schedule with the classic five fields:
+/// `minute hour day-of-month month day-of-week`
+///
+/// Each field accepts `*`, single values, ranges (`1-5`), steps (`*/15`,
+/// `0-30/10`) and comma-separated lists. Day-of-week uses 0-6 with Sunday as 0
+/// (7 is also accepted for Sunday). The `@hourly`, `@daily`, `@weekly` and
+/// `@monthly` shortcuts are supported as well.
+///
+/// Schedules are evaluated in the machine's local time zone, since jobs such as
+/// nightly backups are meant to follow the pharmacy's wall clock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+impl CronSchedule {
+    /// Parse a cron expression
+    pub fn parse(expression: &str) -> ServiceResult<Self> {
+        let trimmed = expression.trim();
+        let expanded = match trimmed {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(ServiceError::BadRequest(format!(
+                "Invalid cron expression '{}': expected 5 fields, found {}",
+                trimmed,
+                fields.len()
+            )));
+        }
+
+        let mut days_of_week = Self::parse_field(fields[4], 0, 7, trimmed)?;
+        // 7 is an alias for Sunday
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        days_of_week.truncate(7);
+
+        Ok(Self {
+            expression: trimmed.to_string(),
+            minutes: Self::parse_field(fields[0], 0, 59, trimmed)?,
+            hours: Self::parse_field(fields[1], 0, 23, trimmed)?,
+            days_of_month: Self::parse_field(fields[2], 1, 31, trimmed)?,
+            months: Self::parse_field(fields[3], 1, 12, trimmed)?,
+            days_of_week,
+            // As in Vixie cron, a day field starting with `*` (such as `*/2`)
+            // does not count as restricted for the day matching below
+            day_of_month_restricted: !fields[2].starts_with('*'),
+            day_of_week_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    /// Get the original expression
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Check whether the schedule fires in the minute containing `at`
+    pub fn matches<Tz: TimeZone>(&self, at: &DateTime<Tz>) -> bool {
+        self.matches_naive(&at.with_timezone(&Local).naive_local())
+    }
+
+    /// Find the first minute strictly after `after` at which the schedule fires
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after
+            .naive_local()
+            .with_second(0)
+            .and_then(|dt| dt.with_nanosecond(0))?
+            + Duration::minutes(1);
+
+        (0..MAX_SEARCH_MINUTES)
+            .map(|offset| start + Duration::minutes(offset))
+            .filter(|candidate| self.matches_naive(candidate))
+            // Skip local times that do not exist (DST gaps)
+            .find_map(|candidate| Local.from_local_datetime(&candidate).earliest())
+    }
+
+    fn matches_naive(&self, at: &NaiveDateTime) -> bool {
+        if !self.minutes[at.minute() as usize]
+            || !self.hours[at.hour() as usize]
+            || !self.months[at.month() as usize]
+        {
+            return false;
+        }
+
+        let day_of_month = self.days_of_month[at.day() as usize];
+        let day_of_week = self.days_of_week[at.weekday().num_days_from_sunday() as usize];
+
+        // Standard cron semantics: when both day fields are restricted, either
+        // may match; otherwise both must
+        if self.day_of_month_restricted && self.day_of_week_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+
+    /// Parse a single field into a lookup table indexed by value
+    fn parse_field(field: &str, min: u32, max: u32, expression: &str) -> ServiceResult<Vec<bool>> {
+        let invalid = |reason: &str| {
+            ServiceError::BadRequest(format!(
+                "Invalid cron expression '{}': field '{}' {}",
+                expression, field, reason
+            ))
+        };
+
+        let mut allowed = vec![false; max as usize + 1];
+
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step: u32 = step.parse().map_err(|_| invalid("has an invalid step"))?;
+                    if step == 0 {
+                        return Err(invalid("has a zero step"));
+                    }
+                    (range, step)
+                }
+                None => (part, 1),
+            };
+
+            let (start, end) = if range == "*" {
+                (min, max)
+            } else if let Some((start, end)) = range.split_once('-') {
+                let start: u32 = start.parse().map_err(|_| invalid("has an invalid range"))?;
+                let end: u32 = end.parse().map_err(|_| invalid("has an invalid range"))?;
+                (start, end)
+            } else {
+                let value: u32 = range.parse().map_err(|_| invalid("has an invalid value"))?;
+                // "5/10" means "starting at 5, every 10"
+                if part.contains('/') {
+                    (value, max)
+                } else {
+                    (value, value)
+                }
+            };
+
+            if start < min || end > max || start > end {
+                return Err(invalid(&format!("is out of range {}-{}", min, max)));
+            }
+
+            for value in (start..=end).step_by(step as usize) {
+                allowed[value as usize] = true;
+            }
+        }
+
+        Ok(allowed)
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = ServiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(y, mo, d, h, mi, 0)
+            .earliest()
+            .expect("valid local time")
+    }
+
+    #[test]
+    fn test_every_fifteen_minutes() {
+        let schedule = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert!(schedule.matches(&local(2025, 3, 10, 9, 0)));
+        assert!(schedule.matches(&local(2025, 3, 10, 9, 45)));
+        assert!(!schedule.matches(&local(2025, 3, 10, 9, 50)));
+    }
+
+    #[test]
+    fn test_next_after_daily() {
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        let next = schedule.next_after(local(2025, 3, 10, 9, 0)).unwrap();
+        assert_eq!(next, local(2025, 3, 11, 2, 30));
+    }
+
+    #[test]
+    fn test_next_after_is_strictly_later() {
+        let schedule = CronSchedule::parse("0 * * * *").unwrap();
+        let next = schedule.next_after(local(2025, 3, 10, 9, 0)).unwrap();
+        assert_eq!(next, local(2025, 3, 10, 10, 0));
+    }
+
+    #[test]
+    fn test_lists_ranges_and_steps() {
+        let schedule = CronSchedule::parse("0,30 8-18/2 * * 1-5").unwrap();
+        // Monday 10:30
+        assert!(schedule.matches(&local(2025, 3, 10, 10, 30)));
+        // Monday 11:30 (odd hour)
+        assert!(!schedule.matches(&local(2025, 3, 10, 11, 30)));
+        // Sunday 10:30
+        assert!(!schedule.matches(&local(2025, 3, 9, 10, 30)));
+    }
+
+    #[test]
+    fn test_day_of_month_or_day_of_week() {
+        // 1st of the month OR any Sunday
+        let schedule = CronSchedule::parse("0 0 1 * 0").unwrap();
+        assert!(schedule.matches(&local(2025, 3, 1, 0, 0))); // Saturday the 1st
+        assert!(schedule.matches(&local(2025, 3, 9, 0, 0))); // Sunday the 9th
+        assert!(!schedule.matches(&local(2025, 3, 10, 0, 0))); // Monday the 10th
+    }
+
+    #[test]
+    fn test_day_field_starting_with_star_is_not_restricted() {
+        // Odd days of the month that are Mondays, not every Monday as well
+        let schedule = CronSchedule::parse("0 3 */2 * 1").unwrap();
+        assert!(schedule.matches(&local(2025, 3, 3, 3, 0))); // Monday the 3rd
+        assert!(!schedule.matches(&local(2025, 3, 10, 3, 0))); // Monday the 10th
+        assert!(!schedule.matches(&local(2025, 3, 5, 3, 0))); // Wednesday the 5th
+
+        // The 1st of the month when it falls on an even weekday
+        let schedule = CronSchedule::parse("0 0 1 * */2").unwrap();
+        assert!(schedule.matches(&local(2025, 3, 1, 0, 0))); // Saturday the 1st
+        assert!(!schedule.matches(&local(2025, 8, 1, 0, 0))); // Friday the 1st
+        assert!(!schedule.matches(&local(2025, 3, 9, 0, 0))); // Sunday the 9th
+    }
+
+    #[test]
+    fn test_sunday_alias() {
+        let schedule = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert!(schedule.matches(&local(2025, 3, 9, 0, 0)));
+    }
+
+    #[test]
+    fn test_shortcuts() {
+        assert_eq!(
+            CronSchedule::parse("@daily")
+                .unwrap()
+                .next_after(local(2025, 3, 10, 9, 0)),
+            Some(local(2025, 3, 11, 0, 0))
+        );
+        assert!(CronSchedule::parse("@hourly").is_ok());
+        assert!(CronSchedule::parse("@weekly").is_ok());
+        assert!(CronSchedule::parse("@monthly").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        assert!(CronSchedule::parse("").is_err());
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("* 24 * * *").is_err());
+        assert!(CronSchedule::parse("* * 0 * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("10-5 * * * *").is_err());
+        assert!(CronSchedule::parse("a * * * *").is_err());
+    }
+}
@@ -7,17 +7,19 @@ use sea_orm::*;
 use std::sync::Arc;
 
 use crate::error::{ServiceError, ServiceResult};
+use crate::events::{DomainEvent, EventBus};
 
 /// Session service for managing user sessions
 /// Handles session creation, validation, and cleanup
 pub struct SessionService {
     db: Arc<DatabaseConnection>,
+    events: Arc<EventBus>,
 }
 
 impl SessionService {
     /// Create a new session service
-    pub fn new(db: Arc<DatabaseConnection>) -> Self {
-        Self { db }
+    pub fn new(db: Arc<DatabaseConnection>, events: Arc<EventBus>) -> Self {
+        Self { db, events }
     }
 
     /// Create a new session for a user
@@ -100,13 +102,25 @@ impl SessionService {
 
     /// Delete a session (logout)
     pub async fn delete_session(&self, token: &str) -> ServiceResult<()> {
-        session::Entity::delete_many()
+        let Some(session) = session::Entity::find()
             .filter(session::Column::Token.eq(token))
+            .one(&*self.db)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        session::Entity::delete_by_id(session.id)
             .exec(&*self.db)
             .await?;
 
         tracing::info!("Deleted session");
 
+        self.events.publish(DomainEvent::SessionRevoked {
+            user_id: session.user_id,
+            session_id: Some(session.id),
+        });
+
         Ok(())
     }
 
@@ -123,6 +137,13 @@ impl SessionService {
             user_id
         );
 
+        if result.rows_affected > 0 {
+            self.events.publish(DomainEvent::SessionRevoked {
+                user_id,
+                session_id: None,
+            });
+        }
+
         Ok(result.rows_affected)
     }
 
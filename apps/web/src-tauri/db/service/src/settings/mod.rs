@@ -10,16 +10,24 @@ use sea_orm::*;
 use tap::{Pipe, Tap, TapFallible};
 
 use crate::error::{ServiceError, ServiceResult};
+use crate::events::{DomainEvent, EventBus};
 
 /// Settings service for managing application settings
 pub struct SettingsService {
     db: Arc<DatabaseConnection>,
+    events: Arc<EventBus>,
 }
 
 impl SettingsService {
     /// Create a new settings service
-    pub fn new(db: Arc<DatabaseConnection>) -> Self {
-        Self { db }
+    pub fn new(db: Arc<DatabaseConnection>, events: Arc<EventBus>) -> Self {
+        Self { db, events }
+    }
+
+    /// Publish a setting change event
+    fn publish_change(&self, key: Option<String>, category: Option<String>) {
+        self.events
+            .publish(DomainEvent::SettingChanged { key, category });
     }
 
     // ========================================================================
@@ -100,6 +108,8 @@ impl SettingsService {
                 .tap_err(|e| tracing::error!("Failed to create setting {}: {}", dto.key, e))?
         };
 
+        self.publish_change(Some(result.key.clone()), result.category.clone());
+
         Ok(SettingResponseDto::from(result))
     }
 
@@ -126,6 +136,7 @@ impl SettingsService {
             }
         }
 
+        let old_key = setting.key.clone();
         let mut setting: setting::ActiveModel = setting.into();
         setting.key = Set(dto.key);
         setting.value = Set(dto.value);
@@ -146,18 +157,25 @@ impl SettingsService {
             .tap_ok(|s| tracing::info!("Updated setting: {} ({})", s.key, id))
             .tap_err(|e| tracing::error!("Failed to update setting {}: {}", id, e))?;
 
+        if old_key != result.key {
+            self.publish_change(Some(old_key), result.category.clone());
+        }
+        self.publish_change(Some(result.key.clone()), result.category.clone());
+
         Ok(SettingResponseDto::from(result))
     }
 
     /// Delete a setting by ID
     pub async fn delete_by_id(&self, id: Id) -> ServiceResult<()> {
-        let result = Setting::delete_by_id(id).exec(&*self.db).await?;
+        let setting = Setting::find_by_id(id)
+            .one(&*self.db)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Setting not found: {}", id)))?;
 
-        if result.rows_affected == 0 {
-            return Err(ServiceError::NotFound(format!("Setting not found: {}", id)));
-        }
+        Setting::delete_by_id(id).exec(&*self.db).await?;
 
         tracing::info!("Deleted setting: {}", id);
+        self.publish_change(Some(setting.key), setting.category);
         Ok(())
     }
 
@@ -172,6 +190,7 @@ impl SettingsService {
         Setting::delete_by_id(setting.id).exec(&*self.db).await?;
 
         tracing::info!("Deleted setting: {}", key);
+        self.publish_change(Some(setting.key), setting.category);
         Ok(())
     }
 
@@ -275,6 +294,10 @@ impl SettingsService {
             })
             .tap_err(|e| tracing::error!("Failed to delete category '{}': {}", category, e))?;
 
+        if result.rows_affected > 0 {
+            self.publish_change(None, Some(category.to_string()));
+        }
+
         Ok(result.rows_affected)
     }
 
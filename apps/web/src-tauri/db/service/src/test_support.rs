@@ -0,0 +1,172 @@
+//! Fixtures shared by the service tests
+
+use std::sync::Arc;
+
+use db_entity::id::Id;
+use db_entity::inventory_item::dto::{CreateBarcodeInput, CreateInventoryItemWithStock};
+use db_entity::inventory_stock::dto::UpdateInventoryStock;
+use db_entity::medicine_form::dto::CreateMedicineForm;
+use db_entity::user::dto::UserResponseDto;
+use db_migration::run_migrations;
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+
+use crate::events::EventBus;
+use crate::inventory::InventoryService;
+use crate::inventory::medicine_forms::MedicineFormsService;
+use crate::inventory::opening_balances::OpeningBalanceService;
+use crate::jwt::JwtService;
+use crate::report::ReportService;
+use crate::settings::SettingsService;
+use crate::staff::StaffService;
+use crate::user::UserService;
+
+/// Empty in-memory SQLite database with every migration applied
+pub(crate) async fn sqlite_db() -> Arc<DatabaseConnection> {
+    let mut opt = ConnectOptions::new("sqlite::memory:");
+    // Every pooled connection would otherwise get its own in-memory database
+    opt.max_connections(1).min_connections(1);
+
+    let db = Database::connect(opt).await.unwrap();
+    run_migrations(&db).await.unwrap();
+    Arc::new(db)
+}
+
+/// Services the printable document services are built from, over
+/// [`sqlite_db`]
+pub(crate) struct TestServices {
+    pub(crate) db: Arc<DatabaseConnection>,
+    pub(crate) inventory: Arc<InventoryService>,
+    pub(crate) settings: Arc<SettingsService>,
+    pub(crate) report: Arc<ReportService>,
+}
+
+pub(crate) async fn services() -> TestServices {
+    let db = sqlite_db().await;
+
+    let events = Arc::new(EventBus::default());
+    let inventory = Arc::new(InventoryService::new(db.clone(), events.clone()));
+    let settings = Arc::new(SettingsService::new(db.clone(), events.clone()));
+    let report = Arc::new(ReportService::new(
+        inventory.clone(),
+        Arc::new(OpeningBalanceService::new(db.clone(), events)),
+        settings.clone(),
+    ));
+    TestServices {
+        db,
+        inventory,
+        settings,
+        report,
+    }
+}
+
+/// Creates the initial admin user
+pub(crate) async fn create_admin(db: &Arc<DatabaseConnection>) -> UserResponseDto {
+    let jwt = JwtService::new(
+        "test-secret-that-is-long-enough".to_string(),
+        "test".to_string(),
+        "test".to_string(),
+        1,
+    )
+    .unwrap();
+    UserService::new(
+        db.clone(),
+        Arc::new(StaffService::new(db.clone())),
+        Arc::new(jwt),
+    )
+    .create_initial_admin()
+    .await
+    .unwrap()
+}
+
+/// Item created by [`create_items`]: a 500mg tablet with 10 in stock at
+/// 12.50 unless changed
+#[derive(Clone, Copy)]
+pub(crate) struct TestItem<'a> {
+    pub(crate) name: &'a str,
+    /// The last barcode is primary
+    pub(crate) barcodes: &'a [&'a str],
+    pub(crate) stock_quantity: i32,
+    pub(crate) unit_price: f64,
+}
+
+impl<'a> TestItem<'a> {
+    pub(crate) fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            barcodes: &[],
+            stock_quantity: 10,
+            unit_price: 12.5,
+        }
+    }
+}
+
+/// Creates a tablet medicine form and the given items, returning their ids
+pub(crate) async fn create_items(
+    db: &Arc<DatabaseConnection>,
+    inventory: &InventoryService,
+    items: &[TestItem<'_>],
+) -> Vec<Id> {
+    let form = MedicineFormsService::new(db.clone())
+        .create(CreateMedicineForm {
+            code: "TAB".to_string(),
+            name_en: "Tablet".to_string(),
+            name_ar: "أقراص".to_string(),
+            display_order: 1,
+        })
+        .await
+        .unwrap();
+
+    let mut ids = Vec::new();
+    for item in items {
+        let created = inventory
+            .create(
+                CreateInventoryItemWithStock {
+                    name: item.name.to_string(),
+                    generic_name: None,
+                    concentration: "500mg".to_string(),
+                    medicine_form_id: form.id,
+                    manufacturer_id: None,
+                    requires_prescription: false,
+                    is_controlled: false,
+                    storage_instructions: None,
+                    notes: None,
+                    barcodes: item
+                        .barcodes
+                        .iter()
+                        .enumerate()
+                        .map(|(index, barcode)| CreateBarcodeInput {
+                            barcode: (*barcode).to_string(),
+                            barcode_type: None,
+                            is_primary: index + 1 == item.barcodes.len(),
+                            description: None,
+                        })
+                        .collect(),
+                    stock_quantity: item.stock_quantity,
+                    min_stock_level: 5,
+                    unit_price: item.unit_price,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        ids.push(created.id);
+    }
+    ids
+}
+
+/// Records a new unit price for an item
+pub(crate) async fn change_price(inventory: &InventoryService, item_id: Id, unit_price: f64) {
+    inventory
+        .update_stock(
+            item_id,
+            UpdateInventoryStock {
+                stock_quantity: None,
+                min_stock_level: None,
+                unit_price: Some(unit_price),
+                reason: Some("Price list update".to_string()),
+                updated_by: None,
+            },
+        )
+        .await
+        .unwrap();
+}
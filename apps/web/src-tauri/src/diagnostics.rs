@@ -0,0 +1,99 @@
+//! Connection test of the configuration TUI
+//!
+//! The TUI lives in `app_config`, which does not depend on the service layer,
+//! so the app passes it a tester running [`db_service::test_connection`].
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use app_config::cli_tui::{
+    CheckItem, CheckStatus, ConnectionCheck, ConnectionCheckError, ConnectionTester,
+};
+use db_service::{ConnectionError, ConnectionFailure, ConnectionReport, MigrationStatus};
+
+/// Tester of the database settings edited in the configuration TUI
+pub(crate) fn connection_tester() -> ConnectionTester {
+    Arc::new(|database| {
+        Box::pin(async move {
+            let url = database.connection_url().map_err(|e| {
+                check_error(ConnectionError {
+                    failure: ConnectionFailure::Other,
+                    message: e.to_string(),
+                })
+            })?;
+            db_service::test_connection(&url, Duration::from_secs(database.connect_timeout.max(1)))
+                .await
+                .map(check)
+                .map_err(check_error)
+        })
+    })
+}
+
+fn check(report: ConnectionReport) -> ConnectionCheck {
+    let applied_migrations = report
+        .migrations
+        .iter()
+        .filter(|migration| migration.status == MigrationStatus::Applied)
+        .count();
+    // Applied migrations are only counted; the others are listed
+    let migrations = report
+        .migrations
+        .into_iter()
+        .filter_map(|migration| {
+            let (note, status) = match migration.status {
+                MigrationStatus::Applied => return None,
+                MigrationStatus::Pending => ("pending", CheckStatus::Warning),
+                MigrationStatus::Unknown => ("unknown to this version", CheckStatus::Failed),
+            };
+            Some(CheckItem {
+                name: migration.name,
+                note: note.to_string(),
+                status,
+            })
+        })
+        .collect();
+    let extensions = report
+        .extensions
+        .into_iter()
+        .map(|extension| {
+            let (note, status) = match (extension.installed, extension.available) {
+                (Some(version), _) => (version, CheckStatus::Ok),
+                (None, true) => ("not installed".to_string(), CheckStatus::Warning),
+                (None, false) => (
+                    "not available on the server".to_string(),
+                    CheckStatus::Failed,
+                ),
+            };
+            CheckItem {
+                name: extension.name,
+                note,
+                status,
+            }
+        })
+        .collect();
+
+    ConnectionCheck {
+        connect_time: report.connect_time,
+        latency: report.latency,
+        server_version: report.server_version,
+        applied_migrations,
+        migrations,
+        extensions,
+    }
+}
+
+fn check_error(error: ConnectionError) -> ConnectionCheckError {
+    let hint = match error.failure {
+        ConnectionFailure::Dns => "Check the host name",
+        ConnectionFailure::Refused => "Check that the server is running and the port is right",
+        ConnectionFailure::Auth => "Check the username and password",
+        ConnectionFailure::MissingDatabase => "Create the database or check its name",
+        ConnectionFailure::Timeout => "Check the firewall, or raise the connect timeout",
+        ConnectionFailure::Other => "See the error above",
+    };
+    ConnectionCheckError {
+        summary: error.failure.to_string(),
+        message: error.message,
+        hint: hint.to_string(),
+    }
+}
@@ -0,0 +1,74 @@
+use db_service::{ApplyStockRecommendations, ItemAnalytics, ItemAnalyticsQuery, PaginationResult};
+use tap::TapFallible;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    error::AppResult,
+    ipc::{
+        params::{CreateParams, ListParams},
+        response::IpcResponse,
+    },
+    state::AppState,
+};
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Helper to get analytics service from app state
+#[inline]
+fn get_analytics_service(app: &AppHandle) -> std::sync::Arc<db_service::AnalyticsService> {
+    let state = app.state::<AppState>();
+    let service_manager = state.service_manager();
+    service_manager.analytics().clone()
+}
+
+// ============================================================================
+// Analytics Commands
+// ============================================================================
+
+/// List consumption velocity, ABC class, dead stock and days of cover per item
+#[tauri::command]
+pub async fn get_item_analytics(
+    app: AppHandle,
+    params: ListParams<ItemAnalyticsQuery>,
+) -> IpcResponse<PaginationResult<ItemAnalytics>> {
+    let result: AppResult<PaginationResult<ItemAnalytics>> = async {
+        let query = params.filter().clone().unwrap_or_default();
+
+        get_analytics_service(&app)
+            .item_analytics(query, *params.pagination())
+            .await
+            .tap_ok(|result| {
+                tracing::debug!(
+                    "Listed analytics for {} items (page {}/{})",
+                    result.items_ref().len(),
+                    result.page(),
+                    result.total_pages()
+                )
+            })
+            .tap_err(|e| tracing::error!("Failed to get item analytics: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
+/// Set the minimum stock level of the requested items to their
+/// recommendation, as the requesting user
+#[tauri::command]
+pub async fn apply_min_stock_recommendations(
+    app: AppHandle,
+    params: CreateParams<ApplyStockRecommendations>,
+) -> IpcResponse<u64> {
+    let result: AppResult<u64> = async {
+        get_analytics_service(&app)
+            .apply_min_stock_recommendations(params.data().clone())
+            .await
+            .tap_ok(|updated| tracing::info!("Updated {} minimum stock levels", updated))
+            .tap_err(|e| tracing::error!("Failed to apply stock recommendations: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
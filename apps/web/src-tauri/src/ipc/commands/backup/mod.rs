@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use db_service::{BackupCipher, BackupFile, BackupManifest};
+use tap::TapFallible;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    error::AppResult,
+    ipc::response::IpcResponse,
+    state::{AppState, SecureStorageCipher},
+};
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Helper to get backup service from app state
+#[inline]
+fn get_backup_service(app: &AppHandle) -> std::sync::Arc<db_service::BackupService> {
+    let state = app.state::<AppState>();
+    let service_manager = state.service_manager();
+    service_manager.backup().clone()
+}
+
+/// Password-derived key when a password is given, the machine key otherwise
+fn get_cipher(password: Option<&str>) -> SecureStorageCipher {
+    match password {
+        Some(password) => SecureStorageCipher::with_password(password),
+        None => SecureStorageCipher::machine(),
+    }
+}
+
+// ============================================================================
+// Backup Commands
+// ============================================================================
+
+/// Back up the database into the backup directory
+///
+/// The backup is encrypted when `encrypt` is set or a password is given.
+#[tauri::command]
+pub async fn create_database_backup(
+    app: AppHandle,
+    encrypt: bool,
+    password: Option<String>,
+) -> IpcResponse<BackupFile> {
+    let result: AppResult<BackupFile> = async {
+        let cipher = if encrypt || password.is_some() {
+            Some(get_cipher(password.as_deref()))
+        } else {
+            None
+        };
+
+        get_backup_service(&app)
+            .create_backup(cipher.as_ref().map(|c| c as &dyn BackupCipher))
+            .await
+            .tap_ok(|file| tracing::info!("Created backup {}", file.path.display()))
+            .tap_err(|e| tracing::error!("Failed to create backup: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
+/// List the backups in the backup directory, newest first
+#[tauri::command]
+pub async fn list_database_backups(app: AppHandle) -> IpcResponse<Vec<BackupFile>> {
+    let result: AppResult<Vec<BackupFile>> = async {
+        get_backup_service(&app)
+            .list_backups()
+            .await
+            .tap_ok(|backups| tracing::debug!("Found {} backups", backups.len()))
+            .tap_err(|e| tracing::error!("Failed to list backups: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
+/// Restore an uploaded backup into the (empty) database
+#[tauri::command]
+pub async fn restore_database_backup(
+    app: AppHandle,
+    content: Vec<u8>,
+    password: Option<String>,
+) -> IpcResponse<BackupManifest> {
+    let result: AppResult<BackupManifest> = async {
+        let cipher = get_cipher(password.as_deref());
+
+        get_backup_service(&app)
+            .restore_archive(&content, Some(&cipher))
+            .await
+            .tap_ok(|manifest| tracing::info!("Restored backup from {}", manifest.created_at))
+            .tap_err(|e| tracing::error!("Failed to restore backup: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
+/// Restore a backup from the backup directory into the (empty) database
+#[tauri::command]
+pub async fn restore_database_backup_file(
+    app: AppHandle,
+    path: PathBuf,
+    password: Option<String>,
+) -> IpcResponse<BackupManifest> {
+    let result: AppResult<BackupManifest> = async {
+        let cipher = get_cipher(password.as_deref());
+
+        get_backup_service(&app)
+            .restore_backup(&path, Some(&cipher))
+            .await
+            .tap_ok(|_| tracing::info!("Restored backup {}", path.display()))
+            .tap_err(|e| tracing::error!("Failed to restore backup {}: {}", path.display(), e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
@@ -0,0 +1,59 @@
+use db_service::{ExportOptions, ExportedFile};
+use tap::TapFallible;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    error::{AppError, AppResult},
+    ipc::{params::CreateParams, response::IpcResponse},
+    state::AppState,
+};
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Helper to get export service from app state
+#[inline]
+fn get_export_service(app: &AppHandle) -> std::sync::Arc<db_service::ExportService> {
+    let state = app.state::<AppState>();
+    let service_manager = state.service_manager();
+    service_manager.export().clone()
+}
+
+/// Fresh, timestamped directory under the exports directory of the app data
+/// directory
+fn export_dir(app: &AppHandle) -> AppResult<std::path::PathBuf> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::ParseError(format!("Failed to get app data directory: {}", e)))?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S-%6f");
+    Ok(data_dir.join("exports").join(stamp.to_string()))
+}
+
+// ============================================================================
+// Export Commands
+// ============================================================================
+
+/// Export catalogue, stock and history tables as CSV files or a JSON archive
+///
+/// Files are always written to a new directory under the exports directory
+/// of the app data directory; the webview cannot pick where they go.
+#[tauri::command]
+pub async fn export_data(
+    app: AppHandle,
+    params: CreateParams<ExportOptions>,
+) -> IpcResponse<Vec<ExportedFile>> {
+    let result: AppResult<Vec<ExportedFile>> = async {
+        let dir = export_dir(&app)?;
+
+        get_export_service(&app)
+            .export(&dir, params.data().clone())
+            .await
+            .tap_ok(|files| tracing::info!("Exported {} file(s) to {}", files.len(), dir.display()))
+            .tap_err(|e| tracing::error!("Failed to export data: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
@@ -13,19 +13,23 @@ pub use medicine_forms::{
 pub use opening_balance::{
     create_opening_balance, create_opening_balance_adjustment, delete_opening_balance,
     get_opening_balance, get_opening_balance_statistics, get_opening_balances_by_batch,
-    get_opening_balances_by_item, get_unverified_opening_balances, list_opening_balances,
-    reject_opening_balance, update_opening_balance, verify_opening_balance,
+    get_opening_balances_by_item, get_unverified_opening_balances, import_opening_balances,
+    list_opening_balances, reject_opening_balance, revert_opening_balance_import,
+    update_opening_balance, verify_opening_balance,
 };
 
 use db_entity::inventory_item::dto::{
-    CreateBarcodeInput, CreateInventoryItemWithStock, InventoryItemWithStockResponse,
+    CatalogueImportOptions, CatalogueImportResult, CreateBarcodeInput,
+    CreateInventoryItemWithStock, InventoryItemWithStockResponse, ScannedBarcode,
     SetPrimaryBarcode, UpdateInventoryItem,
 };
 use db_entity::inventory_item_barcode::dto::InventoryItemBarcodeResponse;
 use db_entity::inventory_price_history::dto::{
     PriceHistoryQueryDto, PriceHistoryResponse, PriceStatistics,
 };
-use db_entity::inventory_stock::dto::{AdjustStock, UpdateInventoryStock};
+use db_entity::inventory_stock::dto::{
+    AdjustStock, UpdateInventoryStock, ValuationQuery, ValuationReport,
+};
 use db_entity::inventory_stock_history::dto::{
     StockHistoryQueryDto, StockHistoryResponse, StockHistoryStatistics,
 };
@@ -38,6 +42,7 @@ use crate::{
     ipc::{
         params::{CreateParams, GetParams, ListParams, UpdateParams},
         response::{IpcResponse, MutationResult},
+        sheet::read_sheet,
     },
     state::AppState,
 };
@@ -125,6 +130,28 @@ pub async fn get_inventory_item_by_barcode(
     result.into()
 }
 
+/// Identify the item behind scanner input, reading batch, expiry and serial
+/// from GS1 DataMatrix and GS1-128 codes
+#[tauri::command]
+pub async fn scan_inventory_barcode(app: AppHandle, input: String) -> IpcResponse<ScannedBarcode> {
+    let result: AppResult<ScannedBarcode> = async {
+        get_inventory_service(&app)
+            .scan_barcode(&input)
+            .await
+            .tap_ok(|scanned| {
+                tracing::debug!(
+                    "Scanned {} (batch {:?})",
+                    scanned.item.name,
+                    scanned.batch_number
+                )
+            })
+            .tap_err(|e| tracing::error!("Failed to resolve scanned barcode {:?}: {}", input, e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
 /// Update inventory item (catalog only)
 #[tauri::command]
 pub async fn update_inventory_item(
@@ -182,6 +209,41 @@ pub async fn restore_inventory_item(
     result.into()
 }
 
+// ============================================================================
+// Catalogue Import
+// ============================================================================
+
+/// Import catalogue items from an uploaded CSV or spreadsheet file
+///
+/// With `dry_run` set the file is only validated; either way the result
+/// carries a CSV error report for rejected rows.
+#[tauri::command]
+pub async fn import_inventory_catalogue(
+    app: AppHandle,
+    params: CreateParams<CatalogueImportOptions>,
+    content: Vec<u8>,
+) -> IpcResponse<CatalogueImportResult> {
+    let result: AppResult<CatalogueImportResult> = async {
+        let options = params.data().clone();
+        let sheet = read_sheet(&options.file_name, &content)?;
+        get_inventory_service(&app)
+            .import_catalogue(&sheet, options)
+            .await
+            .tap_ok(|result| {
+                tracing::info!(
+                    "Catalogue import: {} of {} rows valid (dry run: {})",
+                    result.created_count,
+                    result.total_rows,
+                    result.dry_run
+                )
+            })
+            .tap_err(|e| tracing::error!("Failed to import catalogue: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
 // ============================================================================
 // Stock Management Operations
 // ============================================================================
@@ -336,6 +398,31 @@ pub async fn get_inventory_statistics(app: AppHandle) -> IpcResponse<InventorySt
     result.into()
 }
 
+/// Value the stock held at the end of a past date
+#[tauri::command]
+pub async fn get_inventory_valuation(
+    app: AppHandle,
+    params: CreateParams<ValuationQuery>,
+) -> IpcResponse<ValuationReport> {
+    let result: AppResult<ValuationReport> = async {
+        get_inventory_service(&app)
+            .get_valuation(params.data().clone())
+            .await
+            .tap_ok(|report| {
+                tracing::debug!(
+                    "Valued inventory as of {}: {} units, {}",
+                    report.as_of,
+                    report.total_quantity,
+                    report.total_value
+                )
+            })
+            .tap_err(|e| tracing::error!("Failed to get inventory valuation: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
 // ============================================================================
 // Barcode Management Operations
 // ============================================================================
@@ -1,7 +1,7 @@
 use db_entity::id::Id;
 use db_entity::inventory_opening_balance::dto::{
-    CreateOpeningBalanceDto, OpeningBalanceQueryDto, OpeningBalanceResponse,
-    OpeningBalanceStatistics, UpdateOpeningBalanceDto,
+    CreateOpeningBalanceDto, ImportResult, OpeningBalanceImportOptions, OpeningBalanceQueryDto,
+    OpeningBalanceResponse, OpeningBalanceStatistics, UpdateOpeningBalanceDto,
 };
 use tap::TapFallible;
 use tauri::{AppHandle, Manager};
@@ -11,6 +11,7 @@ use crate::{
     ipc::{
         params::{CreateParams, GetParams, ListParams, UpdateParams},
         response::{IpcResponse, MutationResult},
+        sheet::read_sheet,
     },
     state::AppState,
 };
@@ -37,12 +38,11 @@ fn get_opening_balance_service(
 #[tauri::command]
 pub async fn create_opening_balance(
     app: AppHandle,
-    params: CreateParams<(CreateOpeningBalanceDto, Id)>,
+    params: CreateParams<CreateOpeningBalanceDto>,
 ) -> IpcResponse<MutationResult> {
     let result: AppResult<MutationResult> = async {
-        let (dto, entered_by) = params.data().clone();
         get_opening_balance_service(&app)
-            .create(dto, entered_by)
+            .create(params.data().clone())
             .await
             .tap_ok(|entry| {
                 tracing::info!(
@@ -212,15 +212,11 @@ pub async fn reject_opening_balance(
 #[tauri::command]
 pub async fn create_opening_balance_adjustment(
     app: AppHandle,
-    params: CreateParams<(
-        db_entity::inventory_opening_balance::dto::CreateAdjustmentDto,
-        Id,
-    )>,
+    params: CreateParams<db_entity::inventory_opening_balance::dto::CreateAdjustmentDto>,
 ) -> IpcResponse<MutationResult> {
     let result: AppResult<MutationResult> = async {
-        let (dto, entered_by) = params.data().clone();
         get_opening_balance_service(&app)
-            .create_adjustment(dto, entered_by)
+            .create_adjustment(params.data().clone())
             .await
             .tap_ok(|entry| {
                 tracing::info!("Created adjustment {} for opening balance entry", entry.id)
@@ -319,6 +315,64 @@ pub async fn get_opening_balances_by_batch(
     result.into()
 }
 
+// ============================================================================
+// Import Operations
+// ============================================================================
+
+/// Import opening balances from an uploaded CSV or spreadsheet file
+///
+/// With `dry_run` set the file is only validated and the row report returned.
+#[tauri::command]
+pub async fn import_opening_balances(
+    app: AppHandle,
+    params: CreateParams<OpeningBalanceImportOptions>,
+    content: Vec<u8>,
+) -> IpcResponse<ImportResult> {
+    let result: AppResult<ImportResult> = async {
+        let options = params.data().clone();
+        let sheet = read_sheet(&options.file_name, &content)?;
+        get_opening_balance_service(&app)
+            .import(&sheet, options)
+            .await
+            .tap_ok(|result| {
+                tracing::info!(
+                    "Opening balance import: {} of {} rows valid (dry run: {})",
+                    result.success_count,
+                    result.total_rows,
+                    result.dry_run
+                )
+            })
+            .tap_err(|e| tracing::error!("Failed to import opening balances: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
+/// Revert all entries of an opening balance import batch
+#[tauri::command]
+pub async fn revert_opening_balance_import(
+    app: AppHandle,
+    params: UpdateParams<Id>,
+) -> IpcResponse<u64> {
+    let result: AppResult<u64> = async {
+        get_opening_balance_service(&app)
+            .revert_import_batch(*params.id(), *params.data())
+            .await
+            .tap_ok(|count| {
+                tracing::info!(
+                    "Reverted {} opening balance entries of batch {}",
+                    count,
+                    params.id()
+                )
+            })
+            .tap_err(|e| tracing::error!("Failed to revert import batch {}: {}", params.id(), e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
 // ============================================================================
 // Statistics Operations
 // ============================================================================
@@ -0,0 +1,140 @@
+use db_entity::id::Id;
+use db_service::{LabelOptions, LabelTemplate, RenderedLabels};
+use tap::TapFallible;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    error::{AppError, AppResult},
+    ipc::{
+        params::CreateParams,
+        printer::{self, PrintTarget},
+        response::IpcResponse,
+    },
+    state::AppState,
+};
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Helper to get label service from app state
+#[inline]
+fn get_label_service(app: &AppHandle) -> std::sync::Arc<db_service::LabelService> {
+    let state = app.state::<AppState>();
+    let service_manager = state.service_manager();
+    service_manager.label().clone()
+}
+
+/// Spool file of its own for each print job, so concurrent jobs never print
+/// each other's labels
+fn spool_path(app: &AppHandle) -> AppResult<std::path::PathBuf> {
+    let cache_dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::ParseError(format!("Failed to get app cache directory: {}", e)))?;
+    Ok(cache_dir
+        .join("print")
+        .join(format!("labels-{}.pdf", Id::new())))
+}
+
+// ============================================================================
+// Label Template Commands
+// ============================================================================
+
+/// List built-in and custom label templates
+#[tauri::command]
+pub async fn list_label_templates(app: AppHandle) -> IpcResponse<Vec<LabelTemplate>> {
+    let result: AppResult<Vec<LabelTemplate>> = async {
+        get_label_service(&app)
+            .templates()
+            .await
+            .tap_err(|e| tracing::error!("Failed to list label templates: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
+/// Create or replace a custom label template
+#[tauri::command]
+pub async fn save_label_template(
+    app: AppHandle,
+    params: CreateParams<LabelTemplate>,
+) -> IpcResponse<LabelTemplate> {
+    let result: AppResult<LabelTemplate> = async {
+        get_label_service(&app)
+            .save_template(params.data().clone())
+            .await
+            .tap_ok(|template| tracing::info!("Saved label template: {}", template.name))
+            .tap_err(|e| tracing::error!("Failed to save label template: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
+/// Delete a custom label template
+#[tauri::command]
+pub async fn delete_label_template(app: AppHandle, name: String) -> IpcResponse<()> {
+    let result: AppResult<()> = async {
+        get_label_service(&app)
+            .delete_template(&name)
+            .await
+            .tap_ok(|_| tracing::info!("Deleted label template: {}", name))
+            .tap_err(|e| tracing::error!("Failed to delete label template '{}': {}", name, e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
+// ============================================================================
+// Label Printing Commands
+// ============================================================================
+
+/// Print labels for the selected items to a system printer or a PDF file
+#[tauri::command]
+pub async fn print_labels(
+    app: AppHandle,
+    params: CreateParams<LabelOptions>,
+    target: PrintTarget,
+) -> IpcResponse<RenderedLabels> {
+    let result: AppResult<RenderedLabels> = async {
+        let service = get_label_service(&app);
+        let options = params.data().clone();
+
+        match target {
+            PrintTarget::File { path } => service
+                .save(options, &path)
+                .await
+                .tap_err(|e| tracing::error!("Failed to save labels: {}", e))
+                .map_err(Into::into),
+            PrintTarget::Printer { name } => {
+                let rendered = service
+                    .save(options, &spool_path(&app)?)
+                    .await
+                    .tap_err(|e| tracing::error!("Failed to render labels: {}", e))?;
+
+                // Submitting a job talks to the print spooler synchronously
+                let path = rendered.path.clone();
+                let printed = tauri::async_runtime::spawn_blocking(move || {
+                    printer::print_file(name.as_deref(), &path, "Labels")
+                })
+                .await
+                .unwrap_or_else(|e| Err(AppError::from(format!("Print job failed: {}", e))));
+
+                // The spooler holds its own copy of a submitted job
+                if let Err(e) = tokio::fs::remove_file(&rendered.path).await {
+                    tracing::warn!("Failed to remove {}: {}", rendered.path.display(), e);
+                }
+
+                printed
+                    .tap_ok(|_| tracing::info!("Sent {} labels to printer", rendered.labels))
+                    .tap_err(|e| tracing::error!("Failed to print labels: {}", e))?;
+                Ok(rendered)
+            }
+        }
+    }
+    .await;
+    result.into()
+}
@@ -1,6 +1,15 @@
+pub mod analytics;
+pub mod backup;
+pub mod export;
 pub mod inventory;
+pub mod label;
 pub mod manufacturer;
 pub mod onboarding;
+pub mod printer;
+pub mod profile;
+pub mod receipt;
+pub mod report;
+pub mod scheduler;
 pub mod session;
 pub mod settings;
 pub mod user;
@@ -70,6 +79,7 @@ pub use inventory::{
     get_inventory_item_by_barcode,
     // Statistics
     get_inventory_statistics,
+    get_inventory_valuation,
     get_item_barcodes,
     // Price history
     get_latest_price,
@@ -94,6 +104,8 @@ pub use inventory::{
     reorder_medicine_forms,
     restore_inventory_item,
     restore_medicine_form,
+    // Scanning
+    scan_inventory_barcode,
     search_inventory_items,
     set_primary_barcode,
     update_barcode,
@@ -111,3 +123,24 @@ pub use session::{
     cleanup_expired_sessions, get_user_sessions, logout_all_sessions, logout_session,
     validate_session,
 };
+
+pub use scheduler::{get_job_schedules, list_job_runs, run_job_now};
+
+pub use export::export_data;
+
+pub use analytics::{apply_min_stock_recommendations, get_item_analytics};
+
+pub use report::save_report;
+
+pub use label::{delete_label_template, list_label_templates, print_labels, save_label_template};
+
+pub use printer::list_printers;
+
+pub use receipt::{get_receipt, print_receipt};
+
+pub use profile::get_profile;
+
+pub use backup::{
+    create_database_backup, list_database_backups, restore_database_backup,
+    restore_database_backup_file,
+};
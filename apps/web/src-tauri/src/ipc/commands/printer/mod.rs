@@ -0,0 +1,22 @@
+use tap::TapFallible;
+use tauri::AppHandle;
+
+use crate::{
+    error::AppResult,
+    ipc::{
+        printer::{self, PrinterInfo},
+        response::IpcResponse,
+    },
+};
+
+// ============================================================================
+// Printer Commands
+// ============================================================================
+
+/// List the printers installed on this machine
+#[tauri::command]
+pub async fn list_printers(_app: AppHandle) -> IpcResponse<Vec<PrinterInfo>> {
+    let result: AppResult<Vec<PrinterInfo>> = AppResult::Ok(printer::list_printers())
+        .tap_ok(|printers| tracing::debug!("Found {} printers", printers.len()));
+    result.into()
+}
@@ -0,0 +1,20 @@
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    error::AppResult,
+    ipc::response::IpcResponse,
+    state::{AppState, ProfileInfo},
+};
+
+// ============================================================================
+// Profile Commands
+// ============================================================================
+
+/// Get the configuration profile the app was started with
+///
+/// The frontend shows a banner while `training` is set.
+#[tauri::command]
+pub async fn get_profile(app: AppHandle) -> IpcResponse<ProfileInfo> {
+    let result: AppResult<ProfileInfo> = Ok(app.state::<AppState>().profile().clone());
+    result.into()
+}
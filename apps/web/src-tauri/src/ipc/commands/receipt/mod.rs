@@ -0,0 +1,77 @@
+use db_service::{Receipt, ReceiptOptions, ReceiptSource};
+use tap::TapFallible;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    error::AppResult,
+    ipc::{
+        params::CreateParams,
+        printer::{self, PrintTarget},
+        response::IpcResponse,
+    },
+    state::AppState,
+};
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Helper to get receipt service from app state
+#[inline]
+fn get_receipt_service(app: &AppHandle) -> std::sync::Arc<db_service::ReceiptService> {
+    let state = app.state::<AppState>();
+    let service_manager = state.service_manager();
+    service_manager.receipt().clone()
+}
+
+// ============================================================================
+// Receipt Commands
+// ============================================================================
+
+/// Build the receipt for a sale or special order deposit without printing it
+#[tauri::command]
+pub async fn get_receipt(app: AppHandle, source: ReceiptSource) -> IpcResponse<Receipt> {
+    let result: AppResult<Receipt> = async {
+        get_receipt_service(&app)
+            .receipt(source)
+            .await
+            .tap_err(|e| tracing::error!("Failed to build receipt for {:?}: {}", source, e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
+/// Print a receipt to a thermal printer, or save its ESC/POS commands to a file
+#[tauri::command]
+pub async fn print_receipt(
+    app: AppHandle,
+    params: CreateParams<ReceiptOptions>,
+    target: PrintTarget,
+) -> IpcResponse<Receipt> {
+    let result: AppResult<Receipt> = async {
+        let service = get_receipt_service(&app);
+        let options = params.data();
+
+        match target {
+            PrintTarget::File { path } => service
+                .save(options, &path)
+                .await
+                .tap_ok(|receipt| tracing::info!("Saved receipt {}", receipt.number))
+                .tap_err(|e| tracing::error!("Failed to save receipt: {}", e))
+                .map_err(Into::into),
+            PrintTarget::Printer { name } => {
+                let (receipt, content) = service
+                    .render(options)
+                    .await
+                    .tap_err(|e| tracing::error!("Failed to render receipt: {}", e))?;
+                printer::print_raw(name.as_deref(), &content, "Receipt")
+                    .tap_ok(|_| tracing::info!("Sent receipt {} to printer", receipt.number))
+                    .tap_err(|e| tracing::error!("Failed to print receipt: {}", e))?;
+                Ok(receipt)
+            }
+        }
+    }
+    .await;
+    result.into()
+}
@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use db_service::{RenderedReport, ReportOptions};
+use tap::TapFallible;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    error::AppResult,
+    ipc::{params::CreateParams, response::IpcResponse},
+    state::AppState,
+};
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Helper to get report service from app state
+#[inline]
+fn get_report_service(app: &AppHandle) -> std::sync::Arc<db_service::ReportService> {
+    let state = app.state::<AppState>();
+    let service_manager = state.service_manager();
+    service_manager.report().clone()
+}
+
+// ============================================================================
+// Report Commands
+// ============================================================================
+
+/// Render a report as printable HTML or PDF and save it to `destination`
+#[tauri::command]
+pub async fn save_report(
+    app: AppHandle,
+    params: CreateParams<ReportOptions>,
+    destination: PathBuf,
+) -> IpcResponse<RenderedReport> {
+    let result: AppResult<RenderedReport> = async {
+        get_report_service(&app)
+            .save(params.data().clone(), &destination)
+            .await
+            .tap_ok(|report| {
+                tracing::info!(
+                    "Saved {}-page report to {}",
+                    report.pages,
+                    report.path.display()
+                )
+            })
+            .tap_err(|e| tracing::error!("Failed to save report: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
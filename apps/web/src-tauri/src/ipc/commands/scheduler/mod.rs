@@ -0,0 +1,82 @@
+use db_entity::job_run::dto::*;
+use tap::TapFallible;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    error::AppResult,
+    ipc::{params::ListParams, response::IpcResponse},
+    state::AppState,
+};
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Helper to get job scheduler from app state
+#[inline]
+fn get_job_scheduler(app: &AppHandle) -> std::sync::Arc<db_service::JobScheduler> {
+    let state = app.state::<AppState>();
+    let service_manager = state.service_manager();
+    service_manager.scheduler().clone()
+}
+
+// ============================================================================
+// Background Job Commands
+// ============================================================================
+
+/// Get the effective schedule of every background job
+#[tauri::command]
+pub async fn get_job_schedules(app: AppHandle) -> IpcResponse<Vec<JobScheduleResponse>> {
+    let result: AppResult<Vec<JobScheduleResponse>> = async {
+        get_job_scheduler(&app)
+            .get_schedules()
+            .await
+            .tap_ok(|schedules| tracing::debug!("Retrieved {} job schedules", schedules.len()))
+            .tap_err(|e| tracing::error!("Failed to get job schedules: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
+/// List recorded job runs with optional filtering and pagination
+#[tauri::command]
+pub async fn list_job_runs(
+    app: AppHandle,
+    params: ListParams<JobRunQueryDto>,
+) -> IpcResponse<db_service::PaginationResult<JobRunResponse>> {
+    let result: AppResult<db_service::PaginationResult<JobRunResponse>> = async {
+        let query = params.filter().clone().unwrap_or_default();
+
+        get_job_scheduler(&app)
+            .list_runs(query, *params.pagination())
+            .await
+            .tap_ok(|result| {
+                tracing::debug!(
+                    "Listed {} job runs (page {}/{})",
+                    result.items_ref().len(),
+                    result.page(),
+                    result.total_pages()
+                )
+            })
+            .tap_err(|e| tracing::error!("Failed to list job runs: {}", e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
+
+/// Run a background job immediately
+#[tauri::command]
+pub async fn run_job_now(app: AppHandle, job_name: String) -> IpcResponse<JobRunResponse> {
+    let result: AppResult<JobRunResponse> = async {
+        get_job_scheduler(&app)
+            .run_now(&job_name)
+            .await
+            .tap_ok(|run| tracing::info!("Ran job '{}' on demand: {:?}", job_name, run.status))
+            .tap_err(|e| tracing::error!("Failed to run job '{}': {}", job_name, e))
+            .map_err(Into::into)
+    }
+    .await;
+    result.into()
+}
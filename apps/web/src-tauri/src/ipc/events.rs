@@ -0,0 +1,32 @@
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::error::RecvError;
+
+/// Name of the Tauri event carrying every domain event to the frontend
+pub const DOMAIN_EVENT: &str = "domain-event";
+
+/// Forward domain events from the service layer to all open windows
+///
+/// Each event is emitted as `domain-event` with a `{ type, payload }` body, so
+/// the frontend can subscribe once and dispatch on `type`.
+pub fn spawn_event_bridge(app_handle: AppHandle, events: &db_service::EventBus) {
+    let mut receiver = events.subscribe();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if let Err(e) = app_handle.emit(DOMAIN_EVENT, &event) {
+                        tracing::error!("Failed to emit domain event {}: {}", event.name(), e);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Event bridge lagged, {} domain events dropped", skipped);
+                }
+                Err(RecvError::Closed) => {
+                    tracing::info!("Event bus closed, stopping event bridge");
+                    break;
+                }
+            }
+        }
+    });
+}
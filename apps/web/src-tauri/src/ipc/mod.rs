@@ -2,5 +2,14 @@ pub mod params;
 
 pub mod response;
 
+/// Domain event forwarding to the frontend
+pub mod events;
+
+/// Import file decoding (CSV and spreadsheets)
+pub mod sheet;
+
+/// System printer access
+pub mod printer;
+
 /// Tauri command handlers
 pub mod commands;
@@ -0,0 +1,85 @@
+//! System printer access through the `printers` crate
+
+use std::path::{Path, PathBuf};
+
+use printers::common::base::job::PrinterJobOptions;
+use printers::common::base::printer::Printer;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+/// Where printable output goes
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PrintTarget {
+    /// A system printer by name, or the default printer
+    Printer { name: Option<String> },
+    /// A file at the given path
+    File { path: PathBuf },
+}
+
+/// Printer installed on this machine
+#[derive(Debug, Clone, Serialize)]
+pub struct PrinterInfo {
+    /// Name to print to
+    pub name: String,
+    pub driver_name: String,
+    pub is_default: bool,
+}
+
+/// List the installed printers
+pub fn list_printers() -> Vec<PrinterInfo> {
+    printers::get_printers()
+        .into_iter()
+        .map(|printer| PrinterInfo {
+            name: printer.system_name,
+            driver_name: printer.driver_name,
+            is_default: printer.is_default,
+        })
+        .collect()
+}
+
+/// Send a file (such as a PDF) to the printer `name`, or the default printer
+pub fn print_file(name: Option<&str>, path: &Path, job_name: &str) -> AppResult<()> {
+    let printer = find_printer(name)?;
+    let path = path
+        .to_str()
+        .ok_or_else(|| AppError::from(format!("Invalid print file path: {}", path.display())))?;
+
+    printer
+        .print_file(
+            path,
+            PrinterJobOptions {
+                name: Some(job_name),
+                raw_properties: &[],
+            },
+        )
+        .map(|_| ())
+        .map_err(|e| AppError::from(format!("Failed to print to {}: {:?}", printer.name, e)))
+}
+
+/// Send raw printer commands (such as ESC/POS) to the printer `name`, or the
+/// default printer, bypassing the system driver's rendering
+pub fn print_raw(name: Option<&str>, data: &[u8], job_name: &str) -> AppResult<()> {
+    let printer = find_printer(name)?;
+
+    printer
+        .print(
+            data,
+            PrinterJobOptions {
+                name: Some(job_name),
+                raw_properties: &[("document-format", "application/vnd.cups-raw")],
+            },
+        )
+        .map(|_| ())
+        .map_err(|e| AppError::from(format!("Failed to print to {}: {:?}", printer.name, e)))
+}
+
+fn find_printer(name: Option<&str>) -> AppResult<Printer> {
+    match name {
+        Some(name) => printers::get_printer_by_name(name)
+            .ok_or_else(|| AppError::from(format!("Printer not found: {}", name))),
+        None => printers::get_default_printer()
+            .ok_or_else(|| AppError::from("No default printer is configured")),
+    }
+}
@@ -0,0 +1,61 @@
+//! Decoding of uploaded import files into an [`ImportSheet`]
+
+use std::io::Cursor;
+
+use calamine::{open_workbook_auto_from_rs, Data, DataType, Reader};
+use db_service::ImportSheet;
+
+use crate::error::{AppError, AppResult};
+
+/// Read the first worksheet of an `.xlsx`/`.xls` file, or a `.csv` file
+pub fn read_sheet(file_name: &str, content: &[u8]) -> AppResult<ImportSheet> {
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "csv" | "txt" => {
+            let text = std::str::from_utf8(content)
+                .map_err(|_| AppError::from("CSV file must be UTF-8 encoded"))?;
+            Ok(ImportSheet::from_csv(text)?)
+        }
+        "xlsx" | "xlsm" | "xls" | "ods" => read_workbook(content),
+        _ => Err(format!("Unsupported import file type: '{}'", file_name).into()),
+    }
+}
+
+fn read_workbook(content: &[u8]) -> AppResult<ImportSheet> {
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(content))
+        .map_err(|e| AppError::from(format!("Failed to open spreadsheet: {}", e)))?;
+
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| AppError::from("Spreadsheet has no worksheets"))?
+        .map_err(|e| AppError::from(format!("Failed to read worksheet: {}", e)))?;
+
+    let mut rows = range
+        .rows()
+        .map(|row| row.iter().map(cell_to_string).collect::<Vec<_>>());
+    let headers = rows
+        .next()
+        .ok_or_else(|| AppError::from("Import file is empty"))?;
+
+    Ok(ImportSheet::new(headers, rows.collect()))
+}
+
+/// Render a cell the way it would appear in a CSV export
+fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::DateTime(_) | Data::DateTimeIso(_) => cell
+            .as_date()
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| cell.to_string()),
+        // Spreadsheets store every number as a float; keep codes like 6221000000011 intact
+        Data::Float(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+            (*value as i64).to_string()
+        }
+        other => other.to_string(),
+    }
+}
@@ -1,6 +1,9 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
 use tauri::Manager;
 
+mod diagnostics;
 mod error;
 /// IPC command handlers
 pub mod ipc;
@@ -11,9 +14,56 @@ mod state;
 #[command(name = "meditrack")]
 #[command(about = "MediTrack - Pharmacy Management System", long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Launch the configuration TUI
     #[arg(short, long)]
     config: bool,
+
+    /// Configuration profile, e.g. `training`
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        default_value = app_config::DEFAULT_PROFILE
+    )]
+    profile: String,
+
+    /// Plain TOML file layered over the stored configuration (default:
+    /// config.toml in the config directory, or MEDITRACK_CONFIG_FILE)
+    #[arg(long, value_name = "PATH")]
+    config_file: Option<PathBuf>,
+
+    /// Override a setting, e.g. `--set database.host=db`; may be repeated
+    #[arg(
+        long = "set",
+        value_name = "KEY=VALUE",
+        value_parser = app_config::cli::parse_key_value
+    )]
+    overrides: Vec<(String, String)>,
+
+    /// Print the effective configuration and where each value comes from,
+    /// then exit
+    #[arg(long)]
+    print_effective_config: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Read and change the configuration without the TUI, printing JSON
+    Config(app_config::cli::ConfigArgs),
+}
+
+/// Configuration directory of `profile`
+pub(crate) fn profile_config_dir(profile: &str) -> app_config::Result<PathBuf> {
+    app_config::app_config_dir(app_config::APP_IDENTIFIER)
+        .ok_or_else(|| {
+            app_config::ConfigError::InvalidConfig(
+                "No configuration directory on this platform".to_string(),
+            )
+        })
+        .and_then(|config_dir| app_config::ConfigStorage::profile_dir(&config_dir, profile))
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -27,7 +77,9 @@ pub async fn run() {
         println!("Launching configuration TUI...");
 
         // Run the TUI
-        match app_config::cli_tui::run_config_tui().await {
+        match app_config::cli_tui::run_config_tui(&cli.profile, diagnostics::connection_tester())
+            .await
+        {
             Ok(_) => std::process::exit(0),
             Err(e) => {
                 eprintln!("Failed to run configuration TUI: {}", e);
@@ -36,15 +88,51 @@ pub async fn run() {
         }
     }
 
+    // Headless configuration commands, for provisioning scripts
+    if let Some(Command::Config(args)) = cli.command {
+        let code = match profile_config_dir(&cli.profile) {
+            Ok(config_dir) => app_config::cli::run(args, config_dir, &cli.profile),
+            // An explicit --config-dir needs no default directory
+            Err(_) if args.config_dir.is_some() => {
+                app_config::cli::run(args, PathBuf::new(), &cli.profile)
+            }
+            Err(e) => {
+                eprintln!("{}", serde_json::json!({ "error": e.to_string() }));
+                1
+            }
+        };
+        std::process::exit(code);
+    }
+
+    // Defaults < config.enc < TOML file < MEDITRACK_* variables < --set
+    let layers = app_config::ConfigLayers::from_env(cli.config_file, cli.overrides);
+
+    if cli.print_effective_config {
+        let effective = profile_config_dir(&cli.profile).and_then(|config_dir| {
+            app_config::AppConfig::load_layered(config_dir, &cli.profile, &layers)
+        });
+
+        match effective {
+            Ok(effective) => {
+                print!("{}", effective);
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Failed to load configuration: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     let builder = tauri::Builder::default()
         .plugin(tauri_plugin_updater::Builder::new().build())
-        .setup(|app| {
+        .setup(move |app| {
             // Initialize database and services
             let app_handle = app.handle().clone();
 
             // Spawn state initialization asynchronously
             tauri::async_runtime::spawn(async move {
-                match state::try_init_state(&app_handle).await {
+                match state::try_init_state(&app_handle, &cli.profile, &layers).await {
                     Ok(state) => {
                         tracing::info!("Application state initialized successfully");
                         app_handle.manage(state);
@@ -110,9 +198,12 @@ pub async fn run() {
         ipc::commands::inventory::create_inventory_item,
         ipc::commands::inventory::get_inventory_item,
         ipc::commands::inventory::get_inventory_item_by_barcode,
+        ipc::commands::inventory::scan_inventory_barcode,
         ipc::commands::inventory::update_inventory_item,
         ipc::commands::inventory::delete_inventory_item,
         ipc::commands::inventory::restore_inventory_item,
+        // Inventory Catalogue Import
+        ipc::commands::inventory::import_inventory_catalogue,
         // Inventory Stock management
         ipc::commands::inventory::update_inventory_stock,
         ipc::commands::inventory::adjust_inventory_stock,
@@ -123,6 +214,7 @@ pub async fn run() {
         ipc::commands::inventory::search_inventory_items,
         // Inventory Statistics
         ipc::commands::inventory::get_inventory_statistics,
+        ipc::commands::inventory::get_inventory_valuation,
         // Inventory Barcode Management
         ipc::commands::inventory::get_item_barcodes,
         ipc::commands::inventory::add_barcode,
@@ -167,6 +259,9 @@ pub async fn run() {
         ipc::commands::inventory::opening_balance::get_opening_balances_by_item,
         ipc::commands::inventory::opening_balance::get_unverified_opening_balances,
         ipc::commands::inventory::opening_balance::get_opening_balances_by_batch,
+        // Opening Balance Import
+        ipc::commands::inventory::opening_balance::import_opening_balances,
+        ipc::commands::inventory::opening_balance::revert_opening_balance_import,
         // Opening Balance Statistics
         ipc::commands::inventory::opening_balance::get_opening_balance_statistics,
         // Manufacturer CRUD operations
@@ -186,6 +281,33 @@ pub async fn run() {
         ipc::commands::session::logout_all_sessions,
         ipc::commands::session::get_user_sessions,
         ipc::commands::session::cleanup_expired_sessions,
+        // Background Jobs
+        ipc::commands::scheduler::get_job_schedules,
+        ipc::commands::scheduler::list_job_runs,
+        ipc::commands::scheduler::run_job_now,
+        // Data Export
+        ipc::commands::export::export_data,
+        // Database Backup
+        ipc::commands::backup::create_database_backup,
+        ipc::commands::backup::list_database_backups,
+        ipc::commands::backup::restore_database_backup,
+        ipc::commands::backup::restore_database_backup_file,
+        // Sales & Consumption Analytics
+        ipc::commands::analytics::get_item_analytics,
+        ipc::commands::analytics::apply_min_stock_recommendations,
+        // Printable Reports
+        ipc::commands::report::save_report,
+        // Labels & Printers
+        ipc::commands::label::list_label_templates,
+        ipc::commands::label::save_label_template,
+        ipc::commands::label::delete_label_template,
+        ipc::commands::label::print_labels,
+        ipc::commands::printer::list_printers,
+        // Receipts
+        ipc::commands::receipt::get_receipt,
+        ipc::commands::receipt::print_receipt,
+        // Configuration Profile
+        ipc::commands::profile::get_profile,
     ]);
 
     builder
@@ -0,0 +1,55 @@
+use app_config::{ConfigError, KdfParams, KeySource, SecureStorage};
+use db_service::{BackupCipher, BackupKdf, ServiceError, ServiceResult};
+
+/// Backup cipher backed by the configuration `SecureStorage`
+///
+/// Every encrypted backup has its own salt, so the key is derived per
+/// archive from the parameters in its manifest.
+pub struct SecureStorageCipher(KeySource);
+
+impl SecureStorageCipher {
+    /// Key derived from this machine, as used for the configuration file
+    ///
+    /// Backups encrypted with it can only be restored on the same machine.
+    pub fn machine() -> Self {
+        Self(KeySource::Machine)
+    }
+
+    /// Key derived from a password, for backups restored elsewhere
+    pub fn with_password(password: &str) -> Self {
+        Self(KeySource::Password(password.to_string()))
+    }
+
+    /// Storage with the key derived with `kdf`
+    fn storage(&self, kdf: &BackupKdf) -> ServiceResult<SecureStorage> {
+        SecureStorage::derive(
+            &self.0,
+            &KdfParams {
+                memory_kib: kdf.memory_kib,
+                iterations: kdf.iterations,
+                parallelism: kdf.parallelism,
+                salt: kdf.salt_bytes()?,
+            },
+        )
+        .map_err(|e| match e {
+            // Parameters read from a damaged or hostile manifest
+            ConfigError::Corrupt(reason) => ServiceError::BadRequest(reason),
+            e => ServiceError::Internal(format!("Failed to derive backup key: {}", e)),
+        })
+    }
+}
+
+impl BackupCipher for SecureStorageCipher {
+    fn encrypt(&self, data: &[u8], kdf: &BackupKdf) -> ServiceResult<Vec<u8>> {
+        self.storage(kdf)?
+            .encrypt_bytes(data)
+            .map_err(|e| ServiceError::Internal(e.to_string()))
+    }
+
+    fn decrypt(&self, data: &[u8], kdf: &BackupKdf) -> ServiceResult<Vec<u8>> {
+        // Failure here almost always means the wrong key or password
+        self.storage(kdf)?
+            .decrypt_bytes(data)
+            .map_err(|e| ServiceError::BadRequest(format!("Failed to decrypt backup: {}", e)))
+    }
+}
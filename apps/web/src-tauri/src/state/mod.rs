@@ -1,40 +1,76 @@
 use derive_getters::Getters;
+use serde::Serialize;
 use tauri::Manager;
 use typed_builder::TypedBuilder;
 
 use crate::error::{AppError, AppResult};
 
+mod backup;
+pub use backup::SecureStorageCipher;
+
 /// Application state container
 #[derive(TypedBuilder, Getters)]
 pub struct AppState {
     #[builder(setter(into))]
     service_manager: db_service::ServiceManager,
+    profile: ProfileInfo,
+}
+
+/// Configuration profile the app was started with
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileInfo {
+    /// Profile name, e.g. `default` or `training`
+    pub name: String,
+    /// Whether the UI shows the training banner
+    pub training: bool,
 }
 
 /// Initialize application state
 ///
 /// # Arguments
 /// * `app_handle` - Tauri application handle for path resolution
+/// * `profile` - Configuration profile to load
+/// * `layers` - Overrides applied over the stored configuration
 ///
 /// # Returns
 /// * `AppResult<AppState>` - Initialized application state or error
-#[tracing::instrument(skip(app_handle))]
-pub async fn try_init_state(app_handle: &tauri::AppHandle) -> AppResult<AppState> {
-    // Get app config directory
-    let config_dir = app_handle
-        .path()
-        .app_config_dir()
+#[tracing::instrument(skip(app_handle, layers))]
+pub async fn try_init_state(
+    app_handle: &tauri::AppHandle,
+    profile: &str,
+    layers: &app_config::ConfigLayers,
+) -> AppResult<AppState> {
+    // Get the config directory of the profile
+    let config_dir = crate::profile_config_dir(profile)
         .map_err(|e| AppError::ParseError(format!("Failed to get app config directory: {}", e)))?;
 
-    // Load application configuration from storage or use defaults
-    let config = app_config::AppConfig::load_or_default(config_dir);
+    // Load application configuration, or create the default on first run; a
+    // configuration that exists but cannot be decrypted stops startup rather
+    // than being replaced by defaults
+    let effective = app_config::AppConfig::load_layered(config_dir.clone(), profile, layers)
+        .map_err(|e| AppError::ParseError(format!("Failed to load configuration: {}", e)))?;
+    for (key, source) in effective.overrides() {
+        tracing::info!("Configuration override: {} from {}", key, source);
+    }
+    let mut config = effective.config;
+    config.database.resolve_sqlite_path(&config_dir);
 
-    tracing::info!("Configuration loaded successfully");
+    tracing::info!("Configuration loaded successfully (profile {})", profile);
+    if config.training {
+        tracing::warn!("Running the training profile {}", profile);
+    }
+    let profile = ProfileInfo {
+        name: profile.to_string(),
+        training: config.training,
+    };
     tracing::info!("Database: {}", config.database().safe_repr());
 
     // Prepare database configuration
     let db_config = db_service::DatabaseConfig {
-        url: config.database.connection_url(),
+        url: config
+            .database
+            .connection_url()
+            .map_err(|e| AppError::ParseError(format!("Invalid database settings: {}", e)))?,
         max_connections: config.database.max_connections,
         min_connections: config.database.min_connections,
         connect_timeout: config.database.connect_timeout,
@@ -47,8 +83,42 @@ pub async fn try_init_state(app_handle: &tauri::AppHandle) -> AppResult<AppState
         issuer: config.jwt.issuer,
         audience: config.jwt.audience,
         expiration_hours: config.jwt.expiration_hours,
+        retired_secrets: config
+            .jwt
+            .retired_secrets
+            .into_iter()
+            .map(|retired| db_service::RetiredSecret {
+                secret: retired.secret,
+                valid_until: retired.valid_until,
+            })
+            .collect(),
+        allow_default_secret: profile.name == app_config::DEV_PROFILE,
+        keys: config.jwt.keys,
+        signing_key: config.jwt.signing_key,
     };
 
-    let service_manager = db_service::ServiceManager::init(db_config, jwt_config).await?;
-    Ok(AppState::builder().service_manager(service_manager).build())
+    // Scheduled backups go to the app data directory and, when encryption is
+    // turned on in the settings, use a key derived from the machine
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::ParseError(format!("Failed to get app data directory: {}", e)))?;
+    let backup_config = db_service::BackupConfig {
+        dir: data_dir.join("backups"),
+        cipher: Some(std::sync::Arc::new(SecureStorageCipher::machine())),
+    };
+
+    let service_manager =
+        db_service::ServiceManager::init(db_config, jwt_config, backup_config).await?;
+
+    // Forward domain events to open windows
+    crate::ipc::events::spawn_event_bridge(app_handle.clone(), service_manager.events());
+
+    // Start background jobs (session cleanup, stock and expiry checks)
+    service_manager.scheduler().start();
+
+    Ok(AppState::builder()
+        .service_manager(service_manager)
+        .profile(profile)
+        .build())
 }
@@ -0,0 +1,139 @@
+/**
+ * Analytics API
+ *
+ * Provides type-safe access to the sales and consumption analytics Tauri
+ * commands: consumption velocity, ABC classification, dead stock, days of
+ * cover and minimum stock level recommendations.
+ *
+ * @module api/analytics
+ */
+
+import { z } from "zod";
+import { invokeCommand, type PaginationParams } from "@/lib/tauri-api";
+import { createLogger } from "@/lib/logger";
+
+const logger = createLogger("AnalyticsAPI");
+
+// ============================================================================
+// Schemas
+// ============================================================================
+
+export const AbcClassSchema = z.enum(["A", "B", "C"]);
+export type AbcClass = z.infer<typeof AbcClassSchema>;
+
+export const AnalyticsSortBySchema = z.enum([
+  "name",
+  "stock_quantity",
+  "daily_velocity",
+  "consumption_value",
+  "days_of_cover",
+  "days_since_movement",
+]);
+export type AnalyticsSortBy = z.infer<typeof AnalyticsSortBySchema>;
+
+/**
+ * Analytics query schema (matches backend ItemAnalyticsQuery)
+ *
+ * Omitted fields use the backend defaults: a 90 day period, dead stock after
+ * 90 days without movement, 14 days of cover, sorted by consumption value.
+ */
+export const ItemAnalyticsQuerySchema = z.object({
+  period_days: z.number().int().positive().optional(),
+  dead_stock_days: z.number().int().nonnegative().optional(),
+  cover_days: z.number().int().nonnegative().optional(),
+  abc_class: AbcClassSchema.nullable().optional(),
+  dead_stock_only: z.boolean().optional(),
+  sort_by: AnalyticsSortBySchema.optional(),
+  descending: z.boolean().optional(),
+});
+export type ItemAnalyticsQuery = z.infer<typeof ItemAnalyticsQuerySchema>;
+
+/**
+ * Item analytics schema (matches backend ItemAnalytics)
+ */
+export const ItemAnalyticsSchema = z.object({
+  inventory_item_id: z.string().uuid(),
+  name: z.string(),
+  stock_quantity: z.number(),
+  min_stock_level: z.number(),
+  sold: z.number(),
+  wasted: z.number(),
+  daily_velocity: z.number(),
+  consumption_value: z.number(),
+  abc_class: AbcClassSchema,
+  last_movement_at: z.string().nullable(),
+  days_since_movement: z.number(),
+  is_dead_stock: z.boolean(),
+  days_of_cover: z.number().nullable(),
+  recommended_min_stock_level: z.number(),
+});
+export type ItemAnalytics = z.infer<typeof ItemAnalyticsSchema>;
+
+/**
+ * Pagination result schema
+ */
+export const PaginationResultSchema = <T extends z.ZodTypeAny>(itemSchema: T) =>
+  z.object({
+    items: z.array(itemSchema),
+    total: z.number(),
+    page: z.number(),
+    page_size: z.number(),
+    total_pages: z.number(),
+  });
+export type PaginationResult<T> = {
+  items: T[];
+  total: number;
+  page: number;
+  page_size: number;
+  total_pages: number;
+};
+
+// ============================================================================
+// Analytics Operations
+// ============================================================================
+
+/**
+ * List per-item analytics, filtered, sorted and paginated
+ */
+export async function getItemAnalytics(
+  query?: ItemAnalyticsQuery,
+  pagination?: PaginationParams,
+): Promise<PaginationResult<ItemAnalytics>> {
+  logger.info("Getting item analytics:", query);
+  return invokeCommand(
+    "get_item_analytics",
+    PaginationResultSchema(ItemAnalyticsSchema),
+    {
+      params: {
+        filter: query || null,
+        pagination: pagination || null,
+      },
+    },
+  );
+}
+
+/**
+ * Set the minimum stock level of the given items to their recommendation
+ *
+ * Either every level changes or none does. Returns the number of items whose
+ * level changed.
+ */
+export async function applyMinStockRecommendations(
+  query: ItemAnalyticsQuery,
+  itemIds: string[],
+  updated_by: string,
+): Promise<number> {
+  logger.info(`Applying stock recommendations to ${itemIds.length} items`);
+  return invokeCommand("apply_min_stock_recommendations", z.number(), {
+    params: { data: { query, item_ids: itemIds, updated_by } },
+  });
+}
+
+// ============================================================================
+// Exports
+// ============================================================================
+
+export const analyticsApi = {
+  getItems: getItemAnalytics,
+  applyRecommendations: applyMinStockRecommendations,
+} as const;
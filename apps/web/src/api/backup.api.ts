@@ -0,0 +1,121 @@
+/**
+ * Backup API
+ *
+ * Provides type-safe access to the database backup Tauri commands.
+ * Backups are compressed, checksummed archives stamped with the schema
+ * version; restores only run into an empty database.
+ *
+ * @module api/backup
+ */
+
+import { z } from "zod";
+import { invokeCommand } from "@/lib/tauri-api";
+import { createLogger } from "@/lib/logger";
+
+const logger = createLogger("BackupAPI");
+
+// ============================================================================
+// Schemas
+// ============================================================================
+
+/**
+ * Backup manifest schema (matches backend BackupManifest)
+ */
+export const BackupManifestSchema = z.object({
+  format_version: z.number(),
+  schema_version: z.string(),
+  created_at: z.string(),
+  encrypted: z.boolean(),
+  checksum: z.string(),
+  tables: z.array(
+    z.object({
+      name: z.string(),
+      rows: z.number(),
+    }),
+  ),
+  kdf: z
+    .object({
+      memory_kib: z.number(),
+      iterations: z.number(),
+      parallelism: z.number(),
+      salt: z.string(),
+    })
+    .optional(),
+});
+export type BackupManifest = z.infer<typeof BackupManifestSchema>;
+
+/**
+ * Backup file schema (matches backend BackupFile)
+ */
+export const BackupFileSchema = z.object({
+  path: z.string(),
+  size: z.number(),
+  manifest: BackupManifestSchema,
+});
+export type BackupFile = z.infer<typeof BackupFileSchema>;
+
+// ============================================================================
+// Backup Operations
+// ============================================================================
+
+/**
+ * Back up the database, encrypted when `encrypt` is set or a password is given
+ */
+export async function createBackup(
+  encrypt: boolean,
+  password?: string,
+): Promise<BackupFile> {
+  logger.info("Creating database backup (encrypted:", encrypt, ")");
+  return invokeCommand("create_database_backup", BackupFileSchema, {
+    encrypt,
+    password,
+  });
+}
+
+/**
+ * List the backups in the backup directory, newest first
+ */
+export async function listBackups(): Promise<BackupFile[]> {
+  logger.info("Listing database backups");
+  return invokeCommand("list_database_backups", z.array(BackupFileSchema));
+}
+
+/**
+ * Restore an uploaded backup file into the (empty) database
+ */
+export async function restoreBackup(
+  file: File,
+  password?: string,
+): Promise<BackupManifest> {
+  logger.info("Restoring database backup:", file.name);
+  const content = Array.from(new Uint8Array(await file.arrayBuffer()));
+  return invokeCommand("restore_database_backup", BackupManifestSchema, {
+    content,
+    password,
+  });
+}
+
+/**
+ * Restore a backup from the backup directory into the (empty) database
+ */
+export async function restoreBackupFile(
+  path: string,
+  password?: string,
+): Promise<BackupManifest> {
+  logger.info("Restoring database backup:", path);
+  return invokeCommand("restore_database_backup_file", BackupManifestSchema, {
+    path,
+    password,
+  });
+}
+
+// ============================================================================
+// Exports
+// ============================================================================
+
+export const backupApi = {
+  create: createBackup,
+  list: listBackups,
+  restore: restoreBackup,
+  restoreFile: restoreBackupFile,
+} as const;
@@ -0,0 +1,96 @@
+/**
+ * Export API
+ *
+ * Provides type-safe access to the data export Tauri command.
+ * Exports catalogue, stock and history tables as CSV files or a JSON archive.
+ *
+ * @module api/export
+ */
+
+import { z } from "zod";
+import { invokeCommand } from "@/lib/tauri-api";
+import { createLogger } from "@/lib/logger";
+
+const logger = createLogger("ExportAPI");
+
+// ============================================================================
+// Schemas
+// ============================================================================
+
+/**
+ * Exportable table schema (matches backend ExportTable)
+ */
+export const ExportTableSchema = z.enum([
+  "manufacturers",
+  "medicine_forms",
+  "inventory_items",
+  "barcodes",
+  "price_history",
+  "stock_history",
+  "opening_balances",
+]);
+export type ExportTable = z.infer<typeof ExportTableSchema>;
+
+/**
+ * Export format schema (matches backend ExportFormat)
+ */
+export const ExportFormatSchema = z.enum(["csv", "json"]);
+export type ExportFormat = z.infer<typeof ExportFormatSchema>;
+
+/**
+ * Date range for history tables, inclusive (matches backend ExportFilter)
+ */
+export const ExportFilterSchema = z.object({
+  from: z.string().nullable().optional(),
+  to: z.string().nullable().optional(),
+});
+export type ExportFilter = z.infer<typeof ExportFilterSchema>;
+
+/**
+ * Export options schema (matches backend ExportOptions)
+ */
+export const ExportOptionsSchema = z.object({
+  format: ExportFormatSchema,
+  tables: z.array(ExportTableSchema).optional(),
+  filter: ExportFilterSchema.optional(),
+});
+export type ExportOptions = z.infer<typeof ExportOptionsSchema>;
+
+/**
+ * Exported file schema (matches backend ExportedFile)
+ */
+export const ExportedFileSchema = z.object({
+  path: z.string(),
+  tables: z.array(
+    z.object({
+      table: ExportTableSchema,
+      rows: z.number(),
+    }),
+  ),
+});
+export type ExportedFile = z.infer<typeof ExportedFileSchema>;
+
+// ============================================================================
+// Export Operations
+// ============================================================================
+
+/**
+ * Export data to a new folder in the exports directory of the app data
+ * directory
+ */
+export async function exportData(
+  options: ExportOptions,
+): Promise<ExportedFile[]> {
+  logger.info("Exporting data as", options.format);
+  return invokeCommand("export_data", z.array(ExportedFileSchema), {
+    params: { data: options },
+  });
+}
+
+// ============================================================================
+// Exports
+// ============================================================================
+
+export const exportApi = {
+  export: exportData,
+} as const;
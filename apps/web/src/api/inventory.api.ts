@@ -10,6 +10,8 @@
 import { z } from "zod";
 import { invokeCommand } from "@/lib/tauri-api";
 import { createLogger } from "@/lib/logger";
+import { ImportRowErrorSchema } from "./opening-balance.api";
+import { UserIdSchema } from "./user.api";
 
 const logger = createLogger("InventoryAPI");
 
@@ -70,8 +72,8 @@ export const InventoryItemWithStockResponseSchema = z.object({
   storage_instructions: z.string().nullable(),
   notes: z.string().nullable(),
   is_active: z.boolean(),
-  created_by: InventoryItemIdSchema.nullable(),
-  updated_by: InventoryItemIdSchema.nullable(),
+  created_by: UserIdSchema.nullable(),
+  updated_by: UserIdSchema.nullable(),
   created_at: z.string(),
   updated_at: z.string(),
   // Stock fields
@@ -88,6 +90,20 @@ export type InventoryItemWithStockResponse = z.infer<
   typeof InventoryItemWithStockResponseSchema
 >;
 
+/**
+ * Scanned barcode schema (matches backend ScannedBarcode)
+ *
+ * Lot fields are filled from GS1 DataMatrix and GS1-128 codes only.
+ */
+export const ScannedBarcodeSchema = z.object({
+  item: InventoryItemWithStockResponseSchema,
+  gtin: z.string().nullable(),
+  batch_number: z.string().nullable(),
+  expiry_date: z.string().nullable(), // YYYY-MM-DD
+  serial_number: z.string().nullable(),
+});
+export type ScannedBarcode = z.infer<typeof ScannedBarcodeSchema>;
+
 /**
  * Inventory item response schema (catalog only, matches backend InventoryItemResponse)
  */
@@ -106,8 +122,8 @@ export const InventoryItemResponseSchema = z.object({
   storage_instructions: z.string().nullable(),
   notes: z.string().nullable(),
   is_active: z.boolean(),
-  created_by: InventoryItemIdSchema.nullable(),
-  updated_by: InventoryItemIdSchema.nullable(),
+  created_by: UserIdSchema.nullable(),
+  updated_by: UserIdSchema.nullable(),
   created_at: z.string(),
   updated_at: z.string(),
   barcodes: z.array(InventoryItemBarcodeResponseSchema),
@@ -169,17 +185,21 @@ export const UpdateInventoryItemSchema = z.object({
   storage_instructions: z.string().optional(),
   notes: z.string().optional(),
   is_active: z.boolean().optional(),
-  updated_by: InventoryItemIdSchema.optional(),
+  updated_by: UserIdSchema.optional(),
 });
 export type UpdateInventoryItem = z.infer<typeof UpdateInventoryItemSchema>;
 
 /**
  * Update inventory stock DTO schema (matches backend UpdateInventoryStock)
+ *
+ * `reason` is required by the backend whenever `unit_price` changes.
  */
 export const UpdateInventoryStockSchema = z.object({
   stock_quantity: z.number().int().nonnegative().optional(),
   min_stock_level: z.number().int().nonnegative().optional(),
   unit_price: z.number().nonnegative().optional(),
+  reason: z.string().optional(),
+  updated_by: UserIdSchema.optional(),
 });
 export type UpdateInventoryStock = z.infer<typeof UpdateInventoryStockSchema>;
 
@@ -204,6 +224,60 @@ export const InventoryStatisticsSchema = z.object({
 });
 export type InventoryStatistics = z.infer<typeof InventoryStatisticsSchema>;
 
+/**
+ * Valuation methods (matches backend ValuationMethod)
+ */
+export const ValuationMethodSchema = z.enum([
+  "current_price",
+  "weighted_average_cost",
+]);
+export type ValuationMethod = z.infer<typeof ValuationMethodSchema>;
+
+export const ValuationGroupBySchema = z.enum(["manufacturer", "medicine_form"]);
+export type ValuationGroupBy = z.infer<typeof ValuationGroupBySchema>;
+
+/**
+ * Valuation query schema (matches backend ValuationQuery)
+ */
+export const ValuationQuerySchema = z.object({
+  as_of: z.string(), // YYYY-MM-DD, valued at the end of the day (UTC)
+  method: ValuationMethodSchema.optional(),
+  group_by: ValuationGroupBySchema.optional(),
+});
+export type ValuationQuery = z.infer<typeof ValuationQuerySchema>;
+
+export const ItemValuationSchema = z.object({
+  inventory_item_id: InventoryItemIdSchema,
+  name: z.string(),
+  quantity: z.number().int(),
+  unit_cost: z.number(),
+  value: z.number(),
+  price_estimated: z.boolean(),
+});
+export type ItemValuation = z.infer<typeof ItemValuationSchema>;
+
+export const ValuationGroupSchema = z.object({
+  id: z.string().uuid().nullable(),
+  name: z.string(),
+  total_quantity: z.number(),
+  total_value: z.number(),
+  items: z.array(ItemValuationSchema),
+});
+export type ValuationGroup = z.infer<typeof ValuationGroupSchema>;
+
+/**
+ * Valuation report schema (matches backend ValuationReport)
+ */
+export const ValuationReportSchema = z.object({
+  as_of: z.string(),
+  method: ValuationMethodSchema,
+  group_by: ValuationGroupBySchema,
+  total_quantity: z.number(),
+  total_value: z.number(),
+  groups: z.array(ValuationGroupSchema),
+});
+export type ValuationReport = z.infer<typeof ValuationReportSchema>;
+
 /**
  * Price history entry schema (matches backend PriceHistoryResponse)
  */
@@ -250,6 +324,10 @@ export const AdjustStockSchema = z.object({
   adjustment: z.number().int(), // Positive for add, negative for subtract
   reason: z.string().optional(),
   adjustment_type: StockAdjustmentTypeSchema.optional(), // Stock adjustment type
+  idempotency_key: z.string().max(100).optional(), // Reuse when retrying the same adjustment
+  adjusted_by: UserIdSchema.optional(),
+  reference_id: z.string().uuid().optional(), // Sale the adjustment belongs to
+  reference_type: z.string().optional(), // Defaults to "sale" for sales
 });
 export type AdjustStock = z.infer<typeof AdjustStockSchema>;
 
@@ -285,6 +363,34 @@ export type StockHistoryStatistics = z.infer<
   typeof StockHistoryStatisticsSchema
 >;
 
+/**
+ * Catalogue import options schema (matches backend CatalogueImportOptions)
+ */
+export const CatalogueImportOptionsSchema = z.object({
+  file_name: z.string().min(1),
+  dry_run: z.boolean().default(false),
+  create_missing: z.boolean().default(true),
+});
+export type CatalogueImportOptions = z.input<
+  typeof CatalogueImportOptionsSchema
+>;
+
+/**
+ * Catalogue import result schema (matches backend CatalogueImportResult)
+ */
+export const CatalogueImportResultSchema = z.object({
+  success: z.boolean(),
+  dry_run: z.boolean(),
+  total_rows: z.number(),
+  created_count: z.number(),
+  error_count: z.number(),
+  created_manufacturers: z.array(z.string()),
+  created_medicine_forms: z.array(z.string()),
+  errors: z.array(ImportRowErrorSchema),
+  error_report: z.string().nullable(),
+});
+export type CatalogueImportResult = z.infer<typeof CatalogueImportResultSchema>;
+
 // ============================================================================
 // CRUD Operations (Catalog + Stock Combined)
 // ============================================================================
@@ -329,6 +435,18 @@ export async function getInventoryItemByBarcode(
   );
 }
 
+/**
+ * Identify the item behind scanner input, with the batch, expiry and serial
+ * of GS1 codes
+ */
+export async function scanInventoryBarcode(
+  input: string,
+): Promise<ScannedBarcode> {
+  return invokeCommand("scan_inventory_barcode", ScannedBarcodeSchema, {
+    input,
+  });
+}
+
 /**
  * Update inventory item (catalog only)
  */
@@ -366,6 +484,38 @@ export async function restoreInventoryItem(
   });
 }
 
+// ============================================================================
+// Catalogue Import
+// ============================================================================
+
+/**
+ * Import catalogue items from a CSV or XLSX file
+ *
+ * With `dry_run` the file is only validated and the row report returned.
+ */
+export async function importInventoryCatalogue(
+  file: File,
+  options: Omit<CatalogueImportOptions, "file_name">,
+  created_by?: string,
+): Promise<CatalogueImportResult> {
+  logger.info("Importing inventory catalogue from file:", file.name);
+  const content = Array.from(new Uint8Array(await file.arrayBuffer()));
+  return invokeCommand(
+    "import_inventory_catalogue",
+    CatalogueImportResultSchema,
+    {
+      params: {
+        data: {
+          ...options,
+          file_name: file.name,
+          created_by: created_by ?? null,
+        },
+      },
+      content,
+    },
+  );
+}
+
 // ============================================================================
 // Stock Management Operations
 // ============================================================================
@@ -474,6 +624,18 @@ export async function getInventoryStatistics(): Promise<InventoryStatistics> {
   );
 }
 
+/**
+ * Value the stock held at the end of a past date
+ */
+export async function getInventoryValuation(
+  query: ValuationQuery,
+): Promise<ValuationReport> {
+  logger.info(`Getting inventory valuation as of ${query.as_of}`);
+  return invokeCommand("get_inventory_valuation", ValuationReportSchema, {
+    params: { data: query },
+  });
+}
+
 // ============================================================================
 // Price History Operations
 // ============================================================================
@@ -652,10 +814,14 @@ export const inventoryApi = {
   create: createInventoryItem,
   get: getInventoryItem,
   getByBarcode: getInventoryItemByBarcode,
+  scanBarcode: scanInventoryBarcode,
   update: updateInventoryItem,
   delete: deleteInventoryItem,
   restore: restoreInventoryItem,
 
+  // Catalogue Import
+  importCatalogue: importInventoryCatalogue,
+
   // Stock Management
   updateStock: updateInventoryStock,
   adjustStock: adjustInventoryStock,
@@ -668,6 +834,7 @@ export const inventoryApi = {
 
   // Statistics
   getStatistics: getInventoryStatistics,
+  getValuation: getInventoryValuation,
 
   // Price History
   getPriceHistory: getPriceHistory,
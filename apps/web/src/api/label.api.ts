@@ -0,0 +1,162 @@
+/**
+ * Label API
+ *
+ * Provides type-safe access to the shelf and barcode label Tauri commands:
+ * label templates, printing labels to a system printer or a PDF file, and
+ * listing the installed printers.
+ *
+ * @module api/label
+ */
+
+import { z } from "zod";
+import { invokeCommand } from "@/lib/tauri-api";
+import { createLogger } from "@/lib/logger";
+
+const logger = createLogger("LabelAPI");
+
+// ============================================================================
+// Schemas
+// ============================================================================
+
+/**
+ * Label field schema (matches backend LabelField)
+ */
+export const LabelFieldSchema = z.enum([
+  "name",
+  "concentration",
+  "price",
+  "barcode",
+]);
+export type LabelField = z.infer<typeof LabelFieldSchema>;
+
+/**
+ * Label template schema (matches backend LabelTemplate)
+ *
+ * Fields print top to bottom; the barcode takes the height left over.
+ */
+export const LabelTemplateSchema = z.object({
+  name: z.string().min(1),
+  width_mm: z.number().min(10).max(200),
+  height_mm: z.number().min(10).max(200),
+  fields: z.array(LabelFieldSchema).min(1),
+  font_size: z.number().min(4).max(24).optional(),
+});
+export type LabelTemplate = z.infer<typeof LabelTemplateSchema>;
+
+/**
+ * Label selection schema (matches backend LabelSelection)
+ */
+export const LabelSelectionSchema = z.discriminatedUnion("type", [
+  z.object({ type: z.literal("items"), query: z.array(z.string().uuid()) }),
+  // Items whose price changed at or after this time (ISO 8601)
+  z.object({ type: z.literal("price_changed_since"), query: z.string() }),
+]);
+export type LabelSelection = z.infer<typeof LabelSelectionSchema>;
+
+/**
+ * Label options schema (matches backend LabelOptions)
+ */
+export const LabelOptionsSchema = z.object({
+  template: z.string(),
+  selection: LabelSelectionSchema,
+  copies: z.number().int().positive().max(100).optional(), // MAX_LABEL_COPIES
+});
+export type LabelOptions = z.infer<typeof LabelOptionsSchema>;
+
+/**
+ * Print target schema: a system printer (default when no name) or a file
+ */
+export const PrintTargetSchema = z.discriminatedUnion("type", [
+  z.object({ type: z.literal("printer"), name: z.string().nullable() }),
+  z.object({ type: z.literal("file"), path: z.string() }),
+]);
+export type PrintTarget = z.infer<typeof PrintTargetSchema>;
+
+/**
+ * Rendered labels schema (matches backend RenderedLabels)
+ */
+export const RenderedLabelsSchema = z.object({
+  path: z.string(),
+  labels: z.number(),
+  size: z.number(),
+});
+export type RenderedLabels = z.infer<typeof RenderedLabelsSchema>;
+
+/**
+ * Printer schema (matches backend PrinterInfo)
+ */
+export const PrinterInfoSchema = z.object({
+  name: z.string(),
+  driver_name: z.string(),
+  is_default: z.boolean(),
+});
+export type PrinterInfo = z.infer<typeof PrinterInfoSchema>;
+
+// ============================================================================
+// Template Operations
+// ============================================================================
+
+/**
+ * List built-in and custom label templates
+ */
+export async function listLabelTemplates(): Promise<LabelTemplate[]> {
+  logger.info("Listing label templates");
+  return invokeCommand("list_label_templates", z.array(LabelTemplateSchema));
+}
+
+/**
+ * Create or replace a custom label template
+ */
+export async function saveLabelTemplate(
+  template: LabelTemplate,
+): Promise<LabelTemplate> {
+  logger.info("Saving label template:", template.name);
+  return invokeCommand("save_label_template", LabelTemplateSchema, {
+    params: { data: template },
+  });
+}
+
+/**
+ * Delete a custom label template
+ */
+export async function deleteLabelTemplate(name: string): Promise<void> {
+  logger.info("Deleting label template:", name);
+  return invokeCommand("delete_label_template", z.void(), { name });
+}
+
+// ============================================================================
+// Printing Operations
+// ============================================================================
+
+/**
+ * Print labels to a system printer or save them as a PDF file
+ */
+export async function printLabels(
+  options: LabelOptions,
+  target: PrintTarget,
+): Promise<RenderedLabels> {
+  logger.info("Printing labels with template", options.template);
+  return invokeCommand("print_labels", RenderedLabelsSchema, {
+    params: { data: options },
+    target,
+  });
+}
+
+/**
+ * List the printers installed on this machine
+ */
+export async function listPrinters(): Promise<PrinterInfo[]> {
+  return invokeCommand("list_printers", z.array(PrinterInfoSchema));
+}
+
+// ============================================================================
+// Exports
+// ============================================================================
+
+export const labelApi = {
+  listTemplates: listLabelTemplates,
+  saveTemplate: saveLabelTemplate,
+  deleteTemplate: deleteLabelTemplate,
+  print: printLabels,
+  listPrinters,
+} as const;
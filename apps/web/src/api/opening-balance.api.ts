@@ -148,6 +148,43 @@ export type OpeningBalanceStatistics = z.infer<
   typeof OpeningBalanceStatisticsSchema
 >;
 
+/**
+ * Import options schema (matches backend OpeningBalanceImportOptions)
+ */
+export const OpeningBalanceImportOptionsSchema = z.object({
+  file_name: z.string().min(1),
+  entry_date: z.string().optional(),
+  dry_run: z.boolean().default(false),
+  allow_override: z.boolean().default(false),
+});
+export type OpeningBalanceImportOptions = z.input<
+  typeof OpeningBalanceImportOptionsSchema
+>;
+
+/**
+ * Import row error schema (matches backend ImportRowError)
+ */
+export const ImportRowErrorSchema = z.object({
+  row_number: z.number(),
+  item_code: z.string(),
+  error_message: z.string(),
+});
+export type ImportRowError = z.infer<typeof ImportRowErrorSchema>;
+
+/**
+ * Import result schema (matches backend ImportResult)
+ */
+export const ImportResultSchema = z.object({
+  success: z.boolean(),
+  dry_run: z.boolean(),
+  total_rows: z.number(),
+  success_count: z.number(),
+  error_count: z.number(),
+  import_batch_id: z.string().uuid().nullable(),
+  errors: z.array(ImportRowErrorSchema),
+});
+export type ImportResult = z.infer<typeof ImportResultSchema>;
+
 /**
  * Pagination result schema
  */
@@ -188,7 +225,7 @@ export async function createOpeningBalance(
 ): Promise<MutationResult> {
   logger.info("Creating opening balance for item:", data.inventory_item_id);
   return invokeCommand("create_opening_balance", MutationResultSchema, {
-    params: { data: [data, entered_by] },
+    params: { data: { ...data, entered_by } },
   });
 }
 
@@ -299,7 +336,7 @@ export async function createOpeningBalanceAdjustment(
     "create_opening_balance_adjustment",
     MutationResultSchema,
     {
-      params: { data: [data, entered_by] },
+      params: { data: { ...data, entered_by } },
     },
   );
 }
@@ -354,6 +391,41 @@ export async function getOpeningBalancesByBatch(
   );
 }
 
+// ============================================================================
+// Import Operations
+// ============================================================================
+
+/**
+ * Import opening balances from a CSV or XLSX file
+ *
+ * With `dry_run` the file is only validated and the row report returned.
+ */
+export async function importOpeningBalances(
+  file: File,
+  options: Omit<OpeningBalanceImportOptions, "file_name">,
+  entered_by: string,
+): Promise<ImportResult> {
+  logger.info("Importing opening balances from file:", file.name);
+  const content = Array.from(new Uint8Array(await file.arrayBuffer()));
+  return invokeCommand("import_opening_balances", ImportResultSchema, {
+    params: { data: { ...options, file_name: file.name, entered_by } },
+    content,
+  });
+}
+
+/**
+ * Revert all entries of an import batch
+ */
+export async function revertOpeningBalanceImport(
+  batch_id: string,
+  reverted_by: string,
+): Promise<number> {
+  logger.info("Reverting opening balance import batch:", batch_id);
+  return invokeCommand("revert_opening_balance_import", z.number(), {
+    params: { id: batch_id, data: reverted_by },
+  });
+}
+
 // ============================================================================
 // Statistics Operations
 // ============================================================================
@@ -394,6 +466,10 @@ export const openingBalanceApi = {
   getUnverified: getUnverifiedOpeningBalances,
   getByBatch: getOpeningBalancesByBatch,
 
+  // Import
+  import: importOpeningBalances,
+  revertImport: revertOpeningBalanceImport,
+
   // Statistics
   getStatistics: getOpeningBalanceStatistics,
 } as const;
@@ -0,0 +1,43 @@
+/**
+ * Profile API
+ *
+ * Provides type-safe access to the configuration profile the desktop app was
+ * started with (`meditrack --profile <name>`).
+ *
+ * @module api/profile
+ */
+
+import { z } from "zod";
+import { invokeCommand } from "@/lib/tauri-api";
+
+// ============================================================================
+// Schemas
+// ============================================================================
+
+/**
+ * Profile info schema (matches backend ProfileInfo)
+ */
+export const ProfileInfoSchema = z.object({
+  name: z.string(),
+  training: z.boolean(),
+});
+export type ProfileInfo = z.infer<typeof ProfileInfoSchema>;
+
+// ============================================================================
+// Profile Operations
+// ============================================================================
+
+/**
+ * Get the configuration profile the app was started with
+ */
+export async function getProfile(): Promise<ProfileInfo> {
+  return invokeCommand("get_profile", ProfileInfoSchema);
+}
+
+// ============================================================================
+// Exports
+// ============================================================================
+
+export const profileApi = {
+  get: getProfile,
+} as const;
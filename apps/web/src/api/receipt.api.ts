@@ -0,0 +1,105 @@
+/**
+ * Receipt API
+ *
+ * Provides type-safe access to the receipt Tauri commands: building and
+ * printing ESC/POS receipts for sales and special order deposits.
+ *
+ * @module api/receipt
+ */
+
+import { z } from "zod";
+import { invokeCommand } from "@/lib/tauri-api";
+import { createLogger } from "@/lib/logger";
+import type { PrintTarget } from "@/api/label.api";
+
+const logger = createLogger("ReceiptAPI");
+
+// ============================================================================
+// Schemas
+// ============================================================================
+
+/**
+ * Receipt source schema (matches backend ReceiptSource)
+ */
+export const ReceiptSourceSchema = z.discriminatedUnion("type", [
+  // Sale reference id, or the id of its only stock movement
+  z.object({ type: z.literal("sale"), id: z.string().uuid() }),
+  z.object({ type: z.literal("special_order_deposit"), id: z.string().uuid() }),
+]);
+export type ReceiptSource = z.infer<typeof ReceiptSourceSchema>;
+
+/**
+ * Receipt paper width schema (matches backend ReceiptPaper)
+ */
+export const ReceiptPaperSchema = z.enum(["mm58", "mm80"]);
+export type ReceiptPaper = z.infer<typeof ReceiptPaperSchema>;
+
+/**
+ * Receipt options schema (matches backend ReceiptOptions)
+ */
+export const ReceiptOptionsSchema = z.object({
+  source: ReceiptSourceSchema,
+  paper: ReceiptPaperSchema.optional(),
+  reprint: z.boolean().optional(),
+});
+export type ReceiptOptions = z.infer<typeof ReceiptOptionsSchema>;
+
+/**
+ * Receipt line schema (matches backend ReceiptLine; amounts are decimals)
+ */
+export const ReceiptLineSchema = z.object({
+  description: z.string(),
+  quantity: z.number(),
+  unit_price: z.string(),
+  total: z.string(),
+});
+export type ReceiptLine = z.infer<typeof ReceiptLineSchema>;
+
+/**
+ * Receipt schema (matches backend Receipt)
+ */
+export const ReceiptSchema = z.object({
+  source: ReceiptSourceSchema,
+  number: z.string(),
+  issued_at: z.string(),
+  customer: z.string().nullable(),
+  lines: z.array(ReceiptLineSchema),
+  total: z.string(),
+  paid: z.string().nullable(),
+  balance_due: z.string().nullable(),
+});
+export type Receipt = z.infer<typeof ReceiptSchema>;
+
+// ============================================================================
+// Receipt Operations
+// ============================================================================
+
+/**
+ * Build the receipt for a sale or special order deposit without printing it
+ */
+export async function getReceipt(source: ReceiptSource): Promise<Receipt> {
+  return invokeCommand("get_receipt", ReceiptSchema, { source });
+}
+
+/**
+ * Print a receipt to a thermal printer or save its ESC/POS commands to a file
+ */
+export async function printReceipt(
+  options: ReceiptOptions,
+  target: PrintTarget,
+): Promise<Receipt> {
+  logger.info("Printing receipt for", options.source.type, options.source.id);
+  return invokeCommand("print_receipt", ReceiptSchema, {
+    params: { data: options },
+    target,
+  });
+}
+
+// ============================================================================
+// Exports
+// ============================================================================
+
+export const receiptApi = {
+  get: getReceipt,
+  print: printReceipt,
+} as const;
@@ -0,0 +1,121 @@
+/**
+ * Report API
+ *
+ * Provides type-safe access to the printable report Tauri command.
+ * Renders report datasets as paginated HTML or PDF with the pharmacy header,
+ * in English or Arabic (right-to-left).
+ *
+ * @module api/report
+ */
+
+import { z } from "zod";
+import { invokeCommand } from "@/lib/tauri-api";
+import { createLogger } from "@/lib/logger";
+import { ValuationQuerySchema } from "./inventory.api";
+import { MultilingualDescriptionSchema } from "./settings.api";
+
+const logger = createLogger("ReportAPI");
+
+// ============================================================================
+// Schemas
+// ============================================================================
+
+/**
+ * Report language schema (matches backend ReportLanguage)
+ */
+export const ReportLanguageSchema = z.enum(["en", "ar"]);
+export type ReportLanguage = z.infer<typeof ReportLanguageSchema>;
+
+/**
+ * Report format schema (matches backend ReportFormat)
+ */
+export const ReportFormatSchema = z.enum(["html", "pdf"]);
+export type ReportFormat = z.infer<typeof ReportFormatSchema>;
+
+/**
+ * Caller-assembled report dataset (matches backend ReportDataset)
+ */
+export const ReportDatasetSchema = z.object({
+  title: MultilingualDescriptionSchema,
+  generated_at: z.string(),
+  summary: z
+    .array(z.object({ label: MultilingualDescriptionSchema, value: z.string() }))
+    .optional(),
+  tables: z
+    .array(
+      z.object({
+        title: MultilingualDescriptionSchema.nullable(),
+        columns: z.array(
+          z.object({
+            label: MultilingualDescriptionSchema,
+            align: z.enum(["start", "end"]).optional(),
+          }),
+        ),
+        rows: z.array(z.array(z.string())),
+      }),
+    )
+    .optional(),
+});
+export type ReportDataset = z.infer<typeof ReportDatasetSchema>;
+
+/**
+ * Report kind schema (matches backend ReportKind)
+ */
+export const ReportKindSchema = z.discriminatedUnion("type", [
+  z.object({ type: z.literal("inventory_statistics") }),
+  z.object({ type: z.literal("low_stock") }),
+  z.object({ type: z.literal("opening_balance_statistics") }),
+  z.object({ type: z.literal("valuation"), query: ValuationQuerySchema }),
+  z.object({ type: z.literal("dataset"), query: ReportDatasetSchema }),
+]);
+export type ReportKind = z.infer<typeof ReportKindSchema>;
+
+/**
+ * Report options schema (matches backend ReportOptions)
+ */
+export const ReportOptionsSchema = z.object({
+  report: ReportKindSchema,
+  format: ReportFormatSchema,
+  language: ReportLanguageSchema.optional(),
+});
+export type ReportOptions = z.infer<typeof ReportOptionsSchema>;
+
+/**
+ * Rendered report schema (matches backend RenderedReport)
+ */
+export const RenderedReportSchema = z.object({
+  path: z.string(),
+  format: ReportFormatSchema,
+  pages: z.number(),
+  size: z.number(),
+});
+export type RenderedReport = z.infer<typeof RenderedReportSchema>;
+
+// ============================================================================
+// Report Operations
+// ============================================================================
+
+/**
+ * Render a report and save it to `destination`
+ *
+ * PDF output needs a font with Arabic glyphs; set `reports.pdf_font_path`
+ * when none of the common system fonts is installed.
+ */
+export async function saveReport(
+  options: ReportOptions,
+  destination: string,
+): Promise<RenderedReport> {
+  logger.info("Saving report", options.report.type, "as", options.format);
+  return invokeCommand("save_report", RenderedReportSchema, {
+    params: { data: options },
+    destination,
+  });
+}
+
+// ============================================================================
+// Exports
+// ============================================================================
+
+export const reportApi = {
+  save: saveReport,
+} as const;
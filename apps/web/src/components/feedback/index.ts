@@ -2,6 +2,7 @@ export { ConfirmationDialog } from "./confirmation-dialog";
 export { EmptyState } from "./empty-state";
 export { SplashScreen } from "./splash-screen";
 export { UpdateDialog } from "./update-dialog";
+export { TrainingBanner } from "./training-banner";
 export { GenericDialog } from "./generic-dialog";
 export type { GenericDialogProps } from "./generic-dialog";
 export { FormDialog } from "./form-dialog";
@@ -0,0 +1,26 @@
+import { GraduationCap } from "lucide-react";
+import { useTranslation } from "@meditrack/i18n";
+import { useProfile } from "@/hooks/use-profile";
+
+/**
+ * Banner shown on every screen while the app runs a training profile, so
+ * nobody mistakes the training database for the live one
+ */
+export function TrainingBanner() {
+  const { t } = useTranslation("common");
+  const { data: profile } = useProfile();
+
+  if (!profile?.training) {
+    return null;
+  }
+
+  return (
+    <div
+      role="status"
+      className="flex items-center justify-center gap-2 bg-amber-500 px-4 py-1.5 text-sm font-semibold text-amber-950"
+    >
+      <GraduationCap className="h-4 w-4" />
+      <span>{t("profile.trainingBanner", { name: profile.name })}</span>
+    </div>
+  );
+}
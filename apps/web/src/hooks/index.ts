@@ -129,6 +129,10 @@ export { useNotifications } from "./use-notifications";
 // Hooks للأرشفة التلقائية
 export { useAutoArchive, useArchivableOrdersStats } from "./use-auto-archive";
 
+// Hooks لأحداث النظام (Domain Events)
+export { useDomainEvents, DOMAIN_EVENT } from "./use-domain-events";
+export type { DomainEvent } from "./use-domain-events";
+
 // Hooks للمخزون (Inventory)
 export {
   // Query Hooks
@@ -153,6 +157,7 @@ export {
   useRestoreInventoryItem,
   useUpdateInventoryStock,
   useAdjustInventoryStock,
+  useScanInventoryBarcode,
   // Utility Hooks
   usePrefetchInventoryItem,
   useInvalidateInventory,
@@ -203,3 +208,41 @@ export {
   // Query Keys
   openingBalanceKeys,
 } from "./use-opening-balances";
+
+// Hooks لتصدير البيانات (Data Export)
+export { useExportData } from "./use-export";
+
+// Hooks للنسخ الاحتياطي (Database Backup)
+export {
+  useBackups,
+  useCreateBackup,
+  useRestoreBackup,
+  useRestoreBackupFile,
+  backupKeys,
+} from "./use-backup";
+
+// Hooks لتحليلات المبيعات والاستهلاك (Sales & Consumption Analytics)
+export {
+  useItemAnalytics,
+  useApplyMinStockRecommendations,
+  analyticsKeys,
+} from "./use-analytics";
+
+// Hooks للتقارير المطبوعة (Printable Reports)
+export { useSaveReport } from "./use-report";
+
+// Hooks لطباعة الملصقات (Shelf & Barcode Labels)
+export {
+  useLabelTemplates,
+  usePrinters,
+  useSaveLabelTemplate,
+  useDeleteLabelTemplate,
+  usePrintLabels,
+  labelKeys,
+} from "./use-labels";
+
+// Hooks لطباعة الإيصالات (Receipts)
+export { useReceipt, usePrintReceipt, receiptKeys } from "./use-receipts";
+
+// Hooks لملف الإعدادات (Configuration Profile)
+export { useProfile, profileKeys } from "./use-profile";
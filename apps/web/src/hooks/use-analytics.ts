@@ -0,0 +1,80 @@
+/**
+ * Analytics Hooks
+ *
+ * React Query hooks for sales and consumption analytics.
+ *
+ * @module hooks/use-analytics
+ */
+
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { toast } from "sonner";
+import { useTranslation } from "@meditrack/i18n";
+import { analyticsApi } from "@/api/analytics.api";
+import type { ItemAnalyticsQuery } from "@/api/analytics.api";
+import type { PaginationParams } from "@/lib/tauri-api";
+import { createLogger } from "@/lib/logger";
+import { inventoryKeys } from "./use-inventory";
+
+const logger = createLogger("AnalyticsHooks");
+
+// ============================================================================
+// Query Keys
+// ============================================================================
+
+export const analyticsKeys = {
+  all: ["analytics"] as const,
+  items: (query?: ItemAnalyticsQuery, pagination?: PaginationParams) =>
+    [...analyticsKeys.all, "items", query, pagination] as const,
+};
+
+// ============================================================================
+// Query Hooks
+// ============================================================================
+
+/**
+ * Get per-item velocity, ABC class, dead stock and days of cover
+ */
+export function useItemAnalytics(
+  query?: ItemAnalyticsQuery,
+  pagination?: PaginationParams,
+) {
+  return useQuery({
+    queryKey: analyticsKeys.items(query, pagination),
+    queryFn: () => analyticsApi.getItems(query, pagination),
+    staleTime: 1000 * 60 * 5, // 5 minutes
+  });
+}
+
+// ============================================================================
+// Mutation Hooks
+// ============================================================================
+
+/**
+ * Apply the recommended minimum stock levels to the selected items
+ */
+export function useApplyMinStockRecommendations() {
+  const queryClient = useQueryClient();
+  const { t } = useTranslation("inventory");
+
+  return useMutation({
+    mutationFn: ({
+      query,
+      itemIds,
+      updated_by,
+    }: {
+      query: ItemAnalyticsQuery;
+      itemIds: string[];
+      updated_by: string;
+    }) => analyticsApi.applyRecommendations(query, itemIds, updated_by),
+    onSuccess: (updated) => {
+      queryClient.invalidateQueries({ queryKey: analyticsKeys.all });
+      queryClient.invalidateQueries({ queryKey: inventoryKeys.all });
+      toast.success(t("messages.minStockLevelsUpdated", { count: updated }));
+      logger.info(`Updated ${updated} minimum stock levels`);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to apply recommendations: ${error.message}`);
+      logger.error("Failed to apply recommendations:", error);
+    },
+  });
+}
@@ -0,0 +1,115 @@
+/**
+ * Backup Hooks
+ *
+ * React Query hooks for database backups and restores.
+ *
+ * @module hooks/use-backup
+ */
+
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { toast } from "sonner";
+import { useTranslation } from "@meditrack/i18n";
+import { backupApi } from "@/api/backup.api";
+import { createLogger } from "@/lib/logger";
+
+const logger = createLogger("BackupHooks");
+
+// ============================================================================
+// Query Keys
+// ============================================================================
+
+export const backupKeys = {
+  all: ["backups"] as const,
+  list: () => [...backupKeys.all, "list"] as const,
+};
+
+// ============================================================================
+// Query Hooks
+// ============================================================================
+
+/**
+ * Get the backups in the backup directory, newest first
+ */
+export function useBackups() {
+  return useQuery({
+    queryKey: backupKeys.list(),
+    queryFn: () => backupApi.list(),
+  });
+}
+
+// ============================================================================
+// Mutation Hooks
+// ============================================================================
+
+/**
+ * Back up the database
+ */
+export function useCreateBackup() {
+  const queryClient = useQueryClient();
+  const { t } = useTranslation("settings");
+
+  return useMutation({
+    mutationFn: ({
+      encrypt,
+      password,
+    }: {
+      encrypt: boolean;
+      password?: string;
+    }) => backupApi.create(encrypt, password),
+    onSuccess: (file) => {
+      queryClient.invalidateQueries({ queryKey: backupKeys.list() });
+      toast.success(t("messages.backupCreated"));
+      logger.info("Backup created:", file.path);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to create backup: ${error.message}`);
+      logger.error("Failed to create backup:", error);
+    },
+  });
+}
+
+/**
+ * Restore an uploaded backup file into the (empty) database
+ */
+export function useRestoreBackup() {
+  const queryClient = useQueryClient();
+  const { t } = useTranslation("settings");
+
+  return useMutation({
+    mutationFn: ({ file, password }: { file: File; password?: string }) =>
+      backupApi.restore(file, password),
+    onSuccess: (manifest) => {
+      // Every cached query is stale after a restore
+      queryClient.invalidateQueries();
+      toast.success(t("messages.backupRestored"));
+      logger.info("Backup restored:", manifest.created_at);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to restore backup: ${error.message}`);
+      logger.error("Failed to restore backup:", error);
+    },
+  });
+}
+
+/**
+ * Restore a backup from the backup directory into the (empty) database
+ */
+export function useRestoreBackupFile() {
+  const queryClient = useQueryClient();
+  const { t } = useTranslation("settings");
+
+  return useMutation({
+    mutationFn: ({ path, password }: { path: string; password?: string }) =>
+      backupApi.restoreFile(path, password),
+    onSuccess: (manifest) => {
+      // Every cached query is stale after a restore
+      queryClient.invalidateQueries();
+      toast.success(t("messages.backupRestored"));
+      logger.info("Backup restored:", manifest.created_at);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to restore backup: ${error.message}`);
+      logger.error("Failed to restore backup:", error);
+    },
+  });
+}
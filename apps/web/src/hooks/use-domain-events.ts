@@ -0,0 +1,139 @@
+/**
+ * Domain Events Hook
+ *
+ * Subscribes to domain events pushed by the Tauri backend and keeps cached
+ * data fresh without polling. Stock changes refresh inventory queries, setting
+ * changes refresh the settings collection, and revoked sessions log the
+ * current user out.
+ *
+ * @module hooks/use-domain-events
+ */
+
+import { useEffect } from "react";
+import { toast } from "sonner";
+import { queryClient } from "@/lib/query-client";
+import { createLogger } from "@/lib/logger";
+import { isTauriEnvironment } from "@/lib/tauri-api";
+import { useAuth } from "./use-auth";
+import { inventoryKeys } from "./use-inventory";
+import { openingBalanceKeys } from "./use-opening-balances";
+import { orderKeys } from "./use-orders-db";
+import { settingKeys } from "./use-settings-db";
+
+const logger = createLogger("DomainEvents");
+
+/**
+ * Name of the Tauri event carrying all domain events
+ */
+export const DOMAIN_EVENT = "domain-event";
+
+// ============================================================================
+// Event Types
+// ============================================================================
+
+export type DomainEvent =
+  | {
+      type: "stock_changed";
+      payload: {
+        inventory_item_id: string;
+        quantity_before: number;
+        quantity_after: number;
+        min_stock_level: number;
+      };
+    }
+  | {
+      type: "low_stock_reached";
+      payload: {
+        inventory_item_id: string;
+        stock_quantity: number;
+        min_stock_level: number;
+      };
+    }
+  | {
+      type: "special_order_status_changed";
+      payload: {
+        special_order_id: string;
+        old_status: string;
+        new_status: string;
+      };
+    }
+  | {
+      type: "session_revoked";
+      payload: { user_id: string; session_id: string | null };
+    }
+  | {
+      type: "setting_changed";
+      payload: { key: string | null; category: string | null };
+    };
+
+// ============================================================================
+// Hook
+// ============================================================================
+
+/**
+ * Listen for backend domain events for the lifetime of the component
+ */
+export function useDomainEvents() {
+  const { user, logout } = useAuth();
+  const userId = user?.id;
+
+  useEffect(() => {
+    if (!isTauriEnvironment()) {
+      return;
+    }
+
+    let unlisten: (() => void) | undefined;
+    let disposed = false;
+
+    const handleEvent = (event: DomainEvent) => {
+      logger.debug("Received domain event:", event.type);
+
+      switch (event.type) {
+        case "stock_changed":
+          queryClient.invalidateQueries({ queryKey: inventoryKeys.all });
+          queryClient.invalidateQueries({ queryKey: openingBalanceKeys.all });
+          break;
+        case "low_stock_reached":
+          queryClient.invalidateQueries({ queryKey: inventoryKeys.lowStock() });
+          toast.warning(
+            `Stock is low (${event.payload.stock_quantity} / ${event.payload.min_stock_level})`,
+          );
+          break;
+        case "special_order_status_changed":
+          queryClient.invalidateQueries({ queryKey: orderKeys.all });
+          break;
+        case "session_revoked":
+          if (userId && event.payload.user_id === userId) {
+            logger.info("Current session was revoked, logging out");
+            logout();
+          }
+          break;
+        case "setting_changed":
+          queryClient.invalidateQueries({ queryKey: settingKeys.all });
+          break;
+      }
+    };
+
+    import("@tauri-apps/api/event")
+      .then(({ listen }) =>
+        listen<DomainEvent>(DOMAIN_EVENT, ({ payload }) =>
+          handleEvent(payload),
+        ),
+      )
+      .then((stop) => {
+        if (disposed) {
+          stop();
+        } else {
+          unlisten = stop;
+        }
+      })
+      .catch((error) => {
+        logger.warn("Failed to subscribe to domain events:", error);
+      });
+
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [userId, logout]);
+}
@@ -0,0 +1,40 @@
+/**
+ * Export Hooks
+ *
+ * React Query hooks for exporting catalogue, stock and history data.
+ *
+ * @module hooks/use-export
+ */
+
+import { useMutation } from "@tanstack/react-query";
+import { toast } from "sonner";
+import { useTranslation } from "@meditrack/i18n";
+import { exportApi } from "@/api/export.api";
+import { createLogger } from "@/lib/logger";
+import type { ExportOptions } from "@/api/export.api";
+
+const logger = createLogger("ExportHooks");
+
+// ============================================================================
+// Mutation Hooks
+// ============================================================================
+
+/**
+ * Export data as CSV files or a JSON archive
+ */
+export function useExportData() {
+  const { t } = useTranslation("inventory");
+
+  return useMutation({
+    mutationFn: ({ options }: { options: ExportOptions }) =>
+      exportApi.export(options),
+    onSuccess: (files) => {
+      toast.success(t("messages.exported"));
+      logger.info("Data exported:", files.map((file) => file.path));
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to export data: ${error.message}`);
+      logger.error("Failed to export data:", error);
+    },
+  });
+}
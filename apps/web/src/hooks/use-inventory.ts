@@ -18,6 +18,8 @@ import type {
   UpdateInventoryItem,
   UpdateInventoryStock,
   AdjustStock,
+  CatalogueImportOptions,
+  ValuationQuery,
 } from "@/api/inventory.api";
 
 const logger = createLogger("InventoryHooks");
@@ -37,6 +39,8 @@ export const inventoryKeys = {
   search: (searchTerm: string) =>
     [...inventoryKeys.all, "search", searchTerm] as const,
   statistics: () => [...inventoryKeys.all, "statistics"] as const,
+  valuation: (query: ValuationQuery) =>
+    [...inventoryKeys.all, "valuation", query] as const,
   lowStock: () => [...inventoryKeys.all, "lowStock"] as const,
   outOfStock: () => [...inventoryKeys.all, "outOfStock"] as const,
   priceHistory: (id: InventoryItemId, limit?: number) =>
@@ -122,6 +126,21 @@ export function useInventoryStatistics() {
   });
 }
 
+/**
+ * Get the stock valuation at the end of a date
+ */
+export function useInventoryValuation(
+  query: ValuationQuery,
+  options?: { enabled?: boolean },
+) {
+  return useQuery({
+    queryKey: inventoryKeys.valuation(query),
+    queryFn: () => inventoryApi.getValuation(query),
+    enabled: (options?.enabled ?? true) && query.as_of.length > 0,
+    staleTime: 1000 * 60 * 2, // 2 minutes
+  });
+}
+
 /**
  * Get low stock items
  */
@@ -373,6 +392,25 @@ export function useUpdateInventoryStock() {
   });
 }
 
+/**
+ * Resolve scanner input to an item, with lot data from GS1 codes
+ *
+ * A mutation rather than a query: every scan is read afresh, and GS1 codes
+ * differ pack to pack by serial.
+ */
+export function useScanInventoryBarcode() {
+  return useMutation({
+    mutationFn: (input: string) => inventoryApi.scanBarcode(input),
+    onSuccess: (scanned) => {
+      logger.info("Scanned item:", scanned.item.name, scanned.batch_number);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to read barcode: ${error.message}`);
+      logger.error("Failed to read barcode:", error);
+    },
+  });
+}
+
 /**
  * Adjust stock (add or subtract)
  */
@@ -399,6 +437,48 @@ export function useAdjustInventoryStock() {
   });
 }
 
+/**
+ * Import catalogue items from a CSV or XLSX file
+ *
+ * Run with `dry_run: true` first to preview; `error_report` on the result
+ * can be saved with `downloadTextFile` for the user to fix and re-import.
+ */
+export function useImportInventoryCatalogue() {
+  const queryClient = useQueryClient();
+  const { t } = useTranslation("inventory");
+
+  return useMutation({
+    mutationFn: ({
+      file,
+      options,
+      created_by,
+    }: {
+      file: File;
+      options: Omit<CatalogueImportOptions, "file_name">;
+      created_by?: string;
+    }) => inventoryApi.importCatalogue(file, options, created_by),
+    onSuccess: (result) => {
+      if (result.dry_run || result.created_count === 0) {
+        logger.info(
+          `Catalogue validated: ${result.created_count} valid, ${result.error_count} invalid`,
+        );
+        return;
+      }
+      queryClient.invalidateQueries({ queryKey: inventoryKeys.all });
+      queryClient.invalidateQueries({ queryKey: ["manufacturers"] });
+      queryClient.invalidateQueries({ queryKey: ["medicine-forms"] });
+      toast.success(
+        t("messages.catalogueImported", { count: result.created_count }),
+      );
+      logger.info("Catalogue imported:", result.created_count);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to import catalogue: ${error.message}`);
+      logger.error("Failed to import catalogue:", error);
+    },
+  });
+}
+
 // ============================================================================
 // Utility Hooks
 // ============================================================================
@@ -0,0 +1,127 @@
+/**
+ * Label Hooks
+ *
+ * React Query hooks for shelf and barcode labels and printer selection.
+ *
+ * @module hooks/use-labels
+ */
+
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { toast } from "sonner";
+import { useTranslation } from "@meditrack/i18n";
+import { labelApi } from "@/api/label.api";
+import type {
+  LabelOptions,
+  LabelTemplate,
+  PrintTarget,
+} from "@/api/label.api";
+import { createLogger } from "@/lib/logger";
+
+const logger = createLogger("LabelHooks");
+
+// ============================================================================
+// Query Keys
+// ============================================================================
+
+export const labelKeys = {
+  all: ["labels"] as const,
+  templates: () => [...labelKeys.all, "templates"] as const,
+  printers: () => [...labelKeys.all, "printers"] as const,
+};
+
+// ============================================================================
+// Query Hooks
+// ============================================================================
+
+/**
+ * Get built-in and custom label templates
+ */
+export function useLabelTemplates() {
+  return useQuery({
+    queryKey: labelKeys.templates(),
+    queryFn: () => labelApi.listTemplates(),
+    staleTime: 1000 * 60 * 5, // 5 minutes
+  });
+}
+
+/**
+ * Get the printers installed on this machine
+ */
+export function usePrinters() {
+  return useQuery({
+    queryKey: labelKeys.printers(),
+    queryFn: () => labelApi.listPrinters(),
+    staleTime: 1000 * 60, // 1 minute
+  });
+}
+
+// ============================================================================
+// Mutation Hooks
+// ============================================================================
+
+/**
+ * Create or replace a custom label template
+ */
+export function useSaveLabelTemplate() {
+  const queryClient = useQueryClient();
+  const { t } = useTranslation("inventory");
+
+  return useMutation({
+    mutationFn: (template: LabelTemplate) => labelApi.saveTemplate(template),
+    onSuccess: (template) => {
+      queryClient.invalidateQueries({ queryKey: labelKeys.templates() });
+      toast.success(t("messages.labelTemplateSaved"));
+      logger.info("Label template saved:", template.name);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to save label template: ${error.message}`);
+      logger.error("Failed to save label template:", error);
+    },
+  });
+}
+
+/**
+ * Delete a custom label template
+ */
+export function useDeleteLabelTemplate() {
+  const queryClient = useQueryClient();
+  const { t } = useTranslation("inventory");
+
+  return useMutation({
+    mutationFn: (name: string) => labelApi.deleteTemplate(name),
+    onSuccess: (_, name) => {
+      queryClient.invalidateQueries({ queryKey: labelKeys.templates() });
+      toast.success(t("messages.labelTemplateDeleted"));
+      logger.info("Label template deleted:", name);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to delete label template: ${error.message}`);
+      logger.error("Failed to delete label template:", error);
+    },
+  });
+}
+
+/**
+ * Print labels to a system printer or save them as a PDF file
+ */
+export function usePrintLabels() {
+  const { t } = useTranslation("inventory");
+
+  return useMutation({
+    mutationFn: ({
+      options,
+      target,
+    }: {
+      options: LabelOptions;
+      target: PrintTarget;
+    }) => labelApi.print(options, target),
+    onSuccess: (result) => {
+      toast.success(t("messages.labelsPrinted", { count: result.labels }));
+      logger.info(`Printed ${result.labels} labels`);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to print labels: ${error.message}`);
+      logger.error("Failed to print labels:", error);
+    },
+  });
+}
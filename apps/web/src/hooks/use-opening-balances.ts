@@ -17,6 +17,7 @@ import type {
   CreateOpeningBalance,
   UpdateOpeningBalance,
   CreateAdjustment,
+  OpeningBalanceImportOptions,
   OpeningBalanceQuery,
 } from "@/api/opening-balance.api";
 import type { PaginationParams } from "@/lib/tauri-api";
@@ -354,6 +355,78 @@ export function useCreateOpeningBalanceAdjustment() {
   });
 }
 
+// ============================================================================
+// Import Mutation Hooks
+// ============================================================================
+
+/**
+ * Import opening balances from a CSV or XLSX file
+ *
+ * Run with `dry_run: true` first to show the row report before committing.
+ */
+export function useImportOpeningBalances() {
+  const queryClient = useQueryClient();
+  const { t } = useTranslation("inventory");
+
+  return useMutation({
+    mutationFn: ({
+      file,
+      options,
+      entered_by,
+    }: {
+      file: File;
+      options: Omit<OpeningBalanceImportOptions, "file_name">;
+      entered_by: string;
+    }) => openingBalanceApi.import(file, options, entered_by),
+    onSuccess: (result) => {
+      if (result.dry_run || !result.import_batch_id) {
+        logger.info(
+          `Import validated: ${result.success_count} valid, ${result.error_count} invalid`,
+        );
+        return;
+      }
+      queryClient.invalidateQueries({ queryKey: openingBalanceKeys.all });
+      // Also invalidate inventory queries since stock is affected
+      queryClient.invalidateQueries({ queryKey: ["inventory"] });
+      toast.success(t("messages.openingBalancesImported"));
+      logger.info("Opening balances imported:", result.import_batch_id);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to import opening balances: ${error.message}`);
+      logger.error("Failed to import opening balances:", error);
+    },
+  });
+}
+
+/**
+ * Revert all entries of an opening balance import batch
+ */
+export function useRevertOpeningBalanceImport() {
+  const queryClient = useQueryClient();
+  const { t } = useTranslation("inventory");
+
+  return useMutation({
+    mutationFn: ({
+      batch_id,
+      reverted_by,
+    }: {
+      batch_id: string;
+      reverted_by: string;
+    }) => openingBalanceApi.revertImport(batch_id, reverted_by),
+    onSuccess: (count, { batch_id }) => {
+      queryClient.invalidateQueries({ queryKey: openingBalanceKeys.all });
+      // Also invalidate inventory queries since stock is affected
+      queryClient.invalidateQueries({ queryKey: ["inventory"] });
+      toast.success(t("messages.openingBalanceImportReverted"));
+      logger.info(`Reverted ${count} entries of import batch:`, batch_id);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to revert import: ${error.message}`);
+      logger.error("Failed to revert opening balance import:", error);
+    },
+  });
+}
+
 // ============================================================================
 // Utility Hooks
 // ============================================================================
@@ -0,0 +1,33 @@
+/**
+ * Profile Hooks
+ *
+ * React Query hook for the configuration profile the app was started with.
+ *
+ * @module hooks/use-profile
+ */
+
+import { useQuery } from "@tanstack/react-query";
+import { profileApi } from "@/api/profile.api";
+
+// ============================================================================
+// Query Keys
+// ============================================================================
+
+export const profileKeys = {
+  all: ["profile"] as const,
+};
+
+// ============================================================================
+// Query Hooks
+// ============================================================================
+
+/**
+ * Get the configuration profile; it cannot change while the app runs
+ */
+export function useProfile() {
+  return useQuery({
+    queryKey: profileKeys.all,
+    queryFn: profileApi.get,
+    staleTime: Infinity,
+  });
+}
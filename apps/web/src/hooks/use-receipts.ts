@@ -0,0 +1,71 @@
+/**
+ * Receipt Hooks
+ *
+ * React Query hooks for sale and special order deposit receipts.
+ *
+ * @module hooks/use-receipts
+ */
+
+import { useMutation, useQuery } from "@tanstack/react-query";
+import { toast } from "sonner";
+import { useTranslation } from "@meditrack/i18n";
+import { receiptApi } from "@/api/receipt.api";
+import type { ReceiptOptions, ReceiptSource } from "@/api/receipt.api";
+import type { PrintTarget } from "@/api/label.api";
+import { createLogger } from "@/lib/logger";
+
+const logger = createLogger("ReceiptHooks");
+
+// ============================================================================
+// Query Keys
+// ============================================================================
+
+export const receiptKeys = {
+  all: ["receipts"] as const,
+  detail: (source: ReceiptSource) =>
+    [...receiptKeys.all, source.type, source.id] as const,
+};
+
+// ============================================================================
+// Query Hooks
+// ============================================================================
+
+/**
+ * Get the receipt for a sale or special order deposit
+ */
+export function useReceipt(source: ReceiptSource | null) {
+  return useQuery({
+    queryKey: source ? receiptKeys.detail(source) : receiptKeys.all,
+    queryFn: () => receiptApi.get(source!),
+    enabled: !!source,
+  });
+}
+
+// ============================================================================
+// Mutation Hooks
+// ============================================================================
+
+/**
+ * Print a receipt to a thermal printer or save it to a file
+ */
+export function usePrintReceipt() {
+  const { t } = useTranslation("inventory");
+
+  return useMutation({
+    mutationFn: ({
+      options,
+      target,
+    }: {
+      options: ReceiptOptions;
+      target: PrintTarget;
+    }) => receiptApi.print(options, target),
+    onSuccess: (receipt) => {
+      toast.success(t("messages.receiptPrinted", { number: receipt.number }));
+      logger.info("Receipt printed:", receipt.number);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to print receipt: ${error.message}`);
+      logger.error("Failed to print receipt:", error);
+    },
+  });
+}
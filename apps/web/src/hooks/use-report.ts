@@ -0,0 +1,45 @@
+/**
+ * Report Hooks
+ *
+ * React Query hooks for printable HTML and PDF reports.
+ *
+ * @module hooks/use-report
+ */
+
+import { useMutation } from "@tanstack/react-query";
+import { toast } from "sonner";
+import { useTranslation } from "@meditrack/i18n";
+import { reportApi } from "@/api/report.api";
+import { createLogger } from "@/lib/logger";
+import type { ReportOptions } from "@/api/report.api";
+
+const logger = createLogger("ReportHooks");
+
+// ============================================================================
+// Mutation Hooks
+// ============================================================================
+
+/**
+ * Render a report as HTML or PDF and save it to the chosen path
+ */
+export function useSaveReport() {
+  const { t } = useTranslation("inventory");
+
+  return useMutation({
+    mutationFn: ({
+      options,
+      destination,
+    }: {
+      options: ReportOptions;
+      destination: string;
+    }) => reportApi.save(options, destination),
+    onSuccess: (report) => {
+      toast.success(t("messages.reportSaved", { count: report.pages }));
+      logger.info("Report saved:", report.path);
+    },
+    onError: (error: Error) => {
+      toast.error(`Failed to save report: ${error.message}`);
+      logger.error("Failed to save report:", error);
+    },
+  });
+}
@@ -180,6 +180,7 @@ export function useSeedData() {
               // Update the price (this will create a price history entry)
               await inventoryApi.updateStock(itemId, {
                 unit_price: Math.round(newPrice * 100) / 100, // Round to 2 decimals
+                reason: "Seed data price change",
               });
 
               // Small delay between updates
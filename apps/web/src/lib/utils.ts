@@ -4,3 +4,21 @@ import { twMerge } from "tailwind-merge";
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
 }
+
+/**
+ * Save text content as a file through the browser's download mechanism
+ */
+export function downloadTextFile(
+  contents: string,
+  fileName: string,
+  type = "text/csv;charset=utf-8",
+) {
+  // BOM so spreadsheet programs read Arabic text as UTF-8
+  const blob = new Blob(["﻿", contents], { type });
+  const url = URL.createObjectURL(blob);
+  const link = document.createElement("a");
+  link.href = url;
+  link.download = fileName;
+  link.click();
+  URL.revokeObjectURL(url);
+}
@@ -6,7 +6,12 @@ import { ZodProvider } from "./zod-provider";
 import { AuthProvider } from "@/contexts/auth-context";
 import { useEffect } from "react";
 import type { Locale } from "@meditrack/i18n";
-import { useNotifications, useAutoArchive, useSettingValue } from "@/hooks";
+import {
+  useNotifications,
+  useAutoArchive,
+  useSettingValue,
+  useDomainEvents,
+} from "@/hooks";
 import {
   SETTING_DEFAULT_LANGUAGE,
   SETTING_DEFAULT_THEME,
@@ -42,6 +47,9 @@ function AppContent({ children }: { children: React.ReactNode }) {
   // Initialize auto-archive system
   useAutoArchive();
 
+  // Keep cached data in sync with backend domain events
+  useDomainEvents();
+
   // Sync language from database (skip during first run/onboarding)
   useEffect(() => {
     if (
@@ -19,6 +19,7 @@ import { useDirection } from "@meditrack/i18n";
 import { SidebarProvider, SidebarInset } from "@/components/ui/sidebar";
 import { useCheckFirstRun } from "@/hooks/use-onboarding-db";
 import { Loading } from "@/components/ui/loading";
+import { TrainingBanner } from "@/components/feedback/training-banner";
 import { NotFoundPage } from "@/routes/404";
 import "../index.css";
 
@@ -95,6 +96,7 @@ function RootComponent() {
     return (
       <>
         <HeadContent />
+        <TrainingBanner />
         <Outlet />
         {import.meta.env.DEV && (
           <TanStackRouterDevtools position="bottom-left" />
@@ -115,6 +117,7 @@ function RootComponent() {
         <div className="flex h-screen w-full overflow-hidden" dir={direction}>
           <AppSidebar />
           <SidebarInset className="flex flex-col flex-1 min-w-0">
+            <TrainingBanner />
             <main className="flex-1 overflow-y-auto overflow-x-hidden">
               <Outlet />
             </main>
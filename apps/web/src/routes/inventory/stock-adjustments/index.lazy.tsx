@@ -111,6 +111,7 @@ function StockAdjustmentsComponent() {
       data: {
         adjustment,
         reason,
+        idempotency_key: crypto.randomUUID(),
         adjustment_type: adjustmentType as
           | "manual_adjustment"
           | "order_arrival"